@@ -3,6 +3,8 @@
 mod ui;
 mod worker;
 
+use tracing_subscriber::prelude::*;
+
 fn main() -> eframe::Result<()> {
     if std::env::var("RUST_BACKTRACE").is_err() {
         std::env::set_var("RUST_BACKTRACE", "1");
@@ -11,13 +13,60 @@ fn main() -> eframe::Result<()> {
         std::env::set_var("RUST_LOG", "info");
     }
 
+    let command = command_from_args();
+
     let ef = tracing_subscriber::EnvFilter::try_from_default_env()
         .unwrap()
         .add_directive("sqlx=warn".parse().unwrap());
 
-    let ts = tracing_subscriber::fmt::fmt().with_env_filter(ef);
+    let appender = tracing_appender::rolling::Builder::new()
+        .rotation(tracing_appender::rolling::Rotation::DAILY)
+        .filename_prefix("tinyrss")
+        .filename_suffix("log")
+        .max_log_files(worker::MAX_LOG_FILES)
+        .build(worker::get_log_dir());
 
-    ts.init();
+    // Keep the guard alive until exit so buffered lines are flushed to the file.
+    let _log_guard = match appender {
+        Ok(appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            tracing_subscriber::registry()
+                .with(ef)
+                // Stdout is left to the output of commands.
+                .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+                .with(
+                    tracing_subscriber::fmt::layer()
+                        .with_ansi(false)
+                        .with_writer(writer),
+                )
+                .init();
+            Some(guard)
+        }
+        Err(err) => {
+            tracing_subscriber::fmt::fmt()
+                .with_env_filter(ef)
+                .with_writer(std::io::stderr)
+                .init();
+            tracing::error!("Failed to open log file: {}", err.to_string());
+            None
+        }
+    };
+
+    worker::install_panic_hook();
+
+    if let Some(profile) = profile_from_args() {
+        if worker::is_valid_profile_name(&profile) {
+            worker::set_profile(Some(profile));
+        } else {
+            tracing::error!("Invalid profile name: {}", profile);
+        }
+    }
+
+    if !command.is_empty() {
+        #[cfg(windows)]
+        attach_console();
+        std::process::exit(worker::run_cli(&command));
+    }
 
     let native_options = eframe::NativeOptions {
         centered: true,
@@ -36,6 +85,34 @@ fn main() -> eframe::Result<()> {
     )
 }
 
+/// Reads `--profile <name>` or `--profile=<name>` from the command line.
+fn profile_from_args() -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--profile" {
+            return args.next();
+        }
+        if let Some(profile) = arg.strip_prefix("--profile=") {
+            return Some(profile.to_string());
+        }
+    }
+    None
+}
+
+/// Arguments left after removing the profile option, a subcommand and its arguments.
+fn command_from_args() -> Vec<String> {
+    let mut command = vec![];
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--profile" {
+            args.next();
+        } else if !arg.starts_with("--profile=") {
+            command.push(arg);
+        }
+    }
+    command
+}
+
 #[cfg(not(unix))]
 fn load_icon() -> eframe::IconData {
     let image_bytes = include_bytes!("../icons/icon.png");
@@ -49,3 +126,17 @@ fn load_icon() -> eframe::IconData {
         height: image.height(),
     }
 }
+
+/// Release builds are GUI programs on Windows, which start without a console. Commands print
+/// to the console they were started from, or to a new one.
+#[cfg(windows)]
+fn attach_console() {
+    use windows_sys::Win32::System::Console::{AllocConsole, AttachConsole, ATTACH_PARENT_PROCESS};
+
+    // SAFETY: neither call takes pointers, both only fail when a console is attached already.
+    unsafe {
+        if AttachConsole(ATTACH_PARENT_PROCESS) == 0 {
+            AllocConsole();
+        }
+    }
+}
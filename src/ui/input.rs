@@ -0,0 +1,124 @@
+use crate::worker::{KeyAction, KeyBinding, CONFIG};
+use egui::{Context, Key};
+use std::collections::HashSet;
+
+/// Keys that can be bound to actions. Escape is reserved for cancelling a recording.
+const BINDABLE_KEYS: &[Key] = &[
+    Key::ArrowDown,
+    Key::ArrowLeft,
+    Key::ArrowRight,
+    Key::ArrowUp,
+    Key::Backspace,
+    Key::Delete,
+    Key::End,
+    Key::Enter,
+    Key::Home,
+    Key::PageDown,
+    Key::PageUp,
+    Key::Space,
+    Key::Tab,
+    Key::Num0,
+    Key::Num1,
+    Key::Num2,
+    Key::Num3,
+    Key::Num4,
+    Key::Num5,
+    Key::Num6,
+    Key::Num7,
+    Key::Num8,
+    Key::Num9,
+    Key::A,
+    Key::B,
+    Key::C,
+    Key::D,
+    Key::E,
+    Key::F,
+    Key::G,
+    Key::H,
+    Key::I,
+    Key::J,
+    Key::K,
+    Key::L,
+    Key::M,
+    Key::N,
+    Key::O,
+    Key::P,
+    Key::Q,
+    Key::R,
+    Key::S,
+    Key::T,
+    Key::U,
+    Key::V,
+    Key::W,
+    Key::X,
+    Key::Y,
+    Key::Z,
+];
+
+pub enum Recording {
+    Pending,
+    Cancelled,
+    Recorded(String),
+}
+
+pub fn action_label(action: KeyAction) -> &'static str {
+    match action {
+        KeyAction::Next => "Next item",
+        KeyAction::Previous => "Previous item",
+        KeyAction::Open => "Open item",
+        KeyAction::Dismiss => "Dismiss / restore",
+        KeyAction::Star => "Star / unstar",
+        KeyAction::Later => "Save for later",
+        KeyAction::CopyLink => "Copy link",
+        KeyAction::DismissMatching => "Dismiss search results",
+    }
+}
+
+fn key_from_name(name: &str) -> Option<Key> {
+    BINDABLE_KEYS.iter().copied().find(|key| key.name() == name)
+}
+
+/// Returns the action bound to a key pressed this frame, using the first binding on conflicts.
+pub fn read_action(ctx: &Context) -> Option<KeyAction> {
+    let bindings = CONFIG.lock().keybindings.clone();
+    ctx.input(|i| {
+        if i.modifiers.any() {
+            return None;
+        }
+        bindings.iter().find_map(|binding| {
+            key_from_name(&binding.key)
+                .filter(|key| i.key_pressed(*key))
+                .map(|_| binding.action)
+        })
+    })
+}
+
+/// Waits for the next bindable key press while the keybinding editor is recording.
+pub fn record_key(ctx: &Context) -> Recording {
+    ctx.input(|i| {
+        if i.key_pressed(Key::Escape) {
+            return Recording::Cancelled;
+        }
+        BINDABLE_KEYS
+            .iter()
+            .find(|key| i.key_pressed(**key))
+            .map(|key| Recording::Recorded(key.name().to_string()))
+            .unwrap_or(Recording::Pending)
+    })
+}
+
+/// Keys bound to more than one action.
+pub fn conflicts(bindings: &[KeyBinding]) -> HashSet<String> {
+    let mut seen: Vec<&KeyBinding> = Vec::new();
+    let mut conflicts = HashSet::new();
+    for binding in bindings {
+        if seen
+            .iter()
+            .any(|other| other.key == binding.key && other.action != binding.action)
+        {
+            conflicts.insert(binding.key.clone());
+        }
+        seen.push(binding);
+    }
+    conflicts
+}
@@ -1,15 +1,31 @@
-use crate::worker::{Channel, ConfigBuilder, Item, ToApp, ToWorker, Worker, WorkerError, CONFIG};
+use crate::worker::{
+    blocks_item, font_definitions, get_app_dir, get_log_dir, get_profile, is_valid_profile_name,
+    list_profiles, local_midnight, read_log_tail, strip_html, take_pending_crash_report,
+    AddChannelOutcome, Backfill, Channel, ChannelHeader, ChannelProgress, ClickAction,
+    ConfigBuilder, DnsMode, Download, ErrorKind, Event, FeedOrder, FetchState, HistoryEntry, Item,
+    KeyAction, KeyBinding, MeteredMode, PushService, Quirk, ReaderFont, RefreshSummary,
+    RepublishedItems, RequestId, RulePack, SendToCommand, Severity, ShareFormat, ShareTarget,
+    Statistics, TimestampFormat, ToApp, ToWorker, UnsubscribeMode, Webhook, Worker, WorkerError,
+    CONFIG,
+};
+use chrono::Datelike;
 use copypasta::ClipboardProvider;
 use crossbeam_channel::{Receiver, Sender};
 use eframe::CreationContext;
 use egui::{
     Align, Button, CentralPanel, CollapsingHeader, ComboBox, Context, Direction, Frame, Label,
-    Layout, Margin, ProgressBar, RichText, ScrollArea, TextEdit, TopBottomPanel, Vec2,
+    Layout, Margin, ProgressBar, RichText, ScrollArea, TextEdit, TopBottomPanel, Vec2, WidgetType,
 };
 use lazy_static::lazy_static;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 use theme::{Colors, Theme};
 use tracing::error;
 
+mod input;
+mod onboarding;
 mod theme;
 mod widgets;
 
@@ -17,31 +33,396 @@ lazy_static! {
     static ref THEME: Theme = Theme::from_colors(Colors::dark());
 }
 
-#[derive(Default, PartialEq)]
+/// Delay after the last keystroke before the search is sent to the worker.
+const SEARCH_DEBOUNCE_SECONDS: f64 = 0.25;
+/// Items scrolled past are dismissed once scrolling paused this long.
+const SCROLL_DISMISS_DELAY_SECONDS: f64 = 1.0;
+
+/// How long the "N new items" toast stays on screen after a refresh.
+const SUMMARY_TOAST_SECONDS: f64 = 4.0;
+
+/// How often the log viewer re-reads the log file while it is open.
+const LOG_REFRESH_SECONDS: f64 = 2.0;
+
+/// Number of lines the log viewer keeps from the end of the log file.
+const LOG_TAIL_LINES: usize = 500;
+
+/// How long warning banners stay in the footer. Errors stay until closed.
+const WARNING_BANNER_SECONDS: u64 = 8;
+
+/// Number of banners shown in the footer at once, the rest are only in the error center.
+const MAX_ERROR_BANNERS: usize = 3;
+
+/// Number of errors kept in the error center history.
+const MAX_ERROR_HISTORY: usize = 100;
+
+#[derive(Default, Clone, Copy, PartialEq)]
 enum Page {
     #[default]
     Feed,
     Channels,
+    Downloads,
+    History,
+    Calendar,
     Settings,
 }
 
-#[derive(Default, PartialEq)]
+#[derive(Default, Clone, Copy, PartialEq, Eq, Hash)]
 enum FeedTypeCombo {
     #[default]
     New,
+    Later,
     Dismissed,
 }
 
+/// Matching items listed while a blocklist keyword is typed.
+const RULE_PREVIEW_ITEMS: usize = 10;
+
+/// Holding the pointer on a card this long enters selection mode.
+const LONG_PRESS_SECONDS: f64 = 0.6;
+
+/// Actions of the bar shown while items are selected.
+#[derive(Clone, Copy)]
+enum BulkAction {
+    SelectPage,
+    Dismiss,
+    Restore,
+    Star,
+    Unstar,
+    Open,
+    Cancel,
+}
+
+/// Views whose scroll offset is remembered while switching between them.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum ScrollView {
+    Feed(FeedTypeCombo, usize),
+    Channels,
+    Downloads,
+    History,
+    Calendar,
+    Settings,
+}
+
+/// Saved items containing the blocklist keyword being typed.
+struct KeywordPreview {
+    keyword: String,
+    /// Amount of all matches, only the first [`RULE_PREVIEW_ITEMS`] are kept.
+    count: usize,
+    items: Vec<Item>,
+}
+
+/// Pasted rule pack, parsed with the saved items each of its keywords matches.
+struct RulePackPreview {
+    input: String,
+    pack: Result<RulePack, String>,
+    matches: HashMap<String, usize>,
+}
+
+/// Month shown on the Calendar page.
+#[derive(Default)]
+struct Calendar {
+    /// First day of the month, `None` for the current one.
+    month: Option<chrono::NaiveDate>,
+    /// Items published on each day, `None` until the worker answers.
+    counts: Option<HashMap<u32, i64>>,
+    /// Day whose items are listed below the grid.
+    day: Option<u32>,
+}
+
+impl Calendar {
+    fn month(&self) -> chrono::NaiveDate {
+        self.month.unwrap_or_else(|| {
+            let today = chrono::Local::now().date_naive();
+            today.with_day(1).unwrap_or(today)
+        })
+    }
+
+    /// Shows another month, its counts have to be loaded again.
+    fn show(&mut self, month: Option<chrono::NaiveDate>) {
+        self.month = month;
+        self.counts = None;
+        self.day = None;
+    }
+}
+
+/// Feed search that runs on the worker once typing pauses.
+#[derive(Default)]
+struct Search {
+    /// Query the current results belong to.
+    query: String,
+    results: Option<HashSet<String>>,
+    deadline: Option<f64>,
+    generation: u64,
+}
+
+/// Items scrolled past the top of the feed, waiting to be dismissed in one batch.
+#[derive(Default)]
+struct ScrollDismiss {
+    ids: HashSet<String>,
+    /// Height the cards take up, the feed is scrolled back by it once they are gone.
+    height: f32,
+    view: Option<ScrollView>,
+    deadline: Option<f64>,
+}
+
+/// Remembers scroll offsets of each [`ScrollView`] and restores them when a view is shown again.
+#[derive(Default)]
+struct ScrollMemory {
+    offsets: HashMap<ScrollView, f32>,
+    shown: Option<ScrollView>,
+}
+
+impl ScrollMemory {
+    fn area(&mut self, view: ScrollView) -> ScrollArea {
+        let area = ScrollArea::vertical().id_source(view);
+        if self.shown == Some(view) {
+            return area;
+        }
+        self.shown = Some(view);
+        area.vertical_scroll_offset(self.offsets.get(&view).copied().unwrap_or_default())
+    }
+
+    fn store<R>(&mut self, view: ScrollView, output: &egui::scroll_area::ScrollAreaOutput<R>) {
+        self.offsets.insert(view, output.state.offset.y);
+    }
+
+    /// Moves the remembered offset of `view`, also while it is shown.
+    fn shift(&mut self, view: ScrollView, delta: f32) {
+        if let Some(offset) = self.offsets.get_mut(&view) {
+            *offset = (*offset + delta).max(0.0);
+        }
+        if self.shown == Some(view) {
+            self.shown = None;
+        }
+    }
+}
+
+/// Lowest level shown in the log viewer.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+}
+
+impl LogLevel {
+    const ALL: [LogLevel; 4] = [
+        LogLevel::Error,
+        LogLevel::Warn,
+        LogLevel::Info,
+        LogLevel::Debug,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            LogLevel::Error => "Error",
+            LogLevel::Warn => "Warning",
+            LogLevel::Info => "Info",
+            LogLevel::Debug => "Debug",
+        }
+    }
+
+    /// Parses the level column written by the fmt layer, e.g. `2023-01-01T00:00:00Z  INFO target: message`.
+    fn of_line(line: &str) -> Option<LogLevel> {
+        match line.split_whitespace().nth(1)? {
+            "ERROR" => Some(LogLevel::Error),
+            "WARN" => Some(LogLevel::Warn),
+            "INFO" => Some(LogLevel::Info),
+            "DEBUG" | "TRACE" => Some(LogLevel::Debug),
+            _ => None,
+        }
+    }
+}
+
+struct ErrorEntry {
+    error: WorkerError,
+    received: Instant,
+    timestamp: i64,
+    /// Whether the error is still shown as a footer banner.
+    banner: bool,
+    /// How many times the same error was received while its banner was shown.
+    count: usize,
+}
+
+/// Every error of the session, listed in the panel behind the bell icon.
+#[derive(Default)]
+struct ErrorCenter {
+    entries: Vec<ErrorEntry>,
+    /// Number of entries there were when the panel was last opened.
+    seen: usize,
+    open: bool,
+}
+
+impl ErrorCenter {
+    fn push(&mut self, error: WorkerError) {
+        // A source that keeps failing updates its banner instead of stacking new ones.
+        let repeated = self.entries.iter().rposition(|entry| {
+            entry.banner
+                && entry.error.kind == error.kind
+                && entry.error.description == error.description
+        });
+        let count = match repeated {
+            Some(index) => {
+                if index < self.seen {
+                    self.seen -= 1;
+                }
+                self.entries.remove(index).count + 1
+            }
+            None => 1,
+        };
+        self.entries.push(ErrorEntry {
+            error,
+            received: Instant::now(),
+            timestamp: chrono::Utc::now().timestamp(),
+            banner: true,
+            count,
+        });
+        if self.entries.len() > MAX_ERROR_HISTORY {
+            let overflow = self.entries.len() - MAX_ERROR_HISTORY;
+            self.entries.drain(..overflow);
+            self.seen = self.seen.saturating_sub(overflow);
+        }
+    }
+
+    fn unseen(&self) -> usize {
+        self.entries.len() - self.seen
+    }
+
+    /// Hides banners of warnings that have been on screen long enough.
+    fn expire(&mut self, ctx: &Context) {
+        let lifetime = Duration::from_secs(WARNING_BANNER_SECONDS);
+        for entry in &mut self.entries {
+            if !entry.banner || entry.error.severity != Severity::Warning {
+                continue;
+            }
+            let elapsed = entry.received.elapsed();
+            if elapsed >= lifetime {
+                entry.banner = false;
+            } else {
+                ctx.request_repaint_after(lifetime - elapsed);
+            }
+        }
+    }
+}
+
+/// Tail of the current log file shown in the settings.
+#[derive(Default)]
+struct LogViewer {
+    level: LogLevel,
+    lines: Vec<String>,
+    loaded_at: Option<f64>,
+}
+
+impl LogViewer {
+    fn reload_if_stale(&mut self, now: f64) {
+        if self
+            .loaded_at
+            .is_some_and(|loaded_at| now - loaded_at < LOG_REFRESH_SECONDS)
+        {
+            return;
+        }
+        self.loaded_at = Some(now);
+        self.lines = match read_log_tail(LOG_TAIL_LINES) {
+            Ok(lines) => lines,
+            Err(err) => vec![format!("Failed to read log file: {}", err)],
+        };
+    }
+
+    /// Lines at or above the selected level. Continuation lines of multi-line messages follow their first line.
+    fn filtered(&self) -> Vec<String> {
+        let mut visible = true;
+        self.lines
+            .iter()
+            .filter(|line| {
+                if let Some(level) = LogLevel::of_line(line) {
+                    visible = level as u8 <= self.level as u8;
+                }
+                visible
+            })
+            .cloned()
+            .collect()
+    }
+}
+
 #[derive(Default)]
 pub struct TinyrssApp {
     page: Page,
     feed_page: usize,
     channel_input: String,
+    /// Subscription the Add button waits for.
+    pending_add: Option<RequestId>,
+    /// Why the last subscription from the Add button failed.
+    add_channel_error: Option<String>,
+    next_request_id: RequestId,
     feed_input: String,
     feed_type_combo: FeedTypeCombo,
+    focused_card: Option<usize>,
+    /// Ids of the selected items, `None` outside of selection mode.
+    selected_items: Option<HashSet<String>>,
+    detail_item: Option<String>,
+    recording_key: Option<KeyAction>,
+    profile_input: String,
+    keyword_input: String,
+    /// Matches of `keyword_input`, computed again when it or `feed_items` change.
+    keyword_preview: Option<KeywordPreview>,
+    rule_pack_input: String,
+    /// Preview of `rule_pack_input`, computed again when it or `feed_items` change.
+    rule_pack_preview: Option<RulePackPreview>,
+    /// Keywords of the pasted rule pack unchecked in its preview.
+    rule_pack_skipped: HashSet<String>,
+    header_name_input: String,
+    header_value_input: String,
+    cookie_domain_input: String,
+    opml_url_input: String,
+    history_input: String,
+    /// Last answer to a history search: its query, entries and total amount of matches.
+    history: Option<(String, Vec<HistoryEntry>, i64)>,
+    calendar: Calendar,
+    opml_keep_synced: bool,
+    clipboard_feed: Option<String>,
+    ignored_clipboard_feed: Option<String>,
+    window_focused: bool,
+    /// Ids of the feed items when the window lost focus or was minimized.
+    away_snapshot: Option<HashSet<String>>,
+    /// Items that arrived since then, shown in the window title. There's no tray icon,
+    /// so the title is what the taskbar shows while the window is in the background.
+    new_since_away: usize,
+    /// Count of the "new since you left" banner, until it's closed.
+    returned_new: Option<usize>,
+    /// Items of the last "Dismiss all" that can still be restored.
+    trashed_items: Option<usize>,
+    previous_feed_visit: i64,
+    scroll_memory: ScrollMemory,
+    search: Search,
+    scroll_dismiss: ScrollDismiss,
+    log_viewer: LogViewer,
+    crash_report: Option<PathBuf>,
+    /// Fingerprints of certificates that failed validation, by channel id.
+    untrusted_certificates: HashMap<String, String>,
+    /// Channel id and fingerprint of the certificate waiting for the user's approval.
+    trusting_certificate: Option<(String, String)>,
 
     channels: Vec<Channel>,
+    subscription_events: Vec<Event>,
+    /// Groups of duplicate channels and which of each is kept when merging.
+    duplicate_channels: Vec<(Vec<String>, usize)>,
+    finding_duplicates: bool,
+    /// Name typed for a new newsletter inbox, and the outcome of creating it.
+    newsletter_title: String,
+    creating_newsletter: bool,
+    newsletter_result: Option<Result<String, String>>,
+    channel_headers: Vec<ChannelHeader>,
+    /// Domains with stored cookies and how many cookies each has.
+    cookie_domains: Vec<(String, usize)>,
     feed_items: Vec<Item>,
+    /// Scores of the items for [`FeedOrder::Hot`], from the last full feed update.
+    feed_scores: HashMap<String, f32>,
+    /// Item thumbnails by url, `None` while loading or when loading failed.
+    thumbnails: HashMap<String, Option<egui::TextureHandle>>,
+    downloads: Vec<Download>,
 
     worker_status: WorkerStatus,
     sender: Option<Sender<ToWorker>>,
@@ -50,11 +431,16 @@ pub struct TinyrssApp {
 
 #[derive(Default)]
 struct WorkerStatus {
+    feed_loaded: bool,
     updating_feed: bool,
-    update_progress: f32,
+    /// Channels of the running refresh and how far each of them got.
+    refresh_channels: Vec<ChannelProgress>,
     importing_channels: bool,
     import_progress: f32,
-    worker_errors: Vec<WorkerError>,
+    errors: ErrorCenter,
+    refresh_summary: Option<RefreshSummary>,
+    summary_toast_until: f64,
+    statistics: Statistics,
 }
 
 impl TinyrssApp {
@@ -62,13 +448,27 @@ impl TinyrssApp {
         let mut app = Self::default();
 
         app.configure_styles(&cc.egui_ctx);
+        app.mark_feed_visited();
+
+        if let Some(path) = take_pending_crash_report() {
+            app.crash_report = Some(path);
+            egui_modal::Modal::new(&cc.egui_ctx, "modal_crash_report").open();
+        }
 
         let (app_tx, app_rx) = crossbeam_channel::unbounded();
         let (worker_tx, worker_rx) = crossbeam_channel::unbounded();
 
         let context = cc.egui_ctx.clone();
+        let loopback = app_tx.clone();
         std::thread::spawn(move || {
-            Worker::new(worker_tx, app_rx, context).init();
+            while Worker::new(
+                worker_tx.clone(),
+                app_rx.clone(),
+                loopback.clone(),
+                context.clone(),
+            )
+            .init()
+            {}
         });
 
         app.sender = Some(app_tx);
@@ -85,42 +485,205 @@ impl TinyrssApp {
 }
 
 impl eframe::App for TinyrssApp {
-    fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
-        if let Some(receiver) = &self.receiver {
-            if let Ok(message) = receiver.try_recv() {
+    fn update(&mut self, ctx: &Context, frame: &mut eframe::Frame) {
+        if let Some(receiver) = self.receiver.clone() {
+            while let Ok(message) = receiver.try_recv() {
                 match message {
-                    ToApp::UpdateFeed { items } => {
-                        self.worker_status.updating_feed = false;
-                        self.worker_status.update_progress = 0.0;
+                    ToApp::UpdateFeed { items, scores } => {
+                        self.worker_status.feed_loaded = true;
+                        // Items change during a refresh too, e.g. when one gets dismissed.
+                        let refreshing = self
+                            .worker_status
+                            .refresh_channels
+                            .iter()
+                            .any(|channel| !channel.state.is_done());
+                        if !refreshing {
+                            self.worker_status.updating_feed = false;
+                            self.worker_status.refresh_channels.clear();
+                        }
                         self.feed_items = items;
+                        self.keyword_preview = None;
+                        self.rule_pack_preview = None;
+                        self.feed_scores = scores;
+                        self.count_new_since_away(frame);
+                        if !self.search.query.is_empty() {
+                            self.search.deadline = Some(ctx.input(|i| i.time));
+                        }
+                        if self.page == Page::Calendar {
+                            self.load_calendar();
+                        }
                     }
-                    ToApp::FeedUpdateProgress { progress } => {
-                        self.worker_status.update_progress = progress;
+                    ToApp::NewItems { items } => {
+                        let known: HashSet<&str> = self
+                            .feed_items
+                            .iter()
+                            .map(|item| item.id.as_str())
+                            .collect();
+                        let items: Vec<Item> = items
+                            .into_iter()
+                            .filter(|item| !known.contains(item.id.as_str()))
+                            .collect();
+                        self.feed_items.extend(items);
+                        self.keyword_preview = None;
+                        self.rule_pack_preview = None;
+                        // Same order the worker loads items in.
+                        let now = chrono::Utc::now().timestamp();
+                        self.feed_items.sort_by_key(|item| {
+                            std::cmp::Reverse((item.rank(now), item.published))
+                        });
+                        if !self.search.query.is_empty() {
+                            self.search.deadline = Some(ctx.input(|i| i.time));
+                        }
+                    }
+                    ToApp::FeedUpdateProgress { channels } => {
+                        self.worker_status.updating_feed = true;
+                        self.worker_status.refresh_channels = channels;
                     }
                     ToApp::WorkerError { error } => {
                         error!(
                             "Received error from worker: {} {}",
                             error.description, error.error_message
                         );
-                        self.worker_status.worker_errors.push(error);
+                        if let ErrorKind::Certificate {
+                            channel_id,
+                            fingerprint,
+                        } = &error.kind
+                        {
+                            self.untrusted_certificates
+                                .insert(channel_id.clone(), fingerprint.clone());
+                        }
+                        self.worker_status.errors.push(error);
                     }
-                    ToApp::UpdateChannels { channels } => {
+                    ToApp::UpdateChannels { channels, headers } => {
                         self.worker_status.importing_channels = false;
                         self.worker_status.import_progress = 0.0;
                         self.channels = channels;
+                        self.channel_headers = headers;
+                    }
+                    ToApp::SubscriptionEvents { events } => {
+                        self.subscription_events = events;
+                    }
+                    ToApp::UpdateCookies { domains } => {
+                        self.cookie_domains = domains;
+                    }
+                    ToApp::AddChannelResult {
+                        request_id,
+                        outcome,
+                    } => {
+                        if self.pending_add == Some(request_id) {
+                            self.pending_add = None;
+                            self.add_channel_error = match outcome {
+                                AddChannelOutcome::Added { .. } | AddChannelOutcome::Restored => {
+                                    None
+                                }
+                                AddChannelOutcome::AlreadySubscribed => {
+                                    Some("Already subscribed to this channel".to_string())
+                                }
+                                AddChannelOutcome::Failed(reason) => Some(reason),
+                            };
+                        }
+                    }
+                    ToApp::DownloadProgress { download } => {
+                        match self.downloads.iter_mut().find(|d| d.id == download.id) {
+                            Some(known) => *known = download,
+                            None => self.downloads.push(download),
+                        }
+                    }
+                    ToApp::History {
+                        query,
+                        entries,
+                        total,
+                    } => {
+                        // Answers to searches typed over since are dropped.
+                        if query == self.history_input.trim() {
+                            self.history = Some((query, entries, total));
+                        }
+                    }
+                    ToApp::Calendar {
+                        year,
+                        month,
+                        counts,
+                    } => {
+                        // Answers for months navigated away from are dropped.
+                        let shown = self.calendar.month();
+                        if (year, month) == (shown.year(), shown.month()) {
+                            self.calendar.counts = Some(counts);
+                        }
+                    }
+                    ToApp::Trash { amount } => {
+                        self.trashed_items = amount;
+                    }
+                    ToApp::NewsletterCreated { result } => {
+                        self.creating_newsletter = false;
+                        self.newsletter_result = Some(result);
+                    }
+                    ToApp::DuplicateChannels { groups } => {
+                        self.finding_duplicates = false;
+                        self.duplicate_channels =
+                            groups.into_iter().map(|group| (group, 0)).collect();
+                        egui_modal::Modal::new(ctx, "modal_duplicate_channels").open();
+                    }
+                    ToApp::Thumbnail { url, image } => {
+                        let texture =
+                            image.map(|image| ctx.load_texture(&url, image, Default::default()));
+                        self.thumbnails.insert(url, texture);
                     }
                     ToApp::ImportProgress { progress } => {
                         self.worker_status.import_progress = progress;
                     }
+                    ToApp::SearchResults { generation, ids } => {
+                        if generation == self.search.generation {
+                            self.search.results = Some(ids.into_iter().collect());
+                        }
+                    }
+                    ToApp::Statistics { statistics } => {
+                        self.worker_status.statistics = statistics;
+                    }
+                    ToApp::RefreshSummary { summary } => {
+                        self.worker_status.summary_toast_until =
+                            ctx.input(|i| i.time) + SUMMARY_TOAST_SECONDS;
+                        self.worker_status.refresh_summary = Some(summary);
+                    }
                 }
             }
         }
 
+        let window_focused = ctx.input(|i| i.raw.has_focus);
+        if window_focused && !self.window_focused && self.page == Page::Channels {
+            self.check_clipboard_for_feed();
+        }
+        self.window_focused = window_focused;
+
+        let away = !window_focused || frame.info().window_info.minimized;
+        if away && self.away_snapshot.is_none() {
+            self.away_snapshot = Some(self.feed_items.iter().map(|i| i.id.clone()).collect());
+            self.new_since_away = 0;
+        } else if !away && self.away_snapshot.is_some() {
+            self.away_snapshot = None;
+            if self.new_since_away > 0 {
+                self.returned_new = Some(self.new_since_away);
+                frame.set_window_title("Tinyrss");
+            }
+            self.new_since_away = 0;
+        }
+
+        self.run_search(ctx);
+
+        self.dismiss_scrolled_past(ctx);
+
         self.render_header(ctx);
 
         self.render_central_panel(ctx);
 
         self.render_footer(ctx);
+
+        self.render_summary_toast(ctx);
+
+        self.render_crash_report(ctx);
+
+        self.render_error_center(ctx);
+
+        self.render_trust_certificate(ctx);
     }
 
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
@@ -136,20 +699,75 @@ impl TinyrssApp {
             .min_height(30.)
             .show(ctx, |ui| {
                 ui.with_layout(Layout::left_to_right(Align::Center), |ui| {
-                    ui.selectable_value(&mut self.page, Page::Feed, "Feed");
-                    ui.selectable_value(&mut self.page, Page::Channels, "Channels");
+                    if ui
+                        .selectable_value(&mut self.page, Page::Feed, "Feed")
+                        .changed()
+                    {
+                        self.mark_feed_visited();
+                    };
+                    if ui
+                        .selectable_value(&mut self.page, Page::Channels, "Channels")
+                        .clicked()
+                    {
+                        self.check_clipboard_for_feed();
+                    };
+                    let active = self
+                        .downloads
+                        .iter()
+                        .filter(|download| !download.state.is_done())
+                        .count();
+                    ui.selectable_value(
+                        &mut self.page,
+                        Page::Downloads,
+                        if active > 0 {
+                            format!("Downloads ({})", active)
+                        } else {
+                            "Downloads".to_string()
+                        },
+                    );
+                    if ui
+                        .selectable_value(&mut self.page, Page::History, "History")
+                        .clicked()
+                    {
+                        self.search_history();
+                    }
+                    if ui
+                        .selectable_value(&mut self.page, Page::Calendar, "Calendar")
+                        .clicked()
+                    {
+                        self.load_calendar();
+                    }
                     ui.selectable_value(&mut self.page, Page::Settings, "Settings");
+                    self.render_profile_menu(ui);
+                    let unseen = self.worker_status.errors.unseen();
+                    let bell = ui.selectable_label(
+                        self.worker_status.errors.open,
+                        if unseen > 0 {
+                            RichText::new(format!("🔔 {}", unseen)).color(THEME.colors.warning)
+                        } else {
+                            RichText::new("🔔")
+                        },
+                    );
+                    widgets::describe(&bell, WidgetType::Button, "Error center");
+                    if bell.clicked() {
+                        if self.worker_status.errors.open {
+                            self.worker_status.errors.open = false;
+                        } else {
+                            self.open_error_center();
+                        }
+                    }
                     ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
                         if self.page == Page::Feed {
-                            if ui
-                                .add_enabled(!self.worker_status.updating_feed, Button::new("⟳"))
-                                .clicked()
-                            {
+                            let refresh =
+                                ui.add_enabled(!self.worker_status.updating_feed, Button::new("⟳"));
+                            widgets::describe(&refresh, WidgetType::Button, "Refresh feed");
+                            if refresh.clicked() {
                                 self.update_feed();
                             };
                             ComboBox::from_id_source("feed_type_combo")
                                 .selected_text(match self.feed_type_combo {
                                     FeedTypeCombo::New => "New",
+                                    FeedTypeCombo::Later => "Later",
                                     FeedTypeCombo::Dismissed => "Dismissed",
                                 })
                                 .show_ui(ui, |ui| {
@@ -163,6 +781,16 @@ impl TinyrssApp {
                                     {
                                         self.feed_page = 0;
                                     };
+                                    if ui
+                                        .selectable_value(
+                                            &mut self.feed_type_combo,
+                                            FeedTypeCombo::Later,
+                                            "Later",
+                                        )
+                                        .changed()
+                                    {
+                                        self.feed_page = 0;
+                                    };
                                     if ui
                                         .selectable_value(
                                             &mut self.feed_type_combo,
@@ -174,15 +802,32 @@ impl TinyrssApp {
                                         self.feed_page = 0;
                                     };
                                 });
+                            if self.feed_type_combo == FeedTypeCombo::New {
+                                let hot = CONFIG.lock().feed_order == FeedOrder::Hot;
+                                let toggle = ui.selectable_label(hot, "🔥").on_hover_text(
+                                    "Hot: recent items of rarely posting channels first",
+                                );
+                                widgets::describe(&toggle, WidgetType::Button, "Sort by hotness");
+                                if toggle.clicked() {
+                                    CONFIG.lock().feed_order = if hot {
+                                        FeedOrder::Latest
+                                    } else {
+                                        FeedOrder::Hot
+                                    };
+                                    ConfigBuilder::from_current().apply();
+                                    self.feed_page = 0;
+                                }
+                            }
                             if CONFIG.lock().show_search_in_feed
                                 && ui
                                     .add(
                                         TextEdit::singleline(&mut self.feed_input)
-                                            .hint_text("Search"),
+                                            .hint_text("Search, author:name"),
                                     )
                                     .changed()
                             {
-                                self.feed_page = 0;
+                                self.search.deadline =
+                                    Some(ctx.input(|i| i.time) + SEARCH_DEBOUNCE_SECONDS);
                             }
                         }
                     });
@@ -198,168 +843,642 @@ impl TinyrssApp {
             Page::Channels => {
                 self.render_channels_page(ui);
             }
+            Page::Downloads => {
+                self.render_downloads_page(ui);
+            }
+            Page::History => {
+                self.render_history_page(ui);
+            }
+            Page::Calendar => {
+                self.render_calendar_page(ctx, ui);
+            }
             Page::Settings => {
                 self.render_settings_page(ctx, ui);
             }
         });
     }
 
-    fn render_feed_page(&mut self, ctx: &Context, ui: &mut egui::Ui) {
-        if self.worker_status.updating_feed {
-            ui.with_layout(
-                Layout::centered_and_justified(Direction::LeftToRight),
-                |ui| {
+    fn render_profile_menu(&mut self, ui: &mut egui::Ui) {
+        let current = get_profile();
+        ui.menu_button(
+            format!("👤 {}", current.as_deref().unwrap_or("default")),
+            |ui| {
+                let mut selected = None;
+                if ui.selectable_label(current.is_none(), "default").clicked() {
+                    selected = Some(None);
+                }
+                for profile in list_profiles() {
+                    if ui
+                        .selectable_label(current.as_ref() == Some(&profile), &profile)
+                        .clicked()
+                    {
+                        selected = Some(Some(profile));
+                    }
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
                     ui.add(
-                        ProgressBar::new(self.worker_status.update_progress)
-                            .desired_width(300.0)
-                            .animate(true),
-                    )
-                },
-            );
-        } else {
-            if self.feed_items.is_empty() {
-                ui.with_layout(
-                    Layout::centered_and_justified(Direction::LeftToRight),
-                    |ui| {
-                        ui.label("No items in feed");
-                    },
-                );
-                return;
-            }
-
-            const ITEMS_PER_PAGE: usize = 10;
-
-            let from = self.feed_page * ITEMS_PER_PAGE;
-
-            let filtered_items: Vec<&Item> = match self.feed_type_combo {
-                FeedTypeCombo::New => self
-                    .feed_items
-                    .iter()
-                    .filter(|item| !item.dismissed)
-                    .filter(|item| {
-                        item.title
-                            .clone()
-                            .unwrap()
-                            .to_lowercase()
-                            .contains(self.feed_input.to_lowercase().as_str())
-                    })
-                    .collect(),
-                FeedTypeCombo::Dismissed => self
-                    .feed_items
-                    .iter()
-                    .filter(|item| item.dismissed)
-                    .filter(|item| {
-                        item.title
-                            .clone()
-                            .unwrap()
-                            .to_lowercase()
-                            .contains(self.feed_input.to_lowercase().as_str())
-                    })
-                    .collect(),
-            };
-
-            let last_page: bool =
-                (filtered_items.len() - (self.feed_page * ITEMS_PER_PAGE)) <= ITEMS_PER_PAGE;
-
-            let to = if from + ITEMS_PER_PAGE > filtered_items.len() {
-                filtered_items.len()
-            } else {
-                from + ITEMS_PER_PAGE
-            };
-
-            if filtered_items.is_empty() {
-                let text = match self.feed_type_combo {
-                    FeedTypeCombo::New => "No new items",
-                    FeedTypeCombo::Dismissed => "No dismissed items",
-                };
-                ui.with_layout(
-                    Layout::centered_and_justified(Direction::LeftToRight),
-                    |ui| {
-                        ui.label(text);
-                    },
-                );
-                return;
-            } else {
-                ScrollArea::vertical().show(ui, |ui| {
-                    for item in &filtered_items[from..to] {
-                        widgets::feed_card(ui, self.sender.clone(), item);
-                        ui.add_space(THEME.spacing.medium);
+                        TextEdit::singleline(&mut self.profile_input)
+                            .hint_text("New profile")
+                            .desired_width(120.0),
+                    );
+                    if ui
+                        .add_enabled(
+                            is_valid_profile_name(&self.profile_input),
+                            Button::new("Create"),
+                        )
+                        .clicked()
+                    {
+                        selected = Some(Some(std::mem::take(&mut self.profile_input)));
                     }
                 });
-            }
+                if let Some(profile) = selected {
+                    ui.close_menu();
+                    if profile != current {
+                        self.switch_profile(profile);
+                    }
+                }
+            },
+        );
+    }
+
+    /// Shown instead of the empty feed until the first channels are added or it is skipped.
+    fn render_welcome(&mut self, ui: &mut egui::Ui) {
+        ScrollArea::vertical().show(ui, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.label(RichText::new("Welcome to tinyrss").strong().heading());
+                ui.label("Start with one of the bundles below or add your own channels.");
+            });
+            ui.add_space(THEME.spacing.large);
 
-            ui.horizontal_centered(|ui| {
-                ui.spacing_mut().button_padding = Vec2::new(10., 2.);
-                ui.with_layout(Layout::bottom_up(Align::LEFT), |ui| {
+            for bundle in onboarding::BUNDLES {
+                Frame {
+                    fill: THEME.colors.bg,
+                    inner_margin: Margin::same(6.0),
+                    rounding: THEME.rounding.large,
+                    ..Default::default()
+                }
+                .show(ui, |ui| {
                     ui.horizontal(|ui| {
-                        if ui
-                            .add_enabled(self.feed_page > 0, Button::new("<"))
-                            .clicked()
-                        {
-                            self.feed_page -= 1;
-                        }
-                        ui.label((self.feed_page + 1).to_string());
-                        if ui.add_enabled(!last_page, Button::new(">")).clicked() {
-                            self.feed_page += 1;
-                        }
+                        ui.vertical(|ui| {
+                            ui.label(RichText::new(bundle.name).strong());
+                            ui.label(bundle.description);
+                        });
+                        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                            let add = ui.add_enabled(
+                                !self.worker_status.importing_channels,
+                                Button::new("Add"),
+                            );
+                            widgets::describe(
+                                &add,
+                                WidgetType::Button,
+                                format!("Add {}", bundle.name),
+                            );
+                            if add.clicked() {
+                                if let Some(sender) = &self.sender {
+                                    self.worker_status.importing_channels = true;
+                                    sender
+                                        .send(ToWorker::ImportOpml {
+                                            xml: bundle.opml.to_string(),
+                                        })
+                                        .unwrap();
+                                }
+                            }
+                        });
                     });
                 });
-
-                let modal = egui_modal::Modal::new(ctx, "modal_dismiss_all");
-
-                modal.show(|ui| {
-                    modal.title(ui, "Warning");
-                    let amount = self
-                        .feed_items
-                        .iter()
-                        .filter(|item| !item.dismissed)
-                        .count();
-                    modal.body(ui, format!("All new items will be dismissed! ({})", amount));
-                    modal.buttons(ui, |ui| {
-                        ui.spacing_mut().button_padding = Vec2::new(8., 4.);
-                        if ui.add(Button::new("Close")).clicked() {
-                            modal.close();
-                        };
-                        if ui
-                            .add(Button::new("Confirm").fill(THEME.colors.warning))
-                            .clicked()
-                        {
-                            self.dismiss_all();
-                            modal.close();
-                        };
-                    });
+                ui.add_space(THEME.spacing.medium);
+            }
+            if self.worker_status.importing_channels {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Adding channels...");
                 });
+            }
+            ui.add_space(THEME.spacing.large);
 
-                ui.with_layout(Layout::bottom_up(Align::RIGHT), |ui| {
-                    if self.feed_type_combo == FeedTypeCombo::New {
-                        ui.with_layout(Layout::right_to_left(Align::BOTTOM), |ui| {
-                            if ui.link("Dismiss all").clicked() {
-                                modal.open();
-                            }
-                        });
+            ui.label(RichText::new("Quick tour").strong());
+            for (page, name, description) in onboarding::TOUR {
+                ui.horizontal(|ui| {
+                    if ui.link(*name).clicked() {
+                        self.page = *page;
                     }
+                    ui.label(*description);
                 });
+            }
+            ui.add_space(THEME.spacing.large);
+
+            ui.vertical_centered(|ui| {
+                if ui.button("Skip").clicked() {
+                    CONFIG.lock().onboarding_dismissed = true;
+                }
             });
-        }
+        });
     }
 
-    fn render_channels_page(&mut self, ui: &mut egui::Ui) {
-        ui.horizontal(|ui| {
-            ui.spacing_mut().button_padding = Vec2::new(6., 4.);
-            if ui.button("Paste").clicked() {
-                let mut ctx = match copypasta::ClipboardContext::new() {
-                    Ok(ctx) => ctx,
+    fn render_feed_page(&mut self, ctx: &Context, ui: &mut egui::Ui) {
+        if let Some(amount) = self.trashed_items {
+            Frame {
+                fill: THEME.colors.bg,
+                inner_margin: Margin::same(6.0),
+                rounding: THEME.rounding.large,
+                ..Default::default()
+            }
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("Dismissed {} items", amount));
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        let undo = ui.add(
+                            Button::new(format!("Undo ({} items)", amount))
+                                .fill(THEME.colors.accent),
+                        );
+                        if undo.clicked() {
+                            self.trashed_items = None;
+                            if let Some(sender) = &self.sender {
+                                sender.send(ToWorker::UndoDismissAll).unwrap();
+                            }
+                        }
+                    });
+                });
+            });
+            ui.add_space(THEME.spacing.medium);
+        }
+
+        if let Some(amount) = self.returned_new {
+            Frame {
+                fill: THEME.colors.bg,
+                inner_margin: Margin::same(6.0),
+                rounding: THEME.rounding.large,
+                ..Default::default()
+            }
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} new items since you left", amount));
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        let close = ui.button("🗙");
+                        widgets::describe(&close, WidgetType::Button, "Close");
+                        if close.clicked() {
+                            self.returned_new = None;
+                        }
+                    });
+                });
+            });
+            ui.add_space(THEME.spacing.medium);
+        }
+
+        if !self.worker_status.feed_loaded {
+            ScrollArea::vertical().show(ui, |ui| {
+                for _ in 0..6 {
+                    widgets::skeleton_card(ui);
+                    ui.add_space(THEME.spacing.medium);
+                }
+            });
+            return;
+        }
+
+        if self.worker_status.updating_feed {
+            self.render_refresh_progress(ui);
+            ui.add_space(THEME.spacing.medium);
+        }
+
+        if self.feed_items.is_empty() {
+            if self.channels.is_empty() && !CONFIG.lock().onboarding_dismissed {
+                self.render_welcome(ui);
+                return;
+            }
+            ui.with_layout(
+                Layout::centered_and_justified(Direction::LeftToRight),
+                |ui| {
+                    ui.label("No items in feed");
+                },
+            );
+            return;
+        }
+
+        let items_per_page = CONFIG.lock().items_per_page.max(1);
+
+        let mut filtered_items: Vec<&Item> = self
+            .feed_items
+            .iter()
+            .filter(|item| match self.feed_type_combo {
+                FeedTypeCombo::New => !item.dismissed && !item.later,
+                FeedTypeCombo::Later => !item.dismissed && item.later,
+                FeedTypeCombo::Dismissed => item.dismissed,
+            })
+            .filter(|item| match &self.search.results {
+                Some(ids) if !self.search.query.is_empty() => ids.contains(&item.id),
+                _ => true,
+            })
+            .collect();
+        if self.feed_type_combo == FeedTypeCombo::New && CONFIG.lock().feed_order == FeedOrder::Hot
+        {
+            let now = chrono::Utc::now().timestamp();
+            // Items of a refresh still running aren't scored yet, they are the newest anyway.
+            let score = |item: &Item| self.feed_scores.get(&item.id).copied().unwrap_or(f32::MAX);
+            filtered_items.sort_by(|a, b| {
+                b.rank(now)
+                    .cmp(&a.rank(now))
+                    .then_with(|| score(b).total_cmp(&score(a)))
+            });
+        }
+
+        // Everything shown for the current search, across all pages.
+        let matching: Option<Vec<String>> = (self.search.results.is_some()
+            && !self.search.query.is_empty()
+            && self.feed_type_combo != FeedTypeCombo::Dismissed)
+            .then(|| filtered_items.iter().map(|item| item.id.clone()).collect());
+
+        let page_count = filtered_items.len().div_ceil(items_per_page);
+        self.feed_page = self.feed_page.min(page_count.saturating_sub(1));
+
+        let last_page: bool = self.feed_page + 1 >= page_count;
+
+        let from = self.feed_page * items_per_page;
+        let to = (from + items_per_page).min(filtered_items.len());
+
+        if filtered_items.is_empty() {
+            let text = match self.feed_type_combo {
+                FeedTypeCombo::New => "No new items",
+                FeedTypeCombo::Later => "No items saved for later",
+                FeedTypeCombo::Dismissed => "No dismissed items",
+            };
+            ui.with_layout(
+                Layout::centered_and_justified(Direction::LeftToRight),
+                |ui| {
+                    ui.label(text);
+                },
+            );
+            return;
+        }
+
+        let key = if ctx.wants_keyboard_input() {
+            None
+        } else {
+            input::read_action(ctx)
+        };
+
+        let page_len = to - from;
+        let mut focus_moved = false;
+        match key {
+            Some(KeyAction::Next) => {
+                focus_moved = true;
+                match self.focused_card {
+                    Some(index) if index + 1 >= page_len && !last_page => {
+                        self.feed_page += 1;
+                        self.focused_card = Some(0);
+                        return;
+                    }
+                    Some(index) => self.focused_card = Some((index + 1).min(page_len - 1)),
+                    None => self.focused_card = Some(0),
+                }
+            }
+            Some(KeyAction::Previous) => {
+                focus_moved = true;
+                match self.focused_card {
+                    Some(0) if self.feed_page > 0 => {
+                        self.feed_page -= 1;
+                        self.focused_card = Some(items_per_page - 1);
+                        return;
+                    }
+                    Some(index) => self.focused_card = Some(index.saturating_sub(1)),
+                    None => self.focused_card = Some(0),
+                }
+            }
+            _ => {}
+        }
+        if let Some(index) = self.focused_card {
+            self.focused_card = Some(index.min(page_len - 1));
+        }
+
+        let focused_item = self
+            .focused_card
+            .and_then(|index| filtered_items[from..to].get(index))
+            .map(|item| {
+                (
+                    item.id.clone(),
+                    item.link.clone(),
+                    item.dismissed,
+                    item.starred,
+                    item.later,
+                )
+            });
+
+        let channel_colors: HashMap<&str, egui::Color32> = self
+            .channels
+            .iter()
+            .filter_map(|channel| {
+                let color = widgets::parse_color(channel.color.as_deref()?)?;
+                Some((channel.id.as_str(), color))
+            })
+            .collect();
+
+        let mut bulk_action = None;
+        if self.selected_items.is_some() {
+            bulk_action = self.render_bulk_bar(ui);
+            ui.add_space(THEME.spacing.medium);
+        }
+        let page_ids: Vec<String> = filtered_items[from..to]
+            .iter()
+            .map(|item| item.id.clone())
+            .collect();
+
+        let scroll_view = ScrollView::Feed(self.feed_type_combo, self.feed_page);
+        let dismiss_on_scroll = CONFIG.lock().dismiss_on_scroll
+            && self.feed_type_combo == FeedTypeCombo::New
+            && self.selected_items.is_none();
+        let previous_offset = self.scroll_memory.offsets.get(&scroll_view).copied();
+        let mut clicked = None;
+        let stale_before = match CONFIG.lock().stale_after_days {
+            0 => i64::MIN,
+            days => chrono::Utc::now().timestamp() - i64::from(days) * 24 * 60 * 60,
+        };
+        // Filtering by author goes through the search field, so it is offered along with it.
+        let author_filter = Cell::new(None);
+        let show_search = CONFIG.lock().show_search_in_feed;
+        let output = self.scroll_memory.area(scroll_view).show(ui, |ui| {
+            for (index, item) in filtered_items[from..to].iter().enumerate() {
+                let focused = self.focused_card == Some(index);
+                let fresh = item.first_seen > self.previous_feed_visit;
+                // Pinned items are kept around on purpose.
+                let stale = item.published < stale_before && !item.pinned;
+                let stripe = channel_colors.get(item.channel.as_str()).copied();
+                if let Some(url) = &item.thumbnail {
+                    if !self.thumbnails.contains_key(url) {
+                        self.thumbnails.insert(url.clone(), None);
+                        if let Some(sender) = &self.sender {
+                            sender
+                                .send(ToWorker::LoadThumbnail { url: url.clone() })
+                                .unwrap();
+                        }
+                    }
+                }
+                let thumbnail = item
+                    .thumbnail
+                    .as_ref()
+                    .and_then(|url| self.thumbnails.get(url)?.as_ref());
+                let card = widgets::feed_card(
+                    ui,
+                    self.sender.clone(),
+                    item,
+                    widgets::CardOptions {
+                        focused,
+                        fresh,
+                        stale,
+                        stripe,
+                        highlight: &self.search.query,
+                        thumbnail,
+                        selected: self
+                            .selected_items
+                            .as_ref()
+                            .map(|selected| selected.contains(&item.id)),
+                        author_filter: show_search.then_some(&author_filter),
+                    },
+                );
+                if card.inner {
+                    let shift = ui.input(|i| i.modifiers.shift);
+                    match &mut self.selected_items {
+                        Some(selected) => {
+                            if !selected.remove(&item.id) {
+                                selected.insert(item.id.clone());
+                            }
+                        }
+                        None if shift => {
+                            self.selected_items = Some(HashSet::from([item.id.clone()]));
+                        }
+                        None => clicked = Some(item.id.clone()),
+                    }
+                } else if self.selected_items.is_none() && card.response.is_pointer_button_down_on()
+                {
+                    let held = ui.input(|i| {
+                        i.pointer
+                            .press_start_time()
+                            .filter(|_| i.pointer.is_still())
+                            .map(|start| i.time - start)
+                    });
+                    match held {
+                        Some(held) if held >= LONG_PRESS_SECONDS => {
+                            self.selected_items = Some(HashSet::from([item.id.clone()]));
+                        }
+                        Some(held) => ctx.request_repaint_after(Duration::from_secs_f64(
+                            LONG_PRESS_SECONDS - held,
+                        )),
+                        None => {}
+                    }
+                }
+                if focused && focus_moved {
+                    card.response.scroll_to_me(Some(Align::Center));
+                }
+                if dismiss_on_scroll
+                    && card.response.rect.bottom() < ui.clip_rect().top()
+                    && self.scroll_dismiss.ids.insert(item.id.clone())
+                {
+                    self.scroll_dismiss.height +=
+                        card.response.rect.height() + THEME.spacing.medium;
+                    self.scroll_dismiss.view = Some(scroll_view);
+                }
+                ui.add_space(THEME.spacing.medium);
+            }
+        });
+        if !self.scroll_dismiss.ids.is_empty() && previous_offset != Some(output.state.offset.y) {
+            self.scroll_dismiss.deadline =
+                Some(ctx.input(|i| i.time) + SCROLL_DISMISS_DELAY_SECONDS);
+        }
+        self.scroll_memory.store(scroll_view, &output);
+
+        if let Some(author) = author_filter.take() {
+            self.feed_input = format!("author:{}", author);
+            self.search.deadline = Some(ctx.input(|i| i.time));
+        }
+
+        if let Some(action) = bulk_action {
+            self.apply_bulk_action(action, page_ids);
+        }
+
+        let open_details = clicked.and_then(|id| self.click_item(id));
+        self.render_item_details(ctx, open_details);
+
+        if let Some((id, link, dismissed, starred, later)) = focused_item {
+            match key {
+                Some(KeyAction::Open) => self.open_item(id),
+                Some(KeyAction::Dismiss) => self.set_dismissed(id, !dismissed),
+                Some(KeyAction::Star) => self.set_starred(id, !starred),
+                Some(KeyAction::Later) => self.set_later(id, !later),
+                Some(KeyAction::CopyLink) => self.copy_to_clipboard(link),
+                _ => {}
+            }
+        }
+
+        ui.horizontal_centered(|ui| {
+            ui.spacing_mut().button_padding = Vec2::new(10., 2.);
+            ui.with_layout(Layout::bottom_up(Align::LEFT), |ui| {
+                ui.horizontal(|ui| {
+                    let first = ui.add_enabled(self.feed_page > 0, Button::new("⏮"));
+                    widgets::describe(&first, WidgetType::Button, "First page");
+                    if first.clicked() {
+                        self.feed_page = 0;
+                    }
+                    let previous = ui.add_enabled(self.feed_page > 0, Button::new("<"));
+                    widgets::describe(&previous, WidgetType::Button, "Previous page");
+                    if previous.clicked() {
+                        self.feed_page -= 1;
+                    }
+                    ui.label("Page");
+                    let mut page_number = self.feed_page + 1;
+                    let jump = ui.add(
+                        egui::DragValue::new(&mut page_number)
+                            .clamp_range(1..=page_count)
+                            .speed(0.1),
+                    );
+                    widgets::describe(&jump, WidgetType::DragValue, "Page number");
+                    if jump.changed() {
+                        self.feed_page = page_number.clamp(1, page_count) - 1;
+                        self.focused_card = None;
+                    }
+                    ui.label(format!("of {}", page_count));
+                    let next = ui.add_enabled(!last_page, Button::new(">"));
+                    widgets::describe(&next, WidgetType::Button, "Next page");
+                    if next.clicked() {
+                        self.feed_page += 1;
+                    }
+                    let last = ui.add_enabled(!last_page, Button::new("⏭"));
+                    widgets::describe(&last, WidgetType::Button, "Last page");
+                    if last.clicked() {
+                        self.feed_page = page_count - 1;
+                    }
+                });
+            });
+
+            let modal = egui_modal::Modal::new(ctx, "modal_dismiss_all");
+
+            modal.show(|ui| {
+                modal.title(ui, "Warning");
+                let amount = self
+                    .feed_items
+                    .iter()
+                    .filter(|item| !item.dismissed && !item.later)
+                    .count();
+                modal.body(ui, format!("All new items will be dismissed! ({})", amount));
+                modal.buttons(ui, |ui| {
+                    ui.spacing_mut().button_padding = Vec2::new(8., 4.);
+                    if ui.add(Button::new("Close")).clicked() {
+                        modal.close();
+                    };
+                    if ui
+                        .add(Button::new("Confirm").fill(THEME.colors.warning))
+                        .clicked()
+                    {
+                        self.dismiss_all();
+                        modal.close();
+                    };
+                });
+            });
+
+            let matching_modal = egui_modal::Modal::new(ctx, "modal_dismiss_matching");
+
+            let has_matching = matching.is_some();
+            if let Some(ids) = matching {
+                matching_modal.show(|ui| {
+                    matching_modal.title(ui, "Warning");
+                    matching_modal.body(
+                        ui,
+                        format!(
+                            "{} items matching \"{}\" will be dismissed!",
+                            ids.len(),
+                            self.search.query
+                        ),
+                    );
+                    matching_modal.buttons(ui, |ui| {
+                        ui.spacing_mut().button_padding = Vec2::new(8., 4.);
+                        if ui.add(Button::new("Close")).clicked() {
+                            matching_modal.close();
+                        };
+                        if ui
+                            .add(Button::new("Confirm").fill(THEME.colors.warning))
+                            .clicked()
+                        {
+                            self.dismiss_matching(ids);
+                            matching_modal.close();
+                        };
+                    });
+                });
+                if key == Some(KeyAction::DismissMatching) {
+                    matching_modal.open();
+                }
+            }
+
+            ui.with_layout(Layout::bottom_up(Align::RIGHT), |ui| {
+                ui.with_layout(Layout::right_to_left(Align::BOTTOM), |ui| {
+                    if self.feed_type_combo == FeedTypeCombo::New
+                        && ui.link("Dismiss all").clicked()
+                    {
+                        modal.open();
+                    }
+                    if has_matching && ui.link("Dismiss matching").clicked() {
+                        matching_modal.open();
+                    }
+                });
+            });
+        });
+    }
+
+    fn render_channels_page(&mut self, ui: &mut egui::Ui) {
+        self.render_duplicate_channels(ui.ctx());
+        self.render_newsletter(ui.ctx());
+
+        if let Some(link) = self.clipboard_feed.clone() {
+            Frame {
+                fill: THEME.colors.bg,
+                inner_margin: Margin::same(6.0),
+                rounding: THEME.rounding.large,
+                ..Default::default()
+            }
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.spacing_mut().button_padding = Vec2::new(6., 3.);
+                    ui.add(
+                        Label::new(format!(
+                            "Subscribe to {}?",
+                            widgets::truncate(&link, 30, None)
+                        ))
+                        .wrap(true),
+                    )
+                    .on_hover_text(&link);
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        let close = ui.button("🗙");
+                        widgets::describe(&close, WidgetType::Button, "Ignore clipboard link");
+                        if close.clicked() {
+                            self.ignored_clipboard_feed = Some(link.clone());
+                            self.clipboard_feed = None;
+                        }
+                        if ui.button("Subscribe").clicked() {
+                            self.add_channel(&link);
+                            self.clipboard_feed = None;
+                        }
+                    });
+                });
+            });
+            ui.add_space(THEME.spacing.medium);
+        }
+
+        ui.horizontal(|ui| {
+            ui.spacing_mut().button_padding = Vec2::new(6., 4.);
+            if ui.button("Paste").clicked() {
+                let mut ctx = match copypasta::ClipboardContext::new() {
+                    Ok(ctx) => ctx,
                     Err(err) => {
-                        self.worker_status
-                            .worker_errors
-                            .push(WorkerError::new("Clipboard error", err.to_string()));
+                        self.worker_status.errors.push(WorkerError::new(
+                            ErrorKind::System,
+                            "Clipboard error",
+                            err.to_string(),
+                        ));
                         return;
                     }
                 };
                 let clipboard_content = match ctx.get_contents() {
                     Ok(ctx) => ctx,
                     Err(err) => {
-                        self.worker_status.worker_errors.push(WorkerError::new(
+                        self.worker_status.errors.push(WorkerError::new(
+                            ErrorKind::System,
                             "Failed to access clipboard",
                             err.to_string(),
                         ));
@@ -368,20 +1487,50 @@ impl TinyrssApp {
                 };
                 self.channel_input = clipboard_content;
             }
-            if ui
+            if self.pending_add.is_some() {
+                ui.spinner().on_hover_text("Subscribing…");
+            } else if ui
                 .add_enabled(!self.channel_input.is_empty(), Button::new("Add"))
                 .clicked()
             {
                 self.add_channel(&self.channel_input.clone());
                 self.channel_input = "".to_string();
             };
+            let mut config = ConfigBuilder::from_current();
+            let before = (config.backfill, config.backfill_count);
+            ComboBox::from_id_source("backfill_combo")
+                .selected_text(config.backfill.label())
+                .show_ui(ui, |ui| {
+                    for mode in Backfill::ALL {
+                        ui.selectable_value(&mut config.backfill, mode, mode.label());
+                    }
+                })
+                .response
+                .on_hover_text("Which items of new subscriptions show up as new,\nthe older ones are kept as dismissed.");
+            if config.backfill == Backfill::Newest {
+                ui.add(
+                    egui::DragValue::new(&mut config.backfill_count)
+                        .clamp_range(1..=500)
+                        .suffix(" items"),
+                );
+            }
+            if (config.backfill, config.backfill_count) != before {
+                config.apply();
+            }
             ui.add_sized(
                 ui.available_size(),
                 TextEdit::singleline(&mut self.channel_input)
-                    .hint_text("Search or add channels")
+                    .hint_text("Search or add channels, @accounts")
                     .margin(Vec2::new(6., 3.)),
             );
         });
+        if let Some(error) = &self.add_channel_error {
+            ui.add_space(THEME.spacing.small);
+            ui.add(
+                Label::new(RichText::new(format!("⚠ {}", error)).color(THEME.colors.warning))
+                    .wrap(true),
+            );
+        }
 
         if self.channels.is_empty() {
             ui.centered_and_justified(|ui| {
@@ -389,6 +1538,42 @@ impl TinyrssApp {
             });
         } else {
             ui.add_space(THEME.spacing.medium);
+            if self.channel_input.is_empty() {
+                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    if self.finding_duplicates {
+                        ui.spinner();
+                    } else {
+                        let find = ui.link("Find duplicates").on_hover_text(
+                            "Look for channels subscribed twice, e.g. over http and https\nor under links redirecting to each other.",
+                        );
+                        if find.clicked() {
+                            if let Some(sender) = &self.sender {
+                                self.finding_duplicates = true;
+                                sender.send(ToWorker::FindDuplicateChannels).unwrap();
+                            }
+                        }
+                    }
+                    ui.label("·");
+                    let newsletter = ui.link("Add newsletter").on_hover_text(
+                        "Get an email address whose mails arrive as a feed,\nfor publications only sent by email.",
+                    );
+                    if newsletter.clicked() {
+                        self.newsletter_title.clear();
+                        self.newsletter_result = None;
+                        egui_modal::Modal::new(ui.ctx(), "modal_newsletter").open();
+                    }
+                    ui.label("·");
+                    let group = ui.link("Group by site").on_hover_text(
+                        "Put channels of the same site into a folder, e.g. all subreddits.\nChannels in a folder already stay where they are.\nFolders can be changed under Settings › Manage channels.",
+                    );
+                    if group.clicked() {
+                        if let Some(sender) = &self.sender {
+                            sender.send(ToWorker::GroupChannelsBySite).unwrap();
+                        }
+                    }
+                });
+                ui.add_space(THEME.spacing.small);
+            }
             let search_result_exists = self.channels.iter().any(|channel| {
                 if let Some(title) = &channel.title {
                     return title
@@ -403,61 +1588,404 @@ impl TinyrssApp {
                     ui.label("No channels matched your search");
                 });
             } else {
-                ScrollArea::vertical().show(ui, |ui| {
-                    for channel in &self.channels {
-                        widgets::channel_card(
-                            ui,
-                            self.sender.clone(),
-                            channel,
-                            &self.channel_input,
-                        );
-                    }
-                });
+                let output = self
+                    .scroll_memory
+                    .area(ScrollView::Channels)
+                    .show(ui, |ui| {
+                        // Search results are listed flat, folders would mostly be empty.
+                        let mut folders: Vec<&str> = self
+                            .channels
+                            .iter()
+                            .filter_map(|channel| channel.folder.as_deref())
+                            .filter(|_| self.channel_input.is_empty())
+                            .collect();
+                        folders.sort_by_key(|folder| folder.to_lowercase());
+                        folders.dedup();
+                        for folder in folders {
+                            let channels: Vec<&Channel> = self
+                                .channels
+                                .iter()
+                                .filter(|channel| channel.folder.as_deref() == Some(folder))
+                                .collect();
+                            CollapsingHeader::new(
+                                RichText::new(format!("{} ({})", folder, channels.len())).strong(),
+                            )
+                            .id_source(("channel_folder", folder))
+                            .default_open(true)
+                            .show(ui, |ui| {
+                                for channel in channels {
+                                    widgets::channel_card(
+                                        ui,
+                                        self.sender.clone(),
+                                        channel,
+                                        &self.channel_input,
+                                    );
+                                }
+                            });
+                        }
+                        for channel in self.channels.iter().filter(|channel| {
+                            channel.folder.is_none() || !self.channel_input.is_empty()
+                        }) {
+                            widgets::channel_card(
+                                ui,
+                                self.sender.clone(),
+                                channel,
+                                &self.channel_input,
+                            );
+                        }
+                    });
+                self.scroll_memory.store(ScrollView::Channels, &output);
             }
         }
     }
 
-    fn render_settings_page(&mut self, ctx: &Context, ui: &mut egui::Ui) {
-        if self.worker_status.importing_channels {
-            ui.with_layout(
-                Layout::centered_and_justified(Direction::LeftToRight),
-                |ui| {
-                    ui.add(
-                        ProgressBar::new(self.worker_status.import_progress)
-                            .desired_width(300.0)
-                            .text("Import in progress...")
-                            .animate(true),
-                    )
-                },
-            );
-        } else {
-            ScrollArea::vertical().show(ui, |ui| {
-                self.render_general_settings(ctx, ui);
-                ui.add_space(THEME.spacing.large);
-                self.render_channels_settings(ctx, ui);
+    fn render_downloads_page(&mut self, ui: &mut egui::Ui) {
+        if self.downloads.is_empty() {
+            ui.centered_and_justified(|ui| {
+                ui.label("Nothing downloaded yet, use \"Download\" on items with attachments");
             });
+            return;
         }
-    }
 
-    fn render_general_settings(&mut self, _ctx: &Context, ui: &mut egui::Ui) {
-        CollapsingHeader::new(RichText::new("General").strong().heading())
-            .default_open(true)
+        ui.horizontal(|ui| {
+            ui.spacing_mut().button_padding = Vec2::new(6., 4.);
+            if ui.button("Open folder").clicked() {
+                let folder = CONFIG
+                    .lock()
+                    .download_folder
+                    .clone()
+                    .or_else(dirs::download_dir);
+                if let Some(Err(err)) = folder.map(open::that) {
+                    self.worker_status.errors.push(WorkerError::new(
+                        ErrorKind::Filesystem,
+                        "Failed to open download folder",
+                        err.to_string(),
+                    ));
+                }
+            }
+            if ui
+                .add_enabled(
+                    self.downloads.iter().any(|d| d.state.is_done()),
+                    Button::new("Clear finished"),
+                )
+                .clicked()
+            {
+                self.downloads.retain(|download| !download.state.is_done());
+            }
+        });
+        ui.add_space(THEME.spacing.medium);
+
+        let output = self
+            .scroll_memory
+            .area(ScrollView::Downloads)
             .show(ui, |ui| {
-                ui.add_space(THEME.spacing.large);
+                for download in self.downloads.iter().rev() {
+                    widgets::download_card(ui, self.sender.clone(), download);
+                    ui.add_space(THEME.spacing.medium);
+                }
+            });
+        self.scroll_memory.store(ScrollView::Downloads, &output);
+    }
+
+    fn render_history_page(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let input = ui.add(
+                TextEdit::singleline(&mut self.history_input)
+                    .hint_text("Search titles and links of every item ever seen")
+                    .desired_width(f32::INFINITY),
+            );
+            widgets::describe(&input, WidgetType::TextEdit, "Search history");
+            if input.changed() {
+                self.search_history();
+            }
+        });
+        ui.add_space(THEME.spacing.medium);
+
+        let Some((_, entries, total)) = &self.history else {
+            ui.centered_and_justified(|ui| {
+                ui.spinner();
+            });
+            return;
+        };
+        if entries.is_empty() {
+            ui.centered_and_justified(|ui| {
+                ui.label("Nothing found");
+            });
+            return;
+        }
+        if *total > entries.len() as i64 {
+            ui.label(
+                RichText::new(format!(
+                    "Showing the newest {} of {} matches",
+                    entries.len(),
+                    widgets::format_count(*total)
+                ))
+                .weak(),
+            );
+            ui.add_space(THEME.spacing.medium);
+        }
+
+        let output = self.scroll_memory.area(ScrollView::History).show(ui, |ui| {
+            for entry in entries {
                 ui.horizontal(|ui| {
-                    ui.label("Auto dismiss");
-                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Dismiss items just by opening them.");
+                    let title = entry.title.as_deref().unwrap_or("<no title>");
+                    ui.hyperlink_to(widgets::truncate(title, 80, None), &entry.link)
+                        .on_hover_text(&entry.link);
                     ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
-                        if ui
-                            .checkbox(&mut CONFIG.lock().auto_dismiss_on_open, "")
-                            .changed()
-                        {
-                            ConfigBuilder::from_current().apply();
-                        };
+                        ui.label(widgets::format_timestamp(entry.published))
+                            .on_hover_text(format!(
+                                "First seen {}",
+                                widgets::format_timestamp(entry.first_seen)
+                            ));
+                        if let Some(channel_title) = &entry.channel_title {
+                            ui.label("·");
+                            ui.label(widgets::truncate(channel_title, 30, None));
+                        }
+                        if entry.opened > 0 {
+                            ui.label("·");
+                            ui.label(RichText::new("opened").color(THEME.colors.text_dim))
+                                .on_hover_text(widgets::format_timestamp(entry.opened));
+                        }
                     });
                 });
-                ui.add_space(THEME.spacing.large);
-                ui.horizontal(|ui| {
+            }
+        });
+        self.scroll_memory.store(ScrollView::History, &output);
+    }
+
+    fn render_calendar_page(&mut self, ctx: &Context, ui: &mut egui::Ui) {
+        let month = self.calendar.month();
+        // Month to show instead, `Some(None)` returns to the current one.
+        let mut shown = None;
+        ui.horizontal(|ui| {
+            if ui.button("◀").on_hover_text("Previous month").clicked() {
+                shown = month.checked_sub_months(chrono::Months::new(1)).map(Some);
+            }
+            ui.label(
+                RichText::new(widgets::format_date(month, "%B %Y"))
+                    .strong()
+                    .heading(),
+            );
+            if ui.button("▶").on_hover_text("Next month").clicked() {
+                shown = month.checked_add_months(chrono::Months::new(1)).map(Some);
+            }
+            if self.calendar.month.is_some() && ui.button("Today").clicked() {
+                shown = Some(None);
+            }
+        });
+        if let Some(shown) = shown {
+            self.calendar.show(shown);
+            self.load_calendar();
+            return;
+        }
+        ui.add_space(THEME.spacing.medium);
+
+        let Some(counts) = &self.calendar.counts else {
+            ui.centered_and_justified(|ui| {
+                ui.spinner();
+            });
+            return;
+        };
+        let today = chrono::Local::now().date_naive();
+        let days = month
+            .checked_add_months(chrono::Months::new(1))
+            .map_or(31, |next| (next - month).num_days() as u32);
+        let mut selected = self.calendar.day;
+        let mut clicked = None;
+
+        let output = self
+            .scroll_memory
+            .area(ScrollView::Calendar)
+            .show(ui, |ui| {
+                egui::Grid::new("calendar_grid")
+                    .spacing(Vec2::splat(THEME.spacing.small))
+                    .show(ui, |ui| {
+                        // 2024-01-01 was a Monday.
+                        let monday = chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap_or(month);
+                        for weekday in 0..7 {
+                            let date = monday + chrono::Duration::days(weekday);
+                            ui.label(
+                                RichText::new(widgets::format_date(date, "%a"))
+                                    .color(THEME.colors.text_dim),
+                            );
+                        }
+                        ui.end_row();
+                        for _ in 0..month.weekday().num_days_from_monday() {
+                            ui.label("");
+                        }
+                        for day in 1..=days {
+                            let count = counts.get(&day).copied().unwrap_or_default();
+                            let date = month.with_day(day).unwrap_or(month);
+                            let mut text = RichText::new(format!(
+                                "{}\n{}",
+                                day,
+                                if count > 0 {
+                                    widgets::format_count(count)
+                                } else {
+                                    String::new()
+                                }
+                            ));
+                            if date == today {
+                                text = text.strong().color(THEME.colors.accent);
+                            }
+                            let cell = ui
+                                .add_enabled(
+                                    count > 0,
+                                    egui::SelectableLabel::new(selected == Some(day), text),
+                                )
+                                .on_hover_text(format!("{} items", count));
+                            if cell.clicked() {
+                                selected = (selected != Some(day)).then_some(day);
+                            }
+                            if date.weekday() == chrono::Weekday::Sun {
+                                ui.end_row();
+                            }
+                        }
+                    });
+
+                let Some(day) = selected else {
+                    return;
+                };
+                let date = month.with_day(day).unwrap_or(month);
+                let (Some(from), Some(to)) = (
+                    local_midnight(date),
+                    date.succ_opt().and_then(local_midnight),
+                ) else {
+                    return;
+                };
+                ui.add_space(THEME.spacing.large);
+                ui.label(
+                    RichText::new(widgets::format_date(date, "%A, %e %B"))
+                        .strong()
+                        .heading(),
+                );
+                ui.add_space(THEME.spacing.medium);
+                let items = self
+                    .feed_items
+                    .iter()
+                    .filter(|item| (from..to).contains(&item.published));
+                for item in items {
+                    ui.horizontal(|ui| {
+                        let title = item.title.as_deref().unwrap_or("<no title>");
+                        if ui
+                            .link(widgets::truncate(title, 80, None))
+                            .on_hover_text(&item.link)
+                            .clicked()
+                        {
+                            clicked = Some(item.id.clone());
+                        }
+                        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                            ui.label(widgets::format_time(item.published));
+                            if let Some(channel_title) = &item.channel_title {
+                                ui.label("·");
+                                ui.label(widgets::truncate(channel_title, 30, None));
+                            }
+                            if item.dismissed {
+                                ui.label("·");
+                                ui.label(RichText::new("dismissed").color(THEME.colors.text_dim));
+                            }
+                        });
+                    });
+                }
+            });
+        self.scroll_memory.store(ScrollView::Calendar, &output);
+        self.calendar.day = selected;
+
+        let open_details = clicked.and_then(|id| self.click_item(id));
+        self.render_item_details(ctx, open_details);
+    }
+
+    fn render_settings_page(&mut self, ctx: &Context, ui: &mut egui::Ui) {
+        if self.worker_status.importing_channels {
+            ui.with_layout(
+                Layout::centered_and_justified(Direction::LeftToRight),
+                |ui| {
+                    let progress = ui.add(
+                        ProgressBar::new(self.worker_status.import_progress)
+                            .desired_width(300.0)
+                            .text("Import in progress...")
+                            .animate(true),
+                    );
+                    widgets::describe_progress(
+                        &progress,
+                        "Importing channels",
+                        self.worker_status.import_progress,
+                    );
+                },
+            );
+        } else {
+            let output = self
+                .scroll_memory
+                .area(ScrollView::Settings)
+                .show(ui, |ui| {
+                    self.render_general_settings(ctx, ui);
+                    ui.add_space(THEME.spacing.large);
+                    self.render_appearance_settings(ctx, ui);
+                    ui.add_space(THEME.spacing.large);
+                    self.render_reader_settings(ui);
+                    ui.add_space(THEME.spacing.large);
+                    self.render_channels_settings(ctx, ui);
+                    ui.add_space(THEME.spacing.large);
+                    self.render_data_settings(ctx, ui);
+                    ui.add_space(THEME.spacing.large);
+                    self.render_cookies_settings(ui);
+                    if self.channels.iter().any(|c| c.last_error.is_some()) {
+                        ui.add_space(THEME.spacing.large);
+                        self.render_problems(ui);
+                    }
+                    ui.add_space(THEME.spacing.large);
+                    self.render_subscription_log(ui);
+                    ui.add_space(THEME.spacing.large);
+                    self.render_digest_settings(ui);
+                    ui.add_space(THEME.spacing.large);
+                    self.render_blocklist_settings(ui);
+                    ui.add_space(THEME.spacing.large);
+                    self.render_send_to_settings(ui);
+                    ui.add_space(THEME.spacing.large);
+                    self.render_integrations_settings(ctx, ui);
+                    ui.add_space(THEME.spacing.large);
+                    self.render_keybindings_settings(ctx, ui);
+                    ui.add_space(THEME.spacing.large);
+                    self.render_logs_settings(ctx, ui);
+                    ui.add_space(THEME.spacing.large);
+                    self.render_diagnostics(ui);
+                });
+            self.scroll_memory.store(ScrollView::Settings, &output);
+        }
+    }
+
+    fn render_general_settings(&mut self, _ctx: &Context, ui: &mut egui::Ui) {
+        CollapsingHeader::new(RichText::new("General").strong().heading())
+            .default_open(true)
+            .show(ui, |ui| {
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Auto dismiss");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Dismiss items just by opening them.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui
+                            .checkbox(&mut CONFIG.lock().auto_dismiss_on_open, "")
+                            .changed()
+                        {
+                            ConfigBuilder::from_current().apply();
+                        };
+                    });
+                });
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Dismiss on scroll");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Dismiss new items scrolled past the top of the feed.\nThey are dismissed together once scrolling pauses.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui
+                            .checkbox(&mut CONFIG.lock().dismiss_on_scroll, "")
+                            .changed()
+                        {
+                            ConfigBuilder::from_current().apply();
+                        };
+                    });
+                });
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
                     ui.label("Show feed search");
                     ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
                         if ui
@@ -465,6 +1993,23 @@ impl TinyrssApp {
                             .changed()
                         {
                             self.feed_input = String::new();
+                            self.search = Search::default();
+                            ConfigBuilder::from_current().apply();
+                        };
+                    });
+                });
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Items per page");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui
+                            .add(egui::Slider::new(
+                                &mut CONFIG.lock().items_per_page,
+                                5..=100,
+                            ))
+                            .changed()
+                        {
+                            self.feed_page = 0;
                             ConfigBuilder::from_current().apply();
                         };
                     });
@@ -485,50 +2030,754 @@ impl TinyrssApp {
                         };
                     });
                 });
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Requests per host");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Amount of requests to the same server at the same time.\nKeeps many feeds from one site from being fetched all at once.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui
+                            .add(egui::Slider::new(
+                                &mut CONFIG.lock().max_requests_per_host,
+                                1..=10,
+                            ))
+                            .changed()
+                        {
+                            ConfigBuilder::from_current().apply();
+                        };
+                    });
+                });
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Max feed size");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Downloads larger than this are stopped and reported as failed.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui
+                            .add(
+                                egui::Slider::new(&mut CONFIG.lock().max_feed_size_mb, 1..=100)
+                                    .suffix(" MB"),
+                            )
+                            .changed()
+                        {
+                            ConfigBuilder::from_current().apply();
+                        };
+                    });
+                });
+                ui.add_space(THEME.spacing.large);
+                let mut config = ConfigBuilder::from_current();
+                let mut changed = false;
+                ui.horizontal(|ui| {
+                    ui.label("DNS");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("How feed hosts are resolved. Use DNS over HTTPS or another server\nif your network blocks or redirects some feeds.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        let before = config.dns_mode;
+                        ComboBox::from_id_source("dns_mode_combo")
+                            .selected_text(dns_mode_label(config.dns_mode))
+                            .show_ui(ui, |ui| {
+                                for mode in [DnsMode::System, DnsMode::Https, DnsMode::Server] {
+                                    ui.selectable_value(
+                                        &mut config.dns_mode,
+                                        mode,
+                                        dns_mode_label(mode),
+                                    );
+                                }
+                            });
+                        changed |= config.dns_mode != before;
+                    });
+                });
+                if config.dns_mode != DnsMode::System {
+                    ui.add_space(THEME.spacing.medium);
+                    ui.horizontal(|ui| {
+                        ui.label(match config.dns_mode {
+                            DnsMode::Https => "Resolver url",
+                            _ => "Server address",
+                        });
+                        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                            changed |= ui
+                                .add(
+                                    TextEdit::singleline(&mut config.dns_server)
+                                        .hint_text(match config.dns_mode {
+                                            DnsMode::Https => "https://cloudflare-dns.com/dns-query",
+                                            _ => "9.9.9.9",
+                                        })
+                                        .desired_width(250.0),
+                                )
+                                .changed();
+                        });
+                    });
+                }
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Proxy");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Sends all requests through this proxy, http, https and socks5 are supported.\nEmpty uses the proxy set in the environment, e.g. HTTPS_PROXY.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        changed |= ui
+                            .add(
+                                TextEdit::singleline(&mut config.proxy)
+                                    .hint_text("socks5://127.0.0.1:9050")
+                                    .desired_width(250.0),
+                            )
+                            .changed();
+                    });
+                });
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Request timeout");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Requests taking longer as a whole are given up, so a slow server\ncan't hold up a refresh. Downloads only stop when no data arrives\nfor this long. 0 waits forever.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        changed |= ui
+                            .add(
+                                egui::DragValue::new(&mut config.request_timeout_seconds)
+                                    .clamp_range(0..=600)
+                                    .suffix(" s"),
+                            )
+                            .changed();
+                    });
+                });
+                if changed {
+                    config.apply();
+                }
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Auto refresh");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Refresh the feed in the background every N minutes,\neven while the window is minimized. 0 disables it.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut CONFIG.lock().auto_refresh_minutes)
+                                    .clamp_range(0..=1440)
+                                    .suffix(" min"),
+                            )
+                            .changed()
+                        {
+                            ConfigBuilder::from_current().apply();
+                        };
+                    });
+                });
+                ui.add_space(THEME.spacing.large);
+                let mut config = ConfigBuilder::from_current();
+                let mut changed = false;
+                ui.horizontal(|ui| {
+                    ui.label("Metered connections");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("On metered connections like phone hotspots refreshes fetch fewer feeds\nat a time and run automatically less often.\nWindows and NetworkManager on Linux report metered connections.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        let before = config.metered_mode;
+                        ComboBox::from_id_source("metered_mode_combo")
+                            .selected_text(config.metered_mode.label())
+                            .show_ui(ui, |ui| {
+                                for mode in MeteredMode::ALL {
+                                    ui.selectable_value(&mut config.metered_mode, mode, mode.label());
+                                }
+                            });
+                        changed |= config.metered_mode != before;
+                    });
+                });
+                if config.metered_mode != MeteredMode::Never {
+                    ui.add_space(THEME.spacing.medium);
+                    ui.horizontal(|ui| {
+                        ui.label("Requests when metered");
+                        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                            changed |= ui
+                                .add(egui::Slider::new(
+                                    &mut config.metered_concurrent_requests,
+                                    1..=10,
+                                ))
+                                .changed();
+                        });
+                    });
+                    ui.add_space(THEME.spacing.medium);
+                    ui.horizontal(|ui| {
+                        ui.label("Auto refresh when metered");
+                        ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Least minutes between automatic refreshes, 0 pauses them.\nNever more often than the regular auto refresh.");
+                        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                            changed |= ui
+                                .add(
+                                    egui::DragValue::new(&mut config.metered_refresh_minutes)
+                                        .clamp_range(0..=1440)
+                                        .suffix(" min"),
+                                )
+                                .changed();
+                        });
+                    });
+                }
+                if changed {
+                    config.apply();
+                }
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Detect feeds in clipboard");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Offer to subscribe when the clipboard contains a feed link\nwhile opening the Channels page.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui
+                            .checkbox(&mut CONFIG.lock().detect_feed_in_clipboard, "")
+                            .changed()
+                        {
+                            self.clipboard_feed = None;
+                            ConfigBuilder::from_current().apply();
+                        };
+                    });
+                });
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Local server");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Accept subscriptions from browser extensions on localhost,\nthey need the API token.\nChanges take effect after restart.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        let mut config = CONFIG.lock();
+                        let enabled = ui.checkbox(&mut config.enable_local_server, "").changed();
+                        let port_changed = ui
+                            .add_enabled(
+                                config.enable_local_server,
+                                egui::DragValue::new(&mut config.local_server_port)
+                                    .clamp_range(1024..=65535)
+                                    .prefix("port "),
+                            )
+                            .changed();
+                        drop(config);
+                        if enabled || port_changed {
+                            ConfigBuilder::from_current().apply();
+                        };
+                    });
+                });
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.spacing_mut().button_padding = Vec2::new(6., 3.);
+                    ui.label("API token");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Send as \"Authorization: Bearer <token>\" to subscribe through /subscribe\nand to use /api/ endpoints.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        let token = CONFIG.lock().local_server_token.to_string();
+                        if ui
+                            .add_enabled(!token.is_empty(), Button::new("Copy"))
+                            .clicked()
+                        {
+                            self.copy_to_clipboard(token);
+                        }
+                    });
+                });
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.spacing_mut().button_padding = Vec2::new(6., 3.);
+                    ui.label("Feed link");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Link of the republished feed for feed readers.\nIts token only opens the feed, not the API.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        let (port, token) = {
+                            let config = CONFIG.lock();
+                            (config.local_server_port, config.local_feed_token.to_string())
+                        };
+                        if ui
+                            .add_enabled(!token.is_empty(), Button::new("Copy"))
+                            .clicked()
+                        {
+                            self.copy_to_clipboard(format!(
+                                "http://127.0.0.1:{}/feed.xml?token={}",
+                                port, token
+                            ));
+                        }
+                    });
+                });
             });
     }
 
-    fn render_channels_settings(&mut self, ctx: &Context, ui: &mut egui::Ui) {
-        CollapsingHeader::new(RichText::new("Channels").strong().heading())
+    fn render_appearance_settings(&mut self, ctx: &Context, ui: &mut egui::Ui) {
+        CollapsingHeader::new(RichText::new("Appearance").strong().heading())
             .default_open(true)
             .show(ui, |ui| {
                 ui.spacing_mut().button_padding = Vec2::new(6., 3.);
                 ui.add_space(THEME.spacing.large);
                 ui.horizontal(|ui| {
-                    ui.label("OPML");
+                    ui.label("Timestamps");
                     ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
-                        if ui.button("Import").clicked() {
-                            if let Some(sender) = &self.sender {
-                                let path = rfd::FileDialog::new()
-                                    .add_filter("OPML", &["xml", "opml"])
-                                    .pick_file();
-                                self.worker_status.importing_channels = true;
-                                sender.send(ToWorker::ImportChannels { path }).unwrap();
-                            }
-                        }
-                        if ui.button("Export").clicked() {
-                            if let Some(sender) = &self.sender {
-                                sender.send(ToWorker::ExportChannels).unwrap();
-                            }
+                        let mut format = CONFIG.lock().timestamp_format;
+                        ComboBox::from_id_source("timestamp_format_combo")
+                            .selected_text(match format {
+                                TimestampFormat::Relative => "Relative",
+                                TimestampFormat::Absolute => "Local time",
+                                TimestampFormat::Iso => "ISO 8601",
+                            })
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(
+                                    &mut format,
+                                    TimestampFormat::Relative,
+                                    "Relative",
+                                );
+                                ui.selectable_value(
+                                    &mut format,
+                                    TimestampFormat::Absolute,
+                                    "Local time",
+                                );
+                                ui.selectable_value(&mut format, TimestampFormat::Iso, "ISO 8601");
+                            });
+                        if format != CONFIG.lock().timestamp_format {
+                            CONFIG.lock().timestamp_format = format;
+                            ConfigBuilder::from_current().apply();
                         }
-                    })
+                    });
                 });
                 ui.add_space(THEME.spacing.large);
-
-                let modal = egui_modal::Modal::new(ctx, "modal_manage_channels");
-
-                if !self.channels.is_empty() {
-                    let combo_id = ui.id().with("combo_channel");
-                    let mut combo_channel = ui.data_mut(|d| {
-                        d.get_temp::<String>(combo_id)
-                            .unwrap_or(self.channels[0].id.clone())
+                ui.horizontal(|ui| {
+                    ui.label("Stale items");
+                    ui.label(
+                        RichText::new("(?)")
+                            .color(THEME.colors.text_dim)
+                            .monospace(),
+                    )
+                    .on_hover_text(
+                        "Dim items published longer ago and show their age.\n0 disables it.",
+                    );
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut CONFIG.lock().stale_after_days)
+                                    .clamp_range(0..=365)
+                                    .suffix(" days"),
+                            )
+                            .changed()
+                        {
+                            ConfigBuilder::from_current().apply();
+                        };
                     });
-
-                    let edit_title_id = ui.id().with("edit_title");
-                    let mut edit_title =
-                        ui.data_mut(|d| d.get_temp::<String>(edit_title_id).unwrap_or_default());
-
-                    modal.show(|ui| {
+                });
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("CJK fallback font");
+                    ui.label(
+                        RichText::new("(?)")
+                            .color(THEME.colors.text_dim)
+                            .monospace(),
+                    )
+                    .on_hover_text(
+                        "Use an installed system font for Chinese, Japanese and Korean text.",
+                    );
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui
+                            .checkbox(&mut CONFIG.lock().cjk_fallback_font, "")
+                            .changed()
+                        {
+                            ConfigBuilder::from_current().apply();
+                            self.configure_fonts(ctx);
+                        };
+                    });
+                });
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Custom fonts");
+                    ui.label(
+                        RichText::new("(?)")
+                            .color(THEME.colors.text_dim)
+                            .monospace(),
+                    )
+                    .on_hover_text("Font files used before the built-in fonts.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui.button("Add").clicked() {
+                            if let Some(path) = rfd::FileDialog::new()
+                                .add_filter("Fonts", &["ttf", "otf", "ttc"])
+                                .pick_file()
+                            {
+                                CONFIG.lock().custom_fonts.push(path);
+                                ConfigBuilder::from_current().apply();
+                                self.configure_fonts(ctx);
+                            }
+                        }
+                    });
+                });
+                let mut removed = None;
+                for (index, path) in CONFIG.lock().custom_fonts.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(
+                            RichText::new(widgets::truncate(&path.to_string_lossy(), 45, None))
+                                .color(THEME.colors.text_dim),
+                        )
+                        .on_hover_text(path.to_string_lossy());
+                        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                            let remove = ui.button("🗙");
+                            widgets::describe(&remove, WidgetType::Button, "Remove font");
+                            if remove.clicked() {
+                                removed = Some(index);
+                            }
+                        });
+                    });
+                }
+                if let Some(index) = removed {
+                    CONFIG.lock().custom_fonts.remove(index);
+                    ConfigBuilder::from_current().apply();
+                    self.configure_fonts(ctx);
+                }
+            });
+    }
+
+    fn render_reader_settings(&mut self, ui: &mut egui::Ui) {
+        CollapsingHeader::new(RichText::new("Reader").strong().heading())
+            .default_open(true)
+            .show(ui, |ui| {
+                ui.spacing_mut().button_padding = Vec2::new(6., 3.);
+                let mut config = ConfigBuilder::from_current();
+                let mut changed = false;
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Font");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        changed |= ui
+                            .add(
+                                egui::DragValue::new(&mut config.reader_font_size)
+                                    .clamp_range(10.0..=32.0)
+                                    .speed(0.2)
+                                    .suffix(" pt"),
+                            )
+                            .changed();
+                        ComboBox::from_id_source("reader_font_combo")
+                            .selected_text(match config.reader_font {
+                                ReaderFont::Proportional => "Proportional",
+                                ReaderFont::Monospace => "Monospace",
+                            })
+                            .show_ui(ui, |ui| {
+                                changed |= ui
+                                    .selectable_value(
+                                        &mut config.reader_font,
+                                        ReaderFont::Proportional,
+                                        "Proportional",
+                                    )
+                                    .changed();
+                                changed |= ui
+                                    .selectable_value(
+                                        &mut config.reader_font,
+                                        ReaderFont::Monospace,
+                                        "Monospace",
+                                    )
+                                    .changed();
+                            });
+                    });
+                });
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Line width");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        changed |= ui
+                            .add(
+                                egui::DragValue::new(&mut config.reader_line_width)
+                                    .clamp_range(300.0..=1200.0)
+                                    .speed(2.0)
+                                    .suffix(" pt"),
+                            )
+                            .changed();
+                    });
+                });
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Line height");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        changed |= ui
+                            .add(
+                                egui::DragValue::new(&mut config.reader_line_height)
+                                    .clamp_range(1.0..=2.5)
+                                    .speed(0.01)
+                                    .fixed_decimals(2)
+                                    .suffix("×"),
+                            )
+                            .changed();
+                    });
+                });
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Justify text");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        changed |= ui.checkbox(&mut config.reader_justify, "").changed();
+                    });
+                });
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Sepia");
+                    ui.label(
+                        RichText::new("(?)")
+                            .color(THEME.colors.text_dim)
+                            .monospace(),
+                    )
+                    .on_hover_text("Dark text on warm paper instead of the app's colors.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        changed |= ui.checkbox(&mut config.reader_sepia, "").changed();
+                    });
+                });
+                if changed {
+                    config.apply();
+                }
+            });
+    }
+
+    fn render_channels_settings(&mut self, ctx: &Context, ui: &mut egui::Ui) {
+        CollapsingHeader::new(RichText::new("Channels").strong().heading())
+            .default_open(true)
+            .show(ui, |ui| {
+                ui.spacing_mut().button_padding = Vec2::new(6., 3.);
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("OPML");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui.button("Import").clicked() {
+                            if let Some(sender) = &self.sender {
+                                let path = rfd::FileDialog::new()
+                                    .add_filter("OPML", &["xml", "opml"])
+                                    .pick_file();
+                                self.worker_status.importing_channels = true;
+                                sender.send(ToWorker::ImportChannels { path }).unwrap();
+                            }
+                        }
+                        if ui.button("Export").clicked() {
+                            if let Some(sender) = &self.sender {
+                                sender.send(ToWorker::ExportChannels).unwrap();
+                            }
+                        }
+                    })
+                });
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("OPML from URL");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Imports a list of channels published by another service.\nKept in sync, the list is checked daily and channels added to it are subscribed to.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        let url = self.opml_url_input.trim().to_string();
+                        if ui
+                            .add_enabled(!url.is_empty(), Button::new("Import"))
+                            .clicked()
+                        {
+                            if let Some(sender) = &self.sender {
+                                self.worker_status.importing_channels = true;
+                                sender
+                                    .send(ToWorker::ImportChannelsFromUrl {
+                                        url,
+                                        keep_synced: self.opml_keep_synced,
+                                    })
+                                    .unwrap();
+                                self.opml_url_input.clear();
+                            }
+                        }
+                        ui.checkbox(&mut self.opml_keep_synced, "Keep in sync");
+                        ui.add(
+                            TextEdit::singleline(&mut self.opml_url_input)
+                                .hint_text("https://")
+                                .desired_width(ui.available_width()),
+                        );
+                    })
+                });
+                let synced: Vec<String> = CONFIG
+                    .lock()
+                    .opml_subscriptions
+                    .iter()
+                    .map(|list| list.url.clone())
+                    .collect();
+                for url in synced {
+                    ui.add_space(THEME.spacing.medium);
+                    ui.horizontal(|ui| {
+                        ui.label(
+                            RichText::new(widgets::truncate(&url, 50, None))
+                                .color(THEME.colors.text_dim),
+                        )
+                        .on_hover_text(&url);
+                        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                            let stop = ui.button("🗙");
+                            widgets::describe(
+                                &stop,
+                                WidgetType::Button,
+                                format!("Stop syncing {}", url),
+                            );
+                            if stop.clicked() {
+                                let mut config = ConfigBuilder::from_current();
+                                config.opml_subscriptions.retain(|list| list.url != url);
+                                config.apply();
+                            }
+                        });
+                    });
+                }
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("High-priority sound");
+                    ui.label(
+                        RichText::new("(?)")
+                            .color(THEME.colors.text_dim)
+                            .monospace(),
+                    )
+                    .on_hover_text("Play the system's alert sound when high-priority channels get new items.\nChannels are marked as high priority in \"Manage channels\".");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        let mut priority_sound = CONFIG.lock().priority_sound;
+                        if ui.checkbox(&mut priority_sound, "").changed() {
+                            CONFIG.lock().priority_sound = priority_sound;
+                            ConfigBuilder::from_current().apply();
+                        }
+                    });
+                });
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Item limit");
+                    ui.label(
+                        RichText::new("(?)")
+                            .color(THEME.colors.text_dim)
+                            .monospace(),
+                    )
+                    .on_hover_text("Keep only the newest entries of each fetch, so feeds listing their\nwhole archive don't flood the feed. 0 keeps all.\nChannels can have their own limit in \"Manage channels\".");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut CONFIG.lock().item_limit)
+                                    .clamp_range(0..=10000),
+                            )
+                            .changed()
+                        {
+                            ConfigBuilder::from_current().apply();
+                        }
+                    });
+                });
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Starred items");
+                    ui.label(
+                        RichText::new("(?)")
+                            .color(THEME.colors.text_dim)
+                            .monospace(),
+                    )
+                    .on_hover_text("Save starred items as a standalone HTML page.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui.button("Export HTML").clicked() {
+                            if let Some(sender) = &self.sender {
+                                sender.send(ToWorker::ExportStarred).unwrap();
+                            }
+                        }
+                    })
+                });
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Republish");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Keep an RSS file of these items up to date.\nAlso served by the local server, see Feed link under General.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        let mut config = ConfigBuilder::from_current();
+                        let mut changed = false;
+                        if config.republish_file.is_some() {
+                            if ui.button("Stop").clicked() {
+                                config.republish_file = None;
+                                changed = true;
+                            }
+                        } else if ui.button("Choose file").clicked() {
+                            if let Some(path) = rfd::FileDialog::new()
+                                .add_filter("RSS", &["xml", "rss"])
+                                .set_file_name("tinyrss.xml")
+                                .save_file()
+                            {
+                                config.republish_file = Some(path);
+                                changed = true;
+                            }
+                        }
+                        ComboBox::from_id_source("republished_items_combo")
+                            .selected_text(match config.republished_items {
+                                RepublishedItems::Starred => "Starred",
+                                RepublishedItems::New => "New",
+                            })
+                            .show_ui(ui, |ui| {
+                                changed |= ui
+                                    .selectable_value(
+                                        &mut config.republished_items,
+                                        RepublishedItems::Starred,
+                                        "Starred",
+                                    )
+                                    .changed();
+                                changed |= ui
+                                    .selectable_value(
+                                        &mut config.republished_items,
+                                        RepublishedItems::New,
+                                        "New",
+                                    )
+                                    .changed();
+                            });
+                        if changed {
+                            config.apply();
+                            if let Some(sender) = &self.sender {
+                                sender.send(ToWorker::Republish).unwrap();
+                            }
+                        }
+                    })
+                });
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Unsubscribing");
+                    ui.label(
+                        RichText::new("(?)")
+                            .color(THEME.colors.text_dim)
+                            .monospace(),
+                    )
+                    .on_hover_text("Archived channels are no longer refreshed or listed,\nbut their items stay in the feed and in search.\nSubscribing to the same link again restores them.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        let mut config = ConfigBuilder::from_current();
+                        let mut changed = false;
+                        ComboBox::from_id_source("unsubscribe_mode_combo")
+                            .selected_text(match config.unsubscribe_mode {
+                                UnsubscribeMode::Delete => "Delete channel and items",
+                                UnsubscribeMode::Archive => "Archive channel",
+                            })
+                            .show_ui(ui, |ui| {
+                                changed |= ui
+                                    .selectable_value(
+                                        &mut config.unsubscribe_mode,
+                                        UnsubscribeMode::Delete,
+                                        "Delete channel and items",
+                                    )
+                                    .changed();
+                                changed |= ui
+                                    .selectable_value(
+                                        &mut config.unsubscribe_mode,
+                                        UnsubscribeMode::Archive,
+                                        "Archive channel",
+                                    )
+                                    .changed();
+                            });
+                        if changed {
+                            config.apply();
+                        }
+                    })
+                });
+                ui.add_space(THEME.spacing.large);
+
+                let modal = egui_modal::Modal::new(ctx, "modal_manage_channels");
+
+                if !self.channels.is_empty() {
+                    let combo_id = ui.id().with("combo_channel");
+                    let mut combo_channel = ui.data_mut(|d| {
+                        d.get_temp::<String>(combo_id)
+                            .unwrap_or(self.channels[0].id.clone())
+                    });
+
+                    let edit_title_id = ui.id().with("edit_title");
+                    let mut edit_title =
+                        ui.data_mut(|d| d.get_temp::<String>(edit_title_id).unwrap_or_default());
+
+                    let edit_folder_id = ui.id().with("edit_folder");
+                    let mut edit_folder = ui.data_mut(|d| {
+                        d.get_temp::<(String, String)>(edit_folder_id)
+                            .filter(|(id, _)| *id == combo_channel)
+                            .map(|(_, folder)| folder)
+                    });
+
+                    let edit_notes_id = ui.id().with("edit_notes");
+                    let mut edit_notes = ui.data_mut(|d| {
+                        d.get_temp::<(String, String)>(edit_notes_id)
+                            .filter(|(id, _)| *id == combo_channel)
+                            .map(|(_, notes)| notes)
+                    });
+
+                    let edit_command_id = ui.id().with("edit_click_command");
+                    let mut edit_command = ui.data_mut(|d| {
+                        d.get_temp::<(String, String)>(edit_command_id)
+                            .filter(|(id, _)| *id == combo_channel)
+                            .map(|(_, command)| command)
+                    });
+
+                    let edit_limit_id = ui.id().with("edit_item_limit");
+                    let mut edit_limit = ui.data_mut(|d| {
+                        d.get_temp::<(String, Option<i64>)>(edit_limit_id)
+                            .filter(|(id, _)| *id == combo_channel)
+                            .map(|(_, limit)| limit)
+                    });
+
+                    let edit_color_id = ui.id().with("edit_color");
+                    let mut edit_color = ui.data_mut(|d| {
+                        d.get_temp::<(String, egui::Color32)>(edit_color_id)
+                            .filter(|(id, _)| *id == combo_channel)
+                            .map(|(_, color)| color)
+                    });
+
+                    modal.show(|ui| {
                         modal.title(ui, "Manage channels");
                         modal.frame(ui, |ui| {
                             ui.add_space(THEME.spacing.medium);
@@ -567,100 +2816,2759 @@ impl TinyrssApp {
                                         .desired_width(ui.available_width()),
                                 );
                             });
-                        });
-                        modal.buttons(ui, |ui| {
-                            ui.spacing_mut().button_padding = Vec2::new(8., 4.);
-                            if ui.add(Button::new("Close")).clicked() {
-                                modal.close();
-                            };
-                            if ui
-                                .add_enabled(!edit_title.is_empty(), Button::new("Save"))
-                                .clicked()
-                            {
-                                let channel = self
+                            ui.add_space(THEME.spacing.large);
+                            ui.horizontal(|ui| {
+                                ui.label("Folder:");
+                                let mut folder = edit_folder.clone().unwrap_or_else(|| {
+                                    self.channels
+                                        .iter()
+                                        .find(|c| c.id == combo_channel)
+                                        .and_then(|c| c.folder.clone())
+                                        .unwrap_or_default()
+                                });
+                                if ui
+                                    .add(
+                                        TextEdit::singleline(&mut folder)
+                                            .hint_text("None")
+                                            .desired_width(ui.available_width()),
+                                    )
+                                    .changed()
+                                {
+                                    edit_folder = Some(folder);
+                                }
+                            });
+                            ui.add_space(THEME.spacing.large);
+                            ui.horizontal(|ui| {
+                                ui.label("Notes:");
+                                let mut notes = edit_notes.clone().unwrap_or_else(|| {
+                                    self.channels
+                                        .iter()
+                                        .find(|c| c.id == combo_channel)
+                                        .and_then(|c| c.notes.clone())
+                                        .unwrap_or_default()
+                                });
+                                if ui
+                                    .add(
+                                        TextEdit::multiline(&mut notes)
+                                            .hint_text("Why you subscribed, login hints…")
+                                            .desired_rows(2)
+                                            .desired_width(ui.available_width()),
+                                    )
+                                    .changed()
+                                {
+                                    edit_notes = Some(notes);
+                                }
+                            });
+                            ui.add_space(THEME.spacing.large);
+                            ui.horizontal(|ui| {
+                                ui.label("Item limit:");
+                                let limit = edit_limit.unwrap_or_else(|| {
+                                    self.channels
+                                        .iter()
+                                        .find(|c| c.id == combo_channel)
+                                        .and_then(|c| c.item_limit)
+                                });
+                                let default = CONFIG.lock().item_limit as i64;
+                                let mut own = limit.is_some();
+                                let mut value = limit.unwrap_or(default);
+                                let mut changed = ui.checkbox(&mut own, "Own").changed();
+                                changed |= ui
+                                    .add_enabled(
+                                        own,
+                                        egui::DragValue::new(&mut value).clamp_range(0..=10000),
+                                    )
+                                    .changed();
+                                ui.label(
+                                    RichText::new("(?)")
+                                        .color(THEME.colors.text_dim)
+                                        .monospace(),
+                                )
+                                .on_hover_text(format!(
+                                    "Newest entries kept from each fetch, 0 keeps all.\nOtherwise the limit from Settings › Channels applies ({}).",
+                                    default
+                                ));
+                                if changed {
+                                    edit_limit = Some(own.then_some(value));
+                                }
+                            });
+                            ui.add_space(THEME.spacing.large);
+                            ui.horizontal(|ui| {
+                                ui.label("Color:");
+                                let current = self
+                                    .channels
+                                    .iter()
+                                    .find(|c| c.id == combo_channel)
+                                    .and_then(|c| c.color.as_deref())
+                                    .and_then(widgets::parse_color);
+                                let mut color = edit_color.or(current).unwrap_or(THEME.colors.bg);
+                                if ui.color_edit_button_srgba(&mut color).changed() {
+                                    edit_color = Some(color);
+                                }
+                                if ui
+                                    .add_enabled(current.is_some(), Button::new("Clear"))
+                                    .clicked()
+                                {
+                                    edit_color = None;
+                                    if let Some(sender) = &self.sender {
+                                        sender
+                                            .send(ToWorker::SetChannelColor {
+                                                id: combo_channel.clone(),
+                                                color: None,
+                                            })
+                                            .unwrap();
+                                    }
+                                }
+                            });
+                            ui.add_space(THEME.spacing.large);
+                            let (mut action, command) = self
+                                .channels
+                                .iter()
+                                .find(|c| c.id == combo_channel)
+                                .map(|c| (c.click_action, c.click_command.clone()))
+                                .unwrap_or_default();
+                            let previous_action = action;
+                            ui.horizontal(|ui| {
+                                ui.label("On click:");
+                                ComboBox::from_id_source("channel_click_action_combo")
+                                    .selected_text(action.label())
+                                    .show_ui(ui, |ui| {
+                                        for option in ClickAction::ALL {
+                                            ui.selectable_value(
+                                                &mut action,
+                                                option,
+                                                option.label(),
+                                            );
+                                        }
+                                    });
+                                ui.label(
+                                    RichText::new("(?)")
+                                        .color(THEME.colors.text_dim)
+                                        .monospace(),
+                                )
+                                .on_hover_text(
+                                    "What clicking one of the channel's items in the feed does.\nThe player is set under Settings › Send to, commands get {url} and {title}.\nItems without an attachment or command open the reader view.",
+                                );
+                            });
+                            if action != previous_action {
+                                if let Some(sender) = &self.sender {
+                                    sender
+                                        .send(ToWorker::SetChannelClickAction {
+                                            id: combo_channel.clone(),
+                                            action,
+                                            command: command.clone(),
+                                        })
+                                        .unwrap();
+                                }
+                            }
+                            if action == ClickAction::Command {
+                                ui.add_space(THEME.spacing.large);
+                                ui.horizontal(|ui| {
+                                    ui.label("Command:");
+                                    let mut text = edit_command
+                                        .clone()
+                                        .unwrap_or_else(|| command.clone().unwrap_or_default());
+                                    if ui
+                                        .add(
+                                            TextEdit::singleline(&mut text)
+                                                .hint_text("mpv {url}")
+                                                .desired_width(ui.available_width()),
+                                        )
+                                        .changed()
+                                    {
+                                        edit_command = Some(text);
+                                    }
+                                });
+                            }
+                            ui.add_space(THEME.spacing.large);
+                            ui.horizontal(|ui| {
+                                let mut priority = self
+                                    .channels
+                                    .iter()
+                                    .find(|c| c.id == combo_channel)
+                                    .is_some_and(|c| c.priority);
+                                if ui.checkbox(&mut priority, "High priority").changed() {
+                                    if let Some(sender) = &self.sender {
+                                        sender
+                                            .send(ToWorker::SetChannelPriority {
+                                                id: combo_channel.clone(),
+                                                priority,
+                                            })
+                                            .unwrap();
+                                    }
+                                }
+                                ui.label(
+                                    RichText::new("(?)")
+                                        .color(THEME.colors.text_dim)
+                                        .monospace(),
+                                )
+                                .on_hover_text(
+                                    "New items play the alert sound and stay on top of the feed for a day.",
+                                );
+                            });
+                            ui.add_space(THEME.spacing.large);
+                            ui.horizontal(|ui| {
+                                let mut cache_articles = self
+                                    .channels
+                                    .iter()
+                                    .find(|c| c.id == combo_channel)
+                                    .is_some_and(|c| c.cache_articles);
+                                if ui
+                                    .checkbox(&mut cache_articles, "Cache full articles")
+                                    .changed()
+                                {
+                                    if let Some(sender) = &self.sender {
+                                        sender
+                                            .send(ToWorker::SetChannelCaching {
+                                                id: combo_channel.clone(),
+                                                cache_articles,
+                                            })
+                                            .unwrap();
+                                    }
+                                }
+                                ui.label(
+                                    RichText::new("(?)")
+                                        .color(THEME.colors.text_dim)
+                                        .monospace(),
+                                )
+                                .on_hover_text(
+                                    "After each refresh the web pages of new items are downloaded,\nso they can be read in the item details without a connection.",
+                                );
+                            });
+                            ui.add_space(THEME.spacing.large);
+                            ui.horizontal(|ui| {
+                                let (detected, mut quirks) = self
+                                    .channels
+                                    .iter()
+                                    .find(|c| c.id == combo_channel)
+                                    .map(|c| (c.quirks.is_none(), Quirk::for_channel(c)))
+                                    .unwrap_or_default();
+                                let mut changed = false;
+                                ui.label("Quirks:");
+                                for quirk in Quirk::ALL {
+                                    let mut enabled = quirks.contains(&quirk);
+                                    if ui.checkbox(&mut enabled, quirk.label()).changed() {
+                                        quirks.retain(|q| *q != quirk);
+                                        if enabled {
+                                            quirks.push(quirk);
+                                        }
+                                        changed = true;
+                                    }
+                                }
+                                ui.label(
+                                    RichText::new("(?)")
+                                        .color(THEME.colors.text_dim)
+                                        .monospace(),
+                                )
+                                .on_hover_text(
+                                    "Fix-ups for the feeds of these sites, applied to items from the next refresh on.\nReddit: drops the \"submitted by\" footer. YouTube: video links, thumbnails and playback.\nHacker News: keeps the discussion as the Comments link.\nSocial: titles Mastodon and Bluesky posts with their first words.\nChosen from the channel's link unless changed here.",
+                                );
+                                if !detected && ui.link("Detect").clicked() {
+                                    if let Some(sender) = &self.sender {
+                                        sender
+                                            .send(ToWorker::SetChannelQuirks {
+                                                id: combo_channel.clone(),
+                                                quirks: None,
+                                            })
+                                            .unwrap();
+                                    }
+                                }
+                                if changed {
+                                    if let Some(sender) = &self.sender {
+                                        sender
+                                            .send(ToWorker::SetChannelQuirks {
+                                                id: combo_channel.clone(),
+                                                quirks: Some(quirks),
+                                            })
+                                            .unwrap();
+                                    }
+                                }
+                            });
+                            let fingerprint = self
+                                .channels
+                                .iter()
+                                .find(|c| c.id == combo_channel)
+                                .and_then(|c| c.tls_fingerprint.clone());
+                            if let Some(fingerprint) = fingerprint {
+                                ui.add_space(THEME.spacing.large);
+                                ui.horizontal(|ui| {
+                                    ui.label("Trusted certificate:")
+                                        .on_hover_text(format!("SHA-256: {}", fingerprint));
+                                    if ui.button("Forget").clicked() {
+                                        if let Some(sender) = &self.sender {
+                                            sender
+                                                .send(ToWorker::SetTrustedCertificate {
+                                                    id: combo_channel.clone(),
+                                                    fingerprint: None,
+                                                })
+                                                .unwrap();
+                                        }
+                                    }
+                                });
+                            }
+                            ui.add_space(THEME.spacing.large);
+                            let mut headers: Vec<(String, String)> = self
+                                .channel_headers
+                                .iter()
+                                .filter(|header| header.channel == combo_channel)
+                                .map(|header| (header.name.clone(), header.value.clone()))
+                                .collect();
+                            let mut headers_changed = false;
+                            ui.horizontal(|ui| {
+                                ui.label("Headers:");
+                                ui.label(
+                                    RichText::new("(?)")
+                                        .color(THEME.colors.text_dim)
+                                        .monospace(),
+                                )
+                                .on_hover_text(
+                                    "Sent with every request for this channel,\ne.g. Authorization or Accept.",
+                                );
+                            });
+                            let mut removed = None;
+                            for (index, (name, value)) in headers.iter().enumerate() {
+                                ui.horizontal(|ui| {
+                                    ui.label(RichText::new(format!("{}:", name)).monospace());
+                                    ui.label(
+                                        RichText::new(widgets::truncate(value, 30, None))
+                                            .monospace()
+                                            .color(THEME.colors.text_dim),
+                                    );
+                                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                                        let remove = ui.button("🗙");
+                                        widgets::describe(
+                                            &remove,
+                                            WidgetType::Button,
+                                            format!("Remove header {}", name),
+                                        );
+                                        if remove.clicked() {
+                                            removed = Some(index);
+                                        }
+                                    });
+                                });
+                            }
+                            if let Some(index) = removed {
+                                headers.remove(index);
+                                headers_changed = true;
+                            }
+                            ui.horizontal(|ui| {
+                                ui.add(
+                                    TextEdit::singleline(&mut self.header_name_input)
+                                        .hint_text("Name")
+                                        .desired_width(120.0),
+                                );
+                                ui.add(
+                                    TextEdit::singleline(&mut self.header_value_input)
+                                        .hint_text("Value")
+                                        .desired_width(200.0),
+                                );
+                                let name = self.header_name_input.trim().to_string();
+                                if ui
+                                    .add_enabled(!name.is_empty(), Button::new("Add"))
+                                    .clicked()
+                                {
+                                    headers.retain(|(existing, _)| !existing.eq_ignore_ascii_case(&name));
+                                    headers.push((name, self.header_value_input.trim().to_string()));
+                                    self.header_name_input.clear();
+                                    self.header_value_input.clear();
+                                    headers_changed = true;
+                                }
+                            });
+                            if headers_changed {
+                                if let Some(sender) = &self.sender {
+                                    sender
+                                        .send(ToWorker::SetChannelHeaders {
+                                            id: combo_channel.clone(),
+                                            headers,
+                                        })
+                                        .unwrap();
+                                }
+                            }
+                        });
+                        modal.buttons(ui, |ui| {
+                            ui.spacing_mut().button_padding = Vec2::new(8., 4.);
+                            if ui.add(Button::new("Close")).clicked() {
+                                modal.close();
+                            };
+                            if ui
+                                .add_enabled(
+                                    !edit_title.is_empty()
+                                        || edit_color.is_some()
+                                        || edit_folder.is_some()
+                                        || edit_command.is_some()
+                                        || edit_notes.is_some()
+                                        || edit_limit.is_some(),
+                                    Button::new("Save"),
+                                )
+                                .clicked()
+                            {
+                                let channel = self
+                                    .channels
+                                    .iter()
+                                    .find(|c| c.id == combo_channel)
+                                    .unwrap();
+                                if let Some(sender) = &self.sender {
+                                    if !edit_title.is_empty() {
+                                        sender
+                                            .send(ToWorker::EditChannel {
+                                                id: channel.id.clone(),
+                                                title: edit_title.clone(),
+                                            })
+                                            .unwrap();
+                                    }
+                                    if let Some(folder) = edit_folder.take() {
+                                        let folder = folder.trim();
+                                        sender
+                                            .send(ToWorker::SetChannelFolder {
+                                                id: channel.id.clone(),
+                                                folder: (!folder.is_empty())
+                                                    .then(|| folder.to_string()),
+                                            })
+                                            .unwrap();
+                                    }
+                                    if let Some(notes) = edit_notes.take() {
+                                        let notes = notes.trim();
+                                        sender
+                                            .send(ToWorker::SetChannelNotes {
+                                                id: channel.id.clone(),
+                                                notes: (!notes.is_empty())
+                                                    .then(|| notes.to_string()),
+                                            })
+                                            .unwrap();
+                                    }
+                                    if let Some(limit) = edit_limit.take() {
+                                        sender
+                                            .send(ToWorker::SetChannelItemLimit {
+                                                id: channel.id.clone(),
+                                                limit,
+                                            })
+                                            .unwrap();
+                                    }
+                                    if let Some(command) = edit_command.take() {
+                                        let command = command.trim();
+                                        sender
+                                            .send(ToWorker::SetChannelClickAction {
+                                                id: channel.id.clone(),
+                                                action: channel.click_action,
+                                                command: (!command.is_empty())
+                                                    .then(|| command.to_string()),
+                                            })
+                                            .unwrap();
+                                    }
+                                    if let Some(color) = edit_color.take() {
+                                        sender
+                                            .send(ToWorker::SetChannelColor {
+                                                id: channel.id.clone(),
+                                                color: Some(widgets::color_to_hex(color)),
+                                            })
+                                            .unwrap();
+                                    }
+                                }
+                                modal.close();
+                            };
+                        });
+                    });
+
+                    match edit_color {
+                        Some(color) => ui.data_mut(|d| {
+                            d.insert_temp(edit_color_id, (combo_channel.clone(), color))
+                        }),
+                        None => ui.data_mut(|d| d.remove::<(String, egui::Color32)>(edit_color_id)),
+                    }
+                    match edit_limit {
+                        Some(limit) => ui.data_mut(|d| {
+                            d.insert_temp(edit_limit_id, (combo_channel.clone(), limit))
+                        }),
+                        None => ui.data_mut(|d| d.remove::<(String, Option<i64>)>(edit_limit_id)),
+                    }
+                    match edit_notes {
+                        Some(notes) => ui.data_mut(|d| {
+                            d.insert_temp(edit_notes_id, (combo_channel.clone(), notes))
+                        }),
+                        None => ui.data_mut(|d| d.remove::<(String, String)>(edit_notes_id)),
+                    }
+                    match edit_command {
+                        Some(command) => ui.data_mut(|d| {
+                            d.insert_temp(edit_command_id, (combo_channel.clone(), command))
+                        }),
+                        None => ui.data_mut(|d| d.remove::<(String, String)>(edit_command_id)),
+                    }
+                    match edit_folder {
+                        Some(folder) => ui.data_mut(|d| {
+                            d.insert_temp(edit_folder_id, (combo_channel.clone(), folder))
+                        }),
+                        None => ui.data_mut(|d| d.remove::<(String, String)>(edit_folder_id)),
+                    }
+                    ui.data_mut(|d| d.insert_temp(combo_id, combo_channel));
+                    ui.data_mut(|d| d.insert_temp(edit_title_id, edit_title));
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("Manage channels");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui
+                            .add_enabled(!self.channels.is_empty(), Button::new("Manage"))
+                            .clicked()
+                        {
+                            modal.open();
+                        }
+                    })
+                });
+            });
+    }
+
+    fn render_problems(&mut self, ui: &mut egui::Ui) {
+        let mut trusted = None;
+        CollapsingHeader::new(RichText::new("Problems").strong().heading())
+            .default_open(true)
+            .show(ui, |ui| {
+                ui.add_space(THEME.spacing.large);
+                for channel in self.channels.iter().filter(|c| c.last_error.is_some()) {
+                    ui.horizontal(|ui| {
+                        ui.label(widgets::truncate(
+                            channel.title.as_deref().unwrap_or(&channel.link),
+                            30,
+                            None,
+                        ));
+                        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                            if let Some(at) = channel.last_error_at {
+                                ui.label(
+                                    RichText::new(widgets::format_timestamp(at))
+                                        .color(THEME.colors.text_dim),
+                                );
+                            }
+                        });
+                    });
+                    if let Some(error) = &channel.last_error {
+                        ui.add(
+                            Label::new(RichText::new(error).color(THEME.colors.warning)).wrap(true),
+                        );
+                    }
+                    if let Some(fingerprint) = self.untrusted_certificates.get(&channel.id) {
+                        ui.horizontal(|ui| {
+                            ui.label(
+                                RichText::new("Only trust a certificate you have checked.")
+                                    .color(THEME.colors.text_dim),
+                            );
+                            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                                if ui
+                                    .button("Trust certificate")
+                                    .on_hover_text(format!("SHA-256: {}", fingerprint))
+                                    .clicked()
+                                {
+                                    trusted = Some((channel.id.clone(), fingerprint.clone()));
+                                }
+                            });
+                        });
+                    }
+                    ui.add_space(THEME.spacing.medium);
+                }
+            });
+        if let Some(certificate) = trusted {
+            self.trusting_certificate = Some(certificate);
+            egui_modal::Modal::new(ui.ctx(), "modal_trust_certificate").open();
+        }
+    }
+
+    /// Asks before pinning a certificate that failed validation, showing its fingerprint
+    /// to compare with one obtained from the site's owner.
+    fn render_trust_certificate(&mut self, ctx: &Context) {
+        let Some((id, fingerprint)) = self.trusting_certificate.clone() else {
+            return;
+        };
+        let title = self
+            .channels
+            .iter()
+            .find(|channel| channel.id == id)
+            .map(|channel| channel.title.clone().unwrap_or(channel.link.clone()))
+            .unwrap_or_default();
+        let modal = egui_modal::Modal::new(ctx, "modal_trust_certificate");
+        modal.show(|ui| {
+            modal.title(ui, "Trust certificate");
+            modal.body(
+                ui,
+                format!(
+                    "The certificate of \"{}\" failed validation.\nFrom now on only this certificate will be accepted for the channel.\nTrust it only if its SHA-256 fingerprint matches the one\nthe site's owner published:",
+                    title
+                ),
+            );
+            ui.add(Label::new(RichText::new(&fingerprint).monospace()).wrap(true));
+            modal.buttons(ui, |ui| {
+                ui.spacing_mut().button_padding = Vec2::new(8., 4.);
+                if ui.add(Button::new("Close")).clicked() {
+                    modal.close();
+                    self.trusting_certificate = None;
+                };
+                if ui
+                    .add(Button::new("Trust").fill(THEME.colors.warning))
+                    .clicked()
+                {
+                    self.untrusted_certificates.remove(&id);
+                    if let Some(sender) = &self.sender {
+                        sender
+                            .send(ToWorker::SetTrustedCertificate {
+                                id: id.clone(),
+                                fingerprint: Some(fingerprint.clone()),
+                            })
+                            .unwrap();
+                    }
+                    modal.close();
+                    self.trusting_certificate = None;
+                };
+            });
+        });
+    }
+
+    fn render_data_settings(&mut self, ctx: &Context, ui: &mut egui::Ui) {
+        CollapsingHeader::new(RichText::new("Data").strong().heading())
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.spacing_mut().button_padding = Vec2::new(6., 3.);
+                ui.add_space(THEME.spacing.large);
+
+                let modal = egui_modal::Modal::new(ctx, "modal_import_archive");
+                let archive_id = ui.id().with("import_archive");
+
+                modal.show(|ui| {
+                    modal.title(ui, "Warning");
+                    modal.body(
+                        ui,
+                        "The database and settings of this profile will be replaced by the archive!\nThe database is swapped in when tinyrss is started again.",
+                    );
+                    modal.buttons(ui, |ui| {
+                        ui.spacing_mut().button_padding = Vec2::new(8., 4.);
+                        if ui.add(Button::new("Close")).clicked() {
+                            modal.close();
+                        };
+                        if ui
+                            .add(Button::new("Confirm").fill(THEME.colors.warning))
+                            .clicked()
+                        {
+                            let path = ui.data_mut(|d| d.get_temp::<PathBuf>(archive_id));
+                            if let (Some(sender), Some(path)) = (&self.sender, path) {
+                                self.worker_status.feed_loaded = false;
+                                sender.send(ToWorker::ImportArchive { path }).unwrap();
+                            }
+                            modal.close();
+                        };
+                    });
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Sync folder");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Folder shared between machines, e.g. with Syncthing or Dropbox.\nDismissed, starred and later changes are written there\nand changes from other machines are merged on startup.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        let folder = CONFIG.lock().sync_folder.clone();
+                        if folder.is_some() {
+                            let clear = ui.button("🗙");
+                            widgets::describe(&clear, WidgetType::Button, "Stop syncing");
+                            if clear.clicked() {
+                                CONFIG.lock().sync_folder = None;
+                                ConfigBuilder::from_current().apply();
+                            }
+                        }
+                        if ui.button("Choose").clicked() {
+                            if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                                CONFIG.lock().sync_folder = Some(path);
+                                ConfigBuilder::from_current().apply();
+                            }
+                        }
+                        if let Some(folder) = folder {
+                            ui.label(
+                                RichText::new(widgets::truncate(
+                                    &folder.to_string_lossy(),
+                                    30,
+                                    None,
+                                ))
+                                .color(THEME.colors.text_dim),
+                            )
+                            .on_hover_text(folder.to_string_lossy());
+                        }
+                    })
+                });
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Download folder");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Where podcast episodes and other attachments are saved.\nDefaults to the system's download folder.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        let folder = CONFIG.lock().download_folder.clone();
+                        if folder.is_some() {
+                            let reset = ui.button("🗙");
+                            widgets::describe(&reset, WidgetType::Button, "Use the system's download folder");
+                            if reset.clicked() {
+                                CONFIG.lock().download_folder = None;
+                                ConfigBuilder::from_current().apply();
+                            }
+                        }
+                        if ui.button("Choose").clicked() {
+                            if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                                CONFIG.lock().download_folder = Some(path);
+                                ConfigBuilder::from_current().apply();
+                            }
+                        }
+                        if let Some(folder) = folder.or_else(dirs::download_dir) {
+                            ui.label(
+                                RichText::new(widgets::truncate(
+                                    &folder.to_string_lossy(),
+                                    30,
+                                    None,
+                                ))
+                                .color(THEME.colors.text_dim),
+                            )
+                            .on_hover_text(folder.to_string_lossy());
+                        }
+                    })
+                });
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Notes folder");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Where \"To notes\" saves items as Markdown files with front matter.\nDefaults to the system's documents folder.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        let folder = CONFIG.lock().notes_folder.clone();
+                        if folder.is_some() {
+                            let reset = ui.button("🗙");
+                            widgets::describe(&reset, WidgetType::Button, "Use the system's documents folder");
+                            if reset.clicked() {
+                                CONFIG.lock().notes_folder = None;
+                                ConfigBuilder::from_current().apply();
+                            }
+                        }
+                        if ui.button("Choose").clicked() {
+                            if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                                CONFIG.lock().notes_folder = Some(path);
+                                ConfigBuilder::from_current().apply();
+                            }
+                        }
+                        if let Some(folder) = folder.or_else(dirs::document_dir) {
+                            ui.label(
+                                RichText::new(widgets::truncate(
+                                    &folder.to_string_lossy(),
+                                    30,
+                                    None,
+                                ))
+                                .color(THEME.colors.text_dim),
+                            )
+                            .on_hover_text(folder.to_string_lossy());
+                        }
+                    })
+                });
+                ui.add_space(THEME.spacing.large);
+                let mut config = ConfigBuilder::from_current();
+                let mut changed = false;
+                for (label, value, password) in [
+                    ("WebDAV folder", &mut config.webdav_url, false),
+                    ("Username", &mut config.webdav_username, false),
+                    ("Password", &mut *config.webdav_password, true),
+                ] {
+                    ui.horizontal(|ui| {
+                        ui.label(label);
+                        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                            changed |= ui
+                                .add(
+                                    TextEdit::singleline(value)
+                                        .password(password)
+                                        .desired_width(250.0),
+                                )
+                                .changed();
+                        });
+                    });
+                    ui.add_space(THEME.spacing.medium);
+                }
+                ui.horizontal(|ui| {
+                    ui.label("WebDAV sync");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Upload subscriptions and item state to the WebDAV folder\nand pick up changes from other machines every N minutes.\n0 syncs only when clicking \"Sync now\".");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui
+                            .add_enabled(!config.webdav_url.is_empty(), Button::new("Sync now"))
+                            .clicked()
+                        {
+                            if let Some(sender) = &self.sender {
+                                sender.send(ToWorker::WebdavSync).unwrap();
+                            }
+                        }
+                        changed |= ui
+                            .add(
+                                egui::DragValue::new(&mut config.webdav_interval_minutes)
+                                    .clamp_range(0..=1440)
+                                    .suffix(" min"),
+                            )
+                            .changed();
+                    });
+                });
+                if changed {
+                    config.apply();
+                }
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Items as JSON");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Every item with its channel, for merging into another install.\nImporting keeps the items stored here and adds the missing ones,\ntheir channels are added as archived.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui.button("Import").clicked() {
+                            if let Some(sender) = &self.sender {
+                                if let Some(path) = rfd::FileDialog::new()
+                                    .add_filter("JSON", &["json"])
+                                    .pick_file()
+                                {
+                                    sender.send(ToWorker::ImportJson { path }).unwrap();
+                                }
+                            }
+                        }
+                        if ui.button("Export").clicked() {
+                            if let Some(sender) = &self.sender {
+                                sender.send(ToWorker::ExportJson).unwrap();
+                            }
+                        }
+                    })
+                });
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Database copy");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Consistent copy of the database file, safe to make while refreshing.\nOpens with any SQLite tool.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui.button("Save").clicked() {
+                            if let Some(sender) = &self.sender {
+                                sender.send(ToWorker::SaveDatabaseCopy).unwrap();
+                            }
+                        }
+                    })
+                });
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Everything");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Database, settings and cached data of this profile in a single zip archive,\nfor moving to another machine.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui.button("Import").clicked() {
+                            if let Some(path) = rfd::FileDialog::new()
+                                .add_filter("Zip", &["zip"])
+                                .pick_file()
+                            {
+                                ui.data_mut(|d| d.insert_temp(archive_id, path));
+                                modal.open();
+                            }
+                        }
+                        if ui.button("Export").clicked() {
+                            if let Some(sender) = &self.sender {
+                                sender.send(ToWorker::ExportArchive).unwrap();
+                            }
+                        }
+                    })
+                });
+            });
+    }
+
+    fn render_refresh_progress(&self, ui: &mut egui::Ui) {
+        let channels = &self.worker_status.refresh_channels;
+        let done = channels.iter().filter(|c| c.state.is_done()).count();
+        let fraction = if channels.is_empty() {
+            0.0
+        } else {
+            done as f32 / channels.len() as f32
+        };
+        let progress = ui.add(
+            ProgressBar::new(fraction)
+                .desired_width(ui.available_width())
+                .animate(true),
+        );
+        widgets::describe_progress(&progress, "Updating feed", fraction);
+        if channels.is_empty() {
+            return;
+        }
+        CollapsingHeader::new(
+            RichText::new(format!("{} of {} channels", done, channels.len()))
+                .color(THEME.colors.text_dim),
+        )
+        .id_source("refresh_progress")
+        .show(ui, |ui| {
+            ScrollArea::vertical()
+                .id_source("refresh_progress_list")
+                .max_height(200.0)
+                .show(ui, |ui| {
+                    for channel in channels {
+                        ui.horizontal(|ui| {
+                            ui.label(widgets::truncate(&channel.title, 40, None));
+                            ui.with_layout(
+                                Layout::right_to_left(Align::Center),
+                                |ui| match &channel.state {
+                                    FetchState::Queued => {
+                                        ui.label(
+                                            RichText::new("queued").color(THEME.colors.text_dim),
+                                        );
+                                    }
+                                    FetchState::Fetching => {
+                                        ui.label(
+                                            RichText::new("fetching").color(THEME.colors.accent),
+                                        );
+                                    }
+                                    FetchState::Parsed => {
+                                        ui.label(
+                                            RichText::new("parsed").color(THEME.colors.text_dim),
+                                        );
+                                    }
+                                    FetchState::Failed(reason) => {
+                                        ui.label(
+                                            RichText::new("failed").color(THEME.colors.warning),
+                                        )
+                                        .on_hover_text(reason);
+                                    }
+                                },
+                            );
+                        });
+                    }
+                });
+        });
+    }
+
+    fn render_cookies_settings(&mut self, ui: &mut egui::Ui) {
+        CollapsingHeader::new(RichText::new("Cookies").strong().heading())
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.spacing_mut().button_padding = Vec2::new(6., 3.);
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Import cookies.txt");
+                    ui.label(
+                        RichText::new("(?)")
+                            .color(THEME.colors.text_dim)
+                            .monospace(),
+                    )
+                    .on_hover_text(
+                        "Cookies exported from a browser in the Netscape format,\nused to fetch feeds that need a login.\nLeave the domain empty to import all of them.",
+                    );
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui.button("Import").clicked() {
+                            if let Some(path) = rfd::FileDialog::new()
+                                .add_filter("cookies.txt", &["txt"])
+                                .pick_file()
+                            {
+                                if let Some(sender) = &self.sender {
+                                    sender
+                                        .send(ToWorker::ImportCookies {
+                                            path,
+                                            domain: self.cookie_domain_input.trim().to_string(),
+                                        })
+                                        .unwrap();
+                                }
+                            }
+                        }
+                        ui.add(
+                            TextEdit::singleline(&mut self.cookie_domain_input)
+                                .hint_text("Domain")
+                                .desired_width(160.0),
+                        );
+                    });
+                });
+                ui.add_space(THEME.spacing.large);
+                if self.cookie_domains.is_empty() {
+                    ui.label(RichText::new("No cookies stored.").color(THEME.colors.text_dim));
+                }
+                let mut cleared = None;
+                for (domain, count) in &self.cookie_domains {
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(domain).monospace());
+                        ui.label(
+                            RichText::new(format!("{} cookies", count))
+                                .color(THEME.colors.text_dim),
+                        );
+                        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                            if ui.button("Clear").clicked() {
+                                cleared = Some(domain.clone());
+                            }
+                        });
+                    });
+                }
+                if let Some(domain) = cleared {
+                    if let Some(sender) = &self.sender {
+                        sender.send(ToWorker::ClearCookies { domain }).unwrap();
+                    }
+                }
+            });
+    }
+
+    fn render_subscription_log(&mut self, ui: &mut egui::Ui) {
+        CollapsingHeader::new(RichText::new("Subscription log").strong().heading())
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.add_space(THEME.spacing.large);
+                if self.subscription_events.is_empty() {
+                    ui.label(RichText::new("No subscription changes yet").weak());
+                    return;
+                }
+                ScrollArea::vertical()
+                    .id_source("subscription_log")
+                    .max_height(320.0)
+                    .show(ui, |ui| {
+                        for event in &self.subscription_events {
+                            ui.horizontal(|ui| {
+                                ui.label(RichText::new(event.kind.label()).strong());
+                                let title = event.title.as_deref().unwrap_or(&event.link);
+                                ui.label(widgets::truncate(title, 40, None))
+                                    .on_hover_text(&event.link);
+                                if let Some(detail) = &event.detail {
+                                    ui.label(
+                                        RichText::new(widgets::truncate(detail, 40, None))
+                                            .color(THEME.colors.text_dim),
+                                    )
+                                    .on_hover_text(detail);
+                                }
+                                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                                    ui.label(widgets::format_timestamp(event.at));
+                                });
+                            });
+                        }
+                    });
+            });
+    }
+
+    fn render_digest_settings(&mut self, ui: &mut egui::Ui) {
+        CollapsingHeader::new(RichText::new("Email digest").strong().heading())
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.spacing_mut().button_padding = Vec2::new(6., 3.);
+                ui.add_space(THEME.spacing.large);
+                let mut config = ConfigBuilder::from_current();
+                let mut changed = false;
+                for (label, value, password) in [
+                    ("SMTP server", &mut config.smtp_host, false),
+                    ("Username", &mut config.smtp_username, false),
+                    ("Password", &mut *config.smtp_password, true),
+                    ("From", &mut config.digest_from, false),
+                    ("To", &mut config.digest_to, false),
+                ] {
+                    ui.horizontal(|ui| {
+                        ui.label(label);
+                        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                            changed |= ui
+                                .add(
+                                    TextEdit::singleline(value)
+                                        .password(password)
+                                        .desired_width(250.0),
+                                )
+                                .changed();
+                        });
+                    });
+                    ui.add_space(THEME.spacing.medium);
+                }
+                ui.horizontal(|ui| {
+                    ui.label("Port");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        changed |= ui
+                            .add(egui::DragValue::new(&mut config.smtp_port))
+                            .changed();
+                    });
+                });
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Send daily");
+                    ui.label(
+                        RichText::new("(?)")
+                            .color(THEME.colors.text_dim)
+                            .monospace(),
+                    )
+                    .on_hover_text("Email new items once a day while the app is running.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        changed |= ui.checkbox(&mut config.digest_daily, "").changed();
+                    });
+                });
+                ui.add_space(THEME.spacing.large);
+                let header = if config.digest_channels.is_empty() {
+                    "Channels (all)".to_string()
+                } else {
+                    format!("Channels ({})", config.digest_channels.len())
+                };
+                CollapsingHeader::new(header)
+                    .id_source("digest_channels")
+                    .show(ui, |ui| {
+                        for channel in &self.channels {
+                            let mut selected = config.digest_channels.contains(&channel.id);
+                            if ui
+                                .checkbox(
+                                    &mut selected,
+                                    widgets::truncate(
+                                        channel.title.as_deref().unwrap_or("<no title>"),
+                                        40,
+                                        None,
+                                    ),
+                                )
+                                .changed()
+                            {
+                                if selected {
+                                    config.digest_channels.push(channel.id.clone());
+                                } else {
+                                    config.digest_channels.retain(|id| id != &channel.id);
+                                }
+                                changed = true;
+                            }
+                        }
+                    });
+                if changed {
+                    config.apply();
+                }
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Digest");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui.button("Send now").clicked() {
+                            if let Some(sender) = &self.sender {
+                                sender.send(ToWorker::SendDigest).unwrap();
+                            }
+                        }
+                    });
+                });
+            });
+    }
+
+    fn render_blocklist_settings(&mut self, ui: &mut egui::Ui) {
+        CollapsingHeader::new(RichText::new("Blocklist").strong().heading())
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.spacing_mut().button_padding = Vec2::new(6., 3.);
+                ui.add_space(THEME.spacing.large);
+                let mut config = ConfigBuilder::from_current();
+                let mut changed = false;
+                ui.horizontal(|ui| {
+                    ui.label("Keywords");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("New items whose title or summary contains one of these words\nare never saved, not even as dismissed.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        let add = ui.add_enabled(
+                            !self.keyword_input.trim().is_empty(),
+                            Button::new("Add"),
+                        );
+                        let input = ui.add(
+                            TextEdit::singleline(&mut self.keyword_input)
+                                .hint_text("Keyword")
+                                .desired_width(150.0),
+                        );
+                        let submitted =
+                            input.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                        if (add.clicked() || submitted) && !self.keyword_input.trim().is_empty() {
+                            config
+                                .blocked_keywords
+                                .push(self.keyword_input.trim().to_string());
+                            self.keyword_input.clear();
+                            changed = true;
+                        }
+                    });
+                });
+                self.render_keyword_preview(ui);
+                ui.add_space(THEME.spacing.medium);
+                let mut removed = None;
+                for (index, keyword) in config.blocked_keywords.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(keyword);
+                        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                            let remove = ui.button("🗙");
+                            widgets::describe(
+                                &remove,
+                                WidgetType::Button,
+                                format!("Remove keyword {}", keyword),
+                            );
+                            if remove.clicked() {
+                                removed = Some(index);
+                            }
+                        });
+                    });
+                }
+                if let Some(index) = removed {
+                    config.blocked_keywords.remove(index);
+                    changed = true;
+                }
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Rule packs");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Export copies the keywords as a YAML rule pack to share.\nImport adds the rules of a pack someone shared,\neach of them is previewed before anything changes.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui.button("Import").clicked() {
+                            self.rule_pack_input.clear();
+                            self.rule_pack_skipped.clear();
+                            egui_modal::Modal::new(ui.ctx(), "modal_rule_pack").open();
+                        }
+                        if ui
+                            .add_enabled(!config.blocked_keywords.is_empty(), Button::new("Export"))
+                            .on_hover_text("Copy as YAML")
+                            .clicked()
+                        {
+                            let pack = RulePack::from_keywords("Blocklist", &config.blocked_keywords);
+                            ui.output_mut(|o| o.copied_text = pack.to_yaml());
+                        }
+                    });
+                });
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label(format!("Blocked items: {}", config.blocked_items_count));
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui
+                            .add_enabled(config.blocked_items_count > 0, Button::new("Reset"))
+                            .clicked()
+                        {
+                            config.blocked_items_count = 0;
+                            changed = true;
+                        }
+                    });
+                });
+                if changed {
+                    config.apply();
+                }
+            });
+        self.render_rule_pack_import(ui.ctx());
+    }
+
+    /// Saved items the typed keyword matches, to check it before adding it.
+    fn render_keyword_preview(&mut self, ui: &mut egui::Ui) {
+        let keyword = self.keyword_input.trim();
+        if keyword.is_empty() {
+            self.keyword_preview = None;
+            return;
+        }
+        if self
+            .keyword_preview
+            .as_ref()
+            .is_none_or(|preview| preview.keyword != keyword)
+        {
+            let keywords = [keyword.to_string()];
+            let mut matches = self
+                .feed_items
+                .iter()
+                .filter(|item| blocks_item(&keywords, item));
+            let items: Vec<Item> = matches.by_ref().take(RULE_PREVIEW_ITEMS).cloned().collect();
+            self.keyword_preview = Some(KeywordPreview {
+                keyword: keyword.to_string(),
+                count: items.len() + matches.count(),
+                items,
+            });
+        }
+        let Some(preview) = &self.keyword_preview else {
+            return;
+        };
+        ui.add_space(THEME.spacing.medium);
+        let summary = match preview.count {
+            0 => "No saved item contains it.".to_string(),
+            1 => "1 saved item contains it, new items like it won't be saved:".to_string(),
+            count => format!(
+                "{} saved items contain it, new items like them won't be saved:",
+                widgets::format_count(count as i64)
+            ),
+        };
+        ui.label(RichText::new(summary).color(THEME.colors.text_dim));
+        for item in &preview.items {
+            ui.horizontal(|ui| {
+                let title = item.title.as_deref().unwrap_or("<no title>");
+                ui.hyperlink_to(widgets::truncate(title, 60, None), &item.link)
+                    .on_hover_text(&item.link);
+                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    if let Some(channel_title) = &item.channel_title {
+                        ui.label(
+                            RichText::new(widgets::truncate(channel_title, 30, None))
+                                .color(THEME.colors.text_dim),
+                        );
+                    }
+                });
+            });
+        }
+        if preview.count > RULE_PREVIEW_ITEMS {
+            ui.label(
+                RichText::new(format!("and {} more", preview.count - RULE_PREVIEW_ITEMS))
+                    .color(THEME.colors.text_dim),
+            );
+        }
+    }
+
+    fn render_rule_pack_import(&mut self, ctx: &Context) {
+        let modal = egui_modal::Modal::new(ctx, "modal_rule_pack");
+        if !modal.is_open() {
+            return;
+        }
+        if self
+            .rule_pack_preview
+            .as_ref()
+            .is_none_or(|preview| preview.input != self.rule_pack_input)
+        {
+            self.rule_pack_preview = (!self.rule_pack_input.trim().is_empty()).then(|| {
+                let pack = RulePack::from_yaml(&self.rule_pack_input);
+                let matches = pack
+                    .iter()
+                    .flat_map(|pack| &pack.rules)
+                    .map(|rule| {
+                        let keywords = [rule.keyword.trim().to_string()];
+                        let count = self
+                            .feed_items
+                            .iter()
+                            .filter(|item| blocks_item(&keywords, item))
+                            .count();
+                        let [keyword] = keywords;
+                        (keyword, count)
+                    })
+                    .collect();
+                RulePackPreview {
+                    input: self.rule_pack_input.clone(),
+                    pack,
+                    matches,
+                }
+            });
+        }
+        let preview = self.rule_pack_preview.take();
+        let keywords = CONFIG.lock().blocked_keywords.clone();
+        let known = |keyword: &str| {
+            keywords
+                .iter()
+                .any(|known| known.trim().eq_ignore_ascii_case(keyword))
+        };
+        let mut imported = Vec::new();
+        modal.show(|ui| {
+            modal.title(ui, "Import rule pack");
+            modal.frame(ui, |ui| {
+                ui.label(
+                    "Paste a rule pack, e.g. one exported from the blocklist of another install.",
+                );
+                ui.add_space(THEME.spacing.medium);
+                ui.add(
+                    TextEdit::multiline(&mut self.rule_pack_input)
+                        .code_editor()
+                        .hint_text("name: Sponsored posts\nrules:\n  - keyword: sponsored")
+                        .desired_rows(5)
+                        .desired_width(ui.available_width()),
+                );
+                match preview.as_ref().map(|preview| &preview.pack) {
+                    None => {}
+                    Some(Err(reason)) => {
+                        ui.add_space(THEME.spacing.small);
+                        ui.add(
+                            Label::new(
+                                RichText::new(format!("⚠ {}", reason)).color(THEME.colors.warning),
+                            )
+                            .wrap(true),
+                        );
+                    }
+                    Some(Ok(pack)) => {
+                        ui.add_space(THEME.spacing.large);
+                        if !pack.name.trim().is_empty() {
+                            ui.label(RichText::new(pack.name.trim()).strong());
+                        }
+                        if let Some(description) = &pack.description {
+                            ui.add(Label::new(description.trim()).wrap(true));
+                        }
+                        ui.add_space(THEME.spacing.medium);
+                        ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                            for rule in &pack.rules {
+                                let keyword = rule.keyword.trim();
+                                let present = known(keyword);
+                                let mut checked =
+                                    !present && !self.rule_pack_skipped.contains(keyword);
+                                if ui
+                                    .add_enabled(
+                                        !present,
+                                        egui::Checkbox::new(&mut checked, keyword),
+                                    )
+                                    .changed()
+                                {
+                                    if checked {
+                                        self.rule_pack_skipped.remove(keyword);
+                                    } else {
+                                        self.rule_pack_skipped.insert(keyword.to_string());
+                                    }
+                                }
+                                if checked && !imported.iter().any(|k: &String| k == keyword) {
+                                    imported.push(keyword.to_string());
+                                }
+                                ui.indent(keyword, |ui| {
+                                    let effect = if present {
+                                        "Already on the blocklist".to_string()
+                                    } else {
+                                        let matches = preview
+                                            .as_ref()
+                                            .and_then(|preview| preview.matches.get(keyword))
+                                            .copied()
+                                            .unwrap_or_default();
+                                        format!(
+                                            "{}, {} of the saved items contain it",
+                                            rule.describe(),
+                                            matches
+                                        )
+                                    };
+                                    ui.add(
+                                        Label::new(
+                                            RichText::new(effect).color(THEME.colors.text_dim),
+                                        )
+                                        .wrap(true),
+                                    );
+                                    if let Some(note) = &rule.note {
+                                        ui.add(Label::new(note.trim()).wrap(true));
+                                    }
+                                });
+                                ui.add_space(THEME.spacing.small);
+                            }
+                        });
+                    }
+                }
+            });
+            modal.buttons(ui, |ui| {
+                ui.spacing_mut().button_padding = Vec2::new(8., 4.);
+                if ui.add(Button::new("Close")).clicked() {
+                    self.rule_pack_input.clear();
+                    modal.close();
+                }
+                if ui
+                    .add_enabled(
+                        !imported.is_empty(),
+                        Button::new(format!("Import {} rules", imported.len())),
+                    )
+                    .clicked()
+                {
+                    let mut config = ConfigBuilder::from_current();
+                    config.blocked_keywords.append(&mut imported);
+                    config.apply();
+                    self.rule_pack_input.clear();
+                    modal.close();
+                }
+            });
+        });
+        self.rule_pack_preview = preview;
+    }
+
+    fn render_send_to_settings(&mut self, ui: &mut egui::Ui) {
+        CollapsingHeader::new(RichText::new("Send to").strong().heading())
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.spacing_mut().button_padding = Vec2::new(6., 3.);
+                ui.add_space(THEME.spacing.large);
+                let mut config = ConfigBuilder::from_current();
+                let mut changed = false;
+                ui.horizontal(|ui| {
+                    ui.label("Player");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Plays podcast episodes and videos attached to items without downloading them.\n{url} is replaced with the attachment's link, or the item's link\nfor videos without one, e.g. vlc {url}");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        changed |= ui
+                            .add(
+                                TextEdit::singleline(&mut config.player_command)
+                                    .hint_text("mpv {url}")
+                                    .desired_width(200.0),
+                            )
+                            .changed();
+                    });
+                });
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Commands");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Shown when right-clicking an item.\n{url} and {title} are replaced with the item's link and title,\ne.g. yt-dlp {url}");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui.button("Add").clicked() {
+                            config.send_to_commands.push(SendToCommand::default());
+                            changed = true;
+                        }
+                    });
+                });
+                ui.add_space(THEME.spacing.medium);
+                let mut removed = None;
+                for (index, command) in config.send_to_commands.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        changed |= ui
+                            .add(
+                                TextEdit::singleline(&mut command.name)
+                                    .hint_text("Name")
+                                    .desired_width(100.0),
+                            )
+                            .changed();
+                        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                            let remove = ui.button("🗙");
+                            widgets::describe(&remove, WidgetType::Button, "Remove command");
+                            if remove.clicked() {
+                                removed = Some(index);
+                            }
+                            changed |= ui
+                                .add(
+                                    TextEdit::singleline(&mut command.command)
+                                        .hint_text("Command")
+                                        .desired_width(ui.available_width()),
+                                )
+                                .changed();
+                        });
+                    });
+                    ui.add_space(THEME.spacing.medium);
+                }
+                if let Some(index) = removed {
+                    config.send_to_commands.remove(index);
+                    changed = true;
+                }
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Share menu");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Entries of the Share submenu of items.\n{url} and {title} are replaced with the item's link and title,\nescaped for HTML or links as the entry needs.\nLinks open in the browser, e.g. a Mastodon instance's share page.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui.button("Add").clicked() {
+                            config.share_targets.push(ShareTarget::default());
+                            changed = true;
+                        }
+                        if ui.button("Reset").clicked() {
+                            config.share_targets = ShareTarget::defaults();
+                            changed = true;
+                        }
+                    });
+                });
+                ui.add_space(THEME.spacing.medium);
+                let mut removed = None;
+                for (index, target) in config.share_targets.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        changed |= ui
+                            .add(
+                                TextEdit::singleline(&mut target.name)
+                                    .hint_text("Name")
+                                    .desired_width(100.0),
+                            )
+                            .changed();
+                        ComboBox::from_id_source(("share_format", index))
+                            .selected_text(target.format.label())
+                            .show_ui(ui, |ui| {
+                                for format in ShareFormat::ALL {
+                                    changed |= ui
+                                        .selectable_value(&mut target.format, format, format.label())
+                                        .changed();
+                                }
+                            });
+                        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                            let remove = ui.button("🗙");
+                            widgets::describe(&remove, WidgetType::Button, "Remove share entry");
+                            if remove.clicked() {
+                                removed = Some(index);
+                            }
+                            changed |= ui
+                                .add(
+                                    TextEdit::singleline(&mut target.template)
+                                        .hint_text("Template")
+                                        .desired_width(ui.available_width()),
+                                )
+                                .changed();
+                        });
+                    });
+                    ui.add_space(THEME.spacing.medium);
+                }
+                if let Some(index) = removed {
+                    config.share_targets.remove(index);
+                    changed = true;
+                }
+                if changed {
+                    config.apply();
+                }
+            });
+    }
+
+    fn render_newsletter(&mut self, ctx: &Context) {
+        let modal = egui_modal::Modal::new(ctx, "modal_newsletter");
+        modal.show(|ui| {
+            modal.title(ui, "Add newsletter");
+            modal.frame(ui, |ui| {
+                match &self.newsletter_result {
+                    Some(Ok(address)) => {
+                        ui.label("Subscribe to the newsletter with this address,\nits emails will show up as items of the new channel.");
+                        ui.add_space(THEME.spacing.medium);
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new(address).monospace().strong());
+                            if ui.link("Copy").clicked() {
+                                ui.output_mut(|o| o.copied_text = address.clone());
+                            }
+                        });
+                    }
+                    result => {
+                        ui.label("Name the newsletter, an inbox for it is created\non the newsletter server set under Settings › Integrations.");
+                        ui.add_space(THEME.spacing.medium);
+                        ui.add_enabled(
+                            !self.creating_newsletter,
+                            TextEdit::singleline(&mut self.newsletter_title)
+                                .hint_text("Name")
+                                .desired_width(ui.available_width()),
+                        );
+                        if let Some(Err(reason)) = result {
+                            ui.add_space(THEME.spacing.small);
+                            ui.add(
+                                Label::new(
+                                    RichText::new(format!("⚠ {}", reason))
+                                        .color(THEME.colors.warning),
+                                )
+                                .wrap(true),
+                            );
+                        }
+                    }
+                }
+            });
+            modal.buttons(ui, |ui| {
+                ui.spacing_mut().button_padding = Vec2::new(8., 4.);
+                if ui.add(Button::new("Close")).clicked() {
+                    modal.close();
+                }
+                if self.creating_newsletter {
+                    ui.spinner();
+                } else if !matches!(self.newsletter_result, Some(Ok(_)))
+                    && ui
+                        .add_enabled(
+                            !self.newsletter_title.trim().is_empty(),
+                            Button::new("Create"),
+                        )
+                        .clicked()
+                {
+                    if let Some(sender) = &self.sender {
+                        self.creating_newsletter = true;
+                        self.newsletter_result = None;
+                        sender
+                            .send(ToWorker::CreateNewsletter {
+                                title: self.newsletter_title.trim().to_string(),
+                            })
+                            .unwrap();
+                    }
+                }
+            });
+        });
+    }
+
+    fn render_duplicate_channels(&mut self, ctx: &Context) {
+        let modal = egui_modal::Modal::new(ctx, "modal_duplicate_channels");
+        let mut merged = None;
+        modal.show(|ui| {
+            modal.title(ui, "Duplicate channels");
+            modal.frame(ui, |ui| {
+                if self.duplicate_channels.is_empty() {
+                    ui.label("No duplicate channels found.");
+                    return;
+                }
+                ui.label("Items of the other channels are moved to the selected one.");
+                ScrollArea::vertical().max_height(360.0).show(ui, |ui| {
+                    for (index, (group, keep)) in self.duplicate_channels.iter_mut().enumerate() {
+                        ui.add_space(THEME.spacing.large);
+                        for (position, id) in group.iter().enumerate() {
+                            let Some(channel) = self.channels.iter().find(|c| &c.id == id) else {
+                                continue;
+                            };
+                            ui.horizontal(|ui| {
+                                ui.radio_value(
+                                    keep,
+                                    position,
+                                    widgets::truncate(
+                                        channel.title.as_deref().unwrap_or("<no title>"),
+                                        30,
+                                        None,
+                                    ),
+                                );
+                                ui.label(
+                                    RichText::new(widgets::truncate(&channel.link, 40, None))
+                                        .color(THEME.colors.text_dim),
+                                )
+                                .on_hover_text(&channel.link);
+                            });
+                        }
+                        if ui.button("Merge").clicked() {
+                            merged = Some(index);
+                        }
+                    }
+                });
+            });
+            modal.buttons(ui, |ui| {
+                ui.spacing_mut().button_padding = Vec2::new(8., 4.);
+                if ui.add(Button::new("Close")).clicked() {
+                    modal.close();
+                };
+            });
+        });
+        if let Some(index) = merged {
+            let (group, keep) = self.duplicate_channels.remove(index);
+            if let Some(sender) = &self.sender {
+                sender
+                    .send(ToWorker::MergeChannels {
+                        keep: group[keep].clone(),
+                        merged: group,
+                    })
+                    .unwrap();
+            }
+        }
+    }
+
+    fn render_integrations_settings(&mut self, ctx: &Context, ui: &mut egui::Ui) {
+        let command_modal = egui_modal::Modal::new(ctx, "modal_new_item_command");
+        command_modal.show(|ui| {
+            let command = CONFIG.lock().new_item_command.clone();
+            command_modal.title(ui, "Warning");
+            command_modal.body(
+                ui,
+                format!(
+                    "\"{}\" will run on its own for every new item,\nwith text taken from the feeds as arguments.\nOnly enable commands you trust with that.",
+                    command
+                ),
+            );
+            command_modal.buttons(ui, |ui| {
+                ui.spacing_mut().button_padding = Vec2::new(8., 4.);
+                if ui.add(Button::new("Close")).clicked() {
+                    command_modal.close();
+                };
+                if ui
+                    .add(Button::new("Enable").fill(THEME.colors.warning))
+                    .clicked()
+                {
+                    CONFIG.lock().new_item_command_approved = command;
+                    ConfigBuilder::from_current().apply();
+                    command_modal.close();
+                };
+            });
+        });
+
+        CollapsingHeader::new(RichText::new("Integrations").strong().heading())
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.spacing_mut().button_padding = Vec2::new(6., 3.);
+                ui.add_space(THEME.spacing.large);
+                let mut config = ConfigBuilder::from_current();
+                let mut changed = false;
+                ui.horizontal(|ui| {
+                    ui.label("Webhooks");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("After every refresh the new items are posted to these urls\nas JSON: {\"items\": [{\"title\", \"link\", \"summary\", ...}]}.\nThe filter takes comma separated keywords, empty posts all items.\nFailed deliveries are retried a few times.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui.button("Add").clicked() {
+                            config.webhooks.push(Webhook::default());
+                            changed = true;
+                        }
+                    });
+                });
+                ui.add_space(THEME.spacing.medium);
+                let mut removed = None;
+                for (index, webhook) in config.webhooks.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        changed |= ui
+                            .add(
+                                TextEdit::singleline(&mut webhook.filter)
+                                    .hint_text("Filter")
+                                    .desired_width(100.0),
+                            )
+                            .changed();
+                        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                            let remove = ui.button("🗙");
+                            widgets::describe(&remove, WidgetType::Button, "Remove webhook");
+                            if remove.clicked() {
+                                removed = Some(index);
+                            }
+                            changed |= ui
+                                .add(
+                                    TextEdit::singleline(&mut *webhook.url)
+                                        .hint_text("https://")
+                                        .desired_width(ui.available_width()),
+                                )
+                                .changed();
+                        });
+                    });
+                    ui.add_space(THEME.spacing.medium);
+                }
+                if let Some(index) = removed {
+                    config.webhooks.remove(index);
+                    changed = true;
+                }
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Newsletter server");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Kill the Newsletter server that turns newsletters into feeds,
+used by \"Add newsletter\" on the Channels page.\nChange it to use a self-hosted one.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        changed |= ui
+                            .add(
+                                TextEdit::singleline(&mut config.newsletter_server)
+                                    .hint_text("https://")
+                                    .desired_width(200.0),
+                            )
+                            .changed();
+                    });
+                });
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Push notifications");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Push new items to your phone through an ntfy topic or a Gotify server.\nThe filter takes comma separated keywords matched against title,\nsummary and channel, empty pushes all items.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        let before = config.push_service;
+                        ComboBox::from_id_source("push_service_combo")
+                            .selected_text(push_service_label(config.push_service))
+                            .show_ui(ui, |ui| {
+                                for service in
+                                    [PushService::Off, PushService::Ntfy, PushService::Gotify]
+                                {
+                                    ui.selectable_value(
+                                        &mut config.push_service,
+                                        service,
+                                        push_service_label(service),
+                                    );
+                                }
+                            });
+                        changed |= config.push_service != before;
+                    });
+                });
+                if config.push_service != PushService::Off {
+                    let url_hint = match config.push_service {
+                        PushService::Gotify => "https://gotify.example.com",
+                        _ => "https://ntfy.sh/my-topic",
+                    };
+                    for (label, value, hint, password) in [
+                        ("Url", &mut config.push_url, url_hint, false),
+                        ("Token", &mut *config.push_token, "", true),
+                        ("Filter", &mut config.push_filter, "Keywords", false),
+                    ] {
+                        ui.add_space(THEME.spacing.medium);
+                        ui.horizontal(|ui| {
+                            ui.label(label);
+                            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                                changed |= ui
+                                    .add(
+                                        TextEdit::singleline(value)
+                                            .hint_text(hint)
+                                            .password(password)
+                                            .desired_width(250.0),
+                                    )
+                                    .changed();
+                            });
+                        });
+                    }
+                }
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Chats");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Items can be shared to a Telegram chat through a bot from @BotFather\nor to a Matrix room, by right-clicking them. New items of the\nforwarded channels below are posted there after every refresh.");
+                });
+                for (label, value, hint, password) in [
+                    ("Telegram bot token", &mut *config.telegram_bot_token, "", true),
+                    ("Telegram chat id", &mut config.telegram_chat_id, "@channel or 123456", false),
+                    ("Matrix homeserver", &mut config.matrix_homeserver, "https://matrix.org", false),
+                    ("Matrix access token", &mut *config.matrix_access_token, "", true),
+                    ("Matrix room id", &mut config.matrix_room_id, "!room:matrix.org", false),
+                    ("Forward filter", &mut config.forward_filter, "Keywords", false),
+                ] {
+                    ui.add_space(THEME.spacing.medium);
+                    ui.horizontal(|ui| {
+                        ui.label(label);
+                        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                            changed |= ui
+                                .add(
+                                    TextEdit::singleline(value)
+                                        .hint_text(hint)
+                                        .password(password)
+                                        .desired_width(250.0),
+                                )
+                                .changed();
+                        });
+                    });
+                }
+                ui.add_space(THEME.spacing.medium);
+                let header = format!("Forwarded channels ({})", config.forward_channels.len());
+                CollapsingHeader::new(header)
+                    .id_source("forward_channels")
+                    .show(ui, |ui| {
+                        for channel in &self.channels {
+                            let mut selected = config.forward_channels.contains(&channel.id);
+                            if ui
+                                .checkbox(
+                                    &mut selected,
+                                    widgets::truncate(
+                                        channel.title.as_deref().unwrap_or("<no title>"),
+                                        40,
+                                        None,
+                                    ),
+                                )
+                                .changed()
+                            {
+                                if selected {
+                                    config.forward_channels.push(channel.id.clone());
+                                } else {
+                                    config.forward_channels.retain(|id| id != &channel.id);
+                                }
+                                changed = true;
+                            }
+                        }
+                    });
+                ui.add_space(THEME.spacing.large);
+                ui.horizontal(|ui| {
+                    ui.label("Command on new items");
+                    ui.label(RichText::new("(?)").color(THEME.colors.text_dim).monospace()).on_hover_text("Runs for every new item after a refresh, at most 10 times per refresh.\n{url} and {title} are replaced like in \"Send to\", all item fields are also\npassed as TINYRSS_ITEM_* environment variables, e.g. TINYRSS_ITEM_ENCLOSURE.\nThe filter takes comma separated keywords, empty runs it for all items.");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if config.new_item_command_enabled() {
+                            if ui.button("Disable").clicked() {
+                                config.new_item_command_approved.clear();
+                                changed = true;
+                            }
+                            ui.label(RichText::new("Enabled").color(THEME.colors.accent));
+                        } else if ui
+                            .add_enabled(
+                                !config.new_item_command.trim().is_empty(),
+                                Button::new("Enable"),
+                            )
+                            .clicked()
+                        {
+                            command_modal.open();
+                        }
+                    });
+                });
+                for (value, hint) in [
+                    (&mut config.new_item_command, "transmission-remote -a {url}"),
+                    (&mut config.new_item_command_filter, "Filter keywords"),
+                ] {
+                    ui.add_space(THEME.spacing.medium);
+                    changed |= ui
+                        .add(
+                            TextEdit::singleline(value)
+                                .hint_text(hint)
+                                .desired_width(ui.available_width()),
+                        )
+                        .changed();
+                }
+                if changed {
+                    config.apply();
+                }
+            });
+    }
+
+    fn render_keybindings_settings(&mut self, ctx: &Context, ui: &mut egui::Ui) {
+        if let Some(action) = self.recording_key {
+            match input::record_key(ctx) {
+                input::Recording::Recorded(key) => {
+                    self.recording_key = None;
+                    let mut config = ConfigBuilder::from_current();
+                    let binding = KeyBinding::new(action, key);
+                    if !config.keybindings.contains(&binding) {
+                        config.keybindings.push(binding);
+                        config.apply();
+                    }
+                }
+                input::Recording::Cancelled => self.recording_key = None,
+                input::Recording::Pending => {}
+            }
+        }
+
+        CollapsingHeader::new(RichText::new("Keybindings").strong().heading())
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.spacing_mut().button_padding = Vec2::new(6., 3.);
+                ui.add_space(THEME.spacing.large);
+                let mut config = ConfigBuilder::from_current();
+                let conflicts = input::conflicts(&config.keybindings);
+                let mut removed = None;
+                let mut reset = false;
+                for action in KeyAction::ALL {
+                    ui.horizontal(|ui| {
+                        ui.label(input::action_label(action));
+                        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                            if self.recording_key == Some(action) {
+                                ui.label(
+                                    RichText::new("Press a key (Esc to cancel)")
+                                        .color(THEME.colors.accent),
+                                );
+                            } else {
+                                let add = ui.button("+");
+                                widgets::describe(
+                                    &add,
+                                    WidgetType::Button,
+                                    format!("Add key for {}", input::action_label(action)),
+                                );
+                                if add.clicked() {
+                                    self.recording_key = Some(action);
+                                }
+                            }
+                            for (index, binding) in config.keybindings.iter().enumerate().rev() {
+                                if binding.action != action {
+                                    continue;
+                                }
+                                let mut button = Button::new(format!("{} 🗙", binding.key));
+                                if conflicts.contains(&binding.key) {
+                                    button = button.fill(THEME.colors.warning);
+                                }
+                                let remove =
+                                    ui.add(button).on_hover_text("Click to remove this key");
+                                widgets::describe(
+                                    &remove,
+                                    WidgetType::Button,
+                                    format!("Remove key {}", binding.key),
+                                );
+                                if remove.clicked() {
+                                    removed = Some(index);
+                                }
+                            }
+                        });
+                    });
+                    ui.add_space(THEME.spacing.medium);
+                }
+                if !conflicts.is_empty() {
+                    let mut keys: Vec<&String> = conflicts.iter().collect();
+                    keys.sort();
+                    ui.label(
+                        RichText::new(format!(
+                            "Bound to several actions: {}. The first binding wins.",
+                            keys.into_iter().cloned().collect::<Vec<_>>().join(", ")
+                        ))
+                        .color(THEME.colors.warning),
+                    );
+                    ui.add_space(THEME.spacing.medium);
+                }
+                ui.horizontal(|ui| {
+                    ui.label("Defaults");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui.button("Reset").clicked() {
+                            reset = true;
+                        }
+                    });
+                });
+                if reset {
+                    config.keybindings = KeyBinding::defaults();
+                    config.apply();
+                } else if let Some(index) = removed {
+                    config.keybindings.remove(index);
+                    config.apply();
+                }
+            });
+    }
+
+    fn render_logs_settings(&mut self, ctx: &Context, ui: &mut egui::Ui) {
+        CollapsingHeader::new(RichText::new("Logs").strong().heading())
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.spacing_mut().button_padding = Vec2::new(6., 3.);
+                ui.add_space(THEME.spacing.large);
+                let now = ctx.input(|i| i.time);
+                self.log_viewer.reload_if_stale(now);
+                ctx.request_repaint_after(std::time::Duration::from_secs_f64(LOG_REFRESH_SECONDS));
+                let lines = self.log_viewer.filtered();
+                let mut copy = None;
+                ui.horizontal(|ui| {
+                    ui.label("Level");
+                    ui.label(
+                        RichText::new("(?)")
+                            .color(THEME.colors.text_dim)
+                            .monospace(),
+                    )
+                    .on_hover_text(format!(
+                        "Shows the end of today's log file.\nLog files are kept in {}",
+                        get_log_dir().display()
+                    ));
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui
+                            .add_enabled(!lines.is_empty(), Button::new("Copy"))
+                            .clicked()
+                        {
+                            copy = Some(lines.join("\n"));
+                        }
+                        ComboBox::from_id_source("log_level_combo")
+                            .selected_text(self.log_viewer.level.label())
+                            .show_ui(ui, |ui| {
+                                for level in LogLevel::ALL {
+                                    ui.selectable_value(
+                                        &mut self.log_viewer.level,
+                                        level,
+                                        level.label(),
+                                    );
+                                }
+                            });
+                    });
+                });
+                ui.add_space(THEME.spacing.medium);
+                Frame {
+                    fill: THEME.colors.bg_darker,
+                    inner_margin: Margin::same(6.0),
+                    rounding: THEME.rounding.medium,
+                    ..Default::default()
+                }
+                .show(ui, |ui| {
+                    ScrollArea::vertical()
+                        .id_source("log_viewer")
+                        .max_height(300.0)
+                        .stick_to_bottom(true)
+                        .show(ui, |ui| {
+                            ui.set_width(ui.available_width());
+                            if lines.is_empty() {
+                                ui.label(
+                                    RichText::new("Nothing logged yet")
+                                        .color(THEME.colors.text_dim),
+                                );
+                            }
+                            for line in &lines {
+                                let color = match LogLevel::of_line(line) {
+                                    Some(LogLevel::Error | LogLevel::Warn) => THEME.colors.warning,
+                                    Some(LogLevel::Debug) => THEME.colors.text_dim,
+                                    _ => THEME.colors.text,
+                                };
+                                ui.add(
+                                    Label::new(RichText::new(line).monospace().color(color))
+                                        .wrap(true),
+                                );
+                            }
+                        });
+                });
+                if let Some(text) = copy {
+                    self.copy_to_clipboard(text);
+                }
+            });
+    }
+
+    fn render_diagnostics(&mut self, ui: &mut egui::Ui) {
+        CollapsingHeader::new(RichText::new("About").strong().heading())
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.spacing_mut().button_padding = Vec2::new(6., 3.);
+                ui.add_space(THEME.spacing.large);
+                for (name, value) in self.diagnostics() {
+                    ui.horizontal(|ui| {
+                        ui.label(name);
+                        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                            ui.add(
+                                Label::new(RichText::new(value).color(THEME.colors.text_dim))
+                                    .wrap(true),
+                            );
+                        });
+                    });
+                    ui.add_space(THEME.spacing.medium);
+                }
+                CollapsingHeader::new("Config values")
+                    .id_source("diagnostics_config")
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        ui.add(
+                            Label::new(
+                                RichText::new(CONFIG.lock().redacted_yaml())
+                                    .monospace()
+                                    .color(THEME.colors.text_dim),
+                            )
+                            .wrap(true),
+                        );
+                    });
+                ui.add_space(THEME.spacing.large);
+                let mut copy = false;
+                ui.horizontal(|ui| {
+                    ui.label("Diagnostics");
+                    ui.label(
+                        RichText::new("(?)")
+                            .color(THEME.colors.text_dim)
+                            .monospace(),
+                    )
+                    .on_hover_text(
+                        "Copies the values above and the config to the clipboard\nfor attaching to a bug report. Passwords and tokens are left out.",
+                    );
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui.button("Copy report").clicked() {
+                            copy = true;
+                        }
+                        if ui.button("Open data folder").clicked() {
+                            if let Err(err) = open::that(get_app_dir()) {
+                                self.worker_status.errors.push(WorkerError::new(
+                                    ErrorKind::Filesystem,
+                                    "Failed to open data folder",
+                                    err.to_string(),
+                                ));
+                            }
+                        }
+                    });
+                });
+                if copy {
+                    let mut report: String = self
+                        .diagnostics()
+                        .into_iter()
+                        .map(|(name, value)| format!("{}: {}\n", name, value))
+                        .collect();
+                    report.push_str("\nConfig:\n");
+                    report.push_str(&CONFIG.lock().redacted_yaml());
+                    self.copy_to_clipboard(report);
+                }
+            });
+    }
+
+    /// Values shown in the about section and included in the copied diagnostics report.
+    fn diagnostics(&self) -> Vec<(&'static str, String)> {
+        let app_dir = get_app_dir();
+        let database_size = std::fs::metadata(app_dir.join("tinyrss.db"))
+            .map(|metadata| format!("{:.1} MB", metadata.len() as f64 / 1_000_000.0))
+            .unwrap_or_else(|err| err.to_string());
+        let count = |filter: fn(&&Item) -> bool| self.feed_items.iter().filter(filter).count();
+        vec![
+            ("Version", env!("CARGO_PKG_VERSION").to_string()),
+            (
+                "Platform",
+                format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+            ),
+            (
+                "Profile",
+                get_profile().unwrap_or_else(|| "default".to_string()),
+            ),
+            ("Data folder", app_dir.display().to_string()),
+            ("Log folder", get_log_dir().display().to_string()),
+            ("Database size", database_size),
+            ("Channels", self.channels.len().to_string()),
+            (
+                "Channels with errors",
+                self.channels
+                    .iter()
+                    .filter(|channel| channel.last_error.is_some())
+                    .count()
+                    .to_string(),
+            ),
+            ("Items", self.feed_items.len().to_string()),
+            (
+                "New items",
+                count(|item| !item.dismissed && !item.later).to_string(),
+            ),
+            (
+                "Read later",
+                count(|item| !item.dismissed && item.later).to_string(),
+            ),
+            ("Starred", count(|item| item.starred).to_string()),
+            ("Dismissed", count(|item| item.dismissed).to_string()),
+        ]
+    }
+
+    fn render_footer(&mut self, ctx: &Context) {
+        self.worker_status.errors.expire(ctx);
+        // Keeps the relative refresh time current while the app is idle.
+        ctx.request_repaint_after(std::time::Duration::from_secs(60));
+        TopBottomPanel::bottom("footer")
+            .frame(Frame {
+                fill: THEME.colors.bg_darker,
+                inner_margin: Margin::same(6.0),
+                ..Default::default()
+            })
+            .show(ctx, |ui| {
+                let statistics = &self.worker_status.statistics;
+                let mut open_errors = false;
+                ui.horizontal(|ui| {
+                    ui.spacing_mut().item_spacing.x = 4.0;
+                    let dim =
+                        |text: String| RichText::new(text).small().color(THEME.colors.text_dim);
+                    let refreshed = if statistics.refreshed_at > 0 {
+                        format!(
+                            "Last refreshed {}",
+                            widgets::timestamp_to_human_readable(statistics.refreshed_at)
+                        )
+                    } else {
+                        "Not refreshed yet".to_string()
+                    };
+                    let label = ui.label(dim(refreshed));
+                    if let Some(summary) = &self.worker_status.refresh_summary {
+                        let mut lines = vec![summary_text(summary.total)];
+                        lines.extend(
+                            summary
+                                .channels
+                                .iter()
+                                .map(|(title, count)| format!("{}: {}", title, count)),
+                        );
+                        label.on_hover_text(lines.join("\n"));
+                    }
+                    ui.label(dim("·".to_string()));
+                    ui.label(dim(format!(
+                        "{} items",
+                        widgets::format_count(statistics.items)
+                    )));
+                    ui.label(dim("·".to_string()));
+                    ui.label(dim(format!(
+                        "{} new",
+                        widgets::format_count(statistics.new)
+                    )));
+                    if statistics.failing_channels > 0 {
+                        ui.label(dim("·".to_string()));
+                        let errors = ui
+                            .add(
+                                Label::new(
+                                    RichText::new(match statistics.failing_channels {
+                                        1 => "1 error".to_string(),
+                                        n => format!("{} errors", widgets::format_count(n)),
+                                    })
+                                    .small()
+                                    .color(THEME.colors.warning),
+                                )
+                                .sense(egui::Sense::click()),
+                            )
+                            .on_hover_text("Channels that failed to update");
+                        widgets::describe(&errors, WidgetType::Button, "Open error center");
+                        open_errors = errors.clicked();
+                    }
+                });
+                if open_errors {
+                    self.open_error_center();
+                }
+                let entries = &mut self.worker_status.errors.entries;
+                let (channel_banners, banners): (Vec<usize>, Vec<usize>) = (0..entries.len())
+                    .rev()
+                    .filter(|&index| entries[index].banner)
+                    .partition(|&index| entries[index].error.kind.channel_id().is_some());
+                let mut details = false;
+                if !channel_banners.is_empty() {
+                    let text = match channel_banners.len() {
+                        1 => "Failed to update 1 channel".to_string(),
+                        n => format!("Failed to update {} channels", n),
+                    };
+                    if error_banner(ui, Severity::Warning, &text, true, &mut details) {
+                        for index in channel_banners {
+                            entries[index].banner = false;
+                        }
+                    }
+                }
+                for &index in banners.iter().take(MAX_ERROR_BANNERS) {
+                    let entry = &mut entries[index];
+                    let text = error_text(&entry.error, entry.count);
+                    if error_banner(ui, entry.error.severity, &text, false, &mut details) {
+                        entry.banner = false;
+                    }
+                }
+                if banners.len() > MAX_ERROR_BANNERS
+                    && ui
+                        .link(format!(
+                            "{} more in the error center",
+                            banners.len() - MAX_ERROR_BANNERS
+                        ))
+                        .clicked()
+                {
+                    details = true;
+                }
+                if details {
+                    self.open_error_center();
+                }
+            });
+    }
+}
+
+impl TinyrssApp {
+    /// Dismisses the items scrolled past once scrolling paused, see [`ScrollDismiss`].
+    fn dismiss_scrolled_past(&mut self, ctx: &Context) {
+        let deadline = match self.scroll_dismiss.deadline {
+            Some(deadline) => deadline,
+            None => return,
+        };
+        let remaining = deadline - ctx.input(|i| i.time);
+        if remaining > 0.0 {
+            ctx.request_repaint_after(std::time::Duration::from_secs_f64(remaining));
+            return;
+        }
+        let batch = std::mem::take(&mut self.scroll_dismiss);
+        // Hidden right away rather than when the worker answers, so the cards below
+        // stay in place while the scroll offset is moved back by the cards' height.
+        for item in &mut self.feed_items {
+            if batch.ids.contains(&item.id) {
+                item.dismissed = true;
+            }
+        }
+        if let Some(view) = batch.view {
+            self.scroll_memory.shift(view, -batch.height);
+        }
+        if let Some(sender) = &self.sender {
+            sender
+                .send(ToWorker::SetDismissedMany {
+                    ids: batch.ids.into_iter().collect(),
+                    dismissed: true,
+                })
+                .unwrap();
+        }
+    }
+
+    /// Sends the search to the worker once the debounce delay after the last keystroke is over.
+    fn run_search(&mut self, ctx: &Context) {
+        let deadline = match self.search.deadline {
+            Some(deadline) => deadline,
+            None => return,
+        };
+        let remaining = deadline - ctx.input(|i| i.time);
+        if remaining > 0.0 {
+            ctx.request_repaint_after(std::time::Duration::from_secs_f64(remaining));
+            return;
+        }
+        self.search.deadline = None;
+        if self.search.query != self.feed_input {
+            self.feed_page = 0;
+        }
+        self.search.query = self.feed_input.clone();
+        self.search.generation += 1;
+        if self.search.query.is_empty() {
+            self.search.results = None;
+            return;
+        }
+        if let Some(sender) = &self.sender {
+            sender
+                .send(ToWorker::SearchItems {
+                    query: self.search.query.clone(),
+                    generation: self.search.generation,
+                })
+                .unwrap();
+        }
+    }
+
+    fn render_summary_toast(&mut self, ctx: &Context) {
+        let remaining = self.worker_status.summary_toast_until - ctx.input(|i| i.time);
+        if remaining <= 0.0 {
+            return;
+        }
+        if let Some(summary) = &self.worker_status.refresh_summary {
+            egui::Area::new("refresh_summary_toast")
+                .anchor(egui::Align2::RIGHT_BOTTOM, Vec2::new(-12.0, -48.0))
+                .interactable(false)
+                .show(ctx, |ui| {
+                    Frame {
+                        fill: THEME.colors.bg_darker,
+                        inner_margin: Margin::same(8.0),
+                        rounding: THEME.rounding.medium,
+                        ..Default::default()
+                    }
+                    .show(ui, |ui| {
+                        ui.label(RichText::new(summary_text(summary.total)).strong());
+                    });
+                });
+        }
+        ctx.request_repaint_after(std::time::Duration::from_secs_f64(remaining));
+    }
+
+    fn open_error_center(&mut self) {
+        let errors = &mut self.worker_status.errors;
+        errors.open = true;
+        errors.seen = errors.entries.len();
+    }
+
+    fn render_error_center(&mut self, ctx: &Context) {
+        let mut open = self.worker_status.errors.open;
+        let mut retry = None;
+        let mut clear = false;
+        egui::Window::new("Errors")
+            .open(&mut open)
+            .collapsible(false)
+            .default_width(460.0)
+            .anchor(egui::Align2::RIGHT_TOP, Vec2::new(-8.0, 40.0))
+            .show(ctx, |ui| {
+                let entries = &self.worker_status.errors.entries;
+                if entries.is_empty() {
+                    ui.label(RichText::new("No errors so far").color(THEME.colors.text_dim));
+                    return;
+                }
+                let mut grouped: Vec<(&str, usize)> = vec![];
+                for (index, entry) in entries.iter().enumerate().rev() {
+                    if let Some(channel_id) = entry.error.kind.channel_id() {
+                        if !grouped.iter().any(|(id, _)| *id == channel_id) {
+                            grouped.push((channel_id, index));
+                        }
+                    }
+                }
+                ScrollArea::vertical()
+                    .id_source("error_center")
+                    .max_height(400.0)
+                    .show(ui, |ui| {
+                        if !grouped.is_empty() {
+                            ui.label(RichText::new("Channels").strong());
+                            ui.add_space(THEME.spacing.medium);
+                            for (channel_id, index) in &grouped {
+                                let title = self
                                     .channels
                                     .iter()
-                                    .find(|c| c.id == combo_channel)
-                                    .unwrap();
-                                if let Some(sender) = &self.sender {
-                                    sender
-                                        .send(ToWorker::EditChannel {
-                                            id: channel.id.clone(),
-                                            title: edit_title.clone(),
-                                        })
-                                        .unwrap();
+                                    .find(|channel| channel.id == *channel_id)
+                                    .map(|channel| {
+                                        channel.title.clone().unwrap_or(channel.link.clone())
+                                    })
+                                    .unwrap_or(channel_id.to_string());
+                                if error_center_entry(ui, &title, &entries[*index]) {
+                                    retry = Some(*index);
                                 }
-                                modal.close();
+                            }
+                            ui.add_space(THEME.spacing.large);
+                            ui.label(RichText::new("Other").strong());
+                            ui.add_space(THEME.spacing.medium);
+                        }
+                        for (index, entry) in entries.iter().enumerate().rev() {
+                            if entry.error.kind.channel_id().is_some() {
+                                continue;
+                            }
+                            let severity = match entry.error.severity {
+                                Severity::Error => "Error",
+                                Severity::Warning => "Warning",
                             };
-                        });
+                            let title = format!("{} · {}", severity, entry.error.kind.label());
+                            if error_center_entry(ui, &title, entry) {
+                                retry = Some(index);
+                            }
+                        }
                     });
-
-                    ui.data_mut(|d| d.insert_temp(combo_id, combo_channel));
-                    ui.data_mut(|d| d.insert_temp(edit_title_id, edit_title));
+                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    if ui.button("Clear").clicked() {
+                        clear = true;
+                    }
+                });
+            });
+        let errors = &mut self.worker_status.errors;
+        errors.open = open;
+        if clear {
+            errors.entries.clear();
+            errors.seen = 0;
+        } else if let Some(index) = retry {
+            let entry = &mut errors.entries[index];
+            entry.banner = false;
+            match entry.error.retry.take() {
+                // Trusting a certificate needs the user to check its fingerprint first.
+                Some(ToWorker::SetTrustedCertificate {
+                    id,
+                    fingerprint: Some(fingerprint),
+                }) => {
+                    self.trusting_certificate = Some((id, fingerprint));
+                    egui_modal::Modal::new(ctx, "modal_trust_certificate").open();
                 }
+                Some(message) => {
+                    if let Some(sender) = &self.sender {
+                        sender.send(message).unwrap();
+                    }
+                }
+                None => {}
+            }
+        }
+    }
 
-                ui.horizontal(|ui| {
-                    ui.label("Manage channels");
-                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
-                        if ui
-                            .add_enabled(!self.channels.is_empty(), Button::new("Manage"))
-                            .clicked()
-                        {
-                            modal.open();
-                        }
-                    })
-                });
+    fn render_crash_report(&mut self, ctx: &Context) {
+        let path = match &self.crash_report {
+            Some(path) => path.clone(),
+            None => return,
+        };
+        let modal = egui_modal::Modal::new(ctx, "modal_crash_report");
+        let mut open_error = None;
+        modal.show(|ui| {
+            modal.title(ui, "Tinyrss crashed");
+            modal.body(
+                ui,
+                format!(
+                    "Tinyrss closed unexpectedly last time. A crash report was saved to\n{}",
+                    path.display()
+                ),
+            );
+            modal.buttons(ui, |ui| {
+                ui.spacing_mut().button_padding = Vec2::new(8., 4.);
+                if ui.add(Button::new("Close")).clicked() {
+                    modal.close();
+                    self.crash_report = None;
+                };
+                if ui.add(Button::new("Open report")).clicked() {
+                    if let Err(err) = open::that(&path) {
+                        open_error = Some(err.to_string());
+                    }
+                    modal.close();
+                    self.crash_report = None;
+                };
             });
+        });
+        if let Some(err) = open_error {
+            self.worker_status.errors.push(WorkerError::new(
+                ErrorKind::Filesystem,
+                "Failed to open crash report",
+                err,
+            ));
+        }
     }
 
-    fn render_footer(&mut self, ctx: &Context) {
-        if self.worker_status.worker_errors.is_empty() {
-            TopBottomPanel::bottom("footer")
-                .frame(Frame {
-                    fill: THEME.colors.bg_darker,
-                    inner_margin: Margin::same(6.0),
-                    ..Default::default()
-                })
-                .show(ctx, |ui| {
-                    self.worker_status.worker_errors.retain(|error| {
-                        let mut retain = true;
+    fn render_item_details(&mut self, ctx: &Context, open: Option<String>) {
+        let config = ConfigBuilder::from_current();
+        let modal =
+            egui_modal::Modal::new(ctx, "modal_item_details").with_style(&egui_modal::ModalStyle {
+                default_width: Some(config.reader_line_width + 2.0 * THEME.spacing.large),
+                ..Default::default()
+            });
+        if let Some(id) = open {
+            self.detail_item = Some(id);
+            modal.open();
+        }
+        let item = match self
+            .detail_item
+            .as_ref()
+            .and_then(|id| self.feed_items.iter().find(|item| &item.id == id))
+        {
+            Some(item) => item,
+            None => return,
+        };
+        let id = item.id.clone();
+        let (dismissed, later, starred) = (item.dismissed, item.later, item.starred);
+        let has_content = item.content.is_some();
+        let has_text = has_content || item.summary.is_some();
+        let mut action = None;
+        let mut export = None;
 
-                        Frame {
-                            fill: THEME.colors.warning,
-                            inner_margin: Margin::same(6.0),
-                            rounding: THEME.rounding.medium,
-                            ..Default::default()
-                        }
+        modal.show(|ui| {
+            modal.frame(ui, |ui| {
+                let mut title = widgets::highlighted_job(
+                    item.title.as_deref().unwrap_or("<no title>"),
+                    &self.search.query,
+                    egui::TextFormat {
+                        font_id: egui::FontId::proportional(22.0),
+                        color: THEME.colors.text,
+                        ..Default::default()
+                    },
+                );
+                title.wrap.max_width = ui.available_width();
+                ui.add(Label::new(title).wrap(true));
+                ui.add_space(THEME.spacing.small);
+                let mut metadata = vec![widgets::format_timestamp(item.published)];
+                if let Some(author) = &item.author {
+                    metadata.push(format!("by {}", author));
+                }
+                if let Some(channel_title) = &item.channel_title {
+                    metadata.push(channel_title.clone());
+                }
+                if item.reading_time > 0 {
+                    metadata.push(format!("{} min read", item.reading_time));
+                }
+                ui.label(RichText::new(metadata.join(" · ")).color(THEME.colors.text_dim));
+                ui.add_space(THEME.spacing.medium);
+                let (fill, text_color) = if config.reader_sepia {
+                    (theme::SEPIA_BG, theme::SEPIA_TEXT)
+                } else {
+                    (egui::Color32::TRANSPARENT, THEME.colors.text)
+                };
+                Frame {
+                    fill,
+                    inner_margin: Margin::same(THEME.spacing.large),
+                    rounding: THEME.rounding.large,
+                    ..Default::default()
+                }
+                .show(ui, |ui| {
+                    ScrollArea::vertical()
+                        .id_source("item_details_summary")
+                        .max_height(320.0)
                         .show(ui, |ui| {
-                            ui.horizontal(|ui| {
-                                let mut message = String::new();
-                                if !error.error_message.is_empty() {
-                                    message = format!(": {}", error.error_message);
+                            ui.set_width(config.reader_line_width);
+                            match item.content.as_ref().or(item.summary.as_ref()) {
+                                Some(body) => {
+                                    let text = strip_html(body);
+                                    let text =
+                                        text.split_whitespace().collect::<Vec<_>>().join(" ");
+                                    let family = match config.reader_font {
+                                        ReaderFont::Proportional => egui::FontFamily::Proportional,
+                                        ReaderFont::Monospace => egui::FontFamily::Monospace,
+                                    };
+                                    let mut job = widgets::highlighted_job(
+                                        &text,
+                                        &self.search.query,
+                                        egui::TextFormat {
+                                            font_id: egui::FontId::new(
+                                                config.reader_font_size,
+                                                family,
+                                            ),
+                                            color: text_color,
+                                            ..Default::default()
+                                        },
+                                    );
+                                    job.justify = config.reader_justify;
+                                    widgets::spaced_text(ui, job, config.reader_line_height);
                                 }
-                                ui.add(
-                                    Label::new(format!("{}{}", error.description, message))
-                                        .wrap(true),
-                                );
-                                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
-                                    if ui.button("🗙").clicked() {
-                                        retain = false;
-                                    }
-                                });
-                            });
+                                None => {
+                                    ui.label(
+                                        RichText::new("No content").color(THEME.colors.text_dim),
+                                    );
+                                }
+                            }
                         });
-
-                        retain
-                    });
                 });
+                if item.comments.is_some() || !item.links.0.is_empty() {
+                    ui.add_space(THEME.spacing.medium);
+                    ui.horizontal_wrapped(|ui| {
+                        if let Some(comments) = &item.comments {
+                            ui.hyperlink_to("Comments", comments)
+                                .on_hover_text(comments);
+                        }
+                        for link in &item.links.0 {
+                            ui.hyperlink_to(link.label(), &link.href)
+                                .on_hover_text(&link.href);
+                        }
+                    });
+                }
+            });
+            modal.buttons(ui, |ui| {
+                ui.spacing_mut().button_padding = Vec2::new(8., 4.);
+                if ui.add(Button::new("Close")).clicked() {
+                    modal.close();
+                }
+                if ui
+                    .add(Button::new(if starred { "Unstar" } else { "Star" }))
+                    .clicked()
+                {
+                    action = Some(KeyAction::Star);
+                }
+                if has_text
+                    && ui
+                        .add(Button::new("To notes"))
+                        .on_hover_text("Save as Markdown to the notes folder.")
+                        .clicked()
+                {
+                    export = Some(ToWorker::SaveNote { id: id.clone() });
+                }
+                if has_content {
+                    if ui.add(Button::new("Print")).clicked() {
+                        export = Some(ToWorker::PrintItem { id: id.clone() });
+                    }
+                    if ui
+                        .add(Button::new("Save as…"))
+                        .on_hover_text("Save the article as PDF or printable HTML.")
+                        .clicked()
+                    {
+                        export = Some(ToWorker::SaveItem { id: id.clone() });
+                    }
+                }
+                if !dismissed
+                    && ui
+                        .add(Button::new(if later { "To new" } else { "Later" }))
+                        .clicked()
+                {
+                    action = Some(KeyAction::Later);
+                    modal.close();
+                }
+                if ui
+                    .add(Button::new(if dismissed { "Restore" } else { "Dismiss" }))
+                    .clicked()
+                {
+                    action = Some(KeyAction::Dismiss);
+                    modal.close();
+                }
+                if ui
+                    .add(Button::new("Open").fill(THEME.colors.accent))
+                    .clicked()
+                {
+                    action = Some(KeyAction::Open);
+                    modal.close();
+                }
+            });
+        });
+
+        if let (Some(message), Some(sender)) = (export, &self.sender) {
+            sender.send(message).unwrap();
+        }
+        match action {
+            Some(KeyAction::Open) => self.open_item(id),
+            Some(KeyAction::Dismiss) => self.set_dismissed(id, !dismissed),
+            Some(KeyAction::Star) => self.set_starred(id, !starred),
+            Some(KeyAction::Later) => self.set_later(id, !later),
+            _ => {}
+        }
+        if !modal.is_open() {
+            self.detail_item = None;
         }
     }
-}
 
-impl TinyrssApp {
+    /// Items first seen after the previous visit to the Feed page are marked as fresh.
+    fn mark_feed_visited(&mut self) {
+        if self.page != Page::Feed {
+            return;
+        }
+        let now = chrono::Utc::now().timestamp();
+        self.previous_feed_visit = std::mem::replace(&mut CONFIG.lock().last_feed_visit, now);
+        ConfigBuilder::from_current().apply();
+    }
+
+    /// Restarts the worker against another profile and clears state loaded from the previous one.
+    fn switch_profile(&mut self, profile: Option<String>) {
+        if let Some(sender) = &self.sender {
+            sender.send(ToWorker::SwitchProfile { profile }).unwrap();
+        }
+        self.channels.clear();
+        self.feed_items.clear();
+        self.keyword_preview = None;
+        self.rule_pack_preview = None;
+        self.feed_page = 0;
+        self.focused_card = None;
+        self.detail_item = None;
+        self.worker_status = WorkerStatus {
+            updating_feed: true,
+            errors: std::mem::take(&mut self.worker_status.errors),
+            ..Default::default()
+        };
+    }
+
     fn add_channel(&mut self, link: &str) {
         if let Some(sender) = &self.sender {
+            self.next_request_id += 1;
+            self.pending_add = Some(self.next_request_id);
+            self.add_channel_error = None;
             sender
-                .send(ToWorker::AddChannel { link: link.into() })
+                .send(ToWorker::AddChannel {
+                    link: link.into(),
+                    request_id: self.pending_add,
+                })
                 .unwrap();
         }
     }
@@ -672,11 +5580,335 @@ impl TinyrssApp {
         }
     }
 
+    fn search_history(&mut self) {
+        if let Some(sender) = &self.sender {
+            sender
+                .send(ToWorker::SearchHistory {
+                    query: self.history_input.trim().to_string(),
+                })
+                .unwrap();
+        }
+    }
+
+    fn load_calendar(&mut self) {
+        let month = self.calendar.month();
+        if let Some(sender) = &self.sender {
+            sender
+                .send(ToWorker::LoadCalendar {
+                    year: month.year(),
+                    month: month.month(),
+                })
+                .unwrap();
+        }
+    }
+
     fn dismiss_all(&mut self) {
         if let Some(sender) = &self.sender {
             sender.send(ToWorker::DismissAll).unwrap();
         }
     }
+
+    /// Bar above the cards in selection mode, returns the clicked action.
+    fn render_bulk_bar(&self, ui: &mut egui::Ui) -> Option<BulkAction> {
+        let amount = self.selected_items.as_ref().map_or(0, HashSet::len);
+        let mut action = None;
+        Frame {
+            fill: THEME.colors.bg,
+            inner_margin: Margin::same(6.0),
+            rounding: THEME.rounding.large,
+            ..Default::default()
+        }
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(format!("{} selected", amount));
+                if ui.link("Select page").clicked() {
+                    action = Some(BulkAction::SelectPage);
+                }
+                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    let close = ui.button("🗙");
+                    widgets::describe(&close, WidgetType::Button, "Leave selection mode");
+                    if close.clicked() {
+                        action = Some(BulkAction::Cancel);
+                    }
+                    ui.add_enabled_ui(amount > 0, |ui| {
+                        for (label, bulk_action) in [
+                            ("Open", BulkAction::Open),
+                            ("Unstar", BulkAction::Unstar),
+                            ("Star", BulkAction::Star),
+                            ("Restore", BulkAction::Restore),
+                            ("Dismiss", BulkAction::Dismiss),
+                        ] {
+                            if ui.link(label).clicked() {
+                                action = Some(bulk_action);
+                            }
+                        }
+                    });
+                });
+            });
+        });
+        action
+    }
+
+    /// `page_ids` are the items on the current page.
+    fn apply_bulk_action(&mut self, action: BulkAction, page_ids: Vec<String>) {
+        if let (BulkAction::SelectPage, Some(selected)) = (action, &mut self.selected_items) {
+            selected.extend(page_ids);
+            return;
+        }
+        let ids: Vec<String> = self
+            .selected_items
+            .take()
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        let message = match action {
+            BulkAction::Dismiss => Some(ToWorker::SetDismissedMany {
+                ids,
+                dismissed: true,
+            }),
+            BulkAction::Restore => Some(ToWorker::SetDismissedMany {
+                ids,
+                dismissed: false,
+            }),
+            BulkAction::Star => Some(ToWorker::SetStarredMany { ids, starred: true }),
+            BulkAction::Unstar => Some(ToWorker::SetStarredMany {
+                ids,
+                starred: false,
+            }),
+            BulkAction::Open => Some(ToWorker::OpenItems { ids }),
+            BulkAction::SelectPage | BulkAction::Cancel => None,
+        };
+        if let (Some(message), Some(sender)) = (message, &self.sender) {
+            sender.send(message).unwrap();
+        }
+    }
+
+    fn dismiss_matching(&mut self, ids: Vec<String>) {
+        if let Some(sender) = &self.sender {
+            sender
+                .send(ToWorker::DismissMatching {
+                    ids,
+                    later: self.feed_type_combo == FeedTypeCombo::Later,
+                })
+                .unwrap();
+        }
+    }
+
+    /// Runs the click action of the item's channel, returns the id when the details
+    /// should open. Actions that can't run for the item fall back to the details.
+    fn click_item(&mut self, id: String) -> Option<String> {
+        let item = self.feed_items.iter().find(|item| item.id == id)?;
+        let channel = self.channels.iter().find(|c| c.id == item.channel);
+        let action = channel.map(|c| c.click_action).unwrap_or_default();
+        let title = item.title.clone().unwrap_or_default();
+        let command = match action {
+            ClickAction::Reader => None,
+            ClickAction::Browser => {
+                self.open_item(id);
+                return None;
+            }
+            ClickAction::Player => {
+                let url = item.enclosure.clone().unwrap_or(item.link.clone());
+                Some((CONFIG.lock().player_command.clone(), url))
+            }
+            ClickAction::Command => channel
+                .and_then(|c| c.click_command.clone())
+                .map(|command| (command, item.link.clone())),
+        };
+        match (command, &self.sender) {
+            (Some((command, url)), Some(sender)) if !command.trim().is_empty() => {
+                sender
+                    .send(ToWorker::SendTo {
+                        command,
+                        url,
+                        title,
+                    })
+                    .unwrap();
+                None
+            }
+            _ => Some(id),
+        }
+    }
+
+    fn open_item(&mut self, id: String) {
+        if let Some(sender) = &self.sender {
+            sender.send(ToWorker::OpenItem { id }).unwrap();
+        }
+    }
+
+    fn set_dismissed(&mut self, id: String, dismissed: bool) {
+        if let Some(sender) = &self.sender {
+            sender
+                .send(ToWorker::SetDismissed { id, dismissed })
+                .unwrap();
+        }
+    }
+
+    fn set_starred(&mut self, id: String, starred: bool) {
+        if let Some(sender) = &self.sender {
+            sender.send(ToWorker::SetStarred { id, starred }).unwrap();
+        }
+    }
+
+    fn set_later(&mut self, id: String, later: bool) {
+        if let Some(sender) = &self.sender {
+            sender.send(ToWorker::SetLater { id, later }).unwrap();
+        }
+    }
+
+    fn count_new_since_away(&mut self, frame: &mut eframe::Frame) {
+        if let Some(snapshot) = &self.away_snapshot {
+            let amount = self
+                .feed_items
+                .iter()
+                .filter(|item| !item.dismissed && !snapshot.contains(&item.id))
+                .count();
+            if amount != self.new_since_away {
+                self.new_since_away = amount;
+                frame.set_window_title(&format!("Tinyrss ({} new)", amount));
+            }
+        }
+    }
+
+    fn copy_to_clipboard(&mut self, text: String) {
+        let result = copypasta::ClipboardContext::new().and_then(|mut ctx| ctx.set_contents(text));
+        if let Err(err) = result {
+            self.worker_status.errors.push(WorkerError::new(
+                ErrorKind::System,
+                "Failed to access clipboard",
+                err.to_string(),
+            ));
+        }
+    }
+
+    fn check_clipboard_for_feed(&mut self) {
+        if !CONFIG.lock().detect_feed_in_clipboard {
+            return;
+        }
+        let clipboard_content =
+            match copypasta::ClipboardContext::new().and_then(|mut ctx| ctx.get_contents()) {
+                Ok(content) => content,
+                Err(_) => return,
+            };
+        let link = clipboard_content.trim();
+        if looks_like_feed_url(link)
+            && !self.channels.iter().any(|channel| channel.link == link)
+            && self.ignored_clipboard_feed.as_deref() != Some(link)
+        {
+            self.clipboard_feed = Some(link.to_string());
+        }
+    }
+}
+
+fn looks_like_feed_url(text: &str) -> bool {
+    let text = text.to_lowercase();
+    if !(text.starts_with("http://") || text.starts_with("https://"))
+        || text.contains(char::is_whitespace)
+    {
+        return false;
+    }
+    ["rss", "atom", "feed", ".xml"]
+        .iter()
+        .any(|hint| text.contains(hint))
+}
+
+fn push_service_label(service: PushService) -> &'static str {
+    match service {
+        PushService::Off => "Off",
+        PushService::Ntfy => "ntfy",
+        PushService::Gotify => "Gotify",
+    }
+}
+
+fn dns_mode_label(mode: DnsMode) -> &'static str {
+    match mode {
+        DnsMode::System => "System",
+        DnsMode::Https => "DNS over HTTPS",
+        DnsMode::Server => "Custom server",
+    }
+}
+
+fn error_text(error: &WorkerError, count: usize) -> String {
+    let mut text = error.description.clone();
+    if !error.error_message.is_empty() {
+        text.push_str(&format!(": {}", error.error_message));
+    }
+    if count > 1 {
+        text.push_str(&format!(" (×{})", count));
+    }
+    text
+}
+
+/// Shows a footer banner and returns whether it was closed.
+fn error_banner(
+    ui: &mut egui::Ui,
+    severity: Severity,
+    text: &str,
+    with_details: bool,
+    details: &mut bool,
+) -> bool {
+    let (fill, color) = match severity {
+        Severity::Error => (THEME.colors.warning, THEME.colors.text),
+        Severity::Warning => (THEME.colors.bg, THEME.colors.warning),
+    };
+    let mut closed = false;
+    Frame {
+        fill,
+        inner_margin: Margin::same(6.0),
+        rounding: THEME.rounding.medium,
+        ..Default::default()
+    }
+    .show(ui, |ui| {
+        ui.horizontal(|ui| {
+            ui.add(Label::new(RichText::new(text).color(color)).wrap(true));
+            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                let close = ui.button("🗙");
+                widgets::describe(&close, WidgetType::Button, "Close error");
+                closed = close.clicked();
+                if with_details && ui.link("Details").clicked() {
+                    *details = true;
+                }
+            });
+        });
+    });
+    closed
+}
+
+/// Shows one error in the error center and returns whether its retry button was clicked.
+fn error_center_entry(ui: &mut egui::Ui, title: &str, entry: &ErrorEntry) -> bool {
+    let mut retry = false;
+    ui.horizontal(|ui| {
+        ui.label(RichText::new(title).color(THEME.colors.warning));
+        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+            if entry.error.retry.is_some() {
+                let button = ui.button(match entry.error.kind {
+                    ErrorKind::Certificate { .. } => "Trust certificate",
+                    _ => "Retry",
+                });
+                widgets::describe(
+                    &button,
+                    WidgetType::Button,
+                    format!("Retry: {}", entry.error.description),
+                );
+                retry = button.clicked();
+            }
+            ui.label(
+                RichText::new(widgets::format_timestamp(entry.timestamp))
+                    .color(THEME.colors.text_dim),
+            );
+        });
+    });
+    ui.add(Label::new(error_text(&entry.error, entry.count)).wrap(true));
+    ui.separator();
+    retry
+}
+
+fn summary_text(total: u64) -> String {
+    match total {
+        0 => "no new items".to_string(),
+        1 => "1 new item".to_string(),
+        n => format!("{} new items", n),
+    }
 }
 
 impl TinyrssApp {
@@ -707,5 +5939,11 @@ impl TinyrssApp {
         };
 
         ctx.set_style(style);
+
+        self.configure_fonts(ctx);
+    }
+
+    fn configure_fonts(&mut self, ctx: &egui::Context) {
+        ctx.set_fonts(font_definitions(&ConfigBuilder::from_current()));
     }
 }
@@ -0,0 +1,45 @@
+use super::Page;
+
+/// Curated channel lists offered on the welcome screen of an empty profile.
+pub struct Bundle {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub opml: &'static str,
+}
+
+pub const BUNDLES: &[Bundle] = &[
+    Bundle {
+        name: "Tech news",
+        description: "Hacker News, Ars Technica, The Verge and LWN",
+        opml: include_str!("bundles/tech.opml"),
+    },
+    Bundle {
+        name: "Rust blogs",
+        description: "Official Rust blogs, This Week in Rust and fasterthanli.me",
+        opml: include_str!("bundles/rust.opml"),
+    },
+    Bundle {
+        name: "Science",
+        description: "Quanta Magazine, NASA, ScienceDaily and Phys.org",
+        opml: include_str!("bundles/science.opml"),
+    },
+];
+
+/// Short tour of the pages, shown below the bundles.
+pub const TOUR: &[(Page, &str, &str)] = &[
+    (
+        Page::Feed,
+        "Feed",
+        "New items from all channels. Dismiss what you've read or keep it for later.",
+    ),
+    (
+        Page::Channels,
+        "Channels",
+        "Add channels by link, import an OPML file and see which channels fail to refresh.",
+    ),
+    (
+        Page::Settings,
+        "Settings",
+        "Refresh interval, theme, keybindings, sync and backups.",
+    ),
+];
@@ -6,6 +6,10 @@ use eframe::{
     epaint::Shadow,
 };
 
+/// Paper colors of the reader's sepia mode.
+pub const SEPIA_BG: Color32 = Color32::from_rgb(244, 236, 216);
+pub const SEPIA_TEXT: Color32 = Color32::from_rgb(91, 70, 54);
+
 pub struct Spacing {
     pub large: f32,
     pub medium: f32,
@@ -1,14 +1,27 @@
 use super::THEME;
-use crate::worker::{Channel, Item, ToWorker, CONFIG};
-use chrono::{Duration, Local, TimeZone, Utc};
+use crate::worker::{
+    newsletter_address, Channel, ChatService, ConfigBuilder, Download, DownloadState, Item,
+    ShareFormat, TimestampFormat, ToWorker, CONFIG,
+};
+use chrono::{Duration, Local, Locale, NaiveDate, SecondsFormat, TimeZone};
 use crossbeam_channel::Sender;
 use eframe::epaint::text::{LayoutJob, TextWrapping};
 use egui::{
-    Align, Button, CollapsingHeader, FontId, Frame, Hyperlink, Label, Layout, RichText, TextFormat,
-    Vec2,
+    Align, Button, CollapsingHeader, Color32, FontId, Frame, InnerResponse, Label, Layout, Rect,
+    Response, RichText, Rounding, Sense, Stroke, TextFormat, Vec2, WidgetInfo, WidgetType,
 };
+use lazy_static::lazy_static;
+use std::cell::Cell;
+use std::sync::Arc;
+use unicode_bidi::BidiInfo;
 use unicode_truncate::UnicodeTruncateStr;
 
+lazy_static! {
+    static ref LOCALE: Locale = sys_locale::get_locale()
+        .and_then(|locale| Locale::try_from(locale.replace('-', "_").as_str()).ok())
+        .unwrap_or(Locale::POSIX);
+}
+
 pub fn truncate(string: &str, width: usize, trim_char: Option<&str>) -> String {
     let (truncated, width_t) = string.unicode_truncate(width);
     let mut truncated_string = truncated.to_string();
@@ -18,8 +31,198 @@ pub fn truncate(string: &str, width: usize, trim_char: Option<&str>) -> String {
     truncated_string
 }
 
+/// Whether the first paragraph of `text` is written right-to-left.
+pub fn is_rtl(text: &str) -> bool {
+    BidiInfo::new(text, None)
+        .paragraphs
+        .first()
+        .is_some_and(|paragraph| paragraph.level.is_rtl())
+}
+
+/// Reorders `text` into visual order, since egui lays out glyphs strictly left to right.
+pub fn to_visual_order(text: &str) -> String {
+    let bidi = BidiInfo::new(text, None);
+    bidi.paragraphs
+        .iter()
+        .map(|paragraph| bidi.reorder_line(paragraph, paragraph.range.clone()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Sets the role and name announced by screen readers for a widget.
+pub fn describe(response: &Response, typ: WidgetType, label: impl ToString) {
+    let label = label.to_string();
+    response.widget_info(|| WidgetInfo::labeled(typ, &label));
+}
+
+/// Announces a progress bar together with its current fraction.
+pub fn describe_progress(response: &Response, label: impl ToString, progress: f32) {
+    let label = label.to_string();
+    response.widget_info(|| WidgetInfo {
+        value: Some(progress as f64),
+        ..WidgetInfo::labeled(WidgetType::Other, &label)
+    });
+}
+
+/// Byte ranges of non-overlapping, case-insensitive occurrences of `query` in `text`.
+fn find_matches(text: &str, query: &str) -> Vec<(usize, usize)> {
+    let query: Vec<char> = query.to_lowercase().chars().collect();
+    let mut matches = vec![];
+    if query.is_empty() {
+        return matches;
+    }
+    let mut next_allowed = 0;
+    for (start, _) in text.char_indices() {
+        if start < next_allowed {
+            continue;
+        }
+        let mut matched = 0;
+        let mut end = start;
+        for (offset, c) in text[start..].char_indices() {
+            for lower in c.to_lowercase() {
+                if matched < query.len() && query[matched] == lower {
+                    matched += 1;
+                } else {
+                    matched = usize::MAX;
+                    break;
+                }
+            }
+            if matched == usize::MAX {
+                break;
+            }
+            if matched == query.len() {
+                end = start + offset + c.len_utf8();
+                break;
+            }
+        }
+        if matched == query.len() {
+            matches.push((start, end));
+            next_allowed = end;
+        }
+    }
+    matches
+}
+
+/// Lays out `text` with every occurrence of `query` on an accent background.
+pub fn highlighted_job(text: &str, query: &str, format: TextFormat) -> LayoutJob {
+    let highlight = TextFormat {
+        color: THEME.colors.text,
+        background: THEME.colors.accent,
+        ..format.clone()
+    };
+    let mut job = LayoutJob::default();
+    let mut last = 0;
+    for (start, end) in find_matches(text, query) {
+        job.append(&text[last..start], 0.0, format.clone());
+        job.append(&text[start..end], 0.0, highlight.clone());
+        last = end;
+    }
+    job.append(&text[last..], 0.0, format);
+    job
+}
+
+/// Options that change how a feed card is drawn.
+#[derive(Default)]
+pub struct CardOptions<'a> {
+    /// Draws the keyboard focus outline.
+    pub focused: bool,
+    /// Marks the item as new since the previous visit.
+    pub fresh: bool,
+    /// Dims the card and shows the item's age, for items older than `stale_after_days`.
+    pub stale: bool,
+    /// Channel color shown as a stripe along the left edge.
+    pub stripe: Option<Color32>,
+    /// Search query highlighted in the title.
+    pub highlight: &'a str,
+    /// Media thumbnail shown below the title.
+    pub thumbnail: Option<&'a egui::TextureHandle>,
+    /// Shows a selection checkbox in selection mode.
+    pub selected: Option<bool>,
+    /// Receives the author when it is clicked, to filter the feed by them.
+    pub author_filter: Option<&'a Cell<Option<String>>>,
+}
+
+/// How much of their color the text of stale cards keeps.
+const STALE_DIMMING: f32 = 0.6;
+
+/// Widest a thumbnail is drawn on a card.
+const THUMBNAIL_WIDTH: f32 = 160.0;
+
+/// Parses a `#rrggbb` channel color as stored in the database.
+pub fn parse_color(hex: &str) -> Option<Color32> {
+    let hex = hex.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    Some(Color32::from_rgb(
+        (value >> 16) as u8,
+        (value >> 8) as u8,
+        value as u8,
+    ))
+}
+
+pub fn color_to_hex(color: Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
+
+/// Formats a count with thousands separators, e.g. `1,204`.
+pub fn format_count(count: i64) -> String {
+    let digits = count.unsigned_abs().to_string();
+    let mut text = String::new();
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            text.push(',');
+        }
+        text.push(digit);
+    }
+    if count < 0 {
+        text.insert(0, '-');
+    }
+    text
+}
+
+/// Formats a byte count with a binary unit, e.g. `12.3 MiB`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Formats a play time in seconds as `m:ss`, or `h:mm:ss` from an hour on.
+pub fn format_duration(seconds: i64) -> String {
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+pub fn format_timestamp(timestamp: i64) -> String {
+    let dt = match Local.timestamp_opt(timestamp, 0).earliest() {
+        Some(dt) => dt,
+        None => return String::from("???"),
+    };
+
+    match CONFIG.lock().timestamp_format {
+        TimestampFormat::Relative => timestamp_to_human_readable(timestamp),
+        TimestampFormat::Absolute => dt.format_localized("%x %H:%M", *LOCALE).to_string(),
+        TimestampFormat::Iso => dt.to_rfc3339_opts(SecondsFormat::Secs, false),
+    }
+}
+
 pub fn timestamp_to_human_readable(timestamp: i64) -> String {
-    let dt = match Utc.timestamp_millis_opt(timestamp * 1000).earliest() {
+    let dt = match Local.timestamp_opt(timestamp, 0).earliest() {
         Some(dt) => dt,
         None => return String::from("???"),
     };
@@ -51,7 +254,36 @@ pub fn timestamp_to_human_readable(timestamp: i64) -> String {
             format!("{} weeks ago", duration.num_weeks())
         }
     } else {
-        dt.format("%d %b %Y").to_string()
+        dt.format_localized("%e %b %Y", *LOCALE).to_string()
+    }
+}
+
+/// `date` in the words of the system's locale, see [`chrono::format::strftime`].
+pub fn format_date(date: NaiveDate, format: &str) -> String {
+    date.format_localized(format, *LOCALE).to_string()
+}
+
+/// Time of day of `timestamp`, in local time.
+pub fn format_time(timestamp: i64) -> String {
+    match Local.timestamp_opt(timestamp, 0).earliest() {
+        Some(dt) => dt.format_localized("%H:%M", *LOCALE).to_string(),
+        None => String::from("???"),
+    }
+}
+
+/// Age badge of stale items, like "3 weeks old".
+pub fn format_age(timestamp: i64) -> String {
+    let days = Duration::seconds(Local::now().timestamp() - timestamp).num_days();
+    let (amount, unit) = match days {
+        ..=13 => (days, "day"),
+        14..=59 => (days / 7, "week"),
+        60..=729 => (days / 30, "month"),
+        _ => (days / 365, "year"),
+    };
+    if amount == 1 {
+        format!("1 {} old", unit)
+    } else {
+        format!("{} {}s old", amount, unit)
     }
 }
 
@@ -77,36 +309,91 @@ pub fn channel_card(
         .show(ui, |ui| {
             ui.set_width(ui.available_width());
             if let Some(title) = &channel.title {
-                CollapsingHeader::new(RichText::new(truncate(title, 40, None)).strong().heading())
-                    .default_open(false)
-                    .show(ui, |ui| {
-                        ui.spacing_mut().button_padding = Vec2::new(6., 3.);
-                        ui.add_space(THEME.spacing.small);
+                let header = CollapsingHeader::new(
+                    RichText::new(truncate(title, 40, None)).strong().heading(),
+                )
+                .default_open(false)
+                .show(ui, |ui| {
+                    ui.spacing_mut().button_padding = Vec2::new(6., 3.);
+                    ui.add_space(THEME.spacing.small);
+                    if let Some(notes) = &channel.notes {
+                        let notes = ui.add(
+                            Label::new(RichText::new(format!("📝 {}", notes)).italics()).wrap(true),
+                        );
                         if let Some(description) = &channel.description {
+                            notes.on_hover_text(description);
+                        }
+                        ui.add_space(THEME.spacing.medium);
+                    } else if let Some(description) = &channel.description {
+                        if is_rtl(description) {
+                            ui.with_layout(Layout::top_down(Align::RIGHT), |ui| {
+                                ui.add(
+                                    Label::new(RichText::new(to_visual_order(description)))
+                                        .wrap(true),
+                                );
+                            });
+                        } else {
                             ui.add(Label::new(RichText::new(description)).wrap(true));
+                        }
+                        ui.add_space(THEME.spacing.medium);
+                    }
+                    if channel.newsletter {
+                        if let Some(address) = newsletter_address(&channel.link) {
+                            ui.horizontal(|ui| {
+                                ui.label(format!("✉ {}", address))
+                                    .on_hover_text("Emails sent here arrive as items");
+                                if ui.link("Copy").clicked() {
+                                    ui.output_mut(|o| o.copied_text = address.clone());
+                                }
+                            });
                             ui.add_space(THEME.spacing.medium);
                         }
-                        if ui
-                            .add(Button::new("Unsubscribe").fill(THEME.colors.warning))
-                            .clicked()
-                        {
-                            if let Some(sender) = sender {
-                                sender
-                                    .send(ToWorker::Unsubscribe {
-                                        id: channel.id.clone(),
-                                    })
-                                    .unwrap();
-                            }
+                    }
+                    let unsubscribe = ui.add(Button::new("Unsubscribe").fill(THEME.colors.warning));
+                    describe(
+                        &unsubscribe,
+                        WidgetType::Button,
+                        format!("Unsubscribe from {}", title),
+                    );
+                    if unsubscribe.clicked() {
+                        if let Some(sender) = sender {
+                            sender
+                                .send(ToWorker::Unsubscribe {
+                                    id: channel.id.clone(),
+                                })
+                                .unwrap();
                         }
-                    });
+                    }
+                });
+                describe(&header.header_response, WidgetType::CollapsingHeader, title);
             } else {
                 ui.label(RichText::new("<no title>").strong().heading());
             }
+            if let Some(error) = &channel.last_error {
+                let label = ui.add(
+                    Label::new(
+                        RichText::new(format!("⚠ {}", truncate(error, 60, None)))
+                            .color(THEME.colors.warning),
+                    )
+                    .wrap(true),
+                );
+                let since = channel
+                    .last_error_at
+                    .map(format_timestamp)
+                    .unwrap_or_default();
+                label.on_hover_text(format!("{}\n{}", error, since));
+            }
         });
     }
 }
 
-pub fn feed_card(ui: &mut egui::Ui, sender: Option<Sender<ToWorker>>, item: &Item) {
+/// Entry of the downloads page with its progress and the controls that apply to its state.
+pub fn download_card(ui: &mut egui::Ui, sender: Option<Sender<ToWorker>>, download: &Download) {
+    let send = |message: ToWorker| {
+        if let Some(sender) = &sender {
+            sender.send(message).unwrap();
+        }
+    };
     Frame {
         fill: THEME.colors.bg,
         rounding: THEME.rounding.large,
@@ -115,9 +402,191 @@ pub fn feed_card(ui: &mut egui::Ui, sender: Option<Sender<ToWorker>>, item: &Ite
     }
     .show(ui, |ui| {
         ui.set_width(ui.available_width());
+        ui.spacing_mut().button_padding = Vec2::new(6., 3.);
+        let file_name = download
+            .path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy();
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(truncate(&download.title, 45, None)).strong())
+                .on_hover_text(&download.url);
+            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                match download.state {
+                    DownloadState::Queued | DownloadState::Running => {
+                        let cancel = ui.button("🗙");
+                        describe(&cancel, WidgetType::Button, format!("Cancel {}", file_name));
+                        if cancel.clicked() {
+                            send(ToWorker::CancelDownload { id: download.id });
+                        }
+                        if ui.button("Pause").clicked() {
+                            send(ToWorker::PauseDownload { id: download.id });
+                        }
+                    }
+                    DownloadState::Paused => {
+                        let cancel = ui.button("🗙");
+                        describe(&cancel, WidgetType::Button, format!("Cancel {}", file_name));
+                        if cancel.clicked() {
+                            send(ToWorker::CancelDownload { id: download.id });
+                        }
+                        if ui.button("Resume").clicked() {
+                            send(ToWorker::ResumeDownload { id: download.id });
+                        }
+                    }
+                    DownloadState::Done => {
+                        if ui.button("Open").clicked() {
+                            if let Err(err) = open::that(&download.path) {
+                                tracing::error!("Failed to open download: {}", err);
+                            }
+                        }
+                    }
+                    DownloadState::Failed(_) | DownloadState::Cancelled => {
+                        if ui.button("Retry").clicked() {
+                            send(ToWorker::Download {
+                                url: download.url.clone(),
+                                title: download.title.clone(),
+                            });
+                        }
+                    }
+                }
+            });
+        });
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(file_name.as_ref()).color(THEME.colors.text_dim));
+            ui.label("·");
+            let received = format_size(download.received);
+            match &download.state {
+                DownloadState::Queued => ui.label("queued"),
+                DownloadState::Running => match download.total {
+                    Some(total) => ui.label(format!("{} of {}", received, format_size(total))),
+                    None => ui.label(received),
+                },
+                DownloadState::Paused => ui.label(format!("paused at {}", received)),
+                DownloadState::Done => ui.label(received),
+                DownloadState::Failed(reason) => ui
+                    .label(RichText::new("failed").color(THEME.colors.warning))
+                    .on_hover_text(reason),
+                DownloadState::Cancelled => ui.label("cancelled"),
+            };
+        });
+        if !download.state.is_done() {
+            let fraction = match download.total {
+                Some(total) if total > 0 => download.received as f32 / total as f32,
+                _ => 0.0,
+            };
+            let progress = ui.add(
+                egui::ProgressBar::new(fraction)
+                    .desired_width(ui.available_width())
+                    .animate(download.state == DownloadState::Running),
+            );
+            describe_progress(&progress, format!("Downloading {}", file_name), fraction);
+        }
+    });
+}
+
+/// Placeholder shaped like a feed card, shown until cached items arrive.
+pub fn skeleton_card(ui: &mut egui::Ui) {
+    Frame {
+        fill: THEME.colors.bg,
+        rounding: THEME.rounding.large,
+        inner_margin: egui::Margin::same(6.0),
+        ..Default::default()
+    }
+    .show(ui, |ui| {
+        ui.set_width(ui.available_width());
+        let width = ui.available_width();
+        for (bar_width, height) in [(width * 0.8, 22.0), (width * 0.4, 14.0)] {
+            let (rect, _) =
+                ui.allocate_exact_size(Vec2::new(bar_width, height), egui::Sense::hover());
+            ui.painter()
+                .rect_filled(rect, THEME.rounding.medium, THEME.colors.bg_darker);
+        }
+    });
+}
+
+/// Wrapped text with `line_height` times the usual distance between rows,
+/// which labels don't offer.
+pub fn spaced_text(ui: &mut egui::Ui, mut job: LayoutJob, line_height: f32) -> Response {
+    job.wrap.max_width = ui.available_width();
+    let galley = ui.fonts(|fonts| fonts.layout_job(job));
+    let galley = if line_height > 1.0 && galley.rows.len() > 1 {
+        let pixels_per_point = ui.ctx().pixels_per_point();
+        let mut spaced = (*galley).clone();
+        let extra = spaced.rows[0].rect.height() * (line_height - 1.0);
+        for (index, row) in spaced.rows.iter_mut().enumerate() {
+            // Whole pixels keep the glyphs sharp.
+            let offset = Vec2::new(
+                0.0,
+                (extra * index as f32 * pixels_per_point).round() / pixels_per_point,
+            );
+            row.rect = row.rect.translate(offset);
+            row.visuals.mesh.translate(offset);
+            row.visuals.mesh_bounds = row.visuals.mesh_bounds.translate(offset);
+            for glyph in &mut row.glyphs {
+                glyph.pos += offset;
+            }
+        }
+        spaced.rect.max.y = spaced.rows.last().map_or(0.0, |row| row.rect.max.y);
+        spaced.mesh_bounds = spaced.rows.iter().fold(Rect::NOTHING, |bounds, row| {
+            bounds.union(row.visuals.mesh_bounds)
+        });
+        Arc::new(spaced)
+    } else {
+        galley
+    };
+    let (rect, response) = ui.allocate_exact_size(galley.size(), Sense::hover());
+    ui.painter().galley(rect.min, galley);
+    response
+}
+
+pub fn feed_card(
+    ui: &mut egui::Ui,
+    sender: Option<Sender<ToWorker>>,
+    item: &Item,
+    options: CardOptions,
+) -> InnerResponse<bool> {
+    let stroke = if options.focused {
+        Stroke::new(1.5, THEME.colors.accent)
+    } else {
+        Stroke::NONE
+    };
+    let title = item.title.as_deref().unwrap_or("<no title>");
+    let handled = Cell::new(false);
+    let toggled = Cell::new(false);
+    let fill = if options.stale {
+        THEME.colors.bg_darker
+    } else {
+        THEME.colors.bg
+    };
+    let response = Frame {
+        fill,
+        rounding: THEME.rounding.large,
+        inner_margin: egui::Margin::same(6.0),
+        stroke,
+        ..Default::default()
+    }
+    .show(ui, |ui| {
+        ui.set_width(ui.available_width());
+        if options.stale {
+            let visuals = ui.visuals_mut();
+            visuals.override_text_color = Some(THEME.colors.text.linear_multiply(STALE_DIMMING));
+            visuals.hyperlink_color = visuals.hyperlink_color.linear_multiply(STALE_DIMMING);
+        }
+        let rtl = is_rtl(title);
+        let line_layout = if rtl {
+            Layout::right_to_left(Align::Center)
+        } else {
+            Layout::left_to_right(Align::Center)
+        };
         if let Some(title) = &item.title {
-            let mut job = LayoutJob::single_section(
-                title.to_string(),
+            let text = if rtl {
+                to_visual_order(&truncate(title, 45, None))
+            } else {
+                title.to_string()
+            };
+            let mut job = highlighted_job(
+                &text,
+                options.highlight,
                 TextFormat {
                     font_id: FontId::proportional(22.0),
                     ..Default::default()
@@ -129,42 +598,322 @@ pub fn feed_card(ui: &mut egui::Ui, sender: Option<Sender<ToWorker>>, item: &Ite
                 overflow_character: Some('…'),
                 ..Default::default()
             };
-            if ui
-                .add(Hyperlink::from_label_and_url(job, &item.link))
-                .clicked()
-                && CONFIG.lock().auto_dismiss_on_open
-                && !item.dismissed
-            {
-                dismisss(item, &sender);
-            };
+            ui.horizontal(|ui| {
+                ui.with_layout(line_layout, |ui| {
+                    if let Some(mut selected) = options.selected {
+                        let checkbox = ui.checkbox(&mut selected, "");
+                        describe(&checkbox, WidgetType::Checkbox, format!("Select {}", title));
+                        if checkbox.changed() {
+                            toggled.set(true);
+                        }
+                    }
+                    if item.pinned && !item.dismissed {
+                        let pin = ui.label(RichText::new("📌").color(THEME.colors.accent));
+                        describe(&pin, WidgetType::Label, "Pinned");
+                    }
+                    if options.fresh {
+                        let dot = ui.label(RichText::new("●").color(THEME.colors.accent));
+                        describe(&dot, WidgetType::Label, "New since last visit");
+                    }
+                    let link = ui.link(job).on_hover_text(&item.link);
+                    if link.clicked() {
+                        handled.set(true);
+                        if let Some(sender) = &sender {
+                            sender
+                                .send(ToWorker::OpenItem {
+                                    id: item.id.clone(),
+                                })
+                                .unwrap();
+                        }
+                    };
+                });
+            });
         } else {
             ui.add(Label::new(RichText::new("<no title>")));
         }
-        ui.horizontal(|ui| {
-            ui.label(timestamp_to_human_readable(item.published));
+        if let Some(texture) = options.thumbnail {
+            let size = texture.size_vec2();
+            let scale = (THUMBNAIL_WIDTH / size.x).min(1.0);
+            ui.image(texture.id(), size * scale);
+        }
+        let metadata = |ui: &mut egui::Ui| {
+            if options.stale {
+                ui.label(
+                    RichText::new(format_age(item.published))
+                        .small()
+                        .background_color(THEME.colors.bg_darkest),
+                );
+            }
+            ui.label(format_timestamp(item.published));
             ui.label("·");
+            if item.duration > 0 {
+                ui.label(format_duration(item.duration));
+                ui.label("·");
+            }
+            if item.changed {
+                ui.label(RichText::new("updated").color(THEME.colors.accent))
+                    .on_hover_text(format!(
+                        "Changed by the feed {}",
+                        format_timestamp(item.updated)
+                    ));
+                ui.label("·");
+            }
+            if item.reading_time > 0 {
+                ui.label(format!("{} min read", item.reading_time));
+                ui.label("·");
+            }
+            if let Some(author) = &item.author {
+                let text = format!("by {}", truncate(author, 40, None));
+                match options.author_filter {
+                    Some(filter) => {
+                        let link = ui.link(text).on_hover_text("Show items by this author");
+                        describe(&link, WidgetType::Link, format!("Items by {}", author));
+                        if link.clicked() {
+                            handled.set(true);
+                            filter.set(Some(author.clone()));
+                        }
+                    }
+                    None => {
+                        ui.label(text);
+                    }
+                }
+                ui.label("·");
+            }
             if let Some(channel_title) = &item.channel_title {
-                ui.label(truncate(channel_title, 40, None));
+                let channel_title = truncate(channel_title, 40, None);
+                if is_rtl(&channel_title) {
+                    ui.label(to_visual_order(&channel_title));
+                } else {
+                    ui.label(channel_title);
+                }
             }
-            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
-                if item.dismissed {
-                    if ui.link("Restore").clicked() {
-                        if let Some(sender) = sender {
+        };
+        let actions = |ui: &mut egui::Ui| {
+            if item.dismissed {
+                let restore = ui.link("Restore");
+                describe(&restore, WidgetType::Link, format!("Restore {}", title));
+                if restore.clicked() {
+                    handled.set(true);
+                    if let Some(sender) = &sender {
+                        sender
+                            .send(ToWorker::SetDismissed {
+                                id: item.id.clone(),
+                                dismissed: false,
+                            })
+                            .unwrap();
+                    }
+                }
+            } else {
+                let dismiss = ui.link("Dismiss");
+                describe(&dismiss, WidgetType::Link, format!("Dismiss {}", title));
+                if dismiss.clicked() {
+                    handled.set(true);
+                    dismisss(item, &sender);
+                }
+            }
+            if !item.dismissed {
+                let later_text = if item.later { "To new" } else { "Later" };
+                let later = ui.link(later_text);
+                describe(
+                    &later,
+                    WidgetType::Link,
+                    format!("{} {}", later_text, title),
+                );
+                if later.clicked() {
+                    handled.set(true);
+                    if let Some(sender) = &sender {
+                        sender
+                            .send(ToWorker::SetLater {
+                                id: item.id.clone(),
+                                later: !item.later,
+                            })
+                            .unwrap();
+                    }
+                }
+                let pin_text = if item.pinned { "Unpin" } else { "Pin" };
+                let pin = ui.link(pin_text);
+                describe(&pin, WidgetType::Link, format!("{} {}", pin_text, title));
+                if pin.on_hover_text("Keep on top of the new items").clicked() {
+                    handled.set(true);
+                    if let Some(sender) = &sender {
+                        sender
+                            .send(ToWorker::SetPinned {
+                                id: item.id.clone(),
+                                pinned: !item.pinned,
+                            })
+                            .unwrap();
+                    }
+                }
+            }
+            let star_text = if item.starred { "Unstar" } else { "Star" };
+            let star = ui.link(star_text);
+            describe(&star, WidgetType::Link, format!("{} {}", star_text, title));
+            if star.clicked() {
+                handled.set(true);
+                if let Some(sender) = &sender {
+                    sender
+                        .send(ToWorker::SetStarred {
+                            id: item.id.clone(),
+                            starred: !item.starred,
+                        })
+                        .unwrap();
+                }
+            }
+            if let Some(comments) = &item.comments {
+                let link = ui.hyperlink_to("Comments", comments);
+                describe(&link, WidgetType::Link, format!("Comments on {}", title));
+                if link.clicked() {
+                    handled.set(true);
+                }
+            }
+            if let Some(via) = item.links.with_rel("via") {
+                let link = ui
+                    .hyperlink_to(via.label(), &via.href)
+                    .on_hover_text(&via.href);
+                describe(&link, WidgetType::Link, format!("Source of {}", title));
+                if link.clicked() {
+                    handled.set(true);
+                }
+            }
+            if let Some(enclosure) = &item.enclosure {
+                let player = CONFIG.lock().player_command.clone();
+                if !player.trim().is_empty() {
+                    let play = ui.link("Play");
+                    describe(
+                        &play,
+                        WidgetType::Link,
+                        format!("Play the attachment of {} externally", title),
+                    );
+                    if play.on_hover_text("Open in the external player").clicked() {
+                        handled.set(true);
+                        if let Some(sender) = &sender {
                             sender
-                                .send(ToWorker::SetDismissed {
+                                .send(ToWorker::SendTo {
+                                    command: player,
+                                    url: enclosure.clone(),
+                                    title: title.to_string(),
+                                })
+                                .unwrap();
+                        }
+                    }
+                }
+                let download = ui.link("Download");
+                describe(
+                    &download,
+                    WidgetType::Link,
+                    format!("Download the attachment of {}", title),
+                );
+                if download.on_hover_text(enclosure).clicked() {
+                    handled.set(true);
+                    if let Some(sender) = &sender {
+                        sender
+                            .send(ToWorker::Download {
+                                url: enclosure.clone(),
+                                title: title.to_string(),
+                            })
+                            .unwrap();
+                    }
+                }
+            }
+        };
+        ui.horizontal(|ui| {
+            if rtl {
+                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    metadata(ui);
+                    ui.with_layout(Layout::left_to_right(Align::Center), actions);
+                });
+            } else {
+                metadata(ui);
+                ui.with_layout(Layout::right_to_left(Align::Center), actions);
+            }
+        });
+    })
+    .response
+    .interact(Sense::click())
+    .context_menu(|ui| {
+        let config = ConfigBuilder::from_current();
+        let chats: Vec<ChatService> = ChatService::ALL
+            .into_iter()
+            .filter(|service| config.chat_configured(*service))
+            .collect();
+        let commands = config.send_to_commands;
+        let targets = config.share_targets;
+        if !targets.is_empty() || !chats.is_empty() {
+            ui.menu_button("Share", |ui| {
+                let title = item.title.as_deref().unwrap_or_default();
+                for target in &targets {
+                    if ui.button(&target.name).clicked() {
+                        let text = target.expand(&item.link, title);
+                        match target.format {
+                            ShareFormat::Text | ShareFormat::Html => {
+                                ui.output_mut(|o| o.copied_text = text);
+                            }
+                            ShareFormat::Link => {
+                                ui.ctx().output_mut(|o| {
+                                    o.open_url = Some(egui::output::OpenUrl::new_tab(text))
+                                });
+                            }
+                        }
+                        ui.close_menu();
+                    }
+                }
+                for service in chats {
+                    if ui.button(service.label()).clicked() {
+                        if let Some(sender) = &sender {
+                            sender
+                                .send(ToWorker::ShareItem {
                                     id: item.id.clone(),
-                                    dismissed: false,
+                                    service,
                                 })
                                 .unwrap();
                         }
+                        ui.close_menu();
                     }
-                } else if ui.link("Dismiss").clicked() {
-                    dismisss(item, &sender);
                 }
             });
-        });
+        } else if commands.is_empty() {
+            ui.label(RichText::new("No \"Send to\" commands").color(THEME.colors.text_dim));
+        }
+        for command in commands {
+            if ui.button(format!("Send to {}", command.name)).clicked() {
+                if let Some(sender) = &sender {
+                    sender
+                        .send(ToWorker::SendTo {
+                            command: command.command,
+                            url: item.link.clone(),
+                            title: item.title.clone().unwrap_or_default(),
+                        })
+                        .unwrap();
+                }
+                ui.close_menu();
+            }
+        }
     });
 
+    if let Some(color) = options.stripe {
+        let rect = Rect::from_min_size(response.rect.min, Vec2::new(4.0, response.rect.height()));
+        ui.painter().rect_filled(
+            rect,
+            Rounding {
+                ne: 0.0,
+                se: 0.0,
+                ..THEME.rounding.large
+            },
+            color,
+        );
+    }
+
+    describe(
+        &response,
+        WidgetType::Label,
+        format!(
+            "{}, {}, {}",
+            title,
+            item.channel_title.as_deref().unwrap_or("unknown channel"),
+            format_timestamp(item.published)
+        ),
+    );
+
     fn dismisss(item: &Item, sender: &Option<Sender<ToWorker>>) {
         if let Some(sender) = sender {
             sender
@@ -175,4 +924,8 @@ pub fn feed_card(ui: &mut egui::Ui, sender: Option<Sender<ToWorker>>, item: &Ite
                 .unwrap();
         }
     }
+
+    // In selection mode the caller toggles the item instead of opening details.
+    let open_details = (response.clicked() && !handled.get()) || toggled.get();
+    InnerResponse::new(open_details, response)
 }
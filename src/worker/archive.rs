@@ -0,0 +1,159 @@
+use super::{db, utils, ConfigBuilder};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::io::{Read, Write};
+use std::path::Path;
+use zip::write::FileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+/// Bumped whenever the archive layout changes in a way older versions can't read.
+const FORMAT_VERSION: u32 = 1;
+
+const MANIFEST: &str = "manifest.json";
+const CONFIG_FILE: &str = "config.yml";
+const DATABASE_FILE: &str = "tinyrss.db";
+
+/// Imported database waiting in the app dir to replace the open one on the next start.
+const STAGED_DATABASE_FILE: &str = "tinyrss.db.import";
+
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    format_version: u32,
+    app_version: String,
+    created: i64,
+}
+
+/// Writes the database and config of the current profile into a zip archive.
+pub async fn export(path: &Path) -> Result<(), Box<dyn Error>> {
+    let app_dir = utils::get_app_dir();
+    let snapshot = app_dir.join("tinyrss.db.export");
+    if snapshot.exists() {
+        std::fs::remove_file(&snapshot)?;
+    }
+    db::vacuum_into(&snapshot).await?;
+
+    let result = write_archive(path, &snapshot);
+    std::fs::remove_file(&snapshot)?;
+    result
+}
+
+fn write_archive(path: &Path, snapshot: &Path) -> Result<(), Box<dyn Error>> {
+    let mut zip = ZipWriter::new(std::fs::File::create(path)?);
+    let options = FileOptions::default();
+
+    let manifest = Manifest {
+        format_version: FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created: chrono::Utc::now().timestamp(),
+    };
+    zip.start_file(MANIFEST, options)?;
+    zip.write_all(serde_json::to_string_pretty(&manifest)?.as_bytes())?;
+
+    zip.start_file(CONFIG_FILE, options)?;
+    // Passwords and tokens stay on this machine.
+    zip.write_all(ConfigBuilder::from_current().archived_yaml()?.as_bytes())?;
+
+    zip.start_file(DATABASE_FILE, options)?;
+    zip.write_all(&std::fs::read(snapshot)?)?;
+
+    zip.finish()?;
+    Ok(())
+}
+
+/// Replaces the current profile's data with the contents of an archive made by [`export`].
+/// The config is replaced right away, the database is staged and swapped in by
+/// [`finish_import`] on the next start.
+pub fn import(path: &Path) -> Result<(), Box<dyn Error>> {
+    let mut zip = ZipArchive::new(std::fs::File::open(path)?)?;
+
+    let manifest: Manifest = {
+        let mut file = zip.by_name(MANIFEST).map_err(|_| "not a tinyrss archive")?;
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+        serde_json::from_str(&contents)?
+    };
+    if manifest.format_version > FORMAT_VERSION {
+        return Err(format!(
+            "archive was created by a newer version of tinyrss ({})",
+            manifest.app_version
+        )
+        .into());
+    }
+
+    // Everything is read before anything is written, so a broken archive changes nothing.
+    let mut database = vec![];
+    zip.by_name(DATABASE_FILE)
+        .map_err(|_| "archive doesn't contain a database")?
+        .read_to_end(&mut database)?;
+    let config = match zip.by_name(CONFIG_FILE) {
+        Ok(mut file) => {
+            let mut contents = String::new();
+            file.read_to_string(&mut contents)?;
+            let mut config: ConfigBuilder = serde_yaml::from_str(&contents)?;
+            config.keep_local(&ConfigBuilder::from_current());
+            Some(config)
+        }
+        Err(_) => None,
+    };
+
+    std::fs::write(utils::get_app_dir().join(STAGED_DATABASE_FILE), database)?;
+    if let Some(config) = config {
+        config.clone().save()?;
+        config.apply();
+    }
+
+    Ok(())
+}
+
+/// Swaps in a database staged by [`import`], before anything opens the database.
+/// Returns whether there was one.
+pub fn finish_import(app_dir: &Path) -> std::io::Result<bool> {
+    let staged = app_dir.join(STAGED_DATABASE_FILE);
+    if !staged.exists() {
+        return Ok(false);
+    }
+    // The journal of the replaced database must not be replayed on top of the imported one.
+    for journal in ["tinyrss.db-wal", "tinyrss.db-shm", "tinyrss.db-journal"] {
+        match std::fs::remove_file(app_dir.join(journal)) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err),
+            _ => {}
+        }
+    }
+    std::fs::rename(staged, app_dir.join(DATABASE_FILE))?;
+    Ok(true)
+}
+
+/// Channels and items of a profile as JSON, for merging into another install.
+#[derive(Serialize, Deserialize)]
+struct Dump {
+    channels: Vec<db::Channel>,
+    items: Vec<db::Item>,
+}
+
+/// Writes every channel, archived ones included, and every item to a JSON file.
+pub async fn export_json(path: &Path) -> Result<usize, Box<dyn Error>> {
+    let dump = Dump {
+        channels: db::get_channels_with_archived().await?,
+        items: db::get_all_items().await?,
+    };
+    std::fs::write(path, serde_json::to_string_pretty(&dump)?)?;
+    Ok(dump.items.len())
+}
+
+/// Merges a file written by [`export_json`] into the database, items already stored win.
+/// A bare list of items, as returned by `/api/items`, is accepted too.
+/// Returns the amount of added channels and items.
+pub async fn import_json(path: &Path) -> Result<(u64, u64), Box<dyn Error>> {
+    let contents = std::fs::read_to_string(path)?;
+    let dump = match serde_json::from_str::<Dump>(&contents) {
+        Ok(dump) => dump,
+        Err(err) => match serde_json::from_str::<Vec<db::Item>>(&contents) {
+            Ok(items) => Dump {
+                channels: vec![],
+                items,
+            },
+            Err(_) => return Err(err.into()),
+        },
+    };
+    Ok(db::merge_items(&dump.channels, &dump.items).await?)
+}
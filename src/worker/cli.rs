@@ -0,0 +1,187 @@
+use super::{db, utils, AddChannelOutcome, ConfigBuilder, Severity, ToApp, Worker};
+use crossbeam_channel::Receiver;
+
+const USAGE: &str = "Usage: tinyrss [--profile <name>] [<command>]
+
+Without a command the window is opened.
+
+Commands:
+  add <url>...          Subscribe to the feeds at the given links
+  list                  Print id, title and link of every channel
+  remove <id>...        Unsubscribe from channels, ids may be shortened to a unique prefix
+  export <file.opml>    Write all channels to an OPML file
+  help                  Show this message
+
+Changes are made to the database directly, a running window shows them after a restart.";
+
+/// Runs a subcommand against the database of the current profile and returns the exit code.
+pub fn run(args: &[String]) -> i32 {
+    ConfigBuilder::from_file().apply();
+
+    let (app_tx, app_rx) = crossbeam_channel::unbounded();
+    let (worker_tx, worker_rx) = crossbeam_channel::unbounded();
+    let mut worker = Worker::new(worker_tx, app_rx, app_tx, Default::default());
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async {
+        worker.initialize_app_fs();
+        worker.cookies = super::cookies::load();
+        worker.initialize_database().await;
+        if print_errors(&worker_rx) {
+            return 1;
+        }
+
+        let (command, rest) = match args.split_first() {
+            Some((command, rest)) => (command.as_str(), rest),
+            None => ("help", &[][..]),
+        };
+        let code = match (command, rest) {
+            ("add", links) if !links.is_empty() => add(&mut worker, &worker_rx, links).await,
+            ("list", []) => list().await,
+            ("remove", ids) if !ids.is_empty() => remove(&mut worker, &worker_rx, ids).await,
+            ("export", [path]) => export(path).await,
+            ("help" | "--help" | "-h", _) => {
+                println!("{}", USAGE);
+                0
+            }
+            _ => {
+                eprintln!("{}", USAGE);
+                2
+            }
+        };
+        // Commands may change settings too, e.g. removing a channel from the digest.
+        if let Err(err) = ConfigBuilder::from_current().save() {
+            eprintln!("Failed to save config: {}", err);
+            return 1;
+        }
+        code
+    })
+}
+
+/// Prints the errors and warnings the worker reported and returns whether there were errors.
+fn print_errors(receiver: &Receiver<ToApp>) -> bool {
+    let mut failed = false;
+    for message in receiver.try_iter() {
+        if let ToApp::WorkerError { error } = message {
+            let prefix = match error.severity {
+                Severity::Warning => "Warning: ",
+                Severity::Error => "",
+            };
+            if error.error_message.is_empty() {
+                eprintln!("{}{}", prefix, error.description);
+            } else {
+                eprintln!("{}{}: {}", prefix, error.description, error.error_message);
+            }
+            failed |= error.severity == Severity::Error;
+        }
+    }
+    failed
+}
+
+async fn add(worker: &mut Worker, receiver: &Receiver<ToApp>, links: &[String]) -> i32 {
+    let outcomes = worker.add_channels(links.to_vec(), "Command line").await;
+    // Failures show up in the outcomes, the reported errors would repeat them.
+    receiver.try_iter().for_each(drop);
+    let mut failed = false;
+    for (link, outcome) in outcomes {
+        match outcome {
+            AddChannelOutcome::Added { title } => {
+                println!(
+                    "Added {}\t{}",
+                    link,
+                    title.as_deref().unwrap_or("<no title>")
+                )
+            }
+            AddChannelOutcome::Restored => println!("Restored {}", link),
+            AddChannelOutcome::AlreadySubscribed => println!("Already subscribed to {}", link),
+            AddChannelOutcome::Failed(reason) => {
+                eprintln!("Failed to add {}: {}", link, reason);
+                failed = true;
+            }
+        }
+    }
+    i32::from(failed)
+}
+
+async fn list() -> i32 {
+    match db::get_all_channels().await {
+        Ok(channels) => {
+            for channel in channels {
+                println!(
+                    "{}\t{}\t{}",
+                    channel.id,
+                    channel.title.as_deref().unwrap_or("<no title>"),
+                    channel.link
+                );
+            }
+            0
+        }
+        Err(err) => {
+            eprintln!("Failed to read channels: {}", err);
+            1
+        }
+    }
+}
+
+async fn remove(worker: &mut Worker, receiver: &Receiver<ToApp>, ids: &[String]) -> i32 {
+    let channels = match db::get_all_channels().await {
+        Ok(channels) => channels,
+        Err(err) => {
+            eprintln!("Failed to read channels: {}", err);
+            return 1;
+        }
+    };
+    let mut failed = false;
+    for id in ids {
+        let matching: Vec<&db::Channel> = channels
+            .iter()
+            .filter(|channel| channel.id.starts_with(id.as_str()))
+            .collect();
+        match matching[..] {
+            [channel] => {
+                worker.unsubscribe(&channel.id).await;
+                if print_errors(receiver) {
+                    failed = true;
+                } else {
+                    println!(
+                        "Removed {}\t{}",
+                        channel.id,
+                        channel.title.as_deref().unwrap_or("<no title>")
+                    );
+                }
+            }
+            [] => {
+                eprintln!("No channel with id {}", id);
+                failed = true;
+            }
+            _ => {
+                eprintln!("More than one channel starts with {}", id);
+                failed = true;
+            }
+        }
+    }
+    i32::from(failed)
+}
+
+async fn export(path: &str) -> i32 {
+    let channels = match db::get_all_channels().await {
+        Ok(channels) => channels,
+        Err(err) => {
+            eprintln!("Failed to read channels: {}", err);
+            return 1;
+        }
+    };
+    let result = utils::render_opml(&channels)
+        .map_err(|err| err.to_string())
+        .and_then(|xml| std::fs::write(path, xml).map_err(|err| err.to_string()));
+    match result {
+        Ok(()) => {
+            println!("Exported {} channels to {}", channels.len(), path);
+            0
+        }
+        Err(err) => {
+            eprintln!("Failed to export channels: {}", err);
+            1
+        }
+    }
+}
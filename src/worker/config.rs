@@ -1,8 +1,12 @@
-use super::utils;
+use super::db::Backfill;
+use super::{digest, utils};
 use lazy_static::lazy_static;
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
-use std::{io::Write, sync::Arc};
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+use std::ops::{Deref, DerefMut};
+use std::{io::Write, path::PathBuf, sync::Arc};
 use tracing::{error, info};
 
 lazy_static! {
@@ -10,24 +14,574 @@ lazy_static! {
         Arc::new(Mutex::new(ConfigBuilder::from_file()));
 }
 
+/// File in the app dir holding [`ConfigBuilder::new_item_command_approved`].
+const APPROVED_COMMAND_FILE: &str = "approved_command";
+
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum TimestampFormat {
+    #[default]
+    Relative,
+    Absolute,
+    Iso,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum ReaderFont {
+    #[default]
+    Proportional,
+    Monospace,
+}
+
+/// Order of new items in the feed.
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum FeedOrder {
+    #[default]
+    Latest,
+    /// Recent items of channels that rarely post first, see [`super::scoring`].
+    Hot,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum RepublishedItems {
+    #[default]
+    Starred,
+    New,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum UnsubscribeMode {
+    /// Remove the channel together with its items.
+    #[default]
+    Delete,
+    /// Stop refreshing and hide the channel, but keep its items.
+    Archive,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum DnsMode {
+    /// Resolve through the operating system.
+    #[default]
+    System,
+    /// DNS over HTTPS (RFC 8484), e.g. `https://cloudflare-dns.com/dns-query`.
+    Https,
+    /// Plain DNS against a specific server, e.g. `9.9.9.9` or `9.9.9.9:53`.
+    Server,
+}
+
+/// Whether refreshes use the reduced profile for metered connections, like phone hotspots.
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum MeteredMode {
+    /// Asks the operating system, Windows and NetworkManager on Linux can tell.
+    #[default]
+    Detect,
+    Always,
+    Never,
+}
+
+impl MeteredMode {
+    pub const ALL: [MeteredMode; 3] =
+        [MeteredMode::Detect, MeteredMode::Always, MeteredMode::Never];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MeteredMode::Detect => "Detect",
+            MeteredMode::Always => "Always metered",
+            MeteredMode::Never => "Never metered",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum KeyAction {
+    Next,
+    Previous,
+    Open,
+    Dismiss,
+    Star,
+    Later,
+    CopyLink,
+    DismissMatching,
+}
+
+impl KeyAction {
+    pub const ALL: [KeyAction; 8] = [
+        KeyAction::Next,
+        KeyAction::Previous,
+        KeyAction::Open,
+        KeyAction::Dismiss,
+        KeyAction::Star,
+        KeyAction::Later,
+        KeyAction::CopyLink,
+        KeyAction::DismissMatching,
+    ];
+}
+
+/// Maps a key, stored by its egui name, to an action in the feed.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyBinding {
+    pub action: KeyAction,
+    pub key: String,
+}
+
+impl KeyBinding {
+    pub fn new(action: KeyAction, key: impl Into<String>) -> Self {
+        Self {
+            action,
+            key: key.into(),
+        }
+    }
+
+    pub fn defaults() -> Vec<KeyBinding> {
+        vec![
+            KeyBinding::new(KeyAction::Next, "Down"),
+            KeyBinding::new(KeyAction::Next, "J"),
+            KeyBinding::new(KeyAction::Previous, "Up"),
+            KeyBinding::new(KeyAction::Previous, "K"),
+            KeyBinding::new(KeyAction::Open, "Enter"),
+            KeyBinding::new(KeyAction::Dismiss, "D"),
+            KeyBinding::new(KeyAction::Star, "S"),
+            KeyBinding::new(KeyAction::Later, "L"),
+            KeyBinding::new(KeyAction::CopyLink, "C"),
+            KeyBinding::new(KeyAction::DismissMatching, "X"),
+        ]
+    }
+}
+
+/// External command offered in the item context menu.
+/// `{url}` and `{title}` in the template are replaced with the item's values.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct SendToCommand {
+    pub name: String,
+    pub command: String,
+}
+
+/// What a Share menu entry does with its filled in template.
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum ShareFormat {
+    /// Copies the text as is.
+    #[default]
+    Text,
+    /// Copies HTML, the title is escaped.
+    Html,
+    /// Opens the text as a link in the browser, the values are percent-encoded.
+    Link,
+}
+
+impl ShareFormat {
+    pub const ALL: [ShareFormat; 3] = [ShareFormat::Text, ShareFormat::Html, ShareFormat::Link];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ShareFormat::Text => "Copy text",
+            ShareFormat::Html => "Copy HTML",
+            ShareFormat::Link => "Open link",
+        }
+    }
+}
+
+/// Entry of the Share submenu of items.
+/// `{url}` and `{title}` in the template are replaced with the item's values.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ShareTarget {
+    pub name: String,
+    pub format: ShareFormat,
+    pub template: String,
+}
+
+impl ShareTarget {
+    fn new(name: &str, format: ShareFormat, template: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            format,
+            template: template.to_string(),
+        }
+    }
+
+    pub fn defaults() -> Vec<ShareTarget> {
+        vec![
+            ShareTarget::new("Copy link", ShareFormat::Text, "{url}"),
+            ShareTarget::new("Copy as Markdown", ShareFormat::Text, "[{title}]({url})"),
+            ShareTarget::new(
+                "Copy as HTML",
+                ShareFormat::Html,
+                "<a href=\"{url}\">{title}</a>",
+            ),
+            ShareTarget::new(
+                "Mastodon",
+                ShareFormat::Link,
+                "https://mastodon.social/share?text={title}%20{url}",
+            ),
+            ShareTarget::new(
+                "Bluesky",
+                ShareFormat::Link,
+                "https://bsky.app/intent/compose?text={title}%20{url}",
+            ),
+        ]
+    }
+
+    /// The template with the item's values filled in, escaped as the format needs.
+    pub fn expand(&self, url: &str, title: &str) -> String {
+        let (url, title) = match self.format {
+            ShareFormat::Text => (url.to_string(), title.to_string()),
+            ShareFormat::Html => (digest::escape(url), digest::escape(title)),
+            ShareFormat::Link => (utils::percent_encode(url), utils::percent_encode(title)),
+        };
+        self.template
+            .replace("{url}", &url)
+            .replace("{title}", &title)
+    }
+}
+
+/// Server new items are pushed to as phone notifications.
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum PushService {
+    #[default]
+    Off,
+    Ntfy,
+    Gotify,
+}
+
+/// Chat items can be shared to and new items forwarded to.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ChatService {
+    Telegram,
+    Matrix,
+}
+
+impl ChatService {
+    pub const ALL: [ChatService; 2] = [ChatService::Telegram, ChatService::Matrix];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ChatService::Telegram => "Telegram",
+            ChatService::Matrix => "Matrix",
+        }
+    }
+}
+
+thread_local! {
+    /// What secrets are replaced with while the config is serialized for someone else,
+    /// see [`ConfigBuilder::redacted_yaml`] and [`ConfigBuilder::archived_yaml`].
+    static REDACTING: Cell<Option<&'static str>> = const { Cell::new(None) };
+}
+
+/// Password, token or url with credentials in the config. Reads like a `String` but is
+/// masked in diagnostics reports and left out of archives, so new secrets can't be
+/// forgotten there. In config.yml itself secrets are stored in plain text.
+#[derive(Clone, Default, PartialEq, Deserialize)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Serialize for Secret {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match REDACTING.with(Cell::get) {
+            Some(mask) if !self.0.is_empty() => serializer.serialize_str(mask),
+            _ => serializer.serialize_str(&self.0),
+        }
+    }
+}
+
+impl Deref for Secret {
+    type Target = String;
+
+    fn deref(&self) -> &String {
+        &self.0
+    }
+}
+
+impl DerefMut for Secret {
+    fn deref_mut(&mut self) -> &mut String {
+        &mut self.0
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+/// Url the new items of every refresh are posted to as JSON.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Webhook {
+    /// Chat services put their credentials into the url.
+    pub url: Secret,
+    /// Comma separated keywords, only items containing one are posted. Empty posts all.
+    pub filter: String,
+}
+
+/// OPML list at a url that is checked daily for channels added to it.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct OpmlSubscription {
+    pub url: String,
+    /// Links the list had at the last check, so channels unsubscribed from meanwhile
+    /// aren't added again.
+    pub known_links: Vec<String>,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ConfigBuilder {
     pub show_search_in_feed: bool,
+    pub feed_order: FeedOrder,
     pub auto_dismiss_on_open: bool,
+    /// New items scrolled past the top of the feed get dismissed.
+    pub dismiss_on_scroll: bool,
     pub max_allowed_concurent_requests: usize,
+    pub max_requests_per_host: usize,
+    /// 0 doesn't limit the size.
+    pub max_feed_size_mb: u32,
+    pub dns_mode: DnsMode,
+    pub dns_server: String,
+    /// Proxy all requests go through, e.g. `socks5://127.0.0.1:9050`. Empty uses the proxy
+    /// of the environment, if any.
+    pub proxy: String,
+    /// Seconds a request may take as a whole, so a server trickling an endless answer can't
+    /// hold up a refresh.
+    pub request_timeout_seconds: u64,
+    pub items_per_page: usize,
+    pub auto_refresh_minutes: u32,
+    pub metered_mode: MeteredMode,
+    /// Concurrent requests of refreshes on a metered connection, one per host.
+    pub metered_concurrent_requests: usize,
+    /// Least minutes between automatic refreshes on a metered connection, 0 stops them.
+    pub metered_refresh_minutes: u32,
+    pub detect_feed_in_clipboard: bool,
+    pub enable_local_server: bool,
+    pub local_server_port: u16,
+    pub local_server_token: Secret,
+    /// Only opens /feed.xml. Feed readers can't send headers and put it into the link, where
+    /// it ends up in logs and configs, so it's kept apart from the API token.
+    pub local_feed_token: Secret,
+    pub custom_fonts: Vec<PathBuf>,
+    pub cjk_fallback_font: bool,
+    pub timestamp_format: TimestampFormat,
+    /// Items published longer ago are dimmed and get an age badge, 0 disables it.
+    pub stale_after_days: u32,
+    pub reader_font: ReaderFont,
+    pub reader_font_size: f32,
+    /// Width of the text in the item details, in points.
+    pub reader_line_width: f32,
+    /// Distance between lines as a multiple of the font's own line height.
+    pub reader_line_height: f32,
+    pub reader_justify: bool,
+    pub reader_sepia: bool,
+    pub smtp_host: String,
+    pub smtp_port: u16,
+    pub smtp_username: String,
+    /// Kept in plain text in config.yml, which only the user should be able to read.
+    pub smtp_password: Secret,
+    pub digest_from: String,
+    pub digest_to: String,
+    pub digest_daily: bool,
+    pub digest_channels: Vec<String>,
+    pub last_digest_sent: i64,
+    pub republished_items: RepublishedItems,
+    pub unsubscribe_mode: UnsubscribeMode,
+    pub republish_file: Option<PathBuf>,
+    pub last_feed_visit: i64,
+    pub last_integrity_check: i64,
+    pub send_to_commands: Vec<SendToCommand>,
+    pub share_targets: Vec<ShareTarget>,
+    /// Kill the Newsletter server that provides inboxes for newsletters.
+    pub newsletter_server: String,
+    pub webhooks: Vec<Webhook>,
+    /// Refreshes bringing items of high-priority channels play the system's alert sound.
+    pub priority_sound: bool,
+    /// Newest entries kept from each fetch of channels without their own limit, 0 keeps all.
+    pub item_limit: usize,
+    /// What new subscriptions bring into "New" on their first fetch.
+    pub backfill: Backfill,
+    /// Items kept as new for [`Backfill::Newest`].
+    pub backfill_count: usize,
+    pub push_service: PushService,
+    /// Topic url for ntfy, server url for Gotify.
+    pub push_url: String,
+    /// Access token for ntfy, optional there, or the application token for Gotify.
+    pub push_token: Secret,
+    /// Comma separated keywords, like the filter of webhooks.
+    pub push_filter: String,
+    pub telegram_bot_token: Secret,
+    pub telegram_chat_id: String,
+    pub matrix_homeserver: String,
+    pub matrix_access_token: Secret,
+    pub matrix_room_id: String,
+    /// Channels whose new items are forwarded to the configured chats.
+    pub forward_channels: Vec<String>,
+    /// Comma separated keywords, like the filter of webhooks.
+    pub forward_filter: String,
+    /// Command template run for every new item, with the "Send to" placeholders.
+    pub new_item_command: String,
+    /// The command the user confirmed, the hook stays off while it differs from the current.
+    /// Kept in its own file next to the config, so an imported config can't approve a command.
+    #[serde(skip)]
+    pub new_item_command_approved: String,
+    /// Comma separated keywords, like the filter of webhooks.
+    pub new_item_command_filter: String,
+    pub opml_subscriptions: Vec<OpmlSubscription>,
+    pub last_opml_sync: i64,
+    /// Set once the welcome screen of an empty profile was skipped.
+    pub onboarding_dismissed: bool,
+    /// Command template for playing attachments, uses the same placeholders as "Send to".
+    pub player_command: String,
+    pub keybindings: Vec<KeyBinding>,
+    pub sync_folder: Option<PathBuf>,
+    /// Folder enclosures are downloaded to, the system's download folder if unset.
+    pub download_folder: Option<PathBuf>,
+    /// Folder items are saved to as Markdown notes, the system's documents folder if unset.
+    pub notes_folder: Option<PathBuf>,
+    pub sync_device_id: String,
+    pub webdav_url: String,
+    pub webdav_username: String,
+    /// Kept in plain text in config.yml like [`Self::smtp_password`].
+    pub webdav_password: Secret,
+    pub webdav_interval_minutes: u32,
+    pub webdav_last_sync: i64,
+    pub blocked_keywords: Vec<String>,
+    pub blocked_items_count: u64,
 }
 
 impl Default for ConfigBuilder {
     fn default() -> Self {
         Self {
             show_search_in_feed: false,
+            feed_order: FeedOrder::default(),
             auto_dismiss_on_open: false,
+            dismiss_on_scroll: false,
             max_allowed_concurent_requests: 5,
+            max_requests_per_host: 2,
+            max_feed_size_mb: 10,
+            dns_mode: DnsMode::default(),
+            dns_server: String::new(),
+            proxy: String::new(),
+            request_timeout_seconds: 60,
+            items_per_page: 10,
+            auto_refresh_minutes: 0,
+            metered_mode: MeteredMode::default(),
+            metered_concurrent_requests: 2,
+            metered_refresh_minutes: 60,
+            detect_feed_in_clipboard: false,
+            enable_local_server: false,
+            local_server_port: 7171,
+            local_server_token: Secret::default(),
+            local_feed_token: Secret::default(),
+            custom_fonts: Vec::new(),
+            cjk_fallback_font: true,
+            timestamp_format: TimestampFormat::default(),
+            stale_after_days: 14,
+            reader_font: ReaderFont::default(),
+            reader_font_size: 14.0,
+            reader_line_width: 520.0,
+            reader_line_height: 1.0,
+            reader_justify: false,
+            reader_sepia: false,
+            smtp_host: String::new(),
+            smtp_port: 587,
+            smtp_username: String::new(),
+            smtp_password: Secret::default(),
+            digest_from: String::new(),
+            digest_to: String::new(),
+            digest_daily: false,
+            digest_channels: Vec::new(),
+            last_digest_sent: 0,
+            republished_items: RepublishedItems::default(),
+            unsubscribe_mode: UnsubscribeMode::default(),
+            republish_file: None,
+            last_feed_visit: 0,
+            last_integrity_check: 0,
+            send_to_commands: Vec::new(),
+            share_targets: ShareTarget::defaults(),
+            newsletter_server: "https://kill-the-newsletter.com".to_string(),
+            webhooks: Vec::new(),
+            priority_sound: true,
+            item_limit: 0,
+            backfill: Backfill::default(),
+            backfill_count: 10,
+            push_service: PushService::default(),
+            push_url: String::new(),
+            push_token: Secret::default(),
+            push_filter: String::new(),
+            telegram_bot_token: Secret::default(),
+            telegram_chat_id: String::new(),
+            matrix_homeserver: String::new(),
+            matrix_access_token: Secret::default(),
+            matrix_room_id: String::new(),
+            forward_channels: Vec::new(),
+            forward_filter: String::new(),
+            new_item_command: String::new(),
+            new_item_command_approved: String::new(),
+            new_item_command_filter: String::new(),
+            opml_subscriptions: Vec::new(),
+            last_opml_sync: 0,
+            onboarding_dismissed: false,
+            player_command: "mpv {url}".to_string(),
+            keybindings: KeyBinding::defaults(),
+            sync_folder: None,
+            download_folder: None,
+            notes_folder: None,
+            sync_device_id: String::new(),
+            webdav_url: String::new(),
+            webdav_username: String::new(),
+            webdav_password: Secret::default(),
+            webdav_interval_minutes: 30,
+            webdav_last_sync: 0,
+            blocked_keywords: Vec::new(),
+            blocked_items_count: 0,
         }
     }
 }
 
 impl ConfigBuilder {
+    /// Replaces the channel ids in every setting listing channels, `moved` maps old ids
+    /// to new ones.
+    pub fn rekey_channels(&mut self, moved: &HashMap<String, String>) {
+        for ids in [&mut self.digest_channels, &mut self.forward_channels] {
+            for id in ids.iter_mut() {
+                if let Some(new) = moved.get(id) {
+                    *id = new.clone();
+                }
+            }
+            // Channels merged into one are listed once.
+            let mut seen = HashSet::new();
+            ids.retain(|id| seen.insert(id.clone()));
+        }
+    }
+
+    /// Concurrent requests overall and per host for refreshes.
+    pub fn request_limits(&self, metered: bool) -> (usize, usize) {
+        if metered {
+            (
+                self.metered_concurrent_requests
+                    .clamp(1, self.max_allowed_concurent_requests.max(1)),
+                1,
+            )
+        } else {
+            (
+                self.max_allowed_concurent_requests,
+                self.max_requests_per_host,
+            )
+        }
+    }
+
+    /// Whether everything needed to post to `service` is filled in.
+    pub fn chat_configured(&self, service: ChatService) -> bool {
+        let fields = match service {
+            ChatService::Telegram => vec![&self.telegram_bot_token, &self.telegram_chat_id],
+            ChatService::Matrix => vec![
+                &self.matrix_homeserver,
+                &self.matrix_access_token,
+                &self.matrix_room_id,
+            ],
+        };
+        fields.iter().all(|field| !field.trim().is_empty())
+    }
+
+    /// Whether the new item command is set and was confirmed as it is.
+    pub fn new_item_command_enabled(&self) -> bool {
+        !self.new_item_command.trim().is_empty()
+            && self.new_item_command == self.new_item_command_approved
+    }
+
     pub fn from_current() -> Self {
         CONFIG.lock().clone()
     }
@@ -36,7 +590,7 @@ impl ConfigBuilder {
         let app_dir = utils::get_app_dir();
         let config_path = app_dir.join("config.yml");
 
-        match std::fs::File::open(config_path) {
+        let mut config: Self = match std::fs::File::open(config_path) {
             Ok(file) => {
                 let reader = std::io::BufReader::new(file);
                 match serde_yaml::from_reader(reader) {
@@ -59,7 +613,54 @@ impl ConfigBuilder {
                 info!("Using default config file.");
                 Self::default()
             }
+        };
+        config.new_item_command_approved =
+            std::fs::read_to_string(app_dir.join(APPROVED_COMMAND_FILE)).unwrap_or_default();
+        config
+    }
+
+    /// Serializes the config for diagnostics reports with every [`Secret`] masked.
+    pub fn redacted_yaml(&self) -> String {
+        self.masked_yaml("<redacted>")
+            .unwrap_or_else(|err| err.to_string())
+    }
+
+    /// Serializes the config for archives, with every [`Secret`] left empty.
+    pub fn archived_yaml(&self) -> Result<String, serde_yaml::Error> {
+        self.masked_yaml("")
+    }
+
+    /// Takes what archives leave out from `current`: the secrets, unless the archive has its
+    /// own, and the approved new item command. The player and "Send to" commands are kept
+    /// too, an archive from elsewhere mustn't choose the programs they run.
+    pub fn keep_local(&mut self, current: &Self) {
+        let secrets = [
+            (&mut self.local_server_token, &current.local_server_token),
+            (&mut self.local_feed_token, &current.local_feed_token),
+            (&mut self.smtp_password, &current.smtp_password),
+            (&mut self.push_token, &current.push_token),
+            (&mut self.telegram_bot_token, &current.telegram_bot_token),
+            (&mut self.matrix_access_token, &current.matrix_access_token),
+            (&mut self.webdav_password, &current.webdav_password),
+        ];
+        let webhooks = self.webhooks.iter_mut().zip(&current.webhooks);
+        let webhooks = webhooks.map(|(webhook, current)| (&mut webhook.url, &current.url));
+        for (secret, current) in secrets.into_iter().chain(webhooks) {
+            if secret.is_empty() {
+                secret.clone_from(current);
+            }
         }
+        self.new_item_command_approved
+            .clone_from(&current.new_item_command_approved);
+        self.player_command.clone_from(&current.player_command);
+        self.send_to_commands.clone_from(&current.send_to_commands);
+    }
+
+    fn masked_yaml(&self, mask: &'static str) -> Result<String, serde_yaml::Error> {
+        REDACTING.with(|redacting| redacting.set(Some(mask)));
+        let yaml = serde_yaml::to_string(self);
+        REDACTING.with(|redacting| redacting.set(None));
+        yaml
     }
 
     pub fn apply(self) {
@@ -76,6 +677,15 @@ impl ConfigBuilder {
 
         file.write_all(yaml.as_bytes())?;
 
+        let approved_path = app_dir.join(APPROVED_COMMAND_FILE);
+        if self.new_item_command_approved.is_empty() {
+            if approved_path.exists() {
+                std::fs::remove_file(approved_path)?;
+            }
+        } else {
+            std::fs::write(approved_path, &self.new_item_command_approved)?;
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,123 @@
+use super::utils::get_app_dir;
+use reqwest::Url;
+use reqwest_cookie_store::{CookieStore, CookieStoreMutex};
+use std::collections::BTreeMap;
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::error;
+
+/// Browsers don't export an expiry for session cookies, keep those for as long as Chrome
+/// would keep a persistent one so they survive a restart.
+const SESSION_COOKIE_MAX_AGE: i64 = 400 * 24 * 60 * 60;
+
+fn cookies_path() -> PathBuf {
+    get_app_dir().join("cookies.json")
+}
+
+/// Loads the cookie jar of the current profile, or an empty one when there is none yet.
+pub fn load() -> Arc<CookieStoreMutex> {
+    let store = match std::fs::File::open(cookies_path()) {
+        Ok(file) => CookieStore::load_json(BufReader::new(file)).unwrap_or_else(|err| {
+            error!("Failed to load cookies: {}", err.to_string());
+            CookieStore::default()
+        }),
+        Err(_) => CookieStore::default(),
+    };
+    Arc::new(CookieStoreMutex::new(store))
+}
+
+/// Writes the persistent cookies of the jar to the current profile's folder.
+pub fn save(cookies: &CookieStoreMutex) -> Result<(), String> {
+    let mut file = std::fs::File::create(cookies_path()).map_err(|err| err.to_string())?;
+    cookies
+        .lock()
+        .unwrap()
+        .save_json(&mut file)
+        .map_err(|err| err.to_string())
+}
+
+/// Imports cookies from a Netscape `cookies.txt` export, which is what most browser
+/// extensions produce. With a `domain`, only cookies of that domain and its subdomains are
+/// taken. Returns the number of imported cookies.
+pub fn import_netscape(
+    cookies: &CookieStoreMutex,
+    text: &str,
+    domain: Option<&str>,
+) -> Result<usize, String> {
+    let domain = domain.map(|domain| domain.trim().trim_start_matches('.').to_lowercase());
+    let now = chrono::Utc::now().timestamp();
+    let mut store = cookies.lock().unwrap();
+    let mut imported = 0;
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        let (line, http_only) = match line.strip_prefix("#HttpOnly_") {
+            Some(line) => (line, true),
+            None => (line, false),
+        };
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [host, subdomains, path, secure, expires, name, value] = fields[..] else {
+            return Err(format!("Line {} is not in cookies.txt format", number + 1));
+        };
+        let host = host.trim_start_matches('.').to_lowercase();
+        if let Some(domain) = &domain {
+            if host != *domain && !host.ends_with(&format!(".{}", domain)) {
+                continue;
+            }
+        }
+
+        let max_age = match expires.parse::<i64>() {
+            Ok(0) | Err(_) => SESSION_COOKIE_MAX_AGE,
+            Ok(expires) if expires <= now => continue,
+            Ok(expires) => expires - now,
+        };
+        let mut cookie = format!("{}={}; Path={}; Max-Age={}", name, value, path, max_age);
+        // Without a Domain attribute the cookie is only sent to this exact host.
+        if subdomains.eq_ignore_ascii_case("TRUE") {
+            cookie.push_str(&format!("; Domain={}", host));
+        }
+        if secure.eq_ignore_ascii_case("TRUE") {
+            cookie.push_str("; Secure");
+        }
+        if http_only {
+            cookie.push_str("; HttpOnly");
+        }
+
+        let url = Url::parse(&format!("https://{}{}", host, path))
+            .map_err(|err| format!("Line {}: {}", number + 1, err))?;
+        store
+            .parse(&cookie, &url)
+            .map_err(|err| format!("Line {}: {}", number + 1, err))?;
+        imported += 1;
+    }
+
+    Ok(imported)
+}
+
+/// Domains with stored cookies and how many cookies each of them has.
+pub fn domains(cookies: &CookieStoreMutex) -> Vec<(String, usize)> {
+    let mut domains: BTreeMap<String, usize> = BTreeMap::new();
+    for cookie in cookies.lock().unwrap().iter_unexpired() {
+        if let Some(domain) = cookie.domain.as_cow() {
+            *domains.entry(domain.into_owned()).or_default() += 1;
+        }
+    }
+    domains.into_iter().collect()
+}
+
+/// Removes every cookie stored for `domain`.
+pub fn clear_domain(cookies: &CookieStoreMutex, domain: &str) {
+    let mut store = cookies.lock().unwrap();
+    let matching: Vec<(String, String)> = store
+        .iter_any()
+        .filter(|cookie| cookie.domain.as_cow().is_some_and(|host| host == domain))
+        .map(|cookie| (String::from(&cookie.path), cookie.name().to_string()))
+        .collect();
+    for (path, name) in matching {
+        store.remove(domain, &path, &name);
+    }
+}
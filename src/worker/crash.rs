@@ -0,0 +1,76 @@
+use super::utils;
+use std::backtrace::Backtrace;
+use std::io::Write;
+use std::path::PathBuf;
+use tracing::error;
+
+/// Number of log lines copied into a crash report.
+const REPORT_LOG_LINES: usize = 100;
+
+fn get_crash_dir() -> PathBuf {
+    utils::get_base_dir().join("crashes")
+}
+
+/// Holds the path of a report that has not been shown to the user yet.
+fn get_pending_path() -> PathBuf {
+    get_crash_dir().join("pending")
+}
+
+/// Writes a crash report for every panic, on top of the default hook printing to stderr.
+///
+/// Release builds on Windows have no console, so without the report a panic just closes the window.
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        error!("{}", info);
+        match write_report(&info.to_string()) {
+            Ok(path) => error!("Crash report written to {}", path.display()),
+            Err(err) => error!("Failed to write crash report: {}", err.to_string()),
+        }
+        default_hook(info);
+    }));
+}
+
+fn write_report(message: &str) -> std::io::Result<PathBuf> {
+    let dir = get_crash_dir();
+    std::fs::create_dir_all(&dir)?;
+    let now = chrono::Local::now();
+    let path = dir.join(format!("crash-{}.txt", now.format("%Y%m%d-%H%M%S")));
+
+    let thread = std::thread::current();
+    let mut report = format!(
+        "Tinyrss {} crashed at {}\n{} {}\n\nThread '{}' {}\n\nBacktrace:\n{}\n",
+        env!("CARGO_PKG_VERSION"),
+        now.to_rfc3339(),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        thread.name().unwrap_or("<unnamed>"),
+        message,
+        Backtrace::force_capture(),
+    );
+    report.push_str("\nRecent log:\n");
+    match utils::read_log_tail(REPORT_LOG_LINES) {
+        Ok(lines) => {
+            for line in lines {
+                report.push_str(&line);
+                report.push('\n');
+            }
+        }
+        Err(err) => report.push_str(&format!("Failed to read log file: {}\n", err)),
+    }
+
+    std::fs::File::create(&path)?.write_all(report.as_bytes())?;
+    std::fs::write(get_pending_path(), path.to_string_lossy().as_bytes())?;
+    Ok(path)
+}
+
+/// Returns the report of a crash that happened since the last launch, so it is only offered once.
+pub fn take_pending_crash_report() -> Option<PathBuf> {
+    let pending = get_pending_path();
+    let path = std::fs::read_to_string(&pending).ok()?;
+    if let Err(err) = std::fs::remove_file(&pending) {
+        error!("Failed to clear pending crash report: {}", err.to_string());
+    }
+    let path = PathBuf::from(path.trim());
+    path.exists().then_some(path)
+}
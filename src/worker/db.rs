@@ -1,10 +1,80 @@
 use crate::worker::utils;
-use sqlx::{query, query_as, FromRow, Result};
-use sqlx::{Connection, SqliteConnection};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{query, query_as, query_scalar, FromRow, QueryBuilder, Result};
+use sqlx::{ConnectOptions, Connection, SqliteConnection};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use tracing::info;
+
+/// Most values SQLite binds to one statement.
+const SQLITE_MAX_VARIABLES: usize = 999;
+
+/// Ids per `IN (...)` list, stays under [`SQLITE_MAX_VARIABLES`] with room for other parameters.
+const ID_BATCH_SIZE: usize = 900;
+
+/// Schema changes applied on top of the initial tables, in order.
+/// The amount of applied migrations is tracked in `PRAGMA user_version`.
+const MIGRATIONS: &[&str] = &[
+    "ALTER TABLE items ADD COLUMN starred BOOLEAN NOT NULL DEFAULT False;",
+    "ALTER TABLE items ADD COLUMN reading_time INTEGER NOT NULL DEFAULT 0;",
+    "ALTER TABLE items ADD COLUMN later BOOLEAN NOT NULL DEFAULT False;",
+    "ALTER TABLE items ADD COLUMN first_seen INTEGER NOT NULL DEFAULT 0;",
+    "ALTER TABLE channels ADD COLUMN color VARCHAR;",
+    "CREATE TABLE blocked_items (id VARCHAR NOT NULL PRIMARY KEY, blocked_at INTEGER NOT NULL);",
+    "ALTER TABLE channels ADD COLUMN last_error VARCHAR; ALTER TABLE channels ADD COLUMN last_error_at INTEGER;",
+    "ALTER TABLE channels ADD COLUMN tls_fingerprint VARCHAR;",
+    "CREATE TABLE channel_headers (channel VARCHAR NOT NULL, name VARCHAR NOT NULL, value VARCHAR NOT NULL, PRIMARY KEY (channel, name), FOREIGN KEY (channel) REFERENCES channels (id) ON DELETE CASCADE);",
+    "DELETE FROM items WHERE channel NOT IN (SELECT id FROM channels); DELETE FROM channel_headers WHERE channel NOT IN (SELECT id FROM channels);",
+    "ALTER TABLE channels ADD COLUMN archived BOOLEAN NOT NULL DEFAULT False;",
+    "ALTER TABLE items ADD COLUMN updated INTEGER NOT NULL DEFAULT 0; ALTER TABLE items ADD COLUMN changed BOOLEAN NOT NULL DEFAULT False;",
+    "ALTER TABLE items ADD COLUMN content VARCHAR;",
+    "ALTER TABLE items ADD COLUMN thumbnail VARCHAR; ALTER TABLE items ADD COLUMN duration INTEGER NOT NULL DEFAULT 0;",
+    "ALTER TABLE items ADD COLUMN enclosure VARCHAR;",
+    "CREATE TABLE history (id VARCHAR NOT NULL PRIMARY KEY, link VARCHAR NOT NULL, title VARCHAR, channel_title VARCHAR, published INTEGER NOT NULL, first_seen INTEGER NOT NULL); INSERT OR IGNORE INTO history SELECT id, link, title, channel_title, published, first_seen FROM items;",
+    "ALTER TABLE channels ADD COLUMN cache_articles BOOLEAN NOT NULL DEFAULT False;",
+    "ALTER TABLE channels ADD COLUMN folder VARCHAR;",
+    "ALTER TABLE channels ADD COLUMN priority BOOLEAN NOT NULL DEFAULT False;",
+    "ALTER TABLE history ADD COLUMN opened INTEGER NOT NULL DEFAULT 0;",
+    "ALTER TABLE items ADD COLUMN pinned BOOLEAN NOT NULL DEFAULT False;",
+    "ALTER TABLE channels ADD COLUMN click_action VARCHAR NOT NULL DEFAULT 'reader'; ALTER TABLE channels ADD COLUMN click_command VARCHAR;",
+    "CREATE TABLE events (at INTEGER NOT NULL, kind VARCHAR NOT NULL, channel VARCHAR NOT NULL, title VARCHAR, link VARCHAR NOT NULL, detail VARCHAR);",
+    "ALTER TABLE channels ADD COLUMN notes VARCHAR;",
+    "ALTER TABLE channels ADD COLUMN quirks VARCHAR; ALTER TABLE items ADD COLUMN comments VARCHAR;",
+    "ALTER TABLE items ADD COLUMN links VARCHAR NOT NULL DEFAULT '[]';",
+    "ALTER TABLE items ADD COLUMN author VARCHAR;",
+    "ALTER TABLE channels ADD COLUMN item_limit INTEGER;",
+    "ALTER TABLE channels ADD COLUMN backfill VARCHAR;",
+    "ALTER TABLE channels ADD COLUMN newsletter BOOLEAN NOT NULL DEFAULT False; UPDATE channels SET newsletter = True WHERE kind = 'Newsletter';",
+    "CREATE TABLE rekeyed_channels (old VARCHAR NOT NULL PRIMARY KEY, new VARCHAR NOT NULL);",
+    // Full text index of the items, kept up to date by the triggers.
+    "CREATE VIRTUAL TABLE items_fts USING fts5(title, author, summary, content, content='items');
+    CREATE TRIGGER items_fts_insert AFTER INSERT ON items BEGIN INSERT INTO items_fts (rowid, title, author, summary, content) VALUES (new.rowid, new.title, new.author, new.summary, new.content); END;
+    CREATE TRIGGER items_fts_delete AFTER DELETE ON items BEGIN INSERT INTO items_fts (items_fts, rowid, title, author, summary, content) VALUES ('delete', old.rowid, old.title, old.author, old.summary, old.content); END;
+    CREATE TRIGGER items_fts_update AFTER UPDATE OF title, author, summary, content ON items BEGIN INSERT INTO items_fts (items_fts, rowid, title, author, summary, content) VALUES ('delete', old.rowid, old.title, old.author, old.summary, old.content); INSERT INTO items_fts (rowid, title, author, summary, content) VALUES (new.rowid, new.title, new.author, new.summary, new.content); END;
+    INSERT INTO items_fts (items_fts) VALUES ('rebuild');",
+];
+
+/// Migration that also runs [`rekey_channels`]. Its table keeps the moved ids until the
+/// config was updated too, see [`get_rekeyed_channels`].
+const REKEY_MIGRATION: usize = 30;
+
+/// How long new items of high-priority channels stay on top of the feed.
+const PRIORITY_PIN_SECONDS: i64 = 24 * 60 * 60;
+
+/// Most history entries returned by one search.
+const HISTORY_LIMIT: i64 = 200;
+
+/// Most subscription events shown in the timeline.
+const EVENTS_LIMIT: i64 = 200;
 
 async fn establish_connection() -> Result<SqliteConnection> {
     let app_dir = utils::get_app_dir();
-    SqliteConnection::connect(app_dir.join("tinyrss.db").to_str().unwrap()).await
+    // Items and headers of a channel are removed along with it by the cascading foreign keys.
+    SqliteConnectOptions::from_str(app_dir.join("tinyrss.db").to_str().unwrap())?
+        .foreign_keys(true)
+        .connect()
+        .await
 }
 
 pub async fn create_tables() -> Result<()> {
@@ -33,42 +103,569 @@ pub async fn create_tables() -> Result<()> {
     )
     .execute(&mut conn)
     .await?;
+
+    migrate(&mut conn).await?;
+
+    Ok(())
+}
+
+/// Moves channels whose id isn't derived from their link yet, e.g. ones subscribed before ids
+/// were derived from links, to the derived id along with their items and headers.
+/// The old and the new id of every moved channel are recorded in `rekeyed_channels`.
+/// Runs inside the transaction of its migration.
+async fn rekey_channels(tz: &mut SqliteConnection) -> Result<()> {
+    let channels = query_as::<_, (String, String)>("SELECT id, link FROM channels")
+        .fetch_all(&mut *tz)
+        .await?;
+    let mut ids: HashSet<String> = channels.iter().map(|(id, _)| id.clone()).collect();
+
+    // Items point at the new id before the channel row has it.
+    query("PRAGMA defer_foreign_keys = ON")
+        .execute(&mut *tz)
+        .await?;
+
+    for (old, link) in channels {
+        let new = utils::channel_id(&link);
+        if new == old {
+            continue;
+        }
+        query("UPDATE items SET channel = ? WHERE channel = ?")
+            .bind(&new)
+            .bind(&old)
+            .execute(&mut *tz)
+            .await?;
+        query("UPDATE OR IGNORE channel_headers SET channel = ? WHERE channel = ?")
+            .bind(&new)
+            .bind(&old)
+            .execute(&mut *tz)
+            .await?;
+        query("DELETE FROM channel_headers WHERE channel = ?")
+            .bind(&old)
+            .execute(&mut *tz)
+            .await?;
+        if ids.contains(&new) {
+            // Another spelling of the same link is subscribed already, keep only that one.
+            query("DELETE FROM channels WHERE id = ?")
+                .bind(&old)
+                .execute(&mut *tz)
+                .await?;
+        } else {
+            query("UPDATE channels SET id = ? WHERE id = ?")
+                .bind(&new)
+                .bind(&old)
+                .execute(&mut *tz)
+                .await?;
+            ids.insert(new.clone());
+        }
+        query("INSERT OR REPLACE INTO rekeyed_channels (old, new) VALUES (?, ?)")
+            .bind(&old)
+            .bind(&new)
+            .execute(&mut *tz)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Old and new ids of the channels moved by [`rekey_channels`] whose new ids aren't in the
+/// config yet.
+pub async fn get_rekeyed_channels() -> Result<HashMap<String, String>> {
+    let mut conn = establish_connection().await?;
+
+    let moved = query_as::<_, (String, String)>("SELECT old, new FROM rekeyed_channels")
+        .fetch_all(&mut conn)
+        .await?;
+
+    Ok(moved.into_iter().collect())
+}
+
+pub async fn clear_rekeyed_channels() -> Result<()> {
+    let mut conn = establish_connection().await?;
+
+    query("DELETE FROM rekeyed_channels")
+        .execute(&mut conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Moves the items and headers of the `merged` channels to `keep` and deletes them.
+/// Headers `keep` has already are left as they are.
+pub async fn merge_channels(keep: &str, merged: &[String]) -> Result<()> {
+    let mut conn = establish_connection().await?;
+
+    let mut tz = conn.begin().await?;
+
+    for id in merged.iter().filter(|id| *id != keep) {
+        query("UPDATE items SET channel = ? WHERE channel = ?")
+            .bind(keep)
+            .bind(id)
+            .execute(&mut tz)
+            .await?;
+        query("UPDATE OR IGNORE channel_headers SET channel = ? WHERE channel = ?")
+            .bind(keep)
+            .bind(id)
+            .execute(&mut tz)
+            .await?;
+        query("DELETE FROM channels WHERE id = ?")
+            .bind(id)
+            .execute(&mut tz)
+            .await?;
+    }
+
+    tz.commit().await?;
+
+    Ok(())
+}
+
+pub async fn record_events(events: &[Event]) -> Result<()> {
+    let mut conn = establish_connection().await?;
+
+    let mut tz = conn.begin().await?;
+
+    for event in events {
+        query(
+            "INSERT INTO events (at, kind, channel, title, link, detail) VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(event.at)
+        .bind(event.kind)
+        .bind(&event.channel)
+        .bind(&event.title)
+        .bind(&event.link)
+        .bind(&event.detail)
+        .execute(&mut tz)
+        .await?;
+    }
+
+    tz.commit().await?;
+
+    Ok(())
+}
+
+/// Newest subscription events first.
+pub async fn get_events() -> Result<Vec<Event>> {
+    let mut conn = establish_connection().await?;
+
+    query_as::<_, Event>(
+        "SELECT at, kind, channel, title, link, detail FROM events ORDER BY at DESC, rowid DESC LIMIT ?",
+    )
+    .bind(EVENTS_LIMIT)
+    .fetch_all(&mut conn)
+    .await
+}
+
+/// Writes a consistent copy of the database to `path`, which must not exist yet.
+pub async fn vacuum_into(path: &std::path::Path) -> Result<()> {
+    let mut conn = establish_connection().await?;
+
+    query("VACUUM INTO ?")
+        .bind(path.to_string_lossy().to_string())
+        .execute(&mut conn)
+        .await?;
+
+    // The copy may number the items differently, the search index refers to those numbers.
+    let mut copy = SqliteConnectOptions::from_str(path.to_str().unwrap())?
+        .connect()
+        .await?;
+    query("INSERT INTO items_fts (items_fts) VALUES ('rebuild')")
+        .execute(&mut copy)
+        .await?;
+
+    Ok(())
+}
+
+/// Copies the database to `path` with SQLite's online backup API. Other connections may keep
+/// writing meanwhile, the copy is taken from one read transaction.
+pub async fn backup_to(path: &std::path::Path) -> Result<()> {
+    let mut source = establish_connection().await?;
+    // Moves everything still in the write-ahead log into the database first.
+    query("PRAGMA wal_checkpoint(TRUNCATE)")
+        .execute(&mut source)
+        .await?;
+    source.close().await?;
+
+    let source = utils::get_app_dir().join("tinyrss.db");
+    let target = path.to_path_buf();
+    tokio::task::spawn_blocking(move || backup_blocking(&source, &target))
+        .await
+        .map_err(|err| sqlx::Error::Protocol(err.to_string()))?
+}
+
+fn backup_blocking(source: &std::path::Path, target: &std::path::Path) -> Result<()> {
+    use libsqlite3_sys::{
+        sqlite3, sqlite3_backup_finish, sqlite3_backup_init, sqlite3_backup_step, sqlite3_close,
+        sqlite3_errmsg, sqlite3_open_v2, SQLITE_BUSY, SQLITE_DONE, SQLITE_LOCKED, SQLITE_OK,
+        SQLITE_OPEN_CREATE, SQLITE_OPEN_READONLY, SQLITE_OPEN_READWRITE,
+    };
+    use std::ffi::CString;
+
+    /// Attempts while writers hold the database busy before giving up.
+    const BUSY_ATTEMPTS: u32 = 100;
+
+    fn last_error(handle: *mut sqlite3) -> sqlx::Error {
+        // SAFETY: the handle is open and the message is copied before the next call on it.
+        let message = unsafe { std::ffi::CStr::from_ptr(sqlite3_errmsg(handle)) };
+        sqlx::Error::Protocol(format!("Backup failed: {}", message.to_string_lossy()))
+    }
+
+    fn open(path: &std::path::Path, flags: i32) -> Result<*mut sqlite3> {
+        let path = CString::new(path.to_string_lossy().as_bytes())
+            .map_err(|err| sqlx::Error::Protocol(err.to_string()))?;
+        let mut handle = std::ptr::null_mut();
+        // SAFETY: the path is a valid C string and the handle is closed by the caller.
+        let result =
+            unsafe { sqlite3_open_v2(path.as_ptr(), &mut handle, flags, std::ptr::null()) };
+        if result != SQLITE_OK {
+            let error = last_error(handle);
+            // SAFETY: a failed open still hands out a handle to be closed, or null.
+            unsafe { sqlite3_close(handle) };
+            return Err(error);
+        }
+        Ok(handle)
+    }
+
+    let source = open(source, SQLITE_OPEN_READONLY)?;
+    let target = match open(target, SQLITE_OPEN_READWRITE | SQLITE_OPEN_CREATE) {
+        Ok(target) => target,
+        Err(err) => {
+            // SAFETY: the source was opened above and isn't used afterwards.
+            unsafe { sqlite3_close(source) };
+            return Err(err);
+        }
+    };
+    let main = c"main".as_ptr();
+
+    // SAFETY: both handles are open and only used on this thread until they are closed.
+    let result = unsafe {
+        let backup = sqlite3_backup_init(target, main, source, main);
+        if backup.is_null() {
+            Err(last_error(target))
+        } else {
+            // All pages in one step, so writes of other connections can't restart the copy.
+            let mut result = sqlite3_backup_step(backup, -1);
+            let mut attempt = 1;
+            while matches!(result, SQLITE_BUSY | SQLITE_LOCKED) && attempt < BUSY_ATTEMPTS {
+                std::thread::sleep(std::time::Duration::from_millis(50));
+                result = sqlite3_backup_step(backup, -1);
+                attempt += 1;
+            }
+            // Reports the error of the failed step, if any.
+            if sqlite3_backup_finish(backup) != SQLITE_OK || result != SQLITE_DONE {
+                Err(last_error(target))
+            } else {
+                Ok(())
+            }
+        }
+    };
+    // SAFETY: the backup is finished, neither handle is used afterwards.
+    unsafe {
+        sqlite3_close(target);
+        sqlite3_close(source);
+    }
+    result
+}
+
+async fn migrate(conn: &mut SqliteConnection) -> Result<()> {
+    let version = query_scalar::<_, i64>("PRAGMA user_version")
+        .fetch_one(&mut *conn)
+        .await? as usize;
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        info!("Applying database migration {}.", index + 1);
+        let mut tz = conn.begin().await?;
+        query(migration).execute(&mut tz).await?;
+        if index == REKEY_MIGRATION {
+            rekey_channels(&mut tz).await?;
+        }
+        query(&format!("PRAGMA user_version = {}", index + 1))
+            .execute(&mut tz)
+            .await?;
+        tz.commit().await?;
+    }
+
     Ok(())
 }
 
-#[derive(Debug, Default, FromRow)]
+#[derive(Debug, Default, FromRow, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Channel {
     pub id: String,
     pub kind: String,
     pub link: String,
     pub title: Option<String>,
     pub description: Option<String>,
+    pub color: Option<String>,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<i64>,
+    /// SHA-256 fingerprint of a certificate the user chose to trust although it failed validation.
+    pub tls_fingerprint: Option<String>,
+    /// Unsubscribed, but kept for its items. Not refreshed or listed.
+    pub archived: bool,
+    /// Web pages of new items are downloaded after each refresh, for reading offline.
+    pub cache_articles: bool,
+    /// Name of the folder the channel is listed in, `None` for top level.
+    pub folder: Option<String>,
+    /// New items are announced with a sound and stay on top of the feed for a day.
+    pub priority: bool,
+    /// What clicking one of the channel's items in the feed does.
+    pub click_action: ClickAction,
+    /// Command run for [`ClickAction::Command`], with `{url}` and `{title}` replaced.
+    pub click_command: Option<String>,
+    /// The user's own text about the channel, shown instead of the feed's description.
+    pub notes: Option<String>,
+    /// Comma separated names of the parsing quirks applied to the feed, `None` to detect them
+    /// from the link. See [`crate::worker::Quirk`].
+    pub quirks: Option<String>,
+    /// Newest entries kept from each fetch, 0 keeps all. `None` uses the global default.
+    pub item_limit: Option<i64>,
+    /// How much of the backlog the first fetch brings into "New", `None` once fetched.
+    pub backfill: Option<Backfill>,
+    /// The feed collects the emails sent to an inbox of the newsletter server.
+    pub newsletter: bool,
+}
+
+/// Change to the subscriptions, kept after the channel itself is gone.
+#[derive(Debug, Clone, FromRow)]
+pub struct Event {
+    pub at: i64,
+    pub kind: EventKind,
+    pub channel: String,
+    pub title: Option<String>,
+    pub link: String,
+    /// Where a subscription came from, the old title of a renamed channel and the like.
+    pub detail: Option<String>,
+}
+
+impl Event {
+    pub fn new(kind: EventKind, channel: &Channel, detail: Option<String>) -> Self {
+        Self {
+            at: chrono::Utc::now().timestamp(),
+            kind,
+            channel: channel.id.clone(),
+            title: channel.title.clone(),
+            link: channel.link.clone(),
+            detail,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+pub enum EventKind {
+    Subscribed,
+    /// An archived channel was subscribed to again.
+    Restored,
+    Unsubscribed,
+    Archived,
+    Renamed,
+    Merged,
+}
+
+impl EventKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            EventKind::Subscribed => "Subscribed",
+            EventKind::Restored => "Restored",
+            EventKind::Unsubscribed => "Unsubscribed",
+            EventKind::Archived => "Archived",
+            EventKind::Renamed => "Renamed",
+            EventKind::Merged => "Merged",
+        }
+    }
+}
+
+/// What clicking an item card does, chosen per channel.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+pub enum ClickAction {
+    /// Shows the item details.
+    #[default]
+    Reader,
+    Browser,
+    /// Plays the enclosure with the configured player, or the link of items without one,
+    /// like videos.
+    Player,
+    Command,
+}
+
+impl ClickAction {
+    pub const ALL: [ClickAction; 4] = [
+        ClickAction::Reader,
+        ClickAction::Browser,
+        ClickAction::Player,
+        ClickAction::Command,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ClickAction::Reader => "Reader view",
+            ClickAction::Browser => "Open in browser",
+            ClickAction::Player => "Play in player",
+            ClickAction::Command => "Run command",
+        }
+    }
+}
+
+/// Which items of a new subscription's first fetch show up as new, the others are saved
+/// as dismissed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+pub enum Backfill {
+    #[default]
+    All,
+    /// The newest `backfill_count` items.
+    Newest,
+    /// Items published today.
+    Today,
+}
+
+impl Backfill {
+    pub const ALL: [Backfill; 3] = [Backfill::All, Backfill::Newest, Backfill::Today];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Backfill::All => "All items",
+            Backfill::Newest => "Newest only",
+            Backfill::Today => "Today's only",
+        }
+    }
+}
+
+/// Extra request header sent when fetching a channel.
+#[derive(Debug, Clone, Default, FromRow)]
+pub struct ChannelHeader {
+    pub channel: String,
+    pub name: String,
+    pub value: String,
+}
+
+/// Link of a feed entry other than the article itself.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ItemLink {
+    pub href: String,
+    /// Relation to the entry as given by Atom feeds, e.g. `via`, `replies` or `enclosure`.
+    pub rel: Option<String>,
+    pub title: Option<String>,
+}
+
+impl ItemLink {
+    pub fn label(&self) -> String {
+        if let Some(title) = self.title.as_ref().filter(|title| !title.trim().is_empty()) {
+            return title.trim().to_string();
+        }
+        match self.rel.as_deref() {
+            Some("replies") => "Comments".to_string(),
+            Some("via") => "Via".to_string(),
+            Some("enclosure") => "Attachment".to_string(),
+            Some("related") | None => utils::site_of(&self.href).unwrap_or("Link".to_string()),
+            Some(rel) => rel.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ItemLinks(pub Vec<ItemLink>);
+
+impl ItemLinks {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.0).unwrap_or("[]".to_string())
+    }
+
+    pub fn with_rel(&self, rel: &str) -> Option<&ItemLink> {
+        self.0.iter().find(|link| link.rel.as_deref() == Some(rel))
+    }
+}
+
+impl TryFrom<String> for ItemLinks {
+    type Error = serde_json::Error;
+
+    fn try_from(json: String) -> std::result::Result<Self, Self::Error> {
+        serde_json::from_str(&json).map(ItemLinks)
+    }
 }
 
-#[derive(Debug, Default, FromRow)]
+#[derive(Debug, Clone, Default, FromRow, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Item {
     pub id: String,
     pub link: String,
     pub title: Option<String>,
     pub summary: Option<String>,
+    /// Full text of the entry, for feeds that include more than a summary.
+    pub content: Option<String>,
     pub published: i64,
     pub dismissed: bool,
+    pub starred: bool,
+    pub later: bool,
+    pub reading_time: i64,
+    pub first_seen: i64,
     pub channel_title: Option<String>,
     pub channel: String,
+    /// When the feed last updated the entry, 0 if it doesn't say.
+    pub updated: i64,
+    /// The title, summary or content changed after the item was first saved.
+    pub changed: bool,
+    /// Image url of the entry's Media RSS thumbnail.
+    pub thumbnail: Option<String>,
+    /// Play time of the entry's video or audio in seconds, 0 if unknown.
+    pub duration: i64,
+    /// Url of the attached audio, video or other file.
+    pub enclosure: Option<String>,
+    /// Url of the discussion of the entry, when it isn't the link itself.
+    pub comments: Option<String>,
+    /// Links of the entry besides [`Item::link`], saved as JSON.
+    #[sqlx(try_from = "String")]
+    pub links: ItemLinks,
+    /// Names of the entry's authors, comma separated.
+    pub author: Option<String>,
+    /// Kept on top of the new items until unpinned.
+    pub pinned: bool,
+    /// Comes from a high-priority channel. Only loaded for the feed.
+    #[sqlx(default)]
+    #[serde(skip)]
+    pub priority: bool,
+}
+
+impl Item {
+    /// Whether the item is kept on top of the feed for its channel, see [`Channel::priority`].
+    pub fn is_prioritized(&self, now: i64) -> bool {
+        self.priority && self.first_seen > now - PRIORITY_PIN_SECONDS
+    }
+
+    /// Key the feed is sorted by before any other order, higher first.
+    pub fn rank(&self, now: i64) -> (bool, bool) {
+        (self.pinned, self.is_prioritized(now))
+    }
+}
+
+/// Every item ever saved, kept when the item itself is removed with its channel.
+#[derive(Debug, Clone, FromRow)]
+pub struct HistoryEntry {
+    pub link: String,
+    pub title: Option<String>,
+    pub channel_title: Option<String>,
+    pub published: i64,
+    pub first_seen: i64,
+    /// When the item's link was last opened, 0 if never.
+    pub opened: i64,
 }
 
+/// Inserts new channels. Channels that exist already keep their link, but take over the
+/// kind, title and description of the freshly fetched feed.
 pub async fn add_channels(channels: Vec<Channel>) -> Result<()> {
     let mut conn = establish_connection().await?;
 
     let mut tz = conn.begin().await?;
 
     for channel in channels {
-        query("INSERT OR IGNORE INTO channels (id, kind, link, title, description) VALUES (?, ?, ?, ?, ?)")
+        query("INSERT INTO channels (id, kind, link, title, description, backfill) VALUES (?, ?, ?, ?, ?, ?) ON CONFLICT(id) DO UPDATE SET kind = excluded.kind, title = excluded.title, description = excluded.description WHERE kind IS NOT excluded.kind OR title IS NOT excluded.title OR description IS NOT excluded.description")
         .bind(channel.id)
         .bind(channel.kind)
         .bind(channel.link)
         .bind(channel.title)
         .bind(channel.description)
+        .bind(channel.backfill)
         .execute(&mut tz)
         .await?;
     }
@@ -78,11 +675,42 @@ pub async fn add_channels(channels: Vec<Channel>) -> Result<()> {
     Ok(())
 }
 
+/// Marks the first fetch of the channels as done.
+pub async fn clear_backfill(ids: &[String]) -> Result<()> {
+    let mut conn = establish_connection().await?;
+
+    let mut tz = conn.begin().await?;
+
+    for id in ids {
+        query("UPDATE channels SET backfill = NULL WHERE id = ?")
+            .bind(id)
+            .execute(&mut tz)
+            .await?;
+    }
+
+    tz.commit().await?;
+
+    Ok(())
+}
+
+/// Like [`get_all_channels`], but with the archived channels.
+pub async fn get_channels_with_archived() -> Result<Vec<Channel>> {
+    let mut conn = establish_connection().await?;
+
+    let channels = query_as::<_, Channel>(
+        "SELECT id, kind, link, title, description, color, last_error, last_error_at, tls_fingerprint, archived, cache_articles, folder, priority, click_action, click_command, notes, quirks, item_limit, backfill, newsletter FROM channels ORDER BY title",
+    )
+    .fetch_all(&mut conn)
+    .await?;
+
+    Ok(channels)
+}
+
 pub async fn get_all_channels() -> Result<Vec<Channel>> {
     let mut conn = establish_connection().await?;
 
     let channels = query_as::<_, Channel>(
-        "SELECT id, kind, link, title, description FROM channels ORDER BY title",
+        "SELECT id, kind, link, title, description, color, last_error, last_error_at, tls_fingerprint, archived, cache_articles, folder, priority, click_action, click_command, notes, quirks, item_limit, backfill, newsletter FROM channels WHERE archived = False ORDER BY title",
     )
     .fetch_all(&mut conn)
     .await?;
@@ -90,39 +718,58 @@ pub async fn get_all_channels() -> Result<Vec<Channel>> {
     Ok(channels)
 }
 
-pub async fn edit_channel(id: String, title: String) -> Result<()> {
+pub async fn get_archived_channels() -> Result<Vec<Channel>> {
     let mut conn = establish_connection().await?;
 
-    query("UPDATE channels SET title = ? WHERE id = ?")
-        .bind(&title)
-        .bind(&id)
-        .execute(&mut conn)
-        .await?;
+    let channels = query_as::<_, Channel>(
+        "SELECT id, kind, link, title, description, color, last_error, last_error_at, tls_fingerprint, archived, cache_articles, folder, priority, click_action, click_command, notes, quirks, item_limit, backfill, newsletter FROM channels WHERE archived = True ORDER BY title",
+    )
+    .fetch_all(&mut conn)
+    .await?;
 
-    query("UPDATE items SET channel_title = ? WHERE channel = ?")
-        .bind(&title)
-        .bind(&id)
+    Ok(channels)
+}
+
+pub async fn set_archived(id: &str, archived: bool) -> Result<()> {
+    let mut conn = establish_connection().await?;
+
+    query("UPDATE channels SET archived = ?, last_error = NULL, last_error_at = NULL WHERE id = ?")
+        .bind(archived)
+        .bind(id)
         .execute(&mut conn)
         .await?;
 
     Ok(())
 }
 
-pub async fn add_items(items: Vec<Item>) -> Result<()> {
+pub async fn get_channel_headers() -> Result<Vec<ChannelHeader>> {
+    let mut conn = establish_connection().await?;
+
+    let headers = query_as::<_, ChannelHeader>(
+        "SELECT channel, name, value FROM channel_headers ORDER BY name",
+    )
+    .fetch_all(&mut conn)
+    .await?;
+
+    Ok(headers)
+}
+
+/// Replaces all headers of a channel.
+pub async fn set_channel_headers(id: &str, headers: Vec<(String, String)>) -> Result<()> {
     let mut conn = establish_connection().await?;
 
     let mut tz = conn.begin().await?;
 
-    for item in items {
-        query("INSERT OR IGNORE INTO items (id, link, title, summary, published, dismissed, channel_title, channel) VALUES (?, ?, ?, ?, ?, ?, ?, ?)")
-            .bind(item.id)
-            .bind(item.link)
-            .bind(item.title)
-            .bind(item.summary)
-            .bind(item.published)
-            .bind(item.dismissed)
-            .bind(item.channel_title)
-            .bind(item.channel)
+    query("DELETE FROM channel_headers WHERE channel = ?")
+        .bind(id)
+        .execute(&mut tz)
+        .await?;
+
+    for (name, value) in headers {
+        query("INSERT OR REPLACE INTO channel_headers (channel, name, value) VALUES (?, ?, ?)")
+            .bind(id)
+            .bind(name)
+            .bind(value)
             .execute(&mut tz)
             .await?;
     }
@@ -132,23 +779,23 @@ pub async fn add_items(items: Vec<Item>) -> Result<()> {
     Ok(())
 }
 
-pub async fn get_all_items() -> Result<Vec<Item>> {
+pub async fn set_channel_color(id: &str, color: Option<&str>) -> Result<()> {
     let mut conn = establish_connection().await?;
 
-    let items = query_as::<_, Item>(
-        "SELECT id, link, title, summary, published, dismissed, channel_title, channel FROM items ORDER BY published DESC",
-    )
-    .fetch_all(&mut conn)
-    .await?;
+    query("UPDATE channels SET color = ? WHERE id = ?")
+        .bind(color)
+        .bind(id)
+        .execute(&mut conn)
+        .await?;
 
-    Ok(items)
+    Ok(())
 }
 
-pub async fn set_dismissed(id: &str, dismissed: bool) -> Result<()> {
+pub async fn set_channel_notes(id: &str, notes: Option<&str>) -> Result<()> {
     let mut conn = establish_connection().await?;
 
-    query("UPDATE items SET dismissed = ? WHERE id = ?")
-        .bind(dismissed)
+    query("UPDATE channels SET notes = ? WHERE id = ?")
+        .bind(notes)
         .bind(id)
         .execute(&mut conn)
         .await?;
@@ -156,23 +803,700 @@ pub async fn set_dismissed(id: &str, dismissed: bool) -> Result<()> {
     Ok(())
 }
 
-pub async fn dismiss_all() -> Result<()> {
+pub async fn set_channel_item_limit(id: &str, limit: Option<i64>) -> Result<()> {
     let mut conn = establish_connection().await?;
 
-    query("UPDATE items SET dismissed = True")
+    query("UPDATE channels SET item_limit = ? WHERE id = ?")
+        .bind(limit)
+        .bind(id)
         .execute(&mut conn)
         .await?;
 
     Ok(())
 }
 
-pub async fn unsubscribe(id: &str) -> Result<()> {
+pub async fn set_channel_newsletter(id: &str) -> Result<()> {
     let mut conn = establish_connection().await?;
 
-    query("DELETE FROM channels WHERE id = ?")
+    query("UPDATE channels SET newsletter = True WHERE id = ?")
+        .bind(id)
+        .execute(&mut conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn set_channel_quirks(id: &str, quirks: Option<&str>) -> Result<()> {
+    let mut conn = establish_connection().await?;
+
+    query("UPDATE channels SET quirks = ? WHERE id = ?")
+        .bind(quirks)
         .bind(id)
         .execute(&mut conn)
         .await?;
 
     Ok(())
 }
+
+pub async fn set_channel_folders(folders: &[(String, Option<String>)]) -> Result<()> {
+    let mut conn = establish_connection().await?;
+
+    let mut tz = conn.begin().await?;
+
+    for (id, folder) in folders {
+        query("UPDATE channels SET folder = ? WHERE id = ?")
+            .bind(folder)
+            .bind(id)
+            .execute(&mut tz)
+            .await?;
+    }
+
+    tz.commit().await?;
+
+    Ok(())
+}
+
+pub async fn set_channel_click_action(
+    id: &str,
+    action: ClickAction,
+    command: Option<&str>,
+) -> Result<()> {
+    let mut conn = establish_connection().await?;
+
+    query("UPDATE channels SET click_action = ?, click_command = ? WHERE id = ?")
+        .bind(action)
+        .bind(command)
+        .bind(id)
+        .execute(&mut conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn set_channel_priority(id: &str, priority: bool) -> Result<()> {
+    let mut conn = establish_connection().await?;
+
+    query("UPDATE channels SET priority = ? WHERE id = ?")
+        .bind(priority)
+        .bind(id)
+        .execute(&mut conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn set_channel_caching(id: &str, cache_articles: bool) -> Result<()> {
+    let mut conn = establish_connection().await?;
+
+    query("UPDATE channels SET cache_articles = ? WHERE id = ?")
+        .bind(cache_articles)
+        .bind(id)
+        .execute(&mut conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Stores the text of an item's web page as its content.
+pub async fn set_item_content(id: &str, content: &str, reading_time: i64) -> Result<()> {
+    let mut conn = establish_connection().await?;
+
+    query("UPDATE items SET content = ?, reading_time = ? WHERE id = ?")
+        .bind(content)
+        .bind(reading_time)
+        .bind(id)
+        .execute(&mut conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn set_channel_fingerprint(id: &str, fingerprint: Option<&str>) -> Result<()> {
+    let mut conn = establish_connection().await?;
+
+    query("UPDATE channels SET tls_fingerprint = ? WHERE id = ?")
+        .bind(fingerprint)
+        .bind(id)
+        .execute(&mut conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Stores the outcome of the last update of each channel; `None` clears a previous error.
+pub async fn set_channel_errors(errors: Vec<(String, Option<String>)>, at: i64) -> Result<()> {
+    let mut conn = establish_connection().await?;
+
+    let mut tz = conn.begin().await?;
+
+    for (id, error) in errors {
+        query("UPDATE channels SET last_error = ?, last_error_at = ? WHERE id = ?")
+            .bind(&error)
+            .bind(error.as_ref().map(|_| at))
+            .bind(id)
+            .execute(&mut tz)
+            .await?;
+    }
+
+    tz.commit().await?;
+
+    Ok(())
+}
+
+pub async fn edit_channel(id: String, title: String) -> Result<()> {
+    let mut conn = establish_connection().await?;
+
+    // Items read the title of their channel when loaded, so nothing else needs updating.
+    query("UPDATE channels SET title = ? WHERE id = ?")
+        .bind(&title)
+        .bind(&id)
+        .execute(&mut conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Saves new items and returns the ones that weren't stored yet.
+pub async fn add_items(items: Vec<Item>) -> Result<Vec<Item>> {
+    let mut conn = establish_connection().await?;
+
+    let mut tz = conn.begin().await?;
+
+    let mut inserted: HashSet<String> = HashSet::new();
+
+    // Values bound per row, must match the column list below.
+    const BOUND_COLUMNS: usize = 18;
+    for chunk in items.chunks(SQLITE_MAX_VARIABLES / BOUND_COLUMNS) {
+        let mut builder = QueryBuilder::new("INSERT OR IGNORE INTO items (id, link, title, summary, content, published, dismissed, reading_time, first_seen, channel_title, channel, updated, thumbnail, duration, enclosure, comments, links, author) ");
+        builder.push_values(chunk, |mut row, item| {
+            row.push_bind(&item.id)
+                .push_bind(&item.link)
+                .push_bind(&item.title)
+                .push_bind(&item.summary)
+                .push_bind(&item.content)
+                .push_bind(item.published)
+                .push_bind(item.dismissed)
+                .push_bind(item.reading_time)
+                .push_bind(item.first_seen)
+                .push_bind(&item.channel_title)
+                .push_bind(&item.channel)
+                .push_bind(item.updated)
+                .push_bind(&item.thumbnail)
+                .push_bind(item.duration)
+                .push_bind(&item.enclosure)
+                .push_bind(&item.comments)
+                .push_bind(item.links.to_json())
+                .push_bind(&item.author);
+        });
+        builder.push(" RETURNING id");
+        let ids: Vec<(String,)> = builder.build_query_as().fetch_all(&mut tz).await?;
+        inserted.extend(ids.into_iter().map(|(id,)| id));
+
+        let mut builder = QueryBuilder::new(
+            "INSERT OR IGNORE INTO history (id, link, title, channel_title, published, first_seen) ",
+        );
+        builder.push_values(chunk, |mut row, item| {
+            row.push_bind(&item.id)
+                .push_bind(&item.link)
+                .push_bind(&item.title)
+                .push_bind(&item.channel_title)
+                .push_bind(item.published)
+                .push_bind(item.first_seen);
+        });
+        builder.build().execute(&mut tz).await?;
+    }
+
+    tz.commit().await?;
+
+    info!("Inserted {} of {} items.", inserted.len(), items.len());
+
+    // Removing the id as it's matched also drops repeated entries of the same feed.
+    Ok(items
+        .into_iter()
+        .filter(|item| inserted.remove(&item.id))
+        .collect())
+}
+
+/// Adds channels and items from another database, keeping what is stored already.
+/// Added channels are archived, so they hold their items without being refreshed.
+/// Items of channels that are known neither here nor in `channels` and blocked items
+/// are left out. Returns the amount of added channels and items.
+pub async fn merge_items(channels: &[Channel], items: &[Item]) -> Result<(u64, u64)> {
+    let mut conn = establish_connection().await?;
+
+    let mut tz = conn.begin().await?;
+
+    let mut added_channels = 0;
+    for channel in channels {
+        added_channels += query("INSERT OR IGNORE INTO channels (id, kind, link, title, description, color, archived) VALUES (?, ?, ?, ?, ?, ?, True)")
+            .bind(&channel.id)
+            .bind(&channel.kind)
+            .bind(&channel.link)
+            .bind(&channel.title)
+            .bind(&channel.description)
+            .bind(&channel.color)
+            .execute(&mut tz)
+            .await?
+            .rows_affected();
+    }
+
+    let known: HashSet<String> = query_scalar("SELECT id FROM channels")
+        .fetch_all(&mut tz)
+        .await?
+        .into_iter()
+        .collect();
+    let blocked: HashSet<String> = query_scalar("SELECT id FROM blocked_items")
+        .fetch_all(&mut tz)
+        .await?
+        .into_iter()
+        .collect();
+
+    let mut added_items = 0;
+    for item in items
+        .iter()
+        .filter(|item| known.contains(&item.channel) && !blocked.contains(&item.id))
+    {
+        added_items += query("INSERT OR IGNORE INTO items (id, link, title, summary, content, published, dismissed, starred, later, reading_time, first_seen, channel_title, channel, updated, changed, thumbnail, duration, enclosure, comments, links, author, pinned) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")
+            .bind(&item.id)
+            .bind(&item.link)
+            .bind(&item.title)
+            .bind(&item.summary)
+            .bind(&item.content)
+            .bind(item.published)
+            .bind(item.dismissed)
+            .bind(item.starred)
+            .bind(item.later)
+            .bind(item.reading_time)
+            .bind(item.first_seen)
+            .bind(&item.channel_title)
+            .bind(&item.channel)
+            .bind(item.updated)
+            .bind(item.changed)
+            .bind(&item.thumbnail)
+            .bind(item.duration)
+            .bind(&item.enclosure)
+            .bind(&item.comments)
+            .bind(item.links.to_json())
+            .bind(&item.author)
+            .bind(item.pinned)
+            .execute(&mut tz)
+            .await?
+            .rows_affected();
+        query("INSERT OR IGNORE INTO history (id, link, title, channel_title, published, first_seen) VALUES (?, ?, ?, ?, ?, ?)")
+            .bind(&item.id)
+            .bind(&item.link)
+            .bind(&item.title)
+            .bind(&item.channel_title)
+            .bind(item.published)
+            .bind(item.first_seen)
+            .execute(&mut tz)
+            .await?;
+    }
+
+    tz.commit().await?;
+
+    Ok((added_channels, added_items))
+}
+
+/// Saves entries of `channel` that the feed updated since they were stored, and flags the ones
+/// whose title, summary or content changed. Returns how many items were flagged.
+pub async fn update_items(channel: &str, items: &[Item]) -> Result<u64> {
+    let mut conn = establish_connection().await?;
+
+    let stored: HashMap<String, i64> =
+        query_as::<_, (String, i64)>("SELECT id, updated FROM items WHERE channel = ?")
+            .bind(channel)
+            .fetch_all(&mut conn)
+            .await?
+            .into_iter()
+            .collect();
+
+    let mut tz = conn.begin().await?;
+
+    let mut changed = 0;
+
+    for item in items {
+        let Some(&updated) = stored.get(&item.id) else {
+            continue;
+        };
+        if item.updated <= updated {
+            continue;
+        }
+        // Rows saved before updates were tracked only learn the timestamp, they aren't flagged.
+        let (flagged,) = query_as::<_, (bool,)>("UPDATE items SET changed = changed OR (updated > 0 AND (title IS NOT ? OR summary IS NOT ? OR content IS NOT ?)), title = ?, summary = ?, content = ?, link = ?, reading_time = ?, updated = ?, thumbnail = ?, duration = ?, enclosure = ?, comments = ?, links = ?, author = ? WHERE id = ? RETURNING changed")
+            .bind(&item.title)
+            .bind(&item.summary)
+            .bind(&item.content)
+            .bind(&item.title)
+            .bind(&item.summary)
+            .bind(&item.content)
+            .bind(&item.link)
+            .bind(item.reading_time)
+            .bind(item.updated)
+            .bind(&item.thumbnail)
+            .bind(item.duration)
+            .bind(&item.enclosure)
+            .bind(&item.comments)
+            .bind(item.links.to_json())
+            .bind(&item.author)
+            .bind(&item.id)
+            .fetch_one(&mut tz)
+            .await?;
+        if flagged {
+            changed += 1;
+        }
+    }
+
+    tz.commit().await?;
+
+    Ok(changed)
+}
+
+/// Remembers ids of items dropped by the blocklist and returns how many weren't seen before.
+/// Items stored before their keyword was added stay where they are and aren't counted.
+pub async fn add_blocked_items(ids: Vec<String>, blocked_at: i64) -> Result<u64> {
+    let mut conn = establish_connection().await?;
+
+    let mut tz = conn.begin().await?;
+
+    let mut inserted = 0;
+
+    for id in ids {
+        inserted += query("INSERT OR IGNORE INTO blocked_items (id, blocked_at) SELECT ?, ? WHERE NOT EXISTS (SELECT 1 FROM items WHERE id = ?)")
+            .bind(&id)
+            .bind(blocked_at)
+            .bind(&id)
+            .execute(&mut tz)
+            .await?
+            .rows_affected();
+    }
+
+    tz.commit().await?;
+
+    Ok(inserted)
+}
+
+pub async fn get_all_items() -> Result<Vec<Item>> {
+    let mut conn = establish_connection().await?;
+
+    let items = query_as::<_, Item>(
+        "SELECT items.id, items.link, items.title, summary, content, published, dismissed, starred, later, reading_time, first_seen, COALESCE(channels.title, channel_title) AS channel_title, channel, updated, changed, thumbnail, duration, enclosure, comments, links, author, pinned, COALESCE(channels.priority, False) AS priority FROM items LEFT JOIN channels ON channels.id = items.channel ORDER BY pinned DESC, (COALESCE(channels.priority, False) AND first_seen > ?) DESC, published DESC",
+    )
+    .bind(chrono::Utc::now().timestamp() - PRIORITY_PIN_SECONDS)
+    .fetch_all(&mut conn)
+    .await?;
+
+    Ok(items)
+}
+
+/// Ids of items whose title, author, summary or content has words starting with every word
+/// of `text`. `author:name` only looks at the authors.
+pub async fn search_items(text: &str) -> Result<Vec<String>> {
+    let text = text.to_lowercase();
+    let (column, text) = match text.strip_prefix("author:") {
+        Some(name) => ("author : ", name),
+        None => ("", text.as_str()),
+    };
+    // Every word is quoted, so nothing the user types is read as FTS5 query syntax.
+    let pattern = text
+        .split_whitespace()
+        .map(|word| format!("{}\"{}\"*", column, word.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ");
+    if pattern.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut conn = establish_connection().await?;
+
+    let ids = query_scalar(
+        "SELECT id FROM items WHERE rowid IN (SELECT rowid FROM items_fts WHERE items_fts MATCH ?)",
+    )
+    .bind(pattern)
+    .fetch_all(&mut conn)
+    .await?;
+
+    Ok(ids)
+}
+
+/// Newest history entries whose title or link contains `text`, and the amount of all matches.
+pub async fn search_history(text: &str) -> Result<(Vec<HistoryEntry>, i64)> {
+    let mut conn = establish_connection().await?;
+
+    let pattern = format!(
+        "%{}%",
+        text.replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")
+    );
+
+    let entries = query_as::<_, HistoryEntry>("SELECT link, title, channel_title, published, first_seen, opened FROM history WHERE title LIKE ?1 ESCAPE '\\' OR link LIKE ?1 ESCAPE '\\' ORDER BY first_seen DESC, published DESC LIMIT ?2")
+        .bind(&pattern)
+        .bind(HISTORY_LIMIT)
+        .fetch_all(&mut conn)
+        .await?;
+
+    let total = query_scalar(
+        "SELECT COUNT(*) FROM history WHERE title LIKE ?1 ESCAPE '\\' OR link LIKE ?1 ESCAPE '\\'",
+    )
+    .bind(&pattern)
+    .fetch_one(&mut conn)
+    .await?;
+
+    Ok((entries, total))
+}
+
+/// Local dates, as `YYYY-MM-DD`, with the amount of items published on them between the
+/// timestamps `from` and `to`.
+pub async fn count_items_by_day(from: i64, to: i64) -> Result<Vec<(String, i64)>> {
+    let mut conn = establish_connection().await?;
+
+    let days = query_as::<_, (String, i64)>(
+        "SELECT date(published, 'unixepoch', 'localtime') AS day, COUNT(*) FROM items WHERE published >= ? AND published < ? GROUP BY day",
+    )
+    .bind(from)
+    .bind(to)
+    .fetch_all(&mut conn)
+    .await?;
+
+    Ok(days)
+}
+
+pub async fn get_item(id: &str) -> Result<Option<Item>> {
+    let mut conn = establish_connection().await?;
+
+    let item = query_as::<_, Item>(
+        "SELECT items.id, items.link, items.title, summary, content, published, dismissed, starred, later, reading_time, first_seen, COALESCE(channels.title, channel_title) AS channel_title, channel, updated, changed, thumbnail, duration, enclosure, comments, links, author, pinned FROM items LEFT JOIN channels ON channels.id = items.channel WHERE items.id = ?",
+    )
+    .bind(id)
+    .fetch_optional(&mut conn)
+    .await?;
+
+    Ok(item)
+}
+
+pub async fn get_starred_items() -> Result<Vec<Item>> {
+    let mut conn = establish_connection().await?;
+
+    let items = query_as::<_, Item>(
+        "SELECT items.id, items.link, items.title, summary, content, published, dismissed, starred, later, reading_time, first_seen, COALESCE(channels.title, channel_title) AS channel_title, channel, updated, changed, thumbnail, duration, enclosure, comments, links, author, pinned FROM items LEFT JOIN channels ON channels.id = items.channel WHERE starred = True ORDER BY published DESC",
+    )
+    .fetch_all(&mut conn)
+    .await?;
+
+    Ok(items)
+}
+
+pub async fn set_dismissed(id: &str, dismissed: bool) -> Result<()> {
+    let mut conn = establish_connection().await?;
+
+    query("UPDATE items SET dismissed = ?, later = False, changed = False WHERE id = ?")
+        .bind(dismissed)
+        .bind(id)
+        .execute(&mut conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn record_open(id: &str, opened: i64) -> Result<()> {
+    let mut conn = establish_connection().await?;
+
+    query("UPDATE history SET opened = ? WHERE id = ?")
+        .bind(opened)
+        .bind(id)
+        .execute(&mut conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn set_dismissed_many(ids: &[String], dismissed: bool) -> Result<()> {
+    let mut conn = establish_connection().await?;
+
+    let mut tz = conn.begin().await?;
+
+    for chunk in ids.chunks(ID_BATCH_SIZE) {
+        let mut builder = QueryBuilder::new("UPDATE items SET dismissed = ");
+        builder.push_bind(dismissed);
+        builder.push(", later = False, changed = False WHERE id IN (");
+        push_ids(&mut builder, chunk);
+        builder.build().execute(&mut tz).await?;
+    }
+
+    tz.commit().await?;
+
+    Ok(())
+}
+
+/// Brings back items of a "Dismiss all" as new, `changed` are the ones that were marked
+/// as changed before.
+pub async fn restore_dismissed(ids: &[String], changed: &[String]) -> Result<()> {
+    let mut conn = establish_connection().await?;
+
+    let mut tz = conn.begin().await?;
+
+    for chunk in ids.chunks(ID_BATCH_SIZE) {
+        let mut builder = QueryBuilder::new("UPDATE items SET dismissed = False WHERE id IN (");
+        push_ids(&mut builder, chunk);
+        builder.build().execute(&mut tz).await?;
+    }
+    for chunk in changed.chunks(ID_BATCH_SIZE) {
+        let mut builder = QueryBuilder::new("UPDATE items SET changed = True WHERE id IN (");
+        push_ids(&mut builder, chunk);
+        builder.build().execute(&mut tz).await?;
+    }
+
+    tz.commit().await?;
+
+    Ok(())
+}
+
+pub async fn set_starred_many(ids: &[String], starred: bool) -> Result<()> {
+    let mut conn = establish_connection().await?;
+
+    let mut tz = conn.begin().await?;
+
+    for chunk in ids.chunks(ID_BATCH_SIZE) {
+        let mut builder = QueryBuilder::new("UPDATE items SET starred = ");
+        builder.push_bind(starred);
+        builder.push(" WHERE id IN (");
+        push_ids(&mut builder, chunk);
+        builder.build().execute(&mut tz).await?;
+    }
+
+    tz.commit().await?;
+
+    Ok(())
+}
+
+/// Binds `ids` as a comma separated list and closes the parenthesis opened before.
+fn push_ids<'a>(builder: &mut QueryBuilder<'a, sqlx::Sqlite>, ids: &'a [String]) {
+    let mut separated = builder.separated(", ");
+    for id in ids {
+        separated.push_bind(id);
+    }
+    separated.push_unseparated(")");
+}
+
+pub async fn set_starred(id: &str, starred: bool) -> Result<()> {
+    let mut conn = establish_connection().await?;
+
+    query("UPDATE items SET starred = ? WHERE id = ?")
+        .bind(starred)
+        .bind(id)
+        .execute(&mut conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn set_pinned(id: &str, pinned: bool) -> Result<()> {
+    let mut conn = establish_connection().await?;
+
+    query("UPDATE items SET pinned = ? WHERE id = ?")
+        .bind(pinned)
+        .bind(id)
+        .execute(&mut conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn set_later(id: &str, later: bool) -> Result<()> {
+    let mut conn = establish_connection().await?;
+
+    query("UPDATE items SET later = ? WHERE id = ?")
+        .bind(later)
+        .bind(id)
+        .execute(&mut conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn dismiss_all() -> Result<()> {
+    let mut conn = establish_connection().await?;
+
+    query("UPDATE items SET dismissed = True, changed = False WHERE later = False")
+        .execute(&mut conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Dismisses the given items, leaving out ones that were dismissed or moved between
+/// new and later meanwhile. Returns the ids of the dismissed items.
+pub async fn dismiss_matching(ids: &[String], later: bool) -> Result<Vec<String>> {
+    let mut conn = establish_connection().await?;
+
+    let mut tz = conn.begin().await?;
+
+    let mut dismissed = vec![];
+    for chunk in ids.chunks(ID_BATCH_SIZE) {
+        let mut builder = QueryBuilder::new(
+            "UPDATE items SET dismissed = True, changed = False WHERE dismissed = False AND later = ",
+        );
+        builder.push_bind(later);
+        builder.push(" AND id IN (");
+        push_ids(&mut builder, chunk);
+        builder.push(" RETURNING id");
+        let ids: Vec<(String,)> = builder.build_query_as().fetch_all(&mut tz).await?;
+        dismissed.extend(ids.into_iter().map(|(id,)| id));
+    }
+
+    tz.commit().await?;
+
+    Ok(dismissed)
+}
+
+pub async fn unsubscribe(id: &str) -> Result<()> {
+    let mut conn = establish_connection().await?;
+
+    query("DELETE FROM channels WHERE id = ?")
+        .bind(id)
+        .execute(&mut conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Amount of all items, of items still to read and of channels whose last update failed.
+pub async fn get_statistics() -> Result<(i64, i64, i64)> {
+    let mut conn = establish_connection().await?;
+
+    query_as("SELECT (SELECT COUNT(*) FROM items), (SELECT COUNT(*) FROM items WHERE dismissed = False AND later = False), (SELECT COUNT(*) FROM channels WHERE last_error IS NOT NULL AND archived = False)")
+        .fetch_one(&mut conn)
+        .await
+}
+
+/// Looks for rows pointing at channels that don't exist and deletes them.
+/// Returns the amount of deleted rows.
+pub async fn remove_orphans() -> Result<u64> {
+    let mut conn = establish_connection().await?;
+
+    // Rows are reported as (table, rowid, parent table, constraint index).
+    let violations = query_as::<_, (String, Option<i64>, String, i64)>("PRAGMA foreign_key_check")
+        .fetch_all(&mut conn)
+        .await?;
+
+    let mut tz = conn.begin().await?;
+
+    let mut removed = 0;
+
+    for (table, rowid, _, _) in violations {
+        if !matches!(table.as_str(), "items" | "channel_headers") {
+            continue;
+        }
+        let Some(rowid) = rowid else { continue };
+        removed += query(&format!("DELETE FROM {} WHERE rowid = ?", table))
+            .bind(rowid)
+            .execute(&mut tz)
+            .await?
+            .rows_affected();
+    }
+
+    tz.commit().await?;
+
+    Ok(removed)
+}
@@ -0,0 +1,178 @@
+use super::{config::RepublishedItems, db::Item, utils, ConfigBuilder};
+use chrono::{Local, TimeZone, Utc};
+use lettre::message::{header::ContentType, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+const SUMMARY_LENGTH: usize = 400;
+
+/// Renders items into a standalone HTML page suitable for sharing.
+pub fn render_html(title: &str, items: &[Item]) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", escape(title)));
+    html.push_str(
+        "<style>\
+body{max-width:42em;margin:2em auto;padding:0 1em;font-family:sans-serif;line-height:1.5;color:#222}\
+article{margin-bottom:2em}\
+h2{margin-bottom:.2em;font-size:1.2em}\
+.meta{color:#777;font-size:.9em}\
+</style>\n",
+    );
+    html.push_str("</head>\n<body>\n");
+    html.push_str(&format!("<h1>{}</h1>\n", escape(title)));
+    for item in items {
+        html.push_str("<article>\n");
+        html.push_str(&format!(
+            "<h2><a href=\"{}\">{}</a></h2>\n",
+            escape(&item.link),
+            escape(item.title.as_deref().unwrap_or("<no title>"))
+        ));
+        html.push_str(&format!(
+            "<div class=\"meta\">{} · {}</div>\n",
+            escape(item.channel_title.as_deref().unwrap_or("Unknown")),
+            format_date(item.published)
+        ));
+        if let Some(summary) = &item.summary {
+            html.push_str(&format!(
+                "<p>{}</p>\n",
+                escape(&summarize(summary, SUMMARY_LENGTH))
+            ));
+        }
+        html.push_str("</article>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Picks the items that make up the republished feed.
+pub fn republished_items(items: Vec<Item>, selection: RepublishedItems) -> Vec<Item> {
+    items
+        .into_iter()
+        .filter(|item| match selection {
+            RepublishedItems::Starred => item.starred,
+            RepublishedItems::New => !item.dismissed && !item.later,
+        })
+        .collect()
+}
+
+/// Renders items into an RSS 2.0 document so other readers can follow them.
+pub fn render_rss(title: &str, items: &[Item]) -> String {
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<rss version=\"2.0\">\n<channel>\n");
+    xml.push_str(&format!("<title>{}</title>\n", escape(title)));
+    xml.push_str("<link>https://github.com/dbsdm/tinyrss</link>\n");
+    xml.push_str(&format!(
+        "<description>{} republished by tinyrss</description>\n",
+        escape(title)
+    ));
+    for item in items {
+        xml.push_str("<item>\n");
+        xml.push_str(&format!(
+            "<title>{}</title>\n",
+            escape(item.title.as_deref().unwrap_or("<no title>"))
+        ));
+        xml.push_str(&format!("<link>{}</link>\n", escape(&item.link)));
+        xml.push_str(&format!(
+            "<guid isPermaLink=\"false\">{}</guid>\n",
+            escape(&item.id)
+        ));
+        if let Some(channel_title) = &item.channel_title {
+            xml.push_str(&format!("<category>{}</category>\n", escape(channel_title)));
+        }
+        if let Some(dt) = Utc.timestamp_opt(item.published, 0).earliest() {
+            xml.push_str(&format!("<pubDate>{}</pubDate>\n", dt.to_rfc2822()));
+        }
+        if let Some(summary) = &item.summary {
+            xml.push_str(&format!("<description>{}</description>\n", escape(summary)));
+        }
+        xml.push_str("</item>\n");
+    }
+    xml.push_str("</channel>\n</rss>\n");
+    xml
+}
+
+/// Renders items as a plain text list, used as the alternative part of digest emails.
+pub fn render_text(items: &[Item]) -> String {
+    let mut text = String::new();
+    for item in items {
+        text.push_str(&format!(
+            "{}\n{} · {}\n{}\n\n",
+            item.title.as_deref().unwrap_or("<no title>"),
+            item.channel_title.as_deref().unwrap_or("Unknown"),
+            format_date(item.published),
+            item.link
+        ));
+    }
+    text
+}
+
+pub async fn send_email(
+    config: &ConfigBuilder,
+    subject: &str,
+    items: &[Item],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let from: Mailbox = config.digest_from.parse()?;
+    let to: Mailbox = config.digest_to.parse()?;
+
+    let message = Message::builder()
+        .from(from)
+        .to(to)
+        .subject(subject)
+        .multipart(
+            MultiPart::alternative()
+                .singlepart(
+                    SinglePart::builder()
+                        .header(ContentType::TEXT_PLAIN)
+                        .body(render_text(items)),
+                )
+                .singlepart(
+                    SinglePart::builder()
+                        .header(ContentType::TEXT_HTML)
+                        .body(render_html(subject, items)),
+                ),
+        )?;
+
+    let mut transport = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.smtp_host)?
+        .port(config.smtp_port);
+    if !config.smtp_username.is_empty() {
+        transport = transport.credentials(Credentials::new(
+            config.smtp_username.clone(),
+            config.smtp_password.to_string(),
+        ));
+    }
+
+    transport.build().send(message).await?;
+
+    Ok(())
+}
+
+/// Collapses an HTML summary into plain text of at most `length` characters.
+pub fn summarize(summary: &str, length: usize) -> String {
+    let text = utils::strip_html(summary)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if text.chars().count() > length {
+        let mut truncated: String = text.chars().take(length).collect();
+        truncated.push('…');
+        truncated
+    } else {
+        text
+    }
+}
+
+pub fn format_date(timestamp: i64) -> String {
+    match Local.timestamp_opt(timestamp, 0).earliest() {
+        Some(dt) => dt.format("%Y-%m-%d").to_string(),
+        None => String::from("unknown date"),
+    }
+}
+
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,300 @@
+use super::config::DnsMode;
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use reqwest::Client;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::UdpSocket;
+
+const RECORD_A: u16 = 1;
+const RECORD_AAAA: u16 = 28;
+
+const QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Clone)]
+enum Upstream {
+    Https { url: String, client: Client },
+    Server(SocketAddr),
+}
+
+/// Resolver handed to reqwest when the user picked something other than the system resolver.
+pub struct CustomResolver {
+    upstream: Upstream,
+}
+
+impl CustomResolver {
+    /// Returns `None` for [`DnsMode::System`], or an error when the server setting can't be used.
+    pub fn from_config(mode: DnsMode, server: &str) -> Result<Option<Arc<Self>>, String> {
+        let server = server.trim();
+        let upstream = match mode {
+            DnsMode::System => return Ok(None),
+            DnsMode::Https => {
+                if !server.starts_with("https://") {
+                    return Err(format!(
+                        "DNS over HTTPS needs an https:// url, got {:?}",
+                        server
+                    ));
+                }
+                // The DoH host itself is resolved by the system, use an IP based url if that is blocked too.
+                let client = Client::builder()
+                    .timeout(QUERY_TIMEOUT)
+                    .build()
+                    .map_err(|err| err.to_string())?;
+                Upstream::Https {
+                    url: server.to_string(),
+                    client,
+                }
+            }
+            DnsMode::Server => {
+                let addr = server
+                    .parse::<SocketAddr>()
+                    .or_else(|_| server.parse::<IpAddr>().map(|ip| SocketAddr::new(ip, 53)))
+                    .map_err(|_| format!("Invalid DNS server address {:?}", server))?;
+                Upstream::Server(addr)
+            }
+        };
+        Ok(Some(Arc::new(Self { upstream })))
+    }
+}
+
+impl Resolve for CustomResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let upstream = self.upstream.clone();
+        Box::pin(async move {
+            // IPv4 addresses come first, hyper tries the addresses in order.
+            let (v4, v6) = futures::join!(
+                lookup(&upstream, name.as_str(), RECORD_A),
+                lookup(&upstream, name.as_str(), RECORD_AAAA)
+            );
+            let ips: Vec<IpAddr> = match (v4, v6) {
+                (Err(err), Err(_)) => return Err(err.into()),
+                (v4, v6) => v4
+                    .unwrap_or_default()
+                    .into_iter()
+                    .chain(v6.unwrap_or_default())
+                    .collect(),
+            };
+            if ips.is_empty() {
+                return Err(format!("No addresses found for {}", name.as_str()).into());
+            }
+            // Hyper fills in the port of the request url.
+            let addrs: Addrs = Box::new(ips.into_iter().map(|ip| SocketAddr::new(ip, 0)));
+            Ok(addrs)
+        })
+    }
+}
+
+async fn lookup(upstream: &Upstream, name: &str, record: u16) -> Result<Vec<IpAddr>, String> {
+    // A random id makes spoofed answers hard to get accepted.
+    let mut id = [0u8; 2];
+    getrandom::getrandom(&mut id).map_err(|err| err.to_string())?;
+    let id = u16::from_be_bytes(id);
+    let query = encode_query(id, name, record)?;
+    let response = match upstream {
+        Upstream::Https { url, client } => client
+            .post(url)
+            .header("Content-Type", "application/dns-message")
+            .header("Accept", "application/dns-message")
+            .body(query)
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status())
+            .map_err(|err| err.to_string())?
+            .bytes()
+            .await
+            .map_err(|err| err.to_string())?
+            .to_vec(),
+        Upstream::Server(addr) => {
+            let bind: SocketAddr = match addr {
+                SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+                SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+            };
+            let socket = UdpSocket::bind(bind).await.map_err(|err| err.to_string())?;
+            // Connected, the socket only receives datagrams sent from the server.
+            socket.connect(addr).await.map_err(|err| err.to_string())?;
+            socket.send(&query).await.map_err(|err| err.to_string())?;
+            let mut buf = vec![0u8; 1500];
+            let len = tokio::time::timeout(QUERY_TIMEOUT, socket.recv(&mut buf))
+                .await
+                .map_err(|_| format!("DNS server {} did not answer", addr))?
+                .map_err(|err| err.to_string())?;
+            buf.truncate(len);
+            buf
+        }
+    };
+    decode_response(id, &response)
+}
+
+fn encode_query(id: u16, name: &str, record: u16) -> Result<Vec<u8>, String> {
+    let mut query = Vec::with_capacity(name.len() + 18);
+    query.extend_from_slice(&id.to_be_bytes());
+    // Standard query with recursion desired, one question.
+    query.extend_from_slice(&[0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0]);
+    for label in name.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(format!("Invalid host name {:?}", name));
+        }
+        query.push(label.len() as u8);
+        query.extend_from_slice(label.as_bytes());
+    }
+    query.push(0);
+    query.extend_from_slice(&record.to_be_bytes());
+    query.extend_from_slice(&1u16.to_be_bytes());
+    Ok(query)
+}
+
+fn decode_response(id: u16, msg: &[u8]) -> Result<Vec<IpAddr>, String> {
+    let invalid = || "Invalid DNS response".to_string();
+    let u16_at = |pos: usize| -> Result<u16, String> {
+        msg.get(pos..pos + 2)
+            .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+            .ok_or_else(invalid)
+    };
+    if u16_at(0)? != id {
+        return Err(invalid());
+    }
+    let rcode = u16_at(2)? & 0x000f;
+    match rcode {
+        0 => {}
+        3 => return Ok(Vec::new()),
+        code => return Err(format!("DNS server returned error code {}", code)),
+    }
+    let questions = u16_at(4)?;
+    let answers = u16_at(6)?;
+    let mut pos = 12;
+    for _ in 0..questions {
+        pos = skip_name(msg, pos).ok_or_else(invalid)? + 4;
+    }
+    let mut ips = Vec::new();
+    for _ in 0..answers {
+        pos = skip_name(msg, pos).ok_or_else(invalid)?;
+        let record = u16_at(pos)?;
+        let len = u16_at(pos + 8)? as usize;
+        let data = msg.get(pos + 10..pos + 10 + len).ok_or_else(invalid)?;
+        match (record, len) {
+            (RECORD_A, 4) => ips.push(IpAddr::from([data[0], data[1], data[2], data[3]])),
+            (RECORD_AAAA, 16) => {
+                let mut octets = [0u8; 16];
+                octets.copy_from_slice(data);
+                ips.push(IpAddr::from(octets));
+            }
+            // CNAME and other records are skipped, the server already followed them.
+            _ => {}
+        }
+        pos += 10 + len;
+    }
+    Ok(ips)
+}
+
+/// Returns the position right after the (possibly compressed) name starting at `pos`.
+fn skip_name(msg: &[u8], mut pos: usize) -> Option<usize> {
+    loop {
+        let len = *msg.get(pos)? as usize;
+        if len == 0 {
+            return Some(pos + 1);
+        }
+        if len & 0xc0 == 0xc0 {
+            return Some(pos + 2);
+        }
+        pos += len + 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Header of an answer to `id` with the given response code and record counts.
+    fn header(id: u16, rcode: u8, questions: u16, answers: u16) -> Vec<u8> {
+        let mut msg = id.to_be_bytes().to_vec();
+        msg.extend_from_slice(&[0x81, 0x80 | rcode]);
+        msg.extend_from_slice(&questions.to_be_bytes());
+        msg.extend_from_slice(&answers.to_be_bytes());
+        msg.extend_from_slice(&[0, 0, 0, 0]);
+        msg
+    }
+
+    fn question(name: &str, record: u16) -> Vec<u8> {
+        encode_query(0, name, record).unwrap()[12..].to_vec()
+    }
+
+    /// An answer whose name points back at the question with compression.
+    fn answer(record: u16, data: &[u8]) -> Vec<u8> {
+        let mut msg = vec![0xc0, 12];
+        msg.extend_from_slice(&record.to_be_bytes());
+        msg.extend_from_slice(&1u16.to_be_bytes());
+        msg.extend_from_slice(&300u32.to_be_bytes());
+        msg.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        msg.extend_from_slice(data);
+        msg
+    }
+
+    #[test]
+    fn encodes_query() {
+        let query = encode_query(0x1234, "example.com.", RECORD_AAAA).unwrap();
+        assert_eq!(&query[..4], &[0x12, 0x34, 0x01, 0x00]);
+        assert_eq!(&query[4..12], &[0, 1, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(&query[12..25], b"\x07example\x03com\x00");
+        assert_eq!(&query[25..], &[0, 28, 0, 1]);
+    }
+
+    #[test]
+    fn rejects_invalid_names() {
+        assert!(encode_query(1, "a..b", RECORD_A).is_err());
+        assert!(encode_query(1, &"a".repeat(64), RECORD_A).is_err());
+    }
+
+    #[test]
+    fn decodes_a_and_aaaa_records() {
+        let mut msg = header(7, 0, 1, 2);
+        msg.extend(question("example.com", RECORD_A));
+        msg.extend(answer(RECORD_A, &[93, 184, 216, 34]));
+        let v6: Ipv6Addr = "2606:2800:220:1:248:1893:25c8:1946".parse().unwrap();
+        msg.extend(answer(RECORD_AAAA, &v6.octets()));
+        assert_eq!(
+            decode_response(7, &msg).unwrap(),
+            vec![IpAddr::from([93, 184, 216, 34]), IpAddr::V6(v6)]
+        );
+    }
+
+    #[test]
+    fn skips_cname_records() {
+        let mut msg = header(7, 0, 1, 2);
+        msg.extend(question("www.example.com", RECORD_A));
+        msg.extend(answer(5, b"\x07example\x03com\x00"));
+        msg.extend(answer(RECORD_A, &[10, 0, 0, 1]));
+        assert_eq!(
+            decode_response(7, &msg).unwrap(),
+            vec![IpAddr::from([10, 0, 0, 1])]
+        );
+    }
+
+    #[test]
+    fn unknown_names_have_no_addresses() {
+        let mut msg = header(7, 3, 1, 0);
+        msg.extend(question("nowhere.example", RECORD_A));
+        assert!(decode_response(7, &msg).unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_other_ids_and_errors() {
+        let mut msg = header(7, 0, 1, 0);
+        msg.extend(question("example.com", RECORD_A));
+        assert!(decode_response(8, &msg).is_err());
+
+        let mut msg = header(7, 2, 1, 0);
+        msg.extend(question("example.com", RECORD_A));
+        assert!(decode_response(7, &msg).is_err());
+    }
+
+    #[test]
+    fn rejects_truncated_answers() {
+        let mut msg = header(7, 0, 1, 1);
+        msg.extend(question("example.com", RECORD_A));
+        msg.extend(answer(RECORD_A, &[93, 184, 216, 34]));
+        msg.truncate(msg.len() - 2);
+        assert!(decode_response(7, &msg).is_err());
+        assert!(decode_response(7, &msg[..5]).is_err());
+    }
+}
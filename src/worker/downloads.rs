@@ -0,0 +1,276 @@
+use super::messages::{Download, DownloadState, ToApp};
+use super::CONFIG;
+use crossbeam_channel::Sender;
+use futures::future::{select, Either};
+use reqwest::header::RANGE;
+use reqwest::{Client, StatusCode, Url};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{watch, Semaphore};
+
+/// Downloads running at the same time, the rest wait in the queue.
+const PARALLEL_DOWNLOADS: usize = 2;
+
+/// Longest a download may take, the request timeout of the settings only applies to the
+/// pauses between chunks.
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Progress of a running download is sent at most this often.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    Run,
+    Pause,
+    Cancel,
+}
+
+/// Queue of enclosure downloads. Every download runs in its own task and is steered through
+/// a watch channel, so pausing or cancelling reaches it even while it waits for data.
+pub struct Downloads {
+    next_id: u64,
+    controls: HashMap<u64, watch::Sender<Control>>,
+    limiter: Arc<Semaphore>,
+}
+
+impl Default for Downloads {
+    fn default() -> Self {
+        Self {
+            next_id: 0,
+            controls: HashMap::new(),
+            limiter: Arc::new(Semaphore::new(PARALLEL_DOWNLOADS)),
+        }
+    }
+}
+
+impl Downloads {
+    /// Queues `url` to be saved into the download folder.
+    pub fn start(
+        &mut self,
+        client: Client,
+        url: &str,
+        title: &str,
+        sender: Sender<ToApp>,
+        egui_ctx: eframe::egui::Context,
+    ) -> Result<(), String> {
+        let path = target_path(url)?;
+        // Finished downloads have dropped their receiver.
+        self.controls.retain(|_, control| !control.is_closed());
+        self.next_id += 1;
+        let (control, receiver) = watch::channel(Control::Run);
+        self.controls.insert(self.next_id, control);
+
+        let download = Download {
+            id: self.next_id,
+            title: title.to_string(),
+            url: url.to_string(),
+            path,
+            received: 0,
+            total: None,
+            state: DownloadState::Queued,
+        };
+        let report = move |download: &Download| {
+            sender
+                .send(ToApp::DownloadProgress {
+                    download: download.clone(),
+                })
+                .unwrap();
+            egui_ctx.request_repaint();
+        };
+        report(&download);
+        tokio::spawn(run(
+            client,
+            download,
+            receiver,
+            self.limiter.clone(),
+            report,
+        ));
+        Ok(())
+    }
+
+    pub fn control(&self, id: u64, control: Control) {
+        if let Some(sender) = self.controls.get(&id) {
+            // Fails only when the download has finished already.
+            let _ = sender.send(control);
+        }
+    }
+}
+
+/// Free path in the download folder named after the last segment of `url`. Its partial file
+/// is created right away, so downloads started at the same time can't pick the same name.
+fn target_path(url: &str) -> Result<PathBuf, String> {
+    let folder = CONFIG
+        .lock()
+        .download_folder
+        .clone()
+        .or_else(dirs::download_dir)
+        .ok_or("No download folder, choose one in the settings")?;
+    std::fs::create_dir_all(&folder).map_err(|err| err.to_string())?;
+
+    let url = Url::parse(url).map_err(|err| err.to_string())?;
+    let name = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|name| !name.is_empty())
+        .unwrap_or("download")
+        .replace(['/', '\\', ':', '*', '?', '"', '<', '>', '|'], "_");
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{}", extension)),
+        _ => (name.as_str(), String::new()),
+    };
+
+    let mut path = folder.join(&name);
+    let mut number = 1;
+    loop {
+        if !path.exists() {
+            match std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(partial_path(&path))
+            {
+                Ok(_) => return Ok(path),
+                Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {}
+                Err(err) => return Err(err.to_string()),
+            }
+        }
+        path = folder.join(format!("{} ({}){}", stem, number, extension));
+        number += 1;
+    }
+}
+
+/// Data is written next to the target and only renamed once complete.
+fn partial_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    path.with_file_name(name)
+}
+
+async fn run(
+    client: Client,
+    mut download: Download,
+    mut control: watch::Receiver<Control>,
+    limiter: Arc<Semaphore>,
+    report: impl Fn(&Download),
+) {
+    let partial = partial_path(&download.path);
+    loop {
+        let current = *control.borrow();
+        match current {
+            Control::Cancel => {
+                let _ = tokio::fs::remove_file(&partial).await;
+                download.state = DownloadState::Cancelled;
+                report(&download);
+                return;
+            }
+            Control::Pause => {
+                download.state = DownloadState::Paused;
+                report(&download);
+                if control.changed().await.is_err() {
+                    return;
+                }
+                continue;
+            }
+            Control::Run => {}
+        }
+
+        download.state = DownloadState::Queued;
+        report(&download);
+        let permit = {
+            let acquire = std::pin::pin!(limiter.clone().acquire_owned());
+            let changed = std::pin::pin!(control.changed());
+            match select(acquire, changed).await {
+                Either::Left((Ok(permit), _)) => permit,
+                Either::Left((Err(_), _)) | Either::Right((Err(_), _)) => return,
+                // Paused or cancelled while waiting in the queue.
+                Either::Right((Ok(()), _)) => continue,
+            }
+        };
+
+        download.state = DownloadState::Running;
+        report(&download);
+        let result = transfer(&client, &mut download, &partial, &mut control, &report).await;
+        drop(permit);
+        match result {
+            Ok(true) => {
+                download.state = match tokio::fs::rename(&partial, &download.path).await {
+                    Ok(()) => DownloadState::Done,
+                    Err(err) => DownloadState::Failed(err.to_string()),
+                };
+                report(&download);
+                return;
+            }
+            Ok(false) => {}
+            Err(err) => {
+                let _ = tokio::fs::remove_file(&partial).await;
+                download.state = DownloadState::Failed(err);
+                report(&download);
+                return;
+            }
+        }
+    }
+}
+
+/// Writes the response into the partial file, continuing where a paused download stopped
+/// when the server supports ranges. Returns `false` when interrupted by a control change.
+async fn transfer(
+    client: &Client,
+    download: &mut Download,
+    partial: &Path,
+    control: &mut watch::Receiver<Control>,
+    report: &impl Fn(&Download),
+) -> Result<bool, String> {
+    let stall_timeout = match CONFIG.lock().request_timeout_seconds {
+        0 => DOWNLOAD_TIMEOUT,
+        seconds => Duration::from_secs(seconds),
+    };
+    let mut request = client.get(&download.url).timeout(DOWNLOAD_TIMEOUT);
+    if download.received > 0 {
+        request = request.header(RANGE, format!("bytes={}-", download.received));
+    }
+    let mut resp = request
+        .send()
+        .await
+        .and_then(|resp| resp.error_for_status())
+        .map_err(|err| err.to_string())?;
+    let append = resp.status() == StatusCode::PARTIAL_CONTENT;
+    if !append {
+        download.received = 0;
+    }
+    download.total = resp.content_length().map(|len| len + download.received);
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(partial)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    let mut last_report = Instant::now();
+    loop {
+        let chunk = {
+            let chunk = std::pin::pin!(tokio::time::timeout(stall_timeout, resp.chunk()));
+            let changed = std::pin::pin!(control.changed());
+            match select(chunk, changed).await {
+                Either::Left((Err(_), _)) => return Err("No data arrived for too long".into()),
+                Either::Left((Ok(chunk), _)) => chunk.map_err(|err| err.to_string())?,
+                Either::Right(_) => return Ok(false),
+            }
+        };
+        let Some(chunk) = chunk else {
+            file.flush().await.map_err(|err| err.to_string())?;
+            return Ok(true);
+        };
+        file.write_all(&chunk)
+            .await
+            .map_err(|err| err.to_string())?;
+        download.received += chunk.len() as u64;
+        if last_report.elapsed() >= PROGRESS_INTERVAL {
+            report(download);
+            last_report = Instant::now();
+        }
+    }
+}
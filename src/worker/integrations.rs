@@ -0,0 +1,331 @@
+use super::config::{ChatService, ConfigBuilder, PushService};
+use super::db::Item;
+use super::digest::escape;
+use super::utils;
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
+use reqwest::{Client, StatusCode, Url};
+use serde::Serialize;
+use serde_json::json;
+use std::time::Duration;
+use tracing::info;
+
+/// Deliveries are given up after this many failed attempts.
+const ATTEMPTS: u32 = 4;
+
+/// Wait before the first retry, doubled after every further failure.
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Notifications sent per refresh, more new items are summed up in a last one.
+const MAX_PUSHES: usize = 5;
+
+/// Items forwarded to a chat per refresh, the rest is skipped.
+const MAX_FORWARDS: usize = 20;
+
+/// Pause between forwarded items, chats limit how fast bots may post.
+const FORWARD_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Runs of the new item command per refresh, so a feed flooding new items can't start
+/// hundreds of processes.
+const MAX_COMMAND_RUNS: usize = 10;
+
+const COMMAND_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Serialize)]
+struct Payload<'a> {
+    items: Vec<PayloadItem<'a>>,
+}
+
+#[derive(Serialize)]
+struct PayloadItem<'a> {
+    id: &'a str,
+    link: &'a str,
+    title: Option<&'a str>,
+    summary: Option<&'a str>,
+    published: i64,
+    channel: &'a str,
+    channel_title: Option<&'a str>,
+    enclosure: Option<&'a str>,
+}
+
+/// Whether `item` passes a filter of comma separated keywords, looked up in its title,
+/// summary and channel title. An empty filter lets every item pass.
+pub fn matches(filter: &str, item: &Item) -> bool {
+    let keywords: Vec<String> = filter
+        .split(',')
+        .map(|keyword| keyword.trim().to_lowercase())
+        .filter(|keyword| !keyword.is_empty())
+        .collect();
+    if keywords.is_empty() {
+        return true;
+    }
+    [&item.title, &item.summary, &item.channel_title]
+        .into_iter()
+        .flatten()
+        .map(|text| text.to_lowercase())
+        .any(|text| keywords.iter().any(|keyword| text.contains(keyword)))
+}
+
+/// Posts `items` to a webhook as one JSON object with an `items` array.
+pub async fn post_webhook(client: &Client, url: &str, items: &[&Item]) -> Result<(), String> {
+    let payload = Payload {
+        items: items
+            .iter()
+            .map(|item| PayloadItem {
+                id: &item.id,
+                link: &item.link,
+                title: item.title.as_deref(),
+                summary: item.summary.as_deref(),
+                published: item.published,
+                channel: &item.channel,
+                channel_title: item.channel_title.as_deref(),
+                enclosure: item.enclosure.as_deref(),
+            })
+            .collect(),
+    };
+    let body = serde_json::to_vec(&payload).map_err(|err| err.to_string())?;
+    with_retries(|| {
+        client
+            .post(url)
+            .header(CONTENT_TYPE, "application/json")
+            .body(body.clone())
+    })
+    .await
+}
+
+/// Pushes a notification per item to ntfy or Gotify. Beyond [`MAX_PUSHES`] items a single
+/// notification tells how many more there are.
+pub async fn push_notifications(
+    client: &Client,
+    service: PushService,
+    url: &str,
+    token: &str,
+    items: &[&Item],
+) -> Result<(), String> {
+    for item in items.iter().take(MAX_PUSHES) {
+        let title = item.channel_title.as_deref().unwrap_or("tinyrss");
+        let message = item.title.as_deref().unwrap_or(&item.link);
+        push(
+            client,
+            service,
+            url,
+            token,
+            title,
+            message,
+            Some(&item.link),
+        )
+        .await?;
+    }
+    if items.len() > MAX_PUSHES {
+        let message = format!("And {} more new items", items.len() - MAX_PUSHES);
+        push(client, service, url, token, "tinyrss", &message, None).await?;
+    }
+    Ok(())
+}
+
+async fn push(
+    client: &Client,
+    service: PushService,
+    url: &str,
+    token: &str,
+    title: &str,
+    message: &str,
+    link: Option<&str>,
+) -> Result<(), String> {
+    let (endpoint, body) = match service {
+        PushService::Off => return Ok(()),
+        // JSON publishing keeps non-ASCII titles intact, unlike the header based one.
+        PushService::Ntfy => {
+            let mut topic_url = Url::parse(url).map_err(|err| err.to_string())?;
+            let topic = topic_url
+                .path_segments()
+                .and_then(|mut segments| segments.next_back())
+                .filter(|topic| !topic.is_empty())
+                .ok_or("The ntfy url has no topic, e.g. https://ntfy.sh/my-topic")?
+                .to_string();
+            // JSON is published to the server's root, which is below a path for servers
+            // behind a prefix, e.g. https://example.com/ntfy/my-topic.
+            if let Ok(mut segments) = topic_url.path_segments_mut() {
+                segments.pop().push("");
+            }
+            let mut body = json!({ "topic": topic, "title": title, "message": message });
+            if let Some(link) = link {
+                body["click"] = json!(link);
+            }
+            (topic_url, body)
+        }
+        PushService::Gotify => {
+            // Servers may live below a path, which joining must not replace.
+            let base = format!("{}/", url.trim_end_matches('/'));
+            let endpoint = Url::parse(&base)
+                .and_then(|url| url.join("message"))
+                .map_err(|err| err.to_string())?;
+            let mut body = json!({ "title": title, "message": message, "priority": 5 });
+            if let Some(link) = link {
+                body["extras"] = json!({ "client::notification": { "click": { "url": link } } });
+            }
+            (endpoint, body)
+        }
+    };
+    let body = body.to_string();
+    with_retries(|| {
+        let request = client
+            .post(endpoint.clone())
+            .header(CONTENT_TYPE, "application/json")
+            .body(body.clone());
+        match service {
+            PushService::Gotify => request.header("X-Gotify-Key", token),
+            _ if token.is_empty() => request,
+            _ => request.header(AUTHORIZATION, format!("Bearer {}", token)),
+        }
+    })
+    .await
+}
+
+/// Forwards items to every configured chat.
+pub async fn forward(
+    client: &Client,
+    config: &ConfigBuilder,
+    items: &[&Item],
+) -> Result<(), String> {
+    for service in ChatService::ALL {
+        if !config.chat_configured(service) {
+            continue;
+        }
+        for item in items.iter().take(MAX_FORWARDS) {
+            share(client, config, service, item).await?;
+            tokio::time::sleep(FORWARD_INTERVAL).await;
+        }
+    }
+    Ok(())
+}
+
+/// Posts the title and link of `item` to a Telegram chat or Matrix room.
+pub async fn share(
+    client: &Client,
+    config: &ConfigBuilder,
+    service: ChatService,
+    item: &Item,
+) -> Result<(), String> {
+    let title = item.title.as_deref().unwrap_or("<no title>");
+    let text = format!("{}\n{}", title, item.link);
+    match service {
+        ChatService::Telegram => {
+            let url = format!(
+                "https://api.telegram.org/bot{}/sendMessage",
+                config.telegram_bot_token.trim()
+            );
+            let body = json!({ "chat_id": config.telegram_chat_id.trim(), "text": text });
+            let body = body.to_string();
+            with_retries(|| {
+                client
+                    .post(&url)
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(body.clone())
+            })
+            .await
+        }
+        ChatService::Matrix => {
+            let base = format!("{}/", config.matrix_homeserver.trim().trim_end_matches('/'));
+            let mut url = Url::parse(&base).map_err(|err| err.to_string())?;
+            // The transaction id stays the same across retries, so the server drops repeats.
+            let transaction = utils::generate_token();
+            url.path_segments_mut()
+                .map_err(|_| "The homeserver url is not a web address")?
+                .pop_if_empty()
+                .extend([
+                    "_matrix",
+                    "client",
+                    "v3",
+                    "rooms",
+                    config.matrix_room_id.trim(),
+                    "send",
+                    "m.room.message",
+                    &transaction,
+                ]);
+            let body = json!({
+                "msgtype": "m.text",
+                "body": text,
+                "format": "org.matrix.custom.html",
+                "formatted_body": format!("<a href=\"{}\">{}</a>", escape(&item.link), escape(title)),
+            });
+            let body = body.to_string();
+            with_retries(|| {
+                client
+                    .put(url.clone())
+                    .bearer_auth(config.matrix_access_token.trim())
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(body.clone())
+            })
+            .await
+        }
+    }
+}
+
+/// Runs the new item command once per item, at most [`MAX_COMMAND_RUNS`] times.
+/// The `{url}` and `{title}` placeholders are filled in, and all item fields are passed as
+/// `TINYRSS_ITEM_*` environment variables.
+pub async fn run_command(template: &str, items: &[&Item]) -> Result<(), String> {
+    for (index, item) in items.iter().take(MAX_COMMAND_RUNS).enumerate() {
+        if index > 0 {
+            tokio::time::sleep(COMMAND_INTERVAL).await;
+        }
+        let title = item.title.as_deref().unwrap_or_default();
+        let args = utils::expand_command(template, &item.link, title)?;
+        let mut child = std::process::Command::new(&args[0])
+            .args(&args[1..])
+            .env("TINYRSS_ITEM_ID", &item.id)
+            .env("TINYRSS_ITEM_LINK", &item.link)
+            .env("TINYRSS_ITEM_TITLE", title)
+            .env(
+                "TINYRSS_ITEM_SUMMARY",
+                utils::strip_html(item.summary.as_deref().unwrap_or_default()).trim(),
+            )
+            .env("TINYRSS_ITEM_PUBLISHED", item.published.to_string())
+            .env("TINYRSS_ITEM_CHANNEL", &item.channel)
+            .env(
+                "TINYRSS_ITEM_CHANNEL_TITLE",
+                item.channel_title.as_deref().unwrap_or_default(),
+            )
+            .env(
+                "TINYRSS_ITEM_ENCLOSURE",
+                item.enclosure.as_deref().unwrap_or_default(),
+            )
+            .spawn()
+            .map_err(|err| format!("{}: {}", args[0], err))?;
+        std::thread::spawn(move || child.wait());
+    }
+    if items.len() > MAX_COMMAND_RUNS {
+        info!(
+            "Skipped the new item command for {} items.",
+            items.len() - MAX_COMMAND_RUNS
+        );
+    }
+    Ok(())
+}
+
+/// Sends the request built by `request` until it succeeds, backing off between attempts.
+/// Only failures that may pass are retried: network errors, server errors and rate limits.
+async fn with_retries(request: impl Fn() -> reqwest::RequestBuilder) -> Result<(), String> {
+    let mut delay = RETRY_DELAY;
+    let mut attempt = 1;
+    loop {
+        let error = match request().send().await {
+            Ok(resp) if resp.status().is_success() => return Ok(()),
+            Ok(resp) => {
+                let status = resp.status();
+                if !status.is_server_error() && status != StatusCode::TOO_MANY_REQUESTS {
+                    return Err(format!("Server answered {}", status));
+                }
+                format!("Server answered {}", status)
+            }
+            // Urls may hold tokens, as Telegram's does, and errors end up in the log.
+            Err(err) => err.without_url().to_string(),
+        };
+        if attempt == ATTEMPTS {
+            return Err(error);
+        }
+        tokio::time::sleep(delay).await;
+        delay *= 2;
+        attempt += 1;
+    }
+}
@@ -0,0 +1,463 @@
+use super::db::Item;
+use super::digest::format_date;
+use super::utils;
+use reqwest::Url;
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct FrontMatter<'a> {
+    title: &'a str,
+    source: &'a str,
+    date: String,
+    link: &'a str,
+    /// Items have no tags of their own, the channel is the closest thing.
+    tags: Vec<String>,
+}
+
+/// Renders an item into a Markdown note with YAML front matter.
+pub fn render_note(item: &Item) -> Result<String, String> {
+    let title = item.title.as_deref().unwrap_or("<no title>");
+    let source = item.channel_title.as_deref().unwrap_or("Unknown");
+    let front_matter = FrontMatter {
+        title,
+        source,
+        date: format_date(item.published),
+        link: &item.link,
+        tags: tag(source).into_iter().collect(),
+    };
+    let yaml = serde_yaml::to_string(&front_matter).map_err(|err| err.to_string())?;
+    let body = item
+        .content
+        .as_deref()
+        .or(item.summary.as_deref())
+        .map(|html| html_to_markdown(html, Url::parse(&item.link).ok()))
+        .unwrap_or_default();
+    Ok(format!("---\n{}---\n\n# {}\n\n{}", yaml, title, body))
+}
+
+/// Tag names may not contain spaces and most punctuation.
+fn tag(text: &str) -> Option<String> {
+    let tag = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-");
+    (!tag.is_empty()).then_some(tag)
+}
+
+/// Converts HTML into Markdown, keeping headings, emphasis, links, images, lists, quotes and
+/// code. Scripts and other non-content elements are dropped, unknown tags keep only their
+/// text. Relative links are resolved against `base`.
+pub fn html_to_markdown(html: &str, base: Option<Url>) -> String {
+    let mut writer = Writer {
+        base,
+        ..Default::default()
+    };
+    let mut rest = html;
+    while let Some(open) = rest.find('<') {
+        writer.text(&rest[..open]);
+        rest = &rest[open..];
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some(close) = rest.find('>') else {
+            rest = "";
+            break;
+        };
+        let tag = &rest[1..close];
+        rest = &rest[close + 1..];
+        let closing = tag.starts_with('/');
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        if !closing && utils::NON_CONTENT_TAGS.contains(&name.as_str()) {
+            let end = format!("</{}", name);
+            rest = match rest.to_ascii_lowercase().find(&end) {
+                Some(position) => rest[position..]
+                    .find('>')
+                    .map_or("", |close| &rest[position + close + 1..]),
+                None => "",
+            };
+            continue;
+        }
+        writer.tag(&name, tag, closing);
+    }
+    writer.text(rest);
+    writer.finish()
+}
+
+#[derive(Default)]
+struct Writer {
+    out: String,
+    base: Option<Url>,
+    /// Nesting of quotes, every level prefixes lines with `> `.
+    quotes: usize,
+    /// Open lists, with the next number for ordered ones.
+    lists: Vec<Option<usize>>,
+    /// Targets of open anchors, `None` for anchors that link nowhere.
+    links: Vec<Option<String>>,
+    pre: bool,
+    /// Whitespace was seen and is written before the next text.
+    space: bool,
+    /// An emphasis or link was just opened, whitespace after it is dropped.
+    opened: bool,
+}
+
+impl Writer {
+    fn at_line_start(&self) -> bool {
+        self.out.is_empty() || self.out.ends_with('\n')
+    }
+
+    fn push(&mut self, text: &str) {
+        if self.at_line_start() {
+            self.out.push_str(&"> ".repeat(self.quotes));
+        } else if self.space && !self.opened && !self.out.ends_with(' ') {
+            self.out.push(' ');
+        }
+        self.out.push_str(text);
+        self.space = false;
+        self.opened = false;
+    }
+
+    /// Closes an emphasis or link right after its text, whitespace goes after the marker.
+    fn push_closing(&mut self, marker: &str) {
+        if self.at_line_start() {
+            self.push(marker);
+        } else {
+            self.out.push_str(marker);
+            self.opened = false;
+        }
+    }
+
+    fn push_opening(&mut self, marker: &str) {
+        self.push(marker);
+        self.opened = true;
+    }
+
+    /// Whether the current line holds more than quote and list markers.
+    fn line_has_text(&self) -> bool {
+        let line = self.out.rsplit('\n').next().unwrap_or_default();
+        !line
+            .trim_matches(|c: char| matches!(c, '>' | ' ' | '-' | '.') || c.is_ascii_digit())
+            .is_empty()
+    }
+
+    fn end_line(&mut self) {
+        if !self.at_line_start() && self.line_has_text() {
+            let trimmed = self.out.trim_end_matches(' ').len();
+            self.out.truncate(trimmed);
+            self.out.push('\n');
+        }
+        self.space = false;
+    }
+
+    fn end_block(&mut self) {
+        if self.out.is_empty() {
+            return;
+        }
+        self.end_line();
+        // Paragraphs in list items keep the list tight.
+        if !self.lists.is_empty() {
+            return;
+        }
+        let last_line = self.out[..self.out.len() - 1]
+            .rsplit('\n')
+            .next()
+            .unwrap_or_default();
+        if !last_line.chars().all(|c| c == '>' || c == ' ') {
+            self.out.push_str("> ".repeat(self.quotes).trim_end());
+            self.out.push('\n');
+        }
+    }
+
+    fn text(&mut self, raw: &str) {
+        if raw.is_empty() {
+            return;
+        }
+        let text = utils::decode_entities(raw);
+        if self.pre {
+            for (index, line) in text.split('\n').enumerate() {
+                if index > 0 {
+                    self.out.push('\n');
+                }
+                if !line.is_empty() {
+                    self.push(line);
+                }
+            }
+            return;
+        }
+        if text.starts_with(char::is_whitespace) {
+            self.space = true;
+        }
+        for (index, word) in text.split_whitespace().enumerate() {
+            if index > 0 {
+                self.space = true;
+            }
+            self.push(&escape(word));
+        }
+        if text.ends_with(char::is_whitespace) {
+            self.space = true;
+        }
+    }
+
+    fn tag(&mut self, name: &str, tag: &str, closing: bool) {
+        match name {
+            "p" | "div" | "section" | "article" | "figure" | "table" => self.end_block(),
+            "tr" | "figcaption" => self.end_line(),
+            "td" | "th" => self.space = true,
+            "br" => self.end_line(),
+            "hr" if !closing => {
+                self.end_block();
+                self.push("---");
+                self.out.push('\n');
+                self.end_block();
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.end_block();
+                if !closing {
+                    let level = name[1..].parse().unwrap_or(1);
+                    self.push(&format!("{} ", "#".repeat(level)));
+                }
+            }
+            "blockquote" if closing => {
+                self.end_line();
+                // An empty line left inside would continue the quote.
+                let trimmed = self.out.trim_end_matches(['>', ' ', '\n']).len();
+                self.out.truncate(trimmed);
+                self.end_line();
+                self.quotes = self.quotes.saturating_sub(1);
+                self.end_block();
+            }
+            "blockquote" => {
+                self.end_block();
+                self.quotes += 1;
+            }
+            "ul" | "ol" if closing => {
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.end_block();
+                } else {
+                    self.end_line();
+                }
+            }
+            "ul" | "ol" => {
+                if self.lists.is_empty() {
+                    self.end_block();
+                } else {
+                    self.end_line();
+                }
+                self.lists.push((name == "ol").then(|| {
+                    attribute(tag, "start")
+                        .and_then(|start| start.parse().ok())
+                        .unwrap_or(1)
+                }));
+            }
+            "li" if !closing => {
+                self.end_line();
+                let indent = "   ".repeat(self.lists.len().saturating_sub(1));
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ => "- ".to_string(),
+                };
+                self.push(&format!("{}{}", indent, marker));
+            }
+            "pre" if closing => {
+                self.end_line();
+                self.pre = false;
+                self.push("```");
+                self.end_block();
+            }
+            "pre" => {
+                self.end_block();
+                self.push("```");
+                self.end_line();
+                self.pre = true;
+            }
+            "code" if !self.pre => {
+                if closing {
+                    self.push_closing("`");
+                } else {
+                    self.push_opening("`");
+                }
+            }
+            "strong" | "b" | "em" | "i" => {
+                let marker = if name == "strong" || name == "b" {
+                    "**"
+                } else {
+                    "*"
+                };
+                if closing {
+                    self.push_closing(marker);
+                } else {
+                    self.push_opening(marker);
+                }
+            }
+            "a" if closing => {
+                if let Some(Some(href)) = self.links.pop() {
+                    self.push_closing(&format!("]({})", href));
+                }
+            }
+            "a" => {
+                let href = attribute(tag, "href").and_then(|href| self.resolve(&href));
+                if href.is_some() {
+                    self.push_opening("[");
+                }
+                self.links.push(href);
+            }
+            "img" => {
+                if let Some(src) = attribute(tag, "src").and_then(|src| self.resolve(&src)) {
+                    let alt = attribute(tag, "alt").unwrap_or_default();
+                    self.push(&format!("![{}]({})", escape(&alt), src));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn resolve(&self, link: &str) -> Option<String> {
+        let link = utils::decode_entities(link.trim());
+        if link.is_empty() || link.starts_with('#') {
+            return None;
+        }
+        let url = match &self.base {
+            Some(base) => base.join(&link).ok()?,
+            None => Url::parse(&link).ok()?,
+        };
+        // The parsed scheme is lowercase and free of whitespace, so `JavaScript:` or
+        // `java\tscript:` don't slip through.
+        if !matches!(url.scheme(), "http" | "https" | "mailto") {
+            return None;
+        }
+        // Spaces and parentheses would end the link early.
+        Some(
+            url.as_str()
+                .replace(' ', "%20")
+                .replace('(', "%28")
+                .replace(')', "%29"),
+        )
+    }
+
+    fn finish(mut self) -> String {
+        self.end_line();
+        let mut out = self.out.trim().to_string();
+        out.push('\n');
+        out
+    }
+}
+
+/// Value of the attribute `name` in the text of an opening tag.
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let pattern = format!("{}=", name);
+    let mut position = 0;
+    while let Some(offset) = lower[position..].find(&pattern) {
+        let start = position + offset;
+        position = start + pattern.len();
+        // Skip matches inside other names, like `data-src` when looking for `src`.
+        if !lower[..start].ends_with(char::is_whitespace) {
+            continue;
+        }
+        let value = &tag[position..];
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => value[1..].split(quote).next(),
+            // Unquoted values may run into the `/` of a self-closing tag.
+            _ => value
+                .split(char::is_whitespace)
+                .next()
+                .map(|value| value.trim_end_matches('/')),
+        };
+        return value.map(str::to_string);
+    }
+    None
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(html: &str) -> String {
+        html_to_markdown(html, Url::parse("https://example.com/posts/1").ok())
+    }
+
+    #[test]
+    fn converts_blocks_and_emphasis() {
+        assert_eq!(
+            convert("<h2>Title</h2><p>Some <b>bold</b> and <em>italic</em> text.</p><p>Next</p>"),
+            "## Title\n\nSome **bold** and *italic* text.\n\nNext\n"
+        );
+    }
+
+    #[test]
+    fn resolves_links_and_images() {
+        assert_eq!(
+            convert(r#"<a href="/about">About</a> <img src="pic.png" alt="A pic">"#),
+            "[About](https://example.com/about) ![A pic](https://example.com/posts/pic.png)\n"
+        );
+    }
+
+    #[test]
+    fn drops_script_links_in_any_case() {
+        for href in [
+            "javascript:alert(1)",
+            "JavaScript:alert(1)",
+            " JAVASCRIPT:alert(1)",
+            "java\tscript:alert(1)",
+            "data:text/html;base64,PHNjcmlwdD5hbGVydCgxKTwvc2NyaXB0Pg==",
+            "vbscript:msgbox(1)",
+        ] {
+            let html = format!(r#"<a href="{}">Click</a>"#, href);
+            assert_eq!(convert(&html), "Click\n", "{}", href);
+        }
+    }
+
+    #[test]
+    fn keeps_mail_links() {
+        assert_eq!(
+            convert(r#"<a href="mailto:me@example.com">Mail</a>"#),
+            "[Mail](mailto:me@example.com)\n"
+        );
+    }
+
+    #[test]
+    fn numbers_nested_lists() {
+        assert_eq!(
+            convert("<ol start=\"3\"><li>One<ul><li>Inner</li></ul></li><li>Two</li></ol>"),
+            "3. One\n   - Inner\n4. Two\n"
+        );
+    }
+
+    #[test]
+    fn quotes_and_code() {
+        assert_eq!(
+            convert("<blockquote><p>Quoted</p></blockquote><pre>let a = 1;\nlet b = 2;</pre>"),
+            "> Quoted\n\n```\nlet a = 1;\nlet b = 2;\n```\n"
+        );
+    }
+
+    #[test]
+    fn drops_non_content_and_escapes_markup() {
+        assert_eq!(
+            convert("<script>var a = '<b>';</script><p>*not* [a] link_</p><!-- hidden -->"),
+            "\\*not\\* \\[a\\] link\\_\n"
+        );
+    }
+}
@@ -1,38 +1,482 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 
+use crate::worker::config::ChatService;
 use crate::worker::db;
+use crate::worker::quirks::Quirk;
 
 pub enum ToApp {
-    WorkerError { error: WorkerError },
-    UpdateFeed { items: Vec<db::Item> },
-    FeedUpdateProgress { progress: f32 },
-    ImportProgress { progress: f32 },
-    UpdateChannels { channels: Vec<db::Channel> },
+    WorkerError {
+        error: WorkerError,
+    },
+    UpdateFeed {
+        items: Vec<db::Item>,
+        /// Item ids with their score for [`FeedOrder::Hot`](crate::worker::FeedOrder::Hot).
+        scores: HashMap<String, f32>,
+    },
+    /// Items saved for one channel while a refresh is still running.
+    NewItems {
+        items: Vec<db::Item>,
+    },
+    /// State of every channel in the running refresh, sent whenever one of them changes.
+    FeedUpdateProgress {
+        channels: Vec<ChannelProgress>,
+    },
+    ImportProgress {
+        progress: f32,
+    },
+    UpdateChannels {
+        channels: Vec<db::Channel>,
+        headers: Vec<db::ChannelHeader>,
+    },
+    RefreshSummary {
+        summary: RefreshSummary,
+    },
+    SearchResults {
+        generation: u64,
+        ids: Vec<String>,
+    },
+    /// History entries matching `query`, `total` counts the ones left out too.
+    History {
+        query: String,
+        entries: Vec<db::HistoryEntry>,
+        total: i64,
+    },
+    /// Amount of items published on each day of a month, days without any are left out.
+    Calendar {
+        year: i32,
+        month: u32,
+        counts: HashMap<u32, i64>,
+    },
+    /// Sent whenever the feed is reloaded.
+    Statistics {
+        statistics: Statistics,
+    },
+    /// Domains with stored cookies and the number of cookies for each.
+    UpdateCookies {
+        domains: Vec<(String, usize)>,
+    },
+    AddChannelResult {
+        request_id: RequestId,
+        outcome: AddChannelOutcome,
+    },
+    /// State of a download, sent when it changes and periodically while data arrives.
+    DownloadProgress {
+        download: Download,
+    },
+    /// Amount of items "Dismiss all" put in the trash, `None` once they can't be restored.
+    Trash {
+        amount: Option<usize>,
+    },
+    /// Latest subscription events, sent along with the channel list.
+    SubscriptionEvents {
+        events: Vec<db::Event>,
+    },
+    /// Email address of a new newsletter inbox, already subscribed to, or why it failed.
+    NewsletterCreated {
+        result: Result<String, String>,
+    },
+    /// Groups of channel ids that serve the same feed, the suggested one to keep first.
+    DuplicateChannels {
+        groups: Vec<Vec<String>>,
+    },
+    /// Decoded thumbnail for `url`, `None` if it couldn't be loaded.
+    Thumbnail {
+        url: String,
+        image: Option<eframe::egui::ColorImage>,
+    },
 }
 
+/// Set by the app on requests it waits for, and sent back with their result.
+pub type RequestId = u64;
+
 pub enum ToWorker {
     Startup,
     Shutdown,
     UpdateFeed,
-    AddChannel { link: String },
-    EditChannel { id: String, title: String },
-    SetDismissed { id: String, dismissed: bool },
+    /// Answered with [`ToApp::AddChannelResult`] when a `request_id` is given.
+    AddChannel {
+        link: String,
+        request_id: Option<RequestId>,
+    },
+    EditChannel {
+        id: String,
+        title: String,
+    },
+    SetChannelColor {
+        id: String,
+        color: Option<String>,
+    },
+    SetChannelNotes {
+        id: String,
+        notes: Option<String>,
+    },
+    /// `None` uses the global default limit.
+    SetChannelItemLimit {
+        id: String,
+        limit: Option<i64>,
+    },
+    /// `None` detects the quirks from the channel's link again.
+    SetChannelQuirks {
+        id: String,
+        quirks: Option<Vec<Quirk>>,
+    },
+    /// `None` moves the channel out of its folder.
+    SetChannelFolder {
+        id: String,
+        folder: Option<String>,
+    },
+    /// Puts channels that aren't in a folder yet into one per site, for sites with more
+    /// than one channel.
+    GroupChannelsBySite,
+    /// Answered with [`ToApp::DuplicateChannels`].
+    FindDuplicateChannels,
+    /// Creates an inbox named `title` on the newsletter server and subscribes to its feed.
+    /// Answered with [`ToApp::NewsletterCreated`].
+    CreateNewsletter {
+        title: String,
+    },
+    /// Moves the items of the `merged` channels to `keep` and unsubscribes from them.
+    MergeChannels {
+        keep: String,
+        merged: Vec<String>,
+    },
+    SetChannelClickAction {
+        id: String,
+        action: db::ClickAction,
+        command: Option<String>,
+    },
+    SetChannelPriority {
+        id: String,
+        priority: bool,
+    },
+    SetChannelCaching {
+        id: String,
+        cache_articles: bool,
+    },
+    SetChannelHeaders {
+        id: String,
+        headers: Vec<(String, String)>,
+    },
+    SetDismissed {
+        id: String,
+        dismissed: bool,
+    },
+    SetStarred {
+        id: String,
+        starred: bool,
+    },
+    SetPinned {
+        id: String,
+        pinned: bool,
+    },
+    SetLater {
+        id: String,
+        later: bool,
+    },
+    /// Bulk variants of the above for items selected in the feed.
+    SetDismissedMany {
+        ids: Vec<String>,
+        dismissed: bool,
+    },
+    SetStarredMany {
+        ids: Vec<String>,
+        starred: bool,
+    },
     DismissAll,
-    Unsubscribe { id: String },
-    ImportChannels { path: Option<PathBuf> },
+    /// Restores the items of the last "Dismiss all" while they are in the trash.
+    UndoDismissAll,
+    /// Dismisses the items matching the search in the feed, `later` tells which list they
+    /// were shown in.
+    DismissMatching {
+        ids: Vec<String>,
+        later: bool,
+    },
+    Unsubscribe {
+        id: String,
+    },
+    ImportChannels {
+        path: Option<PathBuf>,
+    },
+    /// Imports the OPML list at `url`, with `keep_synced` also subscribing to channels
+    /// added to it later.
+    ImportChannelsFromUrl {
+        url: String,
+        keep_synced: bool,
+    },
+    /// Subscribes to the channels of an OPML document and refreshes them.
+    ImportOpml {
+        xml: String,
+    },
     ExportChannels,
+    ExportStarred,
+    ExportArchive,
+    SaveDatabaseCopy,
+    ExportJson,
+    /// Merges channels and items exported as JSON, from another machine or an older install.
+    ImportJson {
+        path: PathBuf,
+    },
+    ImportArchive {
+        path: PathBuf,
+    },
+    SendDigest,
+    Republish,
+    /// Saves the item's article as a PDF or printable HTML file, picked in a dialog.
+    SaveItem {
+        id: String,
+    },
+    /// Posts the item's title and link to a chat.
+    ShareItem {
+        id: String,
+        service: ChatService,
+    },
+    /// Converts the item's article to Markdown and writes it to the notes folder.
+    SaveNote {
+        id: String,
+    },
+    /// Opens the item's link in the browser, recording it in the history and dismissing
+    /// the item when `auto_dismiss_on_open` is set.
+    OpenItem {
+        id: String,
+    },
+    /// [`ToWorker::OpenItem`] for items selected in the feed, which is reloaded once.
+    OpenItems {
+        ids: Vec<String>,
+    },
+    /// Opens the item's article in the browser with its print dialog.
+    PrintItem {
+        id: String,
+    },
+    SendTo {
+        command: String,
+        url: String,
+        title: String,
+    },
+    WebdavSync,
+    SearchItems {
+        query: String,
+        generation: u64,
+    },
+    /// Answered with [`ToApp::History`].
+    SearchHistory {
+        query: String,
+    },
+    /// Answered with [`ToApp::Calendar`], `month` counts from 1.
+    LoadCalendar {
+        year: i32,
+        month: u32,
+    },
+    RefreshChannel {
+        id: String,
+    },
+    /// Trusts the certificate with this fingerprint for one channel, `None` forgets it.
+    SetTrustedCertificate {
+        id: String,
+        fingerprint: Option<String>,
+    },
+    SwitchProfile {
+        profile: Option<String>,
+    },
+    /// Imports a Netscape `cookies.txt` file, only for `domain` and its subdomains if not empty.
+    ImportCookies {
+        path: PathBuf,
+        domain: String,
+    },
+    ClearCookies {
+        domain: String,
+    },
+    LoadThumbnail {
+        url: String,
+    },
+    /// Queues `url` to be saved into the download folder.
+    Download {
+        url: String,
+        title: String,
+    },
+    PauseDownload {
+        id: u64,
+    },
+    ResumeDownload {
+        id: u64,
+    },
+    CancelDownload {
+        id: u64,
+    },
+}
+
+/// What came of subscribing to a single link.
+#[derive(Clone, PartialEq, Eq)]
+pub enum AddChannelOutcome {
+    Added {
+        title: Option<String>,
+    },
+    /// An archived channel with the link was brought back.
+    Restored,
+    AlreadySubscribed,
+    Failed(String),
+}
+
+#[derive(Clone)]
+pub enum FetchState {
+    Queued,
+    Fetching,
+    Parsed,
+    Failed(String),
+}
+
+impl FetchState {
+    pub fn is_done(&self) -> bool {
+        matches!(self, Self::Parsed | Self::Failed(_))
+    }
+}
+
+#[derive(Clone)]
+pub struct ChannelProgress {
+    pub title: String,
+    pub state: FetchState,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub enum DownloadState {
+    Queued,
+    Running,
+    Paused,
+    Done,
+    Failed(String),
+    Cancelled,
+}
+
+impl DownloadState {
+    pub fn is_done(&self) -> bool {
+        matches!(self, Self::Done | Self::Failed(_) | Self::Cancelled)
+    }
+}
+
+#[derive(Clone)]
+pub struct Download {
+    pub id: u64,
+    pub title: String,
+    pub url: String,
+    /// Where the file ends up once complete.
+    pub path: PathBuf,
+    pub received: u64,
+    /// Full size, if the server sent it.
+    pub total: Option<u64>,
+    pub state: DownloadState,
+}
+
+/// Numbers shown in the status bar.
+#[derive(Clone, Default)]
+pub struct Statistics {
+    /// When the last refresh finished, 0 if there was none since startup.
+    pub refreshed_at: i64,
+    pub items: i64,
+    /// Items that are neither dismissed nor saved for later.
+    pub new: i64,
+    pub failing_channels: i64,
+}
+
+/// Rows actually inserted by a refresh, in total and per channel title.
+pub struct RefreshSummary {
+    pub total: u64,
+    pub channels: Vec<(String, u64)>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Transient problems, e.g. being offline. Their banners disappear on their own.
+    Warning,
+    Error,
+}
+
+/// What failed, with enough detail to group errors by their source and offer a matching retry.
+#[derive(Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// No internet connection at all.
+    Offline,
+    /// A request failed without a response or with an error status.
+    Network {
+        channel_id: Option<String>,
+        status: Option<u16>,
+    },
+    /// The server's certificate failed validation or no longer matches the trusted one.
+    Certificate {
+        channel_id: String,
+        fingerprint: String,
+    },
+    /// A response or file could not be parsed as a feed or OPML.
+    Parse {
+        channel_id: Option<String>,
+    },
+    /// The link or the feed behind it is subscribed to already.
+    AlreadySubscribed,
+    Database,
+    Filesystem,
+    Sync,
+    Digest,
+    System,
+}
+
+impl ErrorKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ErrorKind::Offline => "Offline",
+            ErrorKind::Network { .. } => "Network",
+            ErrorKind::Certificate { .. } => "Certificate",
+            ErrorKind::Parse { .. } => "Parse",
+            ErrorKind::AlreadySubscribed => "Subscription",
+            ErrorKind::Database => "Database",
+            ErrorKind::Filesystem => "Files",
+            ErrorKind::Sync => "Sync",
+            ErrorKind::Digest => "Digest",
+            ErrorKind::System => "System",
+        }
+    }
+
+    /// The channel the error belongs to, if it came from refreshing one.
+    pub fn channel_id(&self) -> Option<&str> {
+        match self {
+            ErrorKind::Network { channel_id, .. } | ErrorKind::Parse { channel_id } => {
+                channel_id.as_deref()
+            }
+            ErrorKind::Certificate { channel_id, .. } => Some(channel_id),
+            _ => None,
+        }
+    }
 }
 
 pub struct WorkerError {
     pub description: String,
     pub error_message: String,
+    pub severity: Severity,
+    pub kind: ErrorKind,
+    /// Message that repeats the failed operation when sent to the worker.
+    pub retry: Option<ToWorker>,
 }
 
 impl WorkerError {
-    pub fn new(description: impl Into<String>, error_message: impl Into<String>) -> Self {
+    pub fn new(
+        kind: ErrorKind,
+        description: impl Into<String>,
+        error_message: impl Into<String>,
+    ) -> Self {
         Self {
             description: description.into(),
             error_message: error_message.into(),
+            severity: Severity::Error,
+            kind,
+            retry: None,
         }
     }
+
+    pub fn warning(mut self) -> Self {
+        self.severity = Severity::Warning;
+        self
+    }
+
+    pub fn with_retry(mut self, retry: ToWorker) -> Self {
+        self.retry = Some(retry);
+        self
+    }
 }
@@ -1,42 +1,187 @@
 use bytes::Bytes;
-pub use config::{ConfigBuilder, CONFIG};
+use chrono::Datelike;
+pub use cli::run as run_cli;
+pub use config::{
+    ChatService, ConfigBuilder, DnsMode, FeedOrder, KeyAction, KeyBinding, MeteredMode,
+    OpmlSubscription, PushService, ReaderFont, RepublishedItems, SendToCommand, ShareFormat,
+    ShareTarget, TimestampFormat, UnsubscribeMode, Webhook, CONFIG,
+};
+pub use crash::{install_panic_hook, take_pending_crash_report};
 use crossbeam_channel::{Receiver, Sender};
-pub use db::{Channel, Item};
+pub use db::{
+    Backfill, Channel, ChannelHeader, ClickAction, Event, HistoryEntry, Item, ItemLink, ItemLinks,
+};
 use feed_rs::model::Feed;
 use futures::{stream, StreamExt};
-pub use messages::{ToApp, ToWorker, WorkerError};
+pub use messages::{
+    AddChannelOutcome, ChannelProgress, Download, DownloadState, ErrorKind, FetchState,
+    RefreshSummary, RequestId, Severity, Statistics, ToApp, ToWorker, WorkerError,
+};
+pub use newsletter::newsletter_address;
 use parking_lot::{Mutex, Once};
+pub use quirks::Quirk;
 use reqwest::Client;
-use std::{path::PathBuf, sync::Arc};
+use reqwest_cookie_store::CookieStoreMutex;
+pub use rules::{blocks_item, RulePack};
+use social::Handle;
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 use tracing::{error, info};
+pub use utils::{font_definitions, local_midnight, strip_html};
+pub use utils::{get_app_dir, get_log_dir, read_log_tail};
+pub use utils::{get_profile, is_valid_profile_name, list_profiles, set_profile};
 
+mod archive;
+mod cli;
 mod config;
+mod cookies;
+mod crash;
 mod db;
+mod digest;
+mod dns;
+mod downloads;
+mod integrations;
+mod markdown;
 mod messages;
+mod newsletter;
+mod print;
+mod quirks;
+mod rules;
+mod scoring;
+mod server;
+mod social;
+mod sync;
+mod thumbnails;
 mod utils;
+mod webdav;
 
 static CHANNEL_CLOSED: Once = Once::new();
 
+/// How often the worker runs its scheduled tasks.
+const SCHEDULER_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Number of daily log files kept before the oldest one is removed.
+pub const MAX_LOG_FILES: usize = 7;
+
+/// Long running work that is done in a task of its own, so the worker keeps handling
+/// messages meanwhile.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Operation {
+    Refresh,
+    AddChannels,
+    ImportChannels,
+    WebdavSync,
+    Digest,
+    Deliver,
+    CacheArticles,
+    SaveDatabaseCopy,
+}
+
+type Task = (Operation, tokio::task::JoinHandle<()>);
+
+/// Seconds the items of a "Dismiss all" can be restored for, unless a refresh starts earlier.
+const TRASH_SECONDS: i64 = 5 * 60;
+
+/// Items dismissed by the last "Dismiss all", which can be restored until the trash is
+/// emptied. The sync journal gets the dismissals right away and the restores on undo.
+struct Trash {
+    ids: Vec<String>,
+    /// Items that were marked as changed, restored along with them.
+    changed: Vec<String>,
+    until: i64,
+}
+
+/// Clones share all state, a clone is handed to every spawned [`Operation`].
+#[derive(Clone)]
 pub struct Worker {
     sender: Sender<ToApp>,
     receiver: Receiver<ToWorker>,
+    loopback: Sender<ToWorker>,
     egui_ctx: eframe::egui::Context,
+    last_refresh: Arc<AtomicI64>,
+    refreshed_at: Arc<AtomicI64>,
+    webdav_changed: Arc<Mutex<HashSet<String>>>,
+    cookies: Arc<CookieStoreMutex>,
+    /// Built on first use and kept until the profile or the network settings change. Shared by
+    /// all clones, so spawned operations use the same connection pools.
+    http_clients: Arc<Mutex<Option<utils::HttpClients>>>,
+    downloads: Arc<Mutex<downloads::Downloads>>,
+    tasks: Arc<Mutex<Vec<Task>>>,
+    trash: Arc<Mutex<Option<Trash>>>,
 }
 
 impl Worker {
     pub fn new(
         sender: Sender<ToApp>,
         receiver: Receiver<ToWorker>,
+        loopback: Sender<ToWorker>,
         egui_ctx: eframe::egui::Context,
     ) -> Self {
         Self {
             sender,
             receiver,
+            loopback,
             egui_ctx,
+            last_refresh: Arc::default(),
+            refreshed_at: Arc::default(),
+            webdav_changed: Arc::default(),
+            cookies: Arc::default(),
+            http_clients: Arc::default(),
+            downloads: Arc::default(),
+            tasks: Arc::default(),
+            trash: Arc::default(),
+        }
+    }
+
+    /// Runs `task` on a clone of the worker in the background.
+    fn spawn<F, Fut>(&self, operation: Operation, task: F)
+    where
+        F: FnOnce(Worker) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        info!("Starting {:?} in the background.", operation);
+        let egui_ctx = self.egui_ctx.clone();
+        let task = task(self.clone());
+        let handle = tokio::spawn(async move {
+            task.await;
+            egui_ctx.request_repaint();
+        });
+        let mut tasks = self.tasks.lock();
+        tasks.retain(|(_, handle)| !handle.is_finished());
+        tasks.push((operation, handle));
+    }
+
+    fn is_running(&self, operation: Operation) -> bool {
+        self.tasks
+            .lock()
+            .iter()
+            .any(|(running, handle)| *running == operation && !handle.is_finished())
+    }
+
+    /// Refreshes run one at a time, requests made meanwhile are dropped with a notice
+    /// as the running one picks up the same changes.
+    fn refresh_in_progress(&mut self) -> bool {
+        if !self.is_running(Operation::Refresh) {
+            return false;
         }
+        info!("Ignoring refresh request, a refresh is already running.");
+        self.report(
+            WorkerError::new(ErrorKind::System, "Refresh already in progress", "").warning(),
+        );
+        true
     }
 
-    pub fn init(&mut self) {
+    /// Runs the worker until the app shuts down. Returns `true` when the worker stopped
+    /// to be started again against another profile.
+    pub fn init(&mut self) -> bool {
         info!("Worker starting up.");
 
         let rt = tokio::runtime::Runtime::new().unwrap();
@@ -49,34 +194,192 @@ impl Worker {
                             ToWorker::Startup => {
                                 self.initialize_app_fs();
 
+                                self.cookies = cookies::load();
+
+                                *self.http_clients.lock() = None;
+
                                 self.initialize_database().await;
 
+                                self.merge_sync_journals().await;
+
+                                self.update_feed().await;
+
                                 self.update_channel_list().await;
 
-                                self.parse_channels().await;
+                                self.update_cookie_list();
 
-                                self.update_feed().await;
+                                self.egui_ctx.request_repaint();
+
+                                self.start_local_server();
+
+                                self.spawn(Operation::Refresh, |mut worker| async move {
+                                    worker.parse_channels(None).await;
+
+                                    worker.merge_sync_journals().await;
+
+                                    worker.update_feed().await;
+                                });
+
+                                self.start_scheduler();
                             }
                             ToWorker::Shutdown => {
+                                self.empty_trash();
                                 info!("Saving config.");
                                 if let Err(err) = ConfigBuilder::from_current().save() {
                                     error!("Failed to save config: {}", err.to_string());
                                 };
+                                self.save_cookies();
                                 info!("Shutting down.");
                                 std::process::exit(0);
                             }
                             ToWorker::UpdateFeed => {
-                                self.parse_channels().await;
+                                if !self.refresh_in_progress() {
+                                    self.spawn(Operation::Refresh, |mut worker| async move {
+                                        worker.parse_channels(None).await;
+
+                                        worker.update_feed().await;
+                                    });
+                                }
+                            }
+                            ToWorker::SetChannelHeaders { id, headers } => {
+                                self.set_channel_headers(&id, headers).await;
+                            }
+                            ToWorker::ImportCookies { path, domain } => {
+                                self.import_cookies(&path, &domain);
+                            }
+                            ToWorker::ClearCookies { domain } => {
+                                self.clear_cookies(&domain);
+                            }
+                            ToWorker::LoadThumbnail { url } => {
+                                self.load_thumbnail(url);
+                            }
+                            ToWorker::Download { url, title } => {
+                                self.download(&url, &title);
+                            }
+                            ToWorker::PauseDownload { id } => {
+                                self.downloads.lock().control(id, downloads::Control::Pause);
+                            }
+                            ToWorker::ResumeDownload { id } => {
+                                self.downloads.lock().control(id, downloads::Control::Run);
+                            }
+                            ToWorker::CancelDownload { id } => {
+                                self.downloads
+                                    .lock()
+                                    .control(id, downloads::Control::Cancel);
+                            }
+                            ToWorker::SetTrustedCertificate { id, fingerprint } => {
+                                self.set_trusted_certificate(&id, fingerprint).await;
+                            }
+                            ToWorker::RefreshChannel { id } => {
+                                if !self.refresh_in_progress() {
+                                    self.spawn(Operation::Refresh, |mut worker| async move {
+                                        worker.parse_channels(Some(&id)).await;
+
+                                        worker.update_feed().await;
+                                    });
+                                }
+                            }
+                            ToWorker::AddChannel { link, request_id } => {
+                                self.spawn(Operation::AddChannels, |mut worker| async move {
+                                    let outcomes =
+                                        worker.add_channels(vec![link], "Added by link").await;
+
+                                    worker.update_channel_list().await;
+
+                                    if let Some(request_id) = request_id {
+                                        let outcome = outcomes
+                                            .into_iter()
+                                            .next()
+                                            .map(|(_, outcome)| outcome)
+                                            .unwrap_or(AddChannelOutcome::Failed(
+                                                "Nothing to add".to_string(),
+                                            ));
+                                        worker
+                                            .sender
+                                            .send(ToApp::AddChannelResult {
+                                                request_id,
+                                                outcome,
+                                            })
+                                            .unwrap();
+                                    }
+                                });
+                            }
+                            ToWorker::EditChannel { id, title } => {
+                                self.edit_channel(id, title).await;
+
+                                self.update_channel_list().await;
 
                                 self.update_feed().await;
                             }
-                            ToWorker::AddChannel { link } => {
-                                self.add_channels(vec![link]).await;
+                            ToWorker::SetChannelColor { id, color } => {
+                                self.set_channel_color(&id, color.as_deref()).await;
 
                                 self.update_channel_list().await;
                             }
-                            ToWorker::EditChannel { id, title } => {
-                                self.edit_channel(id, title).await;
+                            ToWorker::SetChannelNotes { id, notes } => {
+                                self.set_channel_notes(&id, notes.as_deref()).await;
+
+                                self.update_channel_list().await;
+                            }
+                            ToWorker::SetChannelItemLimit { id, limit } => {
+                                self.set_channel_item_limit(&id, limit).await;
+
+                                self.update_channel_list().await;
+                            }
+                            ToWorker::SetChannelQuirks { id, quirks } => {
+                                self.set_channel_quirks(&id, quirks.as_deref()).await;
+
+                                self.update_channel_list().await;
+                            }
+                            ToWorker::SetChannelFolder { id, folder } => {
+                                self.set_channel_folders(&[(id, folder)]).await;
+
+                                self.update_channel_list().await;
+                            }
+                            ToWorker::GroupChannelsBySite => {
+                                self.group_channels_by_site().await;
+
+                                self.update_channel_list().await;
+                            }
+                            ToWorker::CreateNewsletter { title } => {
+                                self.spawn(Operation::AddChannels, |mut worker| async move {
+                                    worker.create_newsletter(&title).await;
+
+                                    worker.update_channel_list().await;
+                                });
+                            }
+                            ToWorker::FindDuplicateChannels => {
+                                let mut worker = self.clone();
+                                tokio::spawn(async move {
+                                    worker.find_duplicate_channels().await;
+                                });
+                            }
+                            ToWorker::MergeChannels { keep, merged } => {
+                                self.merge_channels(&keep, &merged).await;
+
+                                self.update_channel_list().await;
+
+                                self.update_feed().await;
+                            }
+                            ToWorker::SetChannelClickAction {
+                                id,
+                                action,
+                                command,
+                            } => {
+                                self.set_channel_click_action(&id, action, command.as_deref())
+                                    .await;
+
+                                self.update_channel_list().await;
+                            }
+                            ToWorker::SetChannelPriority { id, priority } => {
+                                self.set_channel_priority(&id, priority).await;
+
+                                self.update_channel_list().await;
+
+                                self.update_feed().await;
+                            }
+                            ToWorker::SetChannelCaching { id, cache_articles } => {
+                                self.set_channel_caching(&id, cache_articles).await;
 
                                 self.update_channel_list().await;
                             }
@@ -85,11 +388,46 @@ impl Worker {
 
                                 self.update_feed().await;
                             }
+                            ToWorker::SetStarred { id, starred } => {
+                                self.set_starred(&id, starred).await;
+
+                                self.update_feed().await;
+                            }
+                            ToWorker::SetPinned { id, pinned } => {
+                                self.set_pinned(&id, pinned).await;
+
+                                self.update_feed().await;
+                            }
+                            ToWorker::SetLater { id, later } => {
+                                self.set_later(&id, later).await;
+
+                                self.update_feed().await;
+                            }
+                            ToWorker::SetDismissedMany { ids, dismissed } => {
+                                self.set_dismissed_many(ids, dismissed).await;
+
+                                self.update_feed().await;
+                            }
+                            ToWorker::SetStarredMany { ids, starred } => {
+                                self.set_starred_many(ids, starred).await;
+
+                                self.update_feed().await;
+                            }
                             ToWorker::DismissAll => {
                                 self.dismiss_all().await;
 
                                 self.update_feed().await;
                             }
+                            ToWorker::UndoDismissAll => {
+                                self.undo_dismiss_all().await;
+
+                                self.update_feed().await;
+                            }
+                            ToWorker::DismissMatching { ids, later } => {
+                                self.dismiss_matching(ids, later).await;
+
+                                self.update_feed().await;
+                            }
                             ToWorker::Unsubscribe { id } => {
                                 self.unsubscribe(&id).await;
 
@@ -98,13 +436,125 @@ impl Worker {
                                 self.update_feed().await;
                             }
                             ToWorker::ImportChannels { path } => {
-                                self.import_channels(path).await;
+                                self.spawn(Operation::ImportChannels, |mut worker| async move {
+                                    worker.import_channels(path).await;
 
-                                self.update_channel_list().await;
+                                    worker.update_channel_list().await;
+                                });
+                            }
+                            ToWorker::ImportChannelsFromUrl { url, keep_synced } => {
+                                self.spawn(Operation::ImportChannels, |mut worker| async move {
+                                    worker.import_channels_from_url(url, keep_synced).await;
+
+                                    worker.update_channel_list().await;
+                                });
+                            }
+                            ToWorker::ImportOpml { xml } => {
+                                self.spawn(Operation::ImportChannels, |mut worker| async move {
+                                    if let Some(links) = worker.opml_links(&xml).await {
+                                        worker.add_channels(links, "OPML import").await;
+                                    }
+
+                                    worker.update_channel_list().await;
+
+                                    if !worker.is_running(Operation::Refresh) {
+                                        worker.spawn(Operation::Refresh, |mut worker| async move {
+                                            worker.parse_channels(None).await;
+
+                                            worker.update_feed().await;
+                                        });
+                                    }
+                                });
                             }
                             ToWorker::ExportChannels => {
                                 self.export_channels().await;
                             }
+                            ToWorker::ExportStarred => {
+                                self.export_starred().await;
+                            }
+                            ToWorker::ExportArchive => {
+                                self.export_archive().await;
+                            }
+                            ToWorker::SaveDatabaseCopy => {
+                                self.save_database_copy().await;
+                            }
+                            ToWorker::ExportJson => {
+                                self.export_json().await;
+                            }
+                            ToWorker::ImportJson { path } => {
+                                self.import_json(&path).await;
+
+                                self.update_channel_list().await;
+
+                                self.update_feed().await;
+                            }
+                            ToWorker::ImportArchive { path } => {
+                                self.import_archive(path);
+
+                                self.update_feed().await;
+                            }
+                            ToWorker::SendDigest => {
+                                self.spawn(Operation::Digest, |mut worker| async move {
+                                    worker.send_digest().await;
+                                });
+                            }
+                            ToWorker::Republish => {
+                                self.republish().await;
+                            }
+                            ToWorker::SaveItem { id } => {
+                                self.save_item(&id).await;
+                            }
+                            ToWorker::ShareItem { id, service } => {
+                                self.share_item(&id, service).await;
+                            }
+                            ToWorker::SaveNote { id } => {
+                                self.save_note(&id).await;
+                            }
+                            ToWorker::OpenItem { id } => {
+                                self.open_item(&id).await;
+
+                                self.update_feed().await;
+                            }
+                            ToWorker::OpenItems { ids } => {
+                                for id in ids {
+                                    self.open_item(&id).await;
+                                }
+
+                                self.update_feed().await;
+                            }
+                            ToWorker::PrintItem { id } => {
+                                self.print_item(&id).await;
+                            }
+                            ToWorker::SendTo {
+                                command,
+                                url,
+                                title,
+                            } => {
+                                self.send_to(&command, &url, &title);
+                            }
+                            ToWorker::SearchItems { query, generation } => {
+                                self.search_items(&query, generation).await;
+                            }
+                            ToWorker::SearchHistory { query } => {
+                                self.search_history(query).await;
+                            }
+                            ToWorker::LoadCalendar { year, month } => {
+                                self.load_calendar(year, month).await;
+                            }
+                            ToWorker::WebdavSync => {
+                                self.spawn(Operation::WebdavSync, |mut worker| async move {
+                                    worker.webdav_sync().await;
+
+                                    worker.update_channel_list().await;
+
+                                    worker.update_feed().await;
+                                });
+                            }
+                            ToWorker::SwitchProfile { profile } => {
+                                self.switch_profile(profile);
+
+                                return true;
+                            }
                         }
                         self.egui_ctx.request_repaint();
                     }
@@ -115,80 +565,367 @@ impl Worker {
                     }
                 }
             }
-        });
+        })
     }
 
     fn initialize_app_fs(&mut self) {
         let app_dir = utils::get_app_dir();
         let db_path = app_dir.join("tinyrss.db");
 
-        if let Err(err) = std::fs::create_dir_all(utils::get_app_dir()) {
-            self.report_error("Failed to initialize app filesystem", err.to_string());
+        if let Err(err) = std::fs::create_dir_all(&app_dir) {
+            self.report_error(
+                ErrorKind::Filesystem,
+                "Failed to initialize app filesystem",
+                err.to_string(),
+            );
         } else {
             info!("Initialized application filesystem.");
         };
 
+        match archive::finish_import(&app_dir) {
+            Ok(true) => info!("Replaced the database with the imported one."),
+            Ok(false) => {}
+            Err(err) => self.report_error(
+                ErrorKind::Database,
+                "Failed to replace the database with the imported one",
+                err.to_string(),
+            ),
+        }
+
         if !db_path.exists() {
             if let Err(err) = std::fs::File::create(db_path) {
-                self.report_error("Failed to create database", err.to_string());
+                self.report_error(
+                    ErrorKind::Database,
+                    "Failed to create database",
+                    err.to_string(),
+                );
             };
         }
     }
 
     async fn initialize_database(&mut self) {
         if let Err(err) = db::create_tables().await {
-            self.report_error("Failed to initialize database", err.to_string());
+            self.report_error(
+                ErrorKind::Database,
+                "Failed to initialize database",
+                err.to_string(),
+            );
         } else {
             info!("Initialized database.");
         };
+
+        // The ids are only forgotten once the config with the new ones is saved.
+        match db::get_rekeyed_channels().await {
+            Ok(moved) if moved.is_empty() => {}
+            Ok(moved) => {
+                info!("Moved {} channels to link based ids.", moved.len());
+                let mut config = ConfigBuilder::from_current();
+                config.rekey_channels(&moved);
+                config.clone().apply();
+                let saved = config.save().map_err(|err| err.to_string());
+                let result = match saved {
+                    Ok(()) => db::clear_rekeyed_channels()
+                        .await
+                        .map_err(|err| err.to_string()),
+                    Err(err) => Err(err),
+                };
+                if let Err(err) = result {
+                    self.report_error(ErrorKind::Database, "Failed to migrate channel ids", err);
+                }
+            }
+            Err(err) => self.report_error(
+                ErrorKind::Database,
+                "Failed to migrate channel ids",
+                err.to_string(),
+            ),
+        }
+    }
+
+    fn start_local_server(&mut self) {
+        let mut config = ConfigBuilder::from_current();
+        if config.local_server_token.is_empty() || config.local_feed_token.is_empty() {
+            for token in [&mut config.local_server_token, &mut config.local_feed_token] {
+                if token.is_empty() {
+                    *token = utils::generate_token().into();
+                }
+            }
+            config.clone().apply();
+        }
+        if config.enable_local_server {
+            tokio::spawn(server::serve(
+                config.local_server_port,
+                self.loopback.clone(),
+            ));
+        }
+    }
+
+    async fn create_newsletter(&mut self, title: &str) {
+        // A failure is shown in the newsletter dialog, so it isn't reported as well.
+        let result = self.subscribe_newsletter(title).await;
+        if let Err(reason) = &result {
+            error!("Failed to create newsletter inbox: {}", reason);
+        }
+        self.sender
+            .send(ToApp::NewsletterCreated { result })
+            .unwrap();
+    }
+
+    async fn subscribe_newsletter(&mut self, title: &str) -> Result<String, String> {
+        let server = CONFIG.lock().newsletter_server.clone();
+        let client = self.http_client().ok_or("Invalid DNS settings")?;
+        let feed = newsletter::create_inbox(&client, &server, title).await?;
+        let address = newsletter_address(&feed).ok_or(format!("Unexpected feed link: {}", feed))?;
+        let outcomes = self
+            .add_channels(vec![feed.clone()], "Newsletter inbox")
+            .await;
+        match outcomes.into_iter().next() {
+            Some((_, AddChannelOutcome::Added { .. })) => {}
+            Some((_, AddChannelOutcome::Failed(reason))) => return Err(reason),
+            _ => return Err("Already subscribed".to_string()),
+        }
+        db::set_channel_newsletter(&utils::channel_id(&feed))
+            .await
+            .map_err(|err| err.to_string())?;
+        Ok(address)
+    }
+
+    /// Replaces account handles among `links` with the feed links of their posts. Handles
+    /// that can't be looked up are returned with the reason.
+    async fn resolve_handles(
+        &mut self,
+        links: Vec<String>,
+    ) -> (Vec<String>, Vec<(String, AddChannelOutcome)>) {
+        let mut resolved = vec![];
+        let mut unresolved = vec![];
+        for link in links {
+            let (Some(handle), Some(client)) = (Handle::parse(&link), self.http_client()) else {
+                resolved.push(link);
+                continue;
+            };
+            match handle.resolve(&client).await {
+                Ok(feed) => {
+                    info!("Resolved {} to {}", link, feed);
+                    resolved.push(feed);
+                }
+                Err(reason) => {
+                    self.report(
+                        WorkerError::new(
+                            ErrorKind::Network {
+                                channel_id: None,
+                                status: None,
+                            },
+                            "Failed to find account",
+                            &reason,
+                        )
+                        .warning(),
+                    );
+                    unresolved.push((link, AddChannelOutcome::Failed(reason)));
+                }
+            }
+        }
+        (resolved, unresolved)
     }
 
-    async fn add_channels(&mut self, links: Vec<String>) {
+    /// Subscribes to the feeds behind `links`. Returns what came of each link.
+    /// `source` is recorded in the subscription events, e.g. "OPML import".
+    async fn add_channels(
+        &mut self,
+        links: Vec<String>,
+        source: &str,
+    ) -> Vec<(String, AddChannelOutcome)> {
+        let failed = |links: Vec<String>, reason: &str| {
+            links
+                .into_iter()
+                .map(|link| (link, AddChannelOutcome::Failed(reason.to_string())))
+                .collect()
+        };
+        let existing = match db::get_all_channels().await {
+            Ok(channels) => channels,
+            Err(err) => {
+                self.report_error(
+                    ErrorKind::Database,
+                    "Failed to fetch channel from db",
+                    err.to_string(),
+                );
+                return failed(links, "Failed to read channels");
+            }
+        };
+        let (links, unresolved) = self.resolve_handles(links).await;
+        let single = links.len() == 1;
+        let (known, links): (Vec<String>, Vec<String>) = links.into_iter().partition(|link| {
+            existing
+                .iter()
+                .any(|channel| utils::same_link(&channel.link, link))
+        });
+        let mut outcomes: Vec<(String, AddChannelOutcome)> = known
+            .iter()
+            .map(|link| (link.clone(), AddChannelOutcome::AlreadySubscribed))
+            .chain(unresolved)
+            .collect();
+        let archived = match db::get_archived_channels().await {
+            Ok(channels) => channels,
+            Err(err) => {
+                self.report_error(
+                    ErrorKind::Database,
+                    "Failed to fetch channel from db",
+                    err.to_string(),
+                );
+                outcomes.extend(failed(links, "Failed to read channels"));
+                return outcomes;
+            }
+        };
+        let (restored, links): (Vec<String>, Vec<String>) = links.into_iter().partition(|link| {
+            archived
+                .iter()
+                .any(|channel| utils::same_link(&channel.link, link))
+        });
+        for link in restored {
+            let channel = archived
+                .iter()
+                .find(|channel| utils::same_link(&channel.link, &link))
+                .unwrap();
+            info!("Restoring archived channel: {}", channel.link);
+            let outcome = match db::set_archived(&channel.id, false).await {
+                Ok(()) => {
+                    let event =
+                        db::Event::new(db::EventKind::Restored, channel, Some(source.to_string()));
+                    self.record_events(&[event]).await;
+                    AddChannelOutcome::Restored
+                }
+                Err(err) => {
+                    self.report_error(
+                        ErrorKind::Database,
+                        "Failed to restore archived channel",
+                        err.to_string(),
+                    );
+                    AddChannelOutcome::Failed(err.to_string())
+                }
+            };
+            outcomes.push((link, outcome));
+        }
+        if !known.is_empty() {
+            info!("Skipped {} already subscribed links.", known.len());
+            if single {
+                self.report(
+                    WorkerError::new(
+                        ErrorKind::AlreadySubscribed,
+                        "Already subscribed",
+                        &known[0],
+                    )
+                    .warning(),
+                );
+            }
+        }
+        if links.is_empty() {
+            return outcomes;
+        }
+
         if !utils::is_online().await {
-            self.report_error("No internet connection", "");
-            return;
+            let mut error =
+                WorkerError::new(ErrorKind::Offline, "No internet connection", "").warning();
+            if let [link] = links.as_slice() {
+                error = error.with_retry(ToWorker::AddChannel {
+                    link: link.clone(),
+                    request_id: None,
+                });
+            }
+            self.report(error);
+            outcomes.extend(failed(links, "No internet connection"));
+            return outcomes;
         }
 
-        let client = Client::new();
+        let client = match self.http_client() {
+            Some(client) => client,
+            None => {
+                outcomes.extend(failed(links, "Invalid DNS settings"));
+                return outcomes;
+            }
+        };
 
         let channels_total = links.len() as f32;
 
         struct LinkBytesBinding {
             link: String,
-            bytes: Option<Bytes>,
+            bytes: Result<Bytes, String>,
         }
 
+        let limiter = {
+            let config = CONFIG.lock();
+            utils::RequestLimiter::new(
+                config.max_allowed_concurent_requests,
+                config.max_requests_per_host,
+            )
+        };
+
+        let max_feed_size = utils::max_feed_size();
+
         let results = stream::iter(links)
             .map(|link| {
                 let client = &client;
+                let limiter = &limiter;
                 let sender = self.sender.clone();
                 async move {
-                    let resp = match client.get(&link).send().await {
-                        Ok(r) => r,
+                    let _permit = limiter.acquire(&link).await;
+                    let resp =
+                        match client.get(&link).send().await {
+                            Ok(r) => r,
+                            Err(err) => {
+                                sender
+                                    .send(ToApp::WorkerError {
+                                        error: WorkerError::new(
+                                            ErrorKind::Network {
+                                                channel_id: None,
+                                                status: err.status().map(|status| status.as_u16()),
+                                            },
+                                            "Web request failed",
+                                            err.to_string(),
+                                        )
+                                        .warning()
+                                        .with_retry(ToWorker::AddChannel {
+                                            link: link.clone(),
+                                            request_id: None,
+                                        }),
+                                    })
+                                    .unwrap();
+                                return LinkBytesBinding {
+                                    link,
+                                    bytes: Err(err.to_string()),
+                                };
+                            }
+                        };
+                    match utils::read_body(resp, max_feed_size).await {
+                        Ok(bytes) => LinkBytesBinding {
+                            link,
+                            bytes: Ok(bytes),
+                        },
                         Err(err) => {
                             sender
                                 .send(ToApp::WorkerError {
-                                    error: WorkerError::new("Web request failed", err.to_string()),
+                                    error: WorkerError::new(
+                                        ErrorKind::Network {
+                                            channel_id: None,
+                                            status: None,
+                                        },
+                                        "Web request failed",
+                                        err.clone(),
+                                    )
+                                    .warning(),
                                 })
                                 .unwrap();
-                            return LinkBytesBinding { link, bytes: None };
+                            LinkBytesBinding {
+                                link,
+                                bytes: Err(err),
+                            }
                         }
-                    };
-                    let res = resp.bytes().await;
-                    match res {
-                        Ok(bytes) => LinkBytesBinding {
-                            link,
-                            bytes: Some(bytes),
-                        },
-                        Err(_) => LinkBytesBinding { link, bytes: None },
                     }
                 }
             })
-            .buffer_unordered(CONFIG.lock().max_allowed_concurent_requests);
+            // Everything is polled at once, the limiter decides how many requests actually run.
+            .buffer_unordered(channels_total as usize);
 
         struct LinkFeedBinding {
             link: String,
-            feed: Option<Feed>,
+            feed: Result<Feed, String>,
         }
 
         let mut bindings: Vec<LinkFeedBinding> = vec![];
@@ -206,20 +943,10 @@ impl Worker {
                         progress: *processed / channels_total,
                     })
                     .unwrap();
-                match r.bytes {
-                    Some(bytes) => {
-                        let feed = if let Ok(feed) = feed_rs::parser::parse(&bytes[..]) {
-                            Some(feed)
-                        } else {
-                            None
-                        };
-                        bindings.push(LinkFeedBinding { link: r.link, feed })
-                    }
-                    None => bindings.push(LinkFeedBinding {
-                        link: r.link,
-                        feed: None,
-                    }),
-                }
+                let feed = r.bytes.and_then(|bytes| {
+                    feed_rs::parser::parse(&bytes[..]).map_err(|err| format!("Not a feed: {}", err))
+                });
+                bindings.push(LinkFeedBinding { link: r.link, feed });
                 bindings
             })
             .await;
@@ -229,11 +956,14 @@ impl Worker {
         for binding in bindings {
             let link = binding.link;
             let parsed_feed = match binding.feed {
-                Some(feed) => feed,
-                None => continue,
+                Ok(feed) => feed,
+                Err(reason) => {
+                    outcomes.push((link, AddChannelOutcome::Failed(reason)));
+                    continue;
+                }
             };
             let mut channel = db::Channel {
-                id: parsed_feed.id,
+                id: utils::channel_id(&link),
                 ..Default::default()
             };
             channel.kind = match parsed_feed.feed_type {
@@ -244,6 +974,7 @@ impl Worker {
                 feed_rs::model::FeedType::RSS2 => "RSS2".into(),
             };
             channel.link = link.clone();
+            channel.backfill = Some(CONFIG.lock().backfill).filter(|mode| *mode != Backfill::All);
             channel.title = match parsed_feed.title {
                 Some(text) => Some(text.content),
                 None => None,
@@ -252,320 +983,2586 @@ impl Worker {
                 Some(text) => Some(text.content),
                 None => None,
             };
+            // Another spelling of a subscribed link, e.g. without `www.`, is most likely the
+            // same feed. It's still added, as sites may serve different feeds on them.
+            let key = utils::duplicate_key(&link);
+            if let Some(known) = existing
+                .iter()
+                .find(|known| single && utils::duplicate_key(&known.link) == key)
+            {
+                self.report(
+                    WorkerError::new(
+                        ErrorKind::AlreadySubscribed,
+                        "Already subscribed",
+                        format!("{} is likely the same feed as {}", link, known.link),
+                    )
+                    .warning(),
+                );
+            }
             channels.push(channel);
         }
         info!(
             "Saving new channels to database. (amount: {})",
             channels.len()
         );
-        if let Err(err) = db::add_channels(channels).await {
-            self.report_error("Failed to save new channels", err.to_string())
+        let added: Vec<(String, Option<String>)> = channels
+            .iter()
+            .map(|channel| (channel.link.clone(), channel.title.clone()))
+            .collect();
+        let events: Vec<db::Event> = channels
+            .iter()
+            .map(|channel| {
+                db::Event::new(db::EventKind::Subscribed, channel, Some(source.to_string()))
+            })
+            .collect();
+        match db::add_channels(channels).await {
+            Ok(()) => {
+                self.record_events(&events).await;
+                outcomes.extend(
+                    added
+                        .into_iter()
+                        .map(|(link, title)| (link, AddChannelOutcome::Added { title })),
+                )
+            }
+            Err(err) => {
+                self.report_error(
+                    ErrorKind::Database,
+                    "Failed to save new channels",
+                    err.to_string(),
+                );
+                outcomes.extend(failed(
+                    added.into_iter().map(|(link, _)| link).collect(),
+                    "Failed to save channel",
+                ));
+            }
         };
+        outcomes
     }
 
     async fn update_channel_list(&mut self) {
         let channels = match db::get_all_channels().await {
             Ok(channels) => channels,
             Err(err) => {
-                self.report_error("Failed to fetch channel from db", err.to_string());
+                self.report_error(
+                    ErrorKind::Database,
+                    "Failed to fetch channel from db",
+                    err.to_string(),
+                );
+                return;
+            }
+        };
+
+        let headers = match db::get_channel_headers().await {
+            Ok(headers) => headers,
+            Err(err) => {
+                self.report_error(
+                    ErrorKind::Database,
+                    "Failed to fetch channel headers from db",
+                    err.to_string(),
+                );
                 return;
             }
         };
 
         self.sender
-            .send(ToApp::UpdateChannels { channels })
+            .send(ToApp::UpdateChannels { channels, headers })
             .unwrap();
+
+        match db::get_events().await {
+            Ok(events) => self
+                .sender
+                .send(ToApp::SubscriptionEvents { events })
+                .unwrap(),
+            Err(err) => self.report_error(
+                ErrorKind::Database,
+                "Failed to fetch subscription events from db",
+                err.to_string(),
+            ),
+        }
+    }
+
+    async fn record_events(&mut self, events: &[db::Event]) {
+        if let Err(err) = db::record_events(events).await {
+            self.report_error(
+                ErrorKind::Database,
+                "Failed to record subscription events",
+                err.to_string(),
+            );
+        }
+    }
+
+    /// The channel `id`, archived or not.
+    async fn find_channel(&mut self, id: &str) -> Option<Channel> {
+        match db::get_channels_with_archived().await {
+            Ok(channels) => channels.into_iter().find(|channel| channel.id == id),
+            Err(err) => {
+                self.report_error(
+                    ErrorKind::Database,
+                    "Failed to fetch channel from db",
+                    err.to_string(),
+                );
+                None
+            }
+        }
+    }
+
+    async fn set_channel_headers(&mut self, id: &str, headers: Vec<(String, String)>) {
+        if let Err(err) = db::set_channel_headers(id, headers).await {
+            self.report_error(
+                ErrorKind::Database,
+                "Falied to save channel headers",
+                err.to_string(),
+            );
+        }
+        self.update_channel_list().await;
     }
 
     async fn edit_channel(&mut self, id: String, title: String) {
-        if let Err(err) = db::edit_channel(id, title).await {
-            self.report_error("Falied to edit channel", err.to_string());
+        let previous = self.find_channel(&id).await;
+        if let Err(err) = db::edit_channel(id, title.clone()).await {
+            self.report_error(
+                ErrorKind::Database,
+                "Falied to edit channel",
+                err.to_string(),
+            );
+        } else if let Some(mut channel) = previous {
+            let detail = format!("From \"{}\"", channel.title.unwrap_or_default());
+            channel.title = Some(title);
+            let event = db::Event::new(db::EventKind::Renamed, &channel, Some(detail));
+            self.record_events(&[event]).await;
         }
     }
 
-    async fn parse_channels(&mut self) {
-        if !utils::is_online().await {
-            self.report_error("No internet connection", "");
+    async fn set_trusted_certificate(&mut self, id: &str, fingerprint: Option<String>) {
+        if let Err(err) = db::set_channel_fingerprint(id, fingerprint.as_deref()).await {
+            self.report_error(
+                ErrorKind::Database,
+                "Falied to save trusted certificate",
+                err.to_string(),
+            );
             return;
         }
+        if fingerprint.is_some() {
+            self.parse_channels(Some(id)).await;
+            self.update_feed().await;
+        } else {
+            self.update_channel_list().await;
+        }
+    }
+
+    async fn set_channel_color(&mut self, id: &str, color: Option<&str>) {
+        if let Err(err) = db::set_channel_color(id, color).await {
+            self.report_error(
+                ErrorKind::Database,
+                "Falied to set channel color",
+                err.to_string(),
+            );
+        }
+    }
+
+    async fn set_channel_folders(&mut self, folders: &[(String, Option<String>)]) {
+        if let Err(err) = db::set_channel_folders(folders).await {
+            self.report_error(
+                ErrorKind::Database,
+                "Failed to move channels to folders",
+                err.to_string(),
+            );
+        }
+    }
 
+    async fn group_channels_by_site(&mut self) {
         let channels = match db::get_all_channels().await {
             Ok(channels) => channels,
             Err(err) => {
-                self.report_error("Failed to fetch channel from db", err.to_string());
+                self.report_error(
+                    ErrorKind::Database,
+                    "Failed to fetch channels from db",
+                    err.to_string(),
+                );
                 return;
             }
         };
+        let mut sites: HashMap<String, Vec<String>> = HashMap::new();
+        for channel in channels.iter().filter(|channel| channel.folder.is_none()) {
+            if let Some(site) = utils::site_of(&channel.link) {
+                sites.entry(site).or_default().push(channel.id.clone());
+            }
+        }
+        let folders: Vec<(String, Option<String>)> = sites
+            .into_iter()
+            .filter(|(_, ids)| ids.len() > 1)
+            .flat_map(|(site, ids)| ids.into_iter().map(move |id| (id, Some(site.clone()))))
+            .collect();
+        info!("Grouped {} channels by site.", folders.len());
+        self.set_channel_folders(&folders).await;
+    }
 
-        let channels_total: f32 = channels.len() as f32;
+    /// Finds channels whose links differ only in spelling, scheme or `www.`, or redirect to
+    /// each other or to the same place.
+    async fn find_duplicate_channels(&mut self) {
+        let channels = match db::get_all_channels().await {
+            Ok(channels) => channels,
+            Err(err) => {
+                self.report_error(
+                    ErrorKind::Database,
+                    "Failed to fetch channels from db",
+                    err.to_string(),
+                );
+                return;
+            }
+        };
+        // Without a client only the spellings of the links are compared.
+        let client = self.http_client();
+        let concurrency = CONFIG.lock().max_allowed_concurent_requests.max(1);
+        // Where each channel's link ends up after redirects, unreachable ones stay put.
+        let links: Vec<(String, String)> = channels
+            .iter()
+            .filter(|_| client.is_some())
+            .map(|channel| (channel.id.clone(), channel.link.clone()))
+            .collect();
+        let finals: HashMap<String, String> = stream::iter(links)
+            .map(|(id, link)| {
+                let client = client.clone().unwrap_or_default();
+                async move {
+                    let url = match client.get(&link).send().await {
+                        Ok(resp) => resp.url().to_string(),
+                        Err(_) => link,
+                    };
+                    (id, url)
+                }
+            })
+            .buffer_unordered(concurrency)
+            .collect()
+            .await;
 
-        info!("Started parsing.");
+        // Channels sharing the key of their link or of its redirect target are one group.
+        let mut group_of_key: HashMap<String, usize> = HashMap::new();
+        let mut groups: Vec<Vec<&Channel>> = vec![];
+        for channel in &channels {
+            let keys = [
+                utils::duplicate_key(&channel.link),
+                utils::duplicate_key(finals.get(&channel.id).unwrap_or(&channel.link)),
+            ];
+            let mut found: Vec<usize> = keys
+                .iter()
+                .filter_map(|key| group_of_key.get(key).copied())
+                .collect();
+            found.sort_unstable();
+            found.dedup();
+            let index = match found[..] {
+                [] => {
+                    groups.push(vec![]);
+                    groups.len() - 1
+                }
+                [index] => index,
+                [index, other] => {
+                    let moved = std::mem::take(&mut groups[other]);
+                    groups[index].extend(moved);
+                    for group in group_of_key.values_mut() {
+                        if *group == other {
+                            *group = index;
+                        }
+                    }
+                    index
+                }
+                _ => unreachable!("a channel has two keys"),
+            };
+            groups[index].push(channel);
+            for key in keys {
+                group_of_key.insert(key, index);
+            }
+        }
 
-        let client = Client::new();
+        let redirect_targets: HashSet<String> = finals
+            .values()
+            .map(|link| utils::canonical_link(link))
+            .collect();
+        let groups: Vec<Vec<String>> = groups
+            .into_iter()
+            .filter(|group| group.len() > 1)
+            .map(|mut group| {
+                // Keep the link others redirect to, else https, else the shortest.
+                group.sort_by_key(|channel| {
+                    (
+                        !redirect_targets.contains(&utils::canonical_link(&channel.link)),
+                        !channel.link.starts_with("https://"),
+                        channel.link.len(),
+                    )
+                });
+                group
+                    .into_iter()
+                    .map(|channel| channel.id.clone())
+                    .collect()
+            })
+            .collect();
+        info!("Found {} groups of duplicate channels.", groups.len());
+        self.sender
+            .send(ToApp::DuplicateChannels { groups })
+            .unwrap();
+    }
 
-        struct ChannelBytesBinding {
-            channel: Channel,
-            bytes: Option<Bytes>,
+    async fn merge_channels(&mut self, keep: &str, merged: &[String]) {
+        let channels = db::get_channels_with_archived().await.unwrap_or_default();
+        let into = channels
+            .iter()
+            .find(|channel| channel.id == keep)
+            .map(|channel| channel.title.clone().unwrap_or(channel.link.clone()));
+        let events: Vec<db::Event> = channels
+            .iter()
+            .filter(|channel| channel.id != keep && merged.contains(&channel.id))
+            .map(|channel| {
+                let detail = into.as_ref().map(|into| format!("Into \"{}\"", into));
+                db::Event::new(db::EventKind::Merged, channel, detail)
+            })
+            .collect();
+        if let Err(err) = db::merge_channels(keep, merged).await {
+            self.report_error(
+                ErrorKind::Database,
+                "Failed to merge channels",
+                err.to_string(),
+            );
+            return;
+        }
+        info!("Merged {} channels into {}.", merged.len(), keep);
+        self.record_events(&events).await;
+        let mut config = ConfigBuilder::from_current();
+        for ids in [&mut config.digest_channels, &mut config.forward_channels] {
+            if ids.iter().any(|id| merged.contains(id)) {
+                ids.retain(|id| !merged.contains(id) && id != keep);
+                ids.push(keep.to_string());
+            }
+        }
+        config.apply();
+    }
+
+    async fn set_channel_notes(&mut self, id: &str, notes: Option<&str>) {
+        if let Err(err) = db::set_channel_notes(id, notes).await {
+            self.report_error(
+                ErrorKind::Database,
+                "Failed to save channel notes",
+                err.to_string(),
+            );
         }
+    }
 
-        let results = stream::iter(channels)
-            .map(|channel| {
-                let client = &client;
-                let sender = self.sender.clone();
+    async fn set_channel_item_limit(&mut self, id: &str, limit: Option<i64>) {
+        if let Err(err) = db::set_channel_item_limit(id, limit).await {
+            self.report_error(
+                ErrorKind::Database,
+                "Failed to save channel item limit",
+                err.to_string(),
+            );
+        }
+    }
+
+    async fn set_channel_quirks(&mut self, id: &str, quirks: Option<&[Quirk]>) {
+        let quirks = quirks.map(Quirk::join);
+        if let Err(err) = db::set_channel_quirks(id, quirks.as_deref()).await {
+            self.report_error(
+                ErrorKind::Database,
+                "Failed to save channel quirks",
+                err.to_string(),
+            );
+        }
+    }
+
+    async fn set_channel_click_action(
+        &mut self,
+        id: &str,
+        action: ClickAction,
+        command: Option<&str>,
+    ) {
+        if let Err(err) = db::set_channel_click_action(id, action, command).await {
+            self.report_error(
+                ErrorKind::Database,
+                "Failed to set channel click action",
+                err.to_string(),
+            );
+        }
+    }
+
+    async fn set_channel_priority(&mut self, id: &str, priority: bool) {
+        if let Err(err) = db::set_channel_priority(id, priority).await {
+            self.report_error(
+                ErrorKind::Database,
+                "Failed to set channel priority",
+                err.to_string(),
+            );
+        }
+    }
+
+    async fn set_channel_caching(&mut self, id: &str, cache_articles: bool) {
+        if let Err(err) = db::set_channel_caching(id, cache_articles).await {
+            self.report_error(
+                ErrorKind::Database,
+                "Failed to set article caching",
+                err.to_string(),
+            );
+        }
+    }
+
+    /// Downloads the web pages of `items`, given as id and link, and stores their article
+    /// text as the items' content. Pages that fail to load are skipped.
+    async fn cache_articles(&mut self, items: Vec<(String, String)>) {
+        if items.is_empty() {
+            return;
+        }
+        let client = match self.http_client() {
+            Some(client) => client,
+            None => return,
+        };
+        info!("Caching {} articles.", items.len());
+        let limit = utils::max_feed_size();
+        let concurrency = CONFIG.lock().max_allowed_concurent_requests.max(1);
+        let pages = stream::iter(items)
+            .map(|(id, link)| {
+                let client = client.clone();
                 async move {
-                    let resp = match client.get(&channel.link).send().await {
-                        Ok(r) => r,
-                        Err(err) => {
-                            sender
-                                .send(ToApp::WorkerError {
-                                    error: WorkerError::new("Web request failed", err.to_string()),
-                                })
-                                .unwrap();
-                            return ChannelBytesBinding {
-                                channel,
-                                bytes: None,
-                            };
-                        }
+                    let page = match client
+                        .get(&link)
+                        .send()
+                        .await
+                        .and_then(|resp| resp.error_for_status())
+                    {
+                        Ok(resp) => utils::read_body(resp, limit).await,
+                        Err(err) => Err(err.to_string()),
                     };
-                    let res = resp.bytes().await;
-                    match res {
-                        Ok(bytes) => ChannelBytesBinding {
-                            channel,
-                            bytes: Some(bytes),
-                        },
-                        Err(_) => ChannelBytesBinding {
-                            channel,
-                            bytes: None,
-                        },
-                    }
+                    (id, link, page)
                 }
             })
-            .buffer_unordered(CONFIG.lock().max_allowed_concurent_requests);
+            .buffer_unordered(concurrency);
+        let mut pages = std::pin::pin!(pages);
+
+        let mut cached = 0;
+        while let Some((id, link, page)) = pages.next().await {
+            let article = match page {
+                Ok(bytes) => utils::readable_html(&String::from_utf8_lossy(&bytes)),
+                Err(err) => {
+                    info!("Failed to cache {}: {}", link, err);
+                    continue;
+                }
+            };
+            let Some(article) = article else {
+                info!("No article found at {}", link);
+                continue;
+            };
+            let reading_time = utils::estimate_reading_time(&article);
+            match db::set_item_content(&id, &article, reading_time).await {
+                Ok(()) => cached += 1,
+                Err(err) => {
+                    self.report_error(
+                        ErrorKind::Database,
+                        "Failed to save cached article",
+                        err.to_string(),
+                    );
+                    return;
+                }
+            }
+        }
+        info!("Cached {} articles.", cached);
+    }
+
+    /// Refreshes every channel, or just the one with the given id.
+    async fn parse_channels(&mut self, only: Option<&str>) {
+        self.empty_trash();
+        self.last_refresh
+            .store(chrono::Utc::now().timestamp(), Ordering::Relaxed);
+
+        if !utils::is_online().await {
+            self.report(
+                WorkerError::new(ErrorKind::Offline, "No internet connection", "")
+                    .warning()
+                    .with_retry(ToWorker::UpdateFeed),
+            );
+            return;
+        }
+
+        let channels = match db::get_all_channels().await {
+            Ok(channels) => channels,
+            Err(err) => {
+                self.report_error(
+                    ErrorKind::Database,
+                    "Failed to fetch channel from db",
+                    err.to_string(),
+                );
+                return;
+            }
+        };
+        let channels: Vec<Channel> = channels
+            .into_iter()
+            .filter(|channel| only.is_none_or(|id| channel.id == id))
+            .collect();
+
+        let channels_total: f32 = channels.len() as f32;
+
+        info!("Started parsing.");
+
+        let (client, pinned_client) = match self.http_clients() {
+            Some(clients) => (clients.feeds, clients.pinned),
+            None => return,
+        };
 
-        struct ChannelFeedBinding {
+        let mut headers: HashMap<String, Vec<(String, String)>> = HashMap::new();
+        match db::get_channel_headers().await {
+            Ok(rows) => {
+                for row in rows {
+                    headers
+                        .entry(row.channel)
+                        .or_default()
+                        .push((row.name, row.value));
+                }
+            }
+            Err(err) => {
+                self.report_error(
+                    ErrorKind::Database,
+                    "Failed to fetch channel headers from db",
+                    err.to_string(),
+                );
+                return;
+            }
+        }
+
+        struct ChannelBytesBinding {
+            index: usize,
             channel: Channel,
-            feed: Option<Feed>,
+            bytes: Result<Bytes, WorkerError>,
         }
 
-        let mut bindings: Vec<ChannelFeedBinding> = vec![];
+        let progress = Mutex::new(
+            channels
+                .iter()
+                .map(|channel| ChannelProgress {
+                    title: channel.title.clone().unwrap_or(channel.link.clone()),
+                    state: FetchState::Queued,
+                })
+                .collect::<Vec<_>>(),
+        );
+        let sender = self.sender.clone();
+        let set_state = |index: usize, state: FetchState| {
+            let mut progress = progress.lock();
+            progress[index].state = state;
+            sender
+                .send(ToApp::FeedUpdateProgress {
+                    channels: progress.clone(),
+                })
+                .unwrap();
+        };
 
-        let processed_channels: Arc<Mutex<f32>> = Arc::new(Mutex::new(0.0));
+        let metered = self.is_metered().await;
+        let (concurrent, per_host) = CONFIG.lock().request_limits(metered);
+        if metered {
+            info!(
+                "Metered connection, fetching {} feeds at a time.",
+                concurrent
+            );
+        }
+        let limiter = utils::RequestLimiter::new(concurrent, per_host);
 
-        bindings = results
-            .fold(bindings, |mut bindings, r| async {
-                let sender = self.sender.clone();
-                let processed_arc = Arc::clone(&processed_channels);
-                let mut processed = processed_arc.lock();
-                *processed += 1.0;
-                sender
-                    .send(ToApp::FeedUpdateProgress {
-                        progress: *processed / channels_total,
-                    })
-                    .unwrap();
-                match r.bytes {
-                    Some(bytes) => {
-                        let feed = if let Ok(feed) = feed_rs::parser::parse(&bytes[..]) {
-                            Some(feed)
-                        } else {
-                            None
-                        };
-                        bindings.push(ChannelFeedBinding {
-                            channel: r.channel,
-                            feed,
-                        })
+        let max_feed_size = utils::max_feed_size();
+
+        sender
+            .send(ToApp::FeedUpdateProgress {
+                channels: progress.lock().clone(),
+            })
+            .unwrap();
+
+        let results = stream::iter(channels.into_iter().enumerate())
+            .map(|(index, channel)| {
+                let client = &client;
+                let pinned_client = &pinned_client;
+                let limiter = &limiter;
+                let set_state = &set_state;
+                let headers = headers
+                    .get(&channel.id)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                async move {
+                    let _permit = limiter.acquire(&channel.link).await;
+                    set_state(index, FetchState::Fetching);
+                    let bytes =
+                        fetch_channel(client, pinned_client, &channel, headers, max_feed_size)
+                            .await;
+                    ChannelBytesBinding {
+                        index,
+                        channel,
+                        bytes,
                     }
-                    None => bindings.push(ChannelFeedBinding {
-                        channel: r.channel,
-                        feed: None,
-                    }),
                 }
-                bindings
             })
-            .await;
+            // Everything is polled at once, the limiter decides how many requests actually run.
+            .buffer_unordered(channels_total as usize);
+
+        let mut results = std::pin::pin!(results);
+
+        let first_seen = chrono::Utc::now().timestamp();
+        let keywords = CONFIG.lock().blocked_keywords.clone();
+        let default_item_limit = CONFIG.lock().item_limit;
+        let backfill_count = CONFIG.lock().backfill_count;
+        let mut backfilled: Vec<String> = vec![];
+        let mut channel_errors: Vec<(String, Option<String>)> = vec![];
+        let mut inserted: Vec<(String, u64)> = vec![];
+        let mut to_cache: Vec<(String, String)> = vec![];
+        let mut new_items: Vec<Item> = vec![];
+        let mut priority_items = false;
+
+        // Items of every channel are saved and sent to the app as soon as its feed is parsed,
+        // so the feed fills up while slower channels are still downloading.
+        while let Some(r) = results.next().await {
+            let channel = r.channel;
+            let feed = match r.bytes {
+                Ok(bytes) => match feed_rs::parser::parse(&bytes[..]) {
+                    Ok(feed) => Ok(feed),
+                    Err(err) => Err(WorkerError::new(
+                        ErrorKind::Parse {
+                            channel_id: Some(channel.id.clone()),
+                        },
+                        "Failed to parse feed",
+                        err.to_string(),
+                    )),
+                },
+                Err(error) => Err(error),
+            };
+            let feed = match feed {
+                Ok(feed) => {
+                    set_state(r.index, FetchState::Parsed);
+                    feed
+                }
+                Err(error) => {
+                    set_state(r.index, FetchState::Failed(error.error_message.clone()));
+                    info!("Failed to update {}: {}", channel.link, error.error_message);
+                    channel_errors.push((channel.id.clone(), Some(error.error_message.clone())));
+                    if error.retry.is_some() {
+                        self.report(error);
+                    } else {
+                        self.report(
+                            error
+                                .warning()
+                                .with_retry(ToWorker::RefreshChannel { id: channel.id }),
+                        );
+                    }
+                    continue;
+                }
+            };
+            channel_errors.push((channel.id.clone(), None));
+
+            let mut items = feed_items(&channel, feed, first_seen);
+
+            // Feeds dumping their whole archive would otherwise flood the feed on subscribing.
+            let limit = channel
+                .item_limit
+                .map_or(default_item_limit, |limit| limit.max(0) as usize);
+            if limit > 0 && items.len() > limit {
+                info!(
+                    "Keeping the newest {} of {} items of {}",
+                    limit,
+                    items.len(),
+                    channel.link
+                );
+                items.sort_by_key(|item| std::cmp::Reverse(item.published));
+                items.truncate(limit);
+            }
 
-        info!("Finished parsing.");
+            // The backlog of a new subscription is saved as dismissed, dropping it would only
+            // bring it back with the next refresh.
+            if let Some(backfill) = channel.backfill {
+                let today =
+                    utils::local_midnight(chrono::Local::now().date_naive()).unwrap_or(first_seen);
+                items.sort_by_key(|item| std::cmp::Reverse(item.published));
+                for (index, item) in items.iter_mut().enumerate() {
+                    item.dismissed = match backfill {
+                        Backfill::All => false,
+                        Backfill::Newest => index >= backfill_count,
+                        Backfill::Today => item.published < today,
+                    };
+                }
+            }
 
-        let mut items: Vec<Item> = vec![];
+            if !keywords.is_empty() {
+                let (blocked, kept): (Vec<Item>, Vec<Item>) = items
+                    .into_iter()
+                    .partition(|item| rules::blocks_item(&keywords, item));
+                items = kept;
+                let ids = blocked.into_iter().map(|item| item.id).collect();
+                match db::add_blocked_items(ids, first_seen).await {
+                    Ok(count) if count > 0 => {
+                        info!("Blocked {} new items.", count);
+                        CONFIG.lock().blocked_items_count += count;
+                        // Shown as a statistic, so it shouldn't be lost if the app crashes.
+                        if let Err(err) = ConfigBuilder::from_current().save() {
+                            error!("Failed to save config: {}", err.to_string());
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(err) => self.report_error(
+                        ErrorKind::Database,
+                        "Failed to save blocked items",
+                        err.to_string(),
+                    ),
+                }
+            }
 
-        for binding in bindings {
-            if binding.feed.is_none() {
-                continue;
+            match db::update_items(&channel.id, &items).await {
+                Ok(0) => {}
+                Ok(changed) => info!("Updated {} items of {}.", changed, channel.link),
+                Err(err) => self.report_error(
+                    ErrorKind::Database,
+                    "Failed to save updated feed items",
+                    err.to_string(),
+                ),
             }
-            let channel = binding.channel;
-            let feed = binding.feed.unwrap();
-            for entry in feed.entries {
-                let mut item = Item {
-                    id: entry.id,
-                    channel_title: channel.title.clone(),
-                    channel: channel.id.clone(),
-                    dismissed: false,
-                    ..Default::default()
-                };
 
-                if entry.links.is_empty() {
-                    item.link = entry.links[0].href.clone();
-                } else {
-                    item.link = "<no link>".to_string();
+            // A failed save keeps the backfill, so the next refresh applies it again.
+            let added = db::add_items(items).await;
+            if added.is_ok() && channel.backfill.is_some() {
+                backfilled.push(channel.id.clone());
+            }
+            match added {
+                Ok(items) if items.is_empty() => {}
+                Ok(mut items) => {
+                    let title = channel.title.clone().unwrap_or(channel.link.clone());
+                    let fresh = items.iter().filter(|item| !item.dismissed).count() as u64;
+                    if fresh > 0 {
+                        inserted.push((title, fresh));
+                    }
+                    if channel.priority {
+                        priority_items |= fresh > 0;
+                        items.iter_mut().for_each(|item| item.priority = true);
+                    }
+                    if channel.cache_articles {
+                        to_cache.extend(
+                            items
+                                .iter()
+                                .filter(|item| item.content.is_none())
+                                .map(|item| (item.id.clone(), item.link.clone())),
+                        );
+                    }
+                    new_items.extend(items.iter().filter(|item| !item.dismissed).cloned());
+                    self.sender.send(ToApp::NewItems { items }).unwrap();
+                    self.egui_ctx.request_repaint();
                 }
+                Err(err) => self.report_error(
+                    ErrorKind::Database,
+                    "Failed to save new feed items",
+                    err.to_string(),
+                ),
+            }
+        }
 
-                item.title = match entry.title {
-                    Some(text) => Some(text.content),
-                    None => None,
-                };
+        info!("Finished parsing.");
 
-                item.summary = match entry.summary {
-                    Some(text) => Some(text.content),
-                    None => None,
-                };
+        if let Err(err) = db::clear_backfill(&backfilled).await {
+            self.report_error(
+                ErrorKind::Database,
+                "Failed to save first fetches",
+                err.to_string(),
+            );
+        }
 
-                if entry.published.is_some() {
-                    item.published = entry.published.unwrap().timestamp()
-                } else if entry.updated.is_some() {
-                    item.published = entry.updated.unwrap().timestamp()
-                } else {
-                    item.published = 0;
-                }
+        // Servers may have refreshed session cookies while answering.
+        self.save_cookies();
 
-                items.push(item);
-            }
+        if let Err(err) = db::set_channel_errors(channel_errors, first_seen).await {
+            self.report_error(
+                ErrorKind::Database,
+                "Failed to save channel errors",
+                err.to_string(),
+            );
+        } else {
+            self.update_channel_list().await;
         }
 
-        info!(
-            "Saving retrieved items to database (amount: {})",
-            items.len()
-        );
+        inserted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let total = inserted.iter().map(|(_, count)| count).sum();
+        info!("New items inserted: {}", total);
+        self.sender
+            .send(ToApp::RefreshSummary {
+                summary: RefreshSummary {
+                    total,
+                    channels: inserted,
+                },
+            })
+            .unwrap();
 
-        if let Err(err) = db::add_items(items).await {
-            self.report_error("Failed to save new feed items", err.to_string())
-        };
+        if priority_items && CONFIG.lock().priority_sound {
+            utils::play_alert_sound();
+        }
+
+        self.deliver_new_items(new_items);
+
+        if !to_cache.is_empty() {
+            self.spawn(Operation::CacheArticles, |mut worker| async move {
+                worker.cache_articles(to_cache).await;
+            });
+        }
 
+        self.refreshed_at
+            .store(chrono::Utc::now().timestamp(), Ordering::Relaxed);
         info!("Feed update finished.");
     }
 
+    /// Hands new items to the configured webhooks, push service and chats in the
+    /// background, as retries may take minutes.
+    fn deliver_new_items(&mut self, items: Vec<Item>) {
+        let config = ConfigBuilder::from_current();
+        let webhooks = config.webhooks.clone();
+        let push_url = config.push_url.trim().to_string();
+        let push_enabled = config.push_service != PushService::Off && !push_url.is_empty();
+        let forward_enabled = !config.forward_channels.is_empty()
+            && ChatService::ALL
+                .into_iter()
+                .any(|service| config.chat_configured(service));
+        let command_enabled = config.new_item_command_enabled();
+        if items.is_empty()
+            || (!push_enabled
+                && !forward_enabled
+                && !command_enabled
+                && webhooks.iter().all(|webhook| webhook.url.trim().is_empty()))
+        {
+            return;
+        }
+        let client = match self.http_clients() {
+            Some(clients) => clients.plain,
+            None => return,
+        };
+        self.spawn(Operation::Deliver, |mut worker| async move {
+            for Webhook { url, filter } in webhooks {
+                let url = url.trim();
+                // Chat services put their credentials into webhook urls, only the host is shown.
+                let host = reqwest::Url::parse(url)
+                    .ok()
+                    .and_then(|url| url.host_str().map(str::to_string))
+                    .unwrap_or_else(|| "a webhook".to_string());
+                let matching: Vec<&Item> = items
+                    .iter()
+                    .filter(|item| integrations::matches(&filter, item))
+                    .collect();
+                if url.is_empty() || matching.is_empty() {
+                    continue;
+                }
+                match integrations::post_webhook(&client, url, &matching).await {
+                    Ok(()) => info!("Posted {} new items to {}.", matching.len(), host),
+                    Err(err) => worker.report_error(
+                        ErrorKind::Network {
+                            channel_id: None,
+                            status: None,
+                        },
+                        format!("Failed to post new items to {}", host),
+                        err,
+                    ),
+                }
+            }
+            let matching: Vec<&Item> = items
+                .iter()
+                .filter(|item| integrations::matches(&config.push_filter, item))
+                .collect();
+            if push_enabled && !matching.is_empty() {
+                if let Err(err) = integrations::push_notifications(
+                    &client,
+                    config.push_service,
+                    &push_url,
+                    &config.push_token,
+                    &matching,
+                )
+                .await
+                {
+                    worker.report_error(
+                        ErrorKind::Network {
+                            channel_id: None,
+                            status: None,
+                        },
+                        "Failed to push notifications",
+                        err,
+                    );
+                }
+            }
+            let forwarded: Vec<&Item> = items
+                .iter()
+                .filter(|item| {
+                    config.forward_channels.contains(&item.channel)
+                        && integrations::matches(&config.forward_filter, item)
+                })
+                .collect();
+            if forward_enabled && !forwarded.is_empty() {
+                if let Err(err) = integrations::forward(&client, &config, &forwarded).await {
+                    worker.report_error(
+                        ErrorKind::Network {
+                            channel_id: None,
+                            status: None,
+                        },
+                        "Failed to forward new items",
+                        err,
+                    );
+                }
+            }
+            let matching: Vec<&Item> = items
+                .iter()
+                .filter(|item| integrations::matches(&config.new_item_command_filter, item))
+                .collect();
+            if command_enabled && !matching.is_empty() {
+                if let Err(err) =
+                    integrations::run_command(&config.new_item_command, &matching).await
+                {
+                    worker.report_error(
+                        ErrorKind::System,
+                        "Failed to run the new item command",
+                        err,
+                    );
+                }
+            }
+        });
+    }
+
     async fn update_feed(&mut self) {
         let items = match db::get_all_items().await {
             Ok(items) => items,
             Err(err) => {
-                self.report_error("Failed to fetch items from db", err.to_string());
+                self.report_error(
+                    ErrorKind::Database,
+                    "Failed to fetch items from db",
+                    err.to_string(),
+                );
                 return;
             }
         };
 
-        self.sender.send(ToApp::UpdateFeed { items }).unwrap();
+        self.republish().await;
+
+        let scores = scoring::score_items(&items, chrono::Utc::now().timestamp());
+        self.sender
+            .send(ToApp::UpdateFeed { items, scores })
+            .unwrap();
+
+        self.send_statistics().await;
+    }
+
+    async fn send_statistics(&mut self) {
+        match db::get_statistics().await {
+            Ok((items, new, failing_channels)) => {
+                let statistics = Statistics {
+                    refreshed_at: self.refreshed_at.load(Ordering::Relaxed),
+                    items,
+                    new,
+                    failing_channels,
+                };
+                self.sender.send(ToApp::Statistics { statistics }).unwrap();
+            }
+            Err(err) => self.report_error(
+                ErrorKind::Database,
+                "Failed to count items",
+                err.to_string(),
+            ),
+        }
+    }
+
+    async fn republish(&mut self) {
+        let config = ConfigBuilder::from_current();
+        let path = match config.republish_file {
+            Some(path) => path,
+            None => return,
+        };
+        let items = match db::get_all_items().await {
+            Ok(items) => digest::republished_items(items, config.republished_items),
+            Err(err) => {
+                self.report_error(
+                    ErrorKind::Database,
+                    "Failed to fetch items from db",
+                    err.to_string(),
+                );
+                return;
+            }
+        };
+        let xml = digest::render_rss("Tinyrss", &items);
+        // Readers polling the file by its modification time shouldn't refetch it for nothing.
+        if std::fs::read(&path).is_ok_and(|written| written == xml.as_bytes()) {
+            return;
+        }
+        if let Err(err) = std::fs::write(path, xml) {
+            self.report_error(
+                ErrorKind::Filesystem,
+                "Failed to write republished feed",
+                err.to_string(),
+            );
+        }
     }
 
     async fn set_dismissed(&mut self, id: &str, dismissed: bool) {
         if let Err(err) = db::set_dismissed(id, dismissed).await {
-            self.report_error("Falied to set dismissed", err.to_string());
+            self.report_error(
+                ErrorKind::Database,
+                "Falied to set dismissed",
+                err.to_string(),
+            );
+        } else {
+            self.record_changes(&[(id.to_string(), sync::Change::Dismissed(dismissed))]);
         }
     }
 
-    async fn dismiss_all(&mut self) {
-        if let Err(err) = db::dismiss_all().await {
-            self.report_error("Falied to dismiss all", err.to_string());
+    async fn set_starred(&mut self, id: &str, starred: bool) {
+        if let Err(err) = db::set_starred(id, starred).await {
+            self.report_error(
+                ErrorKind::Database,
+                "Falied to set starred",
+                err.to_string(),
+            );
+        } else {
+            self.record_changes(&[(id.to_string(), sync::Change::Starred(starred))]);
         }
     }
 
-    async fn unsubscribe(&mut self, id: &str) {
-        if let Err(err) = db::unsubscribe(id).await {
-            self.report_error("Falied to unsubscribe", err.to_string());
+    async fn set_pinned(&mut self, id: &str, pinned: bool) {
+        if let Err(err) = db::set_pinned(id, pinned).await {
+            self.report_error(ErrorKind::Database, "Failed to set pinned", err.to_string());
         }
     }
 
-    async fn import_channels(&mut self, path: Option<PathBuf>) {
-        if let Some(file_handle) = path {
-            if !utils::is_online().await {
-                self.report_error("No internet connection", "");
-                return;
-            }
+    async fn set_later(&mut self, id: &str, later: bool) {
+        if let Err(err) = db::set_later(id, later).await {
+            self.report_error(
+                ErrorKind::Database,
+                "Falied to set read later",
+                err.to_string(),
+            );
+        } else {
+            self.record_changes(&[(id.to_string(), sync::Change::Later(later))]);
+        }
+    }
 
-            let xml = match std::fs::read_to_string(file_handle) {
-                Ok(string) => string,
-                Err(err) => {
-                    self.report_error("Failed to read file", err.to_string());
-                    return;
-                }
-            };
-            let opml = match opml::OPML::from_str(&xml) {
-                Ok(opml) => opml,
-                Err(err) => {
-                    self.report_error("Failed to parse xml", err.to_string());
-                    return;
-                }
-            };
-            let mut links: Vec<String> = vec![];
-            for outline in opml.body.outlines {
-                links.append(&mut self.traverse_outlines(outline).await);
-            }
-            info!("Amount of links collected: {}", links.len());
-            self.add_channels(links).await;
+    async fn set_dismissed_many(&mut self, ids: Vec<String>, dismissed: bool) {
+        if let Err(err) = db::set_dismissed_many(&ids, dismissed).await {
+            self.report_error(
+                ErrorKind::Database,
+                "Failed to set dismissed",
+                err.to_string(),
+            );
+        } else {
+            let changes: Vec<(String, sync::Change)> = ids
+                .into_iter()
+                .map(|id| (id, sync::Change::Dismissed(dismissed)))
+                .collect();
+            self.record_changes(&changes);
         }
     }
 
-    #[async_recursion::async_recursion]
-    #[allow(clippy::only_used_in_recursion)]
-    async fn traverse_outlines(&mut self, root_outline: opml::Outline) -> Vec<String> {
-        let mut links: Vec<String> = vec![];
-        for outline in root_outline.outlines {
-            links.append(&mut self.traverse_outlines(outline).await);
+    async fn set_starred_many(&mut self, ids: Vec<String>, starred: bool) {
+        if let Err(err) = db::set_starred_many(&ids, starred).await {
+            self.report_error(
+                ErrorKind::Database,
+                "Failed to set starred",
+                err.to_string(),
+            );
+        } else {
+            let changes: Vec<(String, sync::Change)> = ids
+                .into_iter()
+                .map(|id| (id, sync::Change::Starred(starred)))
+                .collect();
+            self.record_changes(&changes);
         }
-        if let Some(link) = root_outline.xml_url {
-            links.push(link);
-        };
-        links
     }
 
-    async fn export_channels(&mut self) {
-        let file_handle = rfd::AsyncFileDialog::new()
-            .add_filter("OPML", &["xml"])
-            .save_file()
-            .await;
-        if let Some(file_handle) = file_handle {
-            let xml = r#"<opml version="2.0"><head/><body><outline text="Outline"/></body></opml>"#;
-            let mut opml = match opml::OPML::from_str(xml) {
-                Ok(opml) => opml,
+    async fn dismiss_all(&mut self) {
+        let dismissed: Vec<Item> = match db::get_all_items().await {
+            Ok(items) => items
+                .into_iter()
+                .filter(|item| !item.dismissed && !item.later)
+                .collect(),
+            Err(_) => vec![],
+        };
+        if let Err(err) = db::dismiss_all().await {
+            self.report_error(
+                ErrorKind::Database,
+                "Falied to dismiss all",
+                err.to_string(),
+            );
+        } else {
+            self.empty_trash();
+            let changes: Vec<(String, sync::Change)> = dismissed
+                .iter()
+                .map(|item| (item.id.clone(), sync::Change::Dismissed(true)))
+                .collect();
+            self.record_changes(&changes);
+            let amount = dismissed.len();
+            *self.trash.lock() = Some(Trash {
+                changed: dismissed
+                    .iter()
+                    .filter(|item| item.changed)
+                    .map(|item| item.id.clone())
+                    .collect(),
+                ids: dismissed.into_iter().map(|item| item.id).collect(),
+                until: chrono::Utc::now().timestamp() + TRASH_SECONDS,
+            });
+            self.sender
+                .send(ToApp::Trash {
+                    amount: Some(amount),
+                })
+                .unwrap();
+        }
+    }
+
+    async fn undo_dismiss_all(&mut self) {
+        let trash = self.trash.lock().take();
+        if let Some(trash) = trash {
+            if let Err(err) = db::restore_dismissed(&trash.ids, &trash.changed).await {
+                self.report_error(
+                    ErrorKind::Database,
+                    "Failed to restore dismissed items",
+                    err.to_string(),
+                );
+            } else {
+                let changes: Vec<(String, sync::Change)> = trash
+                    .ids
+                    .into_iter()
+                    .map(|id| (id, sync::Change::Dismissed(false)))
+                    .collect();
+                self.record_changes(&changes);
+            }
+        }
+        self.sender.send(ToApp::Trash { amount: None }).unwrap();
+    }
+
+    /// Makes the items of the last "Dismiss all" regular dismissed items.
+    fn empty_trash(&mut self) {
+        if self.trash.lock().take().is_some() {
+            // On shutdown the app may be gone already.
+            let _ = self.sender.send(ToApp::Trash { amount: None });
+        }
+    }
+
+    async fn dismiss_matching(&mut self, ids: Vec<String>, later: bool) {
+        match db::dismiss_matching(&ids, later).await {
+            Ok(dismissed) => {
+                info!("Dismissed {} matching items.", dismissed.len());
+                let dismissed: Vec<(String, sync::Change)> = dismissed
+                    .into_iter()
+                    .map(|id| (id, sync::Change::Dismissed(true)))
+                    .collect();
+                self.record_changes(&dismissed);
+            }
+            Err(err) => self.report_error(
+                ErrorKind::Database,
+                "Failed to dismiss matching items",
+                err.to_string(),
+            ),
+        }
+    }
+
+    fn record_changes(&mut self, changes: &[(String, sync::Change)]) {
+        // Items dismissed or restored on their own leave the trash with their new state.
+        if let Some(trash) = self.trash.lock().as_mut() {
+            let handled: HashSet<&String> = changes
+                .iter()
+                .filter(|(_, change)| matches!(change, sync::Change::Dismissed(_)))
+                .map(|(id, _)| id)
+                .collect();
+            trash.ids.retain(|id| !handled.contains(id));
+        }
+        self.webdav_changed
+            .lock()
+            .extend(changes.iter().map(|(id, _)| id.clone()));
+        if let Err(err) = sync::record(changes) {
+            self.report_error(
+                ErrorKind::Sync,
+                "Failed to write sync journal",
+                err.to_string(),
+            );
+        }
+    }
+
+    /// Applies item state changes made on other machines, read from the sync folder.
+    async fn merge_sync_journals(&mut self) {
+        let changes = match sync::pending_changes() {
+            Ok(changes) => changes,
+            Err(err) => {
+                self.report_error(
+                    ErrorKind::Sync,
+                    "Failed to read sync journals",
+                    err.to_string(),
+                );
+                return;
+            }
+        };
+        for (id, change) in &changes {
+            let result = match *change {
+                sync::Change::Dismissed(dismissed) => db::set_dismissed(id, dismissed).await,
+                sync::Change::Starred(starred) => db::set_starred(id, starred).await,
+                sync::Change::Later(later) => db::set_later(id, later).await,
+            };
+            if let Err(err) = result {
+                self.report_error(
+                    ErrorKind::Sync,
+                    "Failed to apply synced changes",
+                    err.to_string(),
+                );
+                return;
+            }
+        }
+        if !changes.is_empty() {
+            info!("Merged {} changes from sync journals.", changes.len());
+        }
+    }
+
+    async fn unsubscribe(&mut self, id: &str) {
+        let mode = CONFIG.lock().unsubscribe_mode;
+        let channel = self.find_channel(id).await;
+        let (result, kind) = match mode {
+            UnsubscribeMode::Delete => (db::unsubscribe(id).await, db::EventKind::Unsubscribed),
+            UnsubscribeMode::Archive => (db::set_archived(id, true).await, db::EventKind::Archived),
+        };
+        if let Err(err) = result {
+            self.report_error(
+                ErrorKind::Database,
+                "Falied to unsubscribe",
+                err.to_string(),
+            );
+        } else if let Some(channel) = channel {
+            self.record_events(&[db::Event::new(kind, &channel, None)])
+                .await;
+        }
+    }
+
+    async fn import_channels(&mut self, path: Option<PathBuf>) {
+        if let Some(file_handle) = path {
+            if !utils::is_online().await {
+                self.report(
+                    WorkerError::new(ErrorKind::Offline, "No internet connection", "")
+                        .warning()
+                        .with_retry(ToWorker::ImportChannels {
+                            path: Some(file_handle),
+                        }),
+                );
+                return;
+            }
+
+            let xml = match std::fs::read_to_string(file_handle) {
+                Ok(string) => string,
                 Err(err) => {
-                    self.report_error("Failed to parse xml", err.to_string());
+                    self.report_error(
+                        ErrorKind::Filesystem,
+                        "Failed to read file",
+                        err.to_string(),
+                    );
                     return;
                 }
             };
+            if let Some(links) = self.opml_links(&xml).await {
+                self.add_channels(links, "OPML import").await;
+            }
+        }
+    }
+
+    async fn import_channels_from_url(&mut self, url: String, keep_synced: bool) {
+        if !utils::is_online().await {
+            self.report(
+                WorkerError::new(ErrorKind::Offline, "No internet connection", "")
+                    .warning()
+                    .with_retry(ToWorker::ImportChannelsFromUrl { url, keep_synced }),
+            );
+            return;
+        }
+        let xml = match self.fetch_opml(&url).await {
+            Ok(xml) => xml,
+            Err(err) => {
+                self.report(err);
+                return;
+            }
+        };
+        let links = match self.opml_links(&xml).await {
+            Some(links) => links,
+            None => return,
+        };
+        if keep_synced {
+            let mut config = CONFIG.lock();
+            config.opml_subscriptions.retain(|list| list.url != url);
+            config.opml_subscriptions.push(OpmlSubscription {
+                url: url.clone(),
+                known_links: links.clone(),
+            });
+        }
+        self.add_channels(links, &format!("OPML list {}", url))
+            .await;
+    }
+
+    async fn fetch_opml(&mut self, url: &str) -> Result<String, WorkerError> {
+        let client = self.http_client().ok_or_else(|| {
+            WorkerError::new(ErrorKind::System, "Failed to fetch OPML", "No HTTP client")
+        })?;
+        let network_error = |err: String| {
+            WorkerError::new(
+                ErrorKind::Network {
+                    channel_id: None,
+                    status: None,
+                },
+                "Failed to fetch OPML",
+                format!("{}: {}", url, err),
+            )
+        };
+        let resp = client
+            .get(url)
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status())
+            .map_err(|err| network_error(err.to_string()))?;
+        let body = utils::read_body(resp, utils::max_feed_size())
+            .await
+            .map_err(network_error)?;
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+
+    /// Feed links of every outline in the OPML document, `None` if it can't be parsed.
+    async fn opml_links(&mut self, xml: &str) -> Option<Vec<String>> {
+        let opml = match opml::OPML::from_str(xml) {
+            Ok(opml) => opml,
+            Err(err) => {
+                self.report_error(
+                    ErrorKind::Parse { channel_id: None },
+                    "Failed to parse xml",
+                    err.to_string(),
+                );
+                return None;
+            }
+        };
+        let mut links: Vec<String> = vec![];
+        for outline in opml.body.outlines {
+            links.append(&mut self.traverse_outlines(outline).await);
+        }
+        info!("Amount of links collected: {}", links.len());
+        Some(links)
+    }
+
+    /// Subscribes to channels that appeared on synced OPML lists since the last check.
+    /// Channels that left a list are kept.
+    async fn sync_opml_subscriptions(&mut self) {
+        let lists = CONFIG.lock().opml_subscriptions.clone();
+        let mut added = vec![];
+        for list in lists {
+            let xml = match self.fetch_opml(&list.url).await {
+                Ok(xml) => xml,
+                Err(err) => {
+                    self.report(err.warning());
+                    continue;
+                }
+            };
+            let links = match self.opml_links(&xml).await {
+                Some(links) => links,
+                None => continue,
+            };
+            added.extend(
+                links
+                    .iter()
+                    .filter(|link| {
+                        !list
+                            .known_links
+                            .iter()
+                            .any(|known| utils::same_link(known, link))
+                    })
+                    .cloned(),
+            );
+            if let Some(list) = CONFIG
+                .lock()
+                .opml_subscriptions
+                .iter_mut()
+                .find(|synced| synced.url == list.url)
+            {
+                list.known_links = links;
+            }
+        }
+        CONFIG.lock().last_opml_sync = chrono::Utc::now().timestamp();
+        if !added.is_empty() {
+            info!("Synced OPML lists added {} links.", added.len());
+            self.add_channels(added, "Synced OPML list").await;
+            self.update_channel_list().await;
+        }
+    }
+
+    #[async_recursion::async_recursion]
+    #[allow(clippy::only_used_in_recursion)]
+    async fn traverse_outlines(&mut self, root_outline: opml::Outline) -> Vec<String> {
+        let mut links: Vec<String> = vec![];
+        for outline in root_outline.outlines {
+            links.append(&mut self.traverse_outlines(outline).await);
+        }
+        if let Some(link) = root_outline.xml_url {
+            links.push(link);
+        };
+        links
+    }
+
+    async fn export_channels(&mut self) {
+        let file_handle = rfd::AsyncFileDialog::new()
+            .add_filter("OPML", &["xml"])
+            .save_file()
+            .await;
+        if let Some(file_handle) = file_handle {
             let channels = match db::get_all_channels().await {
                 Ok(channels) => channels,
                 Err(err) => {
-                    self.report_error("Failed to fetch channel from db", err.to_string());
+                    self.report_error(
+                        ErrorKind::Database,
+                        "Failed to fetch channel from db",
+                        err.to_string(),
+                    );
                     return;
                 }
             };
 
-            let mut group = opml::Outline::default();
+            let xml = match utils::render_opml(&channels) {
+                Ok(xml) => xml,
+                Err(err) => {
+                    self.report_error(
+                        ErrorKind::Filesystem,
+                        "Failed to render xml",
+                        err.to_string(),
+                    );
+                    return;
+                }
+            };
 
-            for channel in channels {
-                group.add_feed(
-                    &channel.title.unwrap_or("Unknown".to_string()),
-                    &channel.link,
+            if let Err(err) = std::fs::write(file_handle.path(), xml) {
+                self.report_error(
+                    ErrorKind::Filesystem,
+                    "Failed to write file",
+                    err.to_string(),
                 );
-            }
-
-            opml.body.outlines.push(group);
+            };
+        };
+    }
 
-            let mut file = match std::fs::File::create(file_handle.path()) {
-                Ok(file) => file,
+    async fn export_starred(&mut self) {
+        let file_handle = rfd::AsyncFileDialog::new()
+            .add_filter("HTML", &["html"])
+            .set_file_name("starred.html")
+            .save_file()
+            .await;
+        if let Some(file_handle) = file_handle {
+            let items = match db::get_starred_items().await {
+                Ok(items) => items,
                 Err(err) => {
-                    self.report_error("Failed to create file", err.to_string());
+                    self.report_error(
+                        ErrorKind::Database,
+                        "Failed to fetch items from db",
+                        err.to_string(),
+                    );
                     return;
                 }
             };
-            if let Err(err) = opml.to_writer(&mut file) {
-                self.report_error("Failed to write file", err.to_string());
+            let html = digest::render_html("Starred items", &items);
+            if let Err(err) = std::fs::write(file_handle.path(), html) {
+                self.report_error(
+                    ErrorKind::Filesystem,
+                    "Failed to write file",
+                    err.to_string(),
+                );
+            } else {
+                info!("Exported {} starred items.", items.len());
+            }
+        }
+    }
+
+    async fn export_archive(&mut self) {
+        let file_handle = rfd::AsyncFileDialog::new()
+            .add_filter("Zip", &["zip"])
+            .set_file_name("tinyrss-export.zip")
+            .save_file()
+            .await;
+        if let Some(file_handle) = file_handle {
+            if let Err(err) = archive::export(file_handle.path()).await {
+                self.report_error(
+                    ErrorKind::Filesystem,
+                    "Failed to export application data",
+                    err.to_string(),
+                );
+            } else {
+                info!("Exported application data.");
+            }
+        }
+    }
+
+    async fn save_database_copy(&mut self) {
+        let file_handle = rfd::AsyncFileDialog::new()
+            .add_filter("SQLite database", &["db"])
+            .set_file_name(format!(
+                "tinyrss-{}.db",
+                chrono::Local::now().format("%Y-%m-%d")
+            ))
+            .save_file()
+            .await;
+        if let Some(file_handle) = file_handle {
+            let path = file_handle.path();
+            // The dialog asked before overwriting, an older copy must not be mixed in.
+            if path.exists() {
+                if let Err(err) = std::fs::remove_file(path) {
+                    self.report_error(
+                        ErrorKind::Filesystem,
+                        "Failed to replace database copy",
+                        err.to_string(),
+                    );
+                    return;
+                }
+            }
+            let path = path.to_path_buf();
+            self.spawn(Operation::SaveDatabaseCopy, |mut worker| async move {
+                if let Err(err) = db::backup_to(&path).await {
+                    worker.report_error(
+                        ErrorKind::Database,
+                        "Failed to save database copy",
+                        err.to_string(),
+                    );
+                } else {
+                    info!("Saved database copy.");
+                }
+            });
+        }
+    }
+
+    async fn export_json(&mut self) {
+        let file_handle = rfd::AsyncFileDialog::new()
+            .add_filter("JSON", &["json"])
+            .set_file_name("tinyrss-items.json")
+            .save_file()
+            .await;
+        if let Some(file_handle) = file_handle {
+            match archive::export_json(file_handle.path()).await {
+                Ok(amount) => info!("Exported {} items as JSON.", amount),
+                Err(err) => self.report_error(
+                    ErrorKind::Filesystem,
+                    "Failed to export items",
+                    err.to_string(),
+                ),
+            }
+        }
+    }
+
+    async fn import_json(&mut self, path: &Path) {
+        match archive::import_json(path).await {
+            Ok((channels, items)) => {
+                info!("Imported {} channels and {} items.", channels, items)
+            }
+            Err(err) => self.report_error(
+                ErrorKind::Filesystem,
+                "Failed to import items",
+                err.to_string(),
+            ),
+        }
+    }
+
+    fn import_archive(&mut self, path: PathBuf) {
+        if let Err(err) = archive::import(&path) {
+            self.report_error(
+                ErrorKind::Filesystem,
+                "Failed to import application data",
+                err.to_string(),
+            );
+        } else {
+            info!("Imported application data, the database is replaced on the next start.");
+        }
+    }
+
+    /// Pulls subscriptions and item state uploaded by other machines, then uploads ours.
+    /// Remote state wins, except for items changed here since the last sync.
+    async fn webdav_sync(&mut self) {
+        let config = ConfigBuilder::from_current();
+        if config.webdav_url.is_empty() {
+            return;
+        }
+        let device = sync::device_id();
+        let client = match self.http_client() {
+            Some(client) => client,
+            None => return,
+        };
+        // Items changed while the sync runs are left for the next one.
+        let changed = self.webdav_changed.lock().clone();
+
+        let (opml, snapshot) = match webdav::download(&client, &config).await {
+            Ok(remote) => remote,
+            Err(err) => {
+                self.report(
+                    WorkerError::new(
+                        ErrorKind::Sync,
+                        "Failed to download from WebDAV",
+                        err.to_string(),
+                    )
+                    .with_retry(ToWorker::WebdavSync),
+                );
+                return;
+            }
+        };
+
+        let channels = match db::get_all_channels().await {
+            Ok(channels) => channels,
+            Err(err) => {
+                self.report_error(
+                    ErrorKind::Database,
+                    "Failed to fetch channel from db",
+                    err.to_string(),
+                );
+                return;
+            }
+        };
+        if let Some(opml) = opml.and_then(|xml| opml::OPML::from_str(&xml).ok()) {
+            let mut links: Vec<String> = vec![];
+            for outline in opml.body.outlines {
+                links.append(&mut self.traverse_outlines(outline).await);
+            }
+            links.retain(|link| !channels.iter().any(|channel| &channel.link == link));
+            if !links.is_empty() {
+                info!("Subscribing to {} channels from WebDAV.", links.len());
+                self.add_channels(links, "WebDAV sync").await;
+            }
+        }
+
+        let items = match db::get_all_items().await {
+            Ok(items) => items,
+            Err(err) => {
+                self.report_error(
+                    ErrorKind::Database,
+                    "Failed to fetch items from db",
+                    err.to_string(),
+                );
+                return;
+            }
+        };
+        let mut states: HashMap<String, webdav::ItemState> = items
+            .into_iter()
+            .map(|item| {
+                let state = webdav::ItemState {
+                    id: item.id.clone(),
+                    dismissed: item.dismissed,
+                    starred: item.starred,
+                    later: item.later,
+                };
+                (item.id, state)
+            })
+            .collect();
+
+        if let Some(snapshot) = snapshot {
+            if snapshot.device != device && snapshot.time > config.webdav_last_sync {
+                for remote in snapshot.items {
+                    if changed.contains(&remote.id) {
+                        continue;
+                    }
+                    let local = match states.get_mut(&remote.id) {
+                        Some(local) => local,
+                        None => continue,
+                    };
+                    let result = async {
+                        if local.dismissed != remote.dismissed {
+                            db::set_dismissed(&remote.id, remote.dismissed).await?;
+                        }
+                        if local.starred != remote.starred {
+                            db::set_starred(&remote.id, remote.starred).await?;
+                        }
+                        if local.later != remote.later {
+                            db::set_later(&remote.id, remote.later).await?;
+                        }
+                        sqlx::Result::Ok(())
+                    }
+                    .await;
+                    if let Err(err) = result {
+                        self.report_error(
+                            ErrorKind::Sync,
+                            "Failed to apply synced changes",
+                            err.to_string(),
+                        );
+                        return;
+                    }
+                    *local = remote;
+                }
+            }
+        }
+
+        let channels = db::get_all_channels().await.unwrap_or(channels);
+        let opml = match utils::render_opml(&channels) {
+            Ok(xml) => xml,
+            Err(err) => {
+                self.report_error(
+                    ErrorKind::Filesystem,
+                    "Failed to render xml",
+                    err.to_string(),
+                );
+                return;
+            }
+        };
+        let now = chrono::Utc::now().timestamp();
+        let snapshot = webdav::Snapshot {
+            device,
+            time: now,
+            items: states.into_values().collect(),
+        };
+        if let Err(err) = webdav::upload(&client, &config, opml, &snapshot).await {
+            self.report(
+                WorkerError::new(
+                    ErrorKind::Sync,
+                    "Failed to upload to WebDAV",
+                    err.to_string(),
+                )
+                .with_retry(ToWorker::WebdavSync),
+            );
+            return;
+        }
+
+        self.webdav_changed
+            .lock()
+            .retain(|id| !changed.contains(id));
+        CONFIG.lock().webdav_last_sync = now;
+        info!("WebDAV sync finished.");
+    }
+
+    async fn search_items(&mut self, query: &str, generation: u64) {
+        let ids = match db::search_items(query).await {
+            Ok(ids) => ids,
+            Err(err) => {
+                self.report_error(
+                    ErrorKind::Database,
+                    "Failed to search items",
+                    err.to_string(),
+                );
+                return;
+            }
+        };
+        self.sender
+            .send(ToApp::SearchResults { generation, ids })
+            .unwrap();
+    }
+
+    async fn search_history(&mut self, query: String) {
+        match db::search_history(&query).await {
+            Ok((entries, total)) => self
+                .sender
+                .send(ToApp::History {
+                    query,
+                    entries,
+                    total,
+                })
+                .unwrap(),
+            Err(err) => self.report_error(
+                ErrorKind::Database,
+                "Failed to search history",
+                err.to_string(),
+            ),
+        }
+    }
+
+    /// Counts the items published on each day of the month, in local time.
+    async fn load_calendar(&mut self, year: i32, month: u32) {
+        let first = chrono::NaiveDate::from_ymd_opt(year, month, 1);
+        let next = first.and_then(|first| first.checked_add_months(chrono::Months::new(1)));
+        let (Some(from), Some(to)) = (
+            first.and_then(utils::local_midnight),
+            next.and_then(utils::local_midnight),
+        ) else {
+            return;
+        };
+        match db::count_items_by_day(from, to).await {
+            Ok(days) => {
+                let counts = days
+                    .into_iter()
+                    .filter_map(|(day, count)| {
+                        let date = chrono::NaiveDate::parse_from_str(&day, "%Y-%m-%d").ok()?;
+                        Some((date.day(), count))
+                    })
+                    .collect();
+                self.sender
+                    .send(ToApp::Calendar {
+                        year,
+                        month,
+                        counts,
+                    })
+                    .unwrap();
+            }
+            Err(err) => self.report_error(
+                ErrorKind::Database,
+                "Failed to count items per day",
+                err.to_string(),
+            ),
+        }
+    }
+
+    /// Whether the connection counts as metered, see [`MeteredMode`].
+    async fn is_metered(&self) -> bool {
+        let mode = CONFIG.lock().metered_mode;
+        match mode {
+            MeteredMode::Always => true,
+            MeteredMode::Never => false,
+            MeteredMode::Detect => tokio::task::spawn_blocking(utils::detect_metered)
+                .await
+                .ok()
+                .flatten()
+                .unwrap_or(false),
+        }
+    }
+
+    /// Runs the scheduled tasks every [`SCHEDULER_INTERVAL`] on a clone, however busy the
+    /// message loop is. It stops with the runtime when the profile is switched.
+    fn start_scheduler(&self) {
+        let mut worker = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(SCHEDULER_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            // The first tick completes right away, startup has just refreshed.
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                worker.run_scheduled_tasks().await;
+            }
+        });
+    }
+
+    async fn run_scheduled_tasks(&mut self) {
+        // Operations still running from an earlier tick haven't updated their timestamps yet.
+        let config = ConfigBuilder::from_current();
+        let trash_expired = self
+            .trash
+            .lock()
+            .as_ref()
+            .is_some_and(|trash| chrono::Utc::now().timestamp() >= trash.until);
+        if trash_expired {
+            self.empty_trash();
+        }
+        let since_refresh =
+            chrono::Utc::now().timestamp() - self.last_refresh.load(Ordering::Relaxed);
+        let due = |minutes: u32| minutes > 0 && since_refresh >= minutes as i64 * 60;
+        let metered_due = config.metered_refresh_minutes > 0
+            && due(config
+                .metered_refresh_minutes
+                .max(config.auto_refresh_minutes));
+        // The connection is only looked at once a refresh is due anyway.
+        if due(config.auto_refresh_minutes)
+            && !self.is_running(Operation::Refresh)
+            && (metered_due || !self.is_metered().await)
+        {
+            info!("Starting scheduled refresh.");
+            self.spawn(Operation::Refresh, |mut worker| async move {
+                worker.parse_channels(None).await;
+                worker.update_feed().await;
+            });
+        }
+        if !config.webdav_url.is_empty()
+            && config.webdav_interval_minutes > 0
+            && !self.is_running(Operation::WebdavSync)
+            && chrono::Utc::now().timestamp() - config.webdav_last_sync
+                >= config.webdav_interval_minutes as i64 * 60
+        {
+            self.spawn(Operation::WebdavSync, |mut worker| async move {
+                worker.webdav_sync().await;
+                worker.update_channel_list().await;
+                worker.update_feed().await;
+            });
+        }
+        if chrono::Utc::now().timestamp() - config.last_integrity_check >= 24 * 60 * 60 {
+            self.remove_orphans().await;
+        }
+        if !config.opml_subscriptions.is_empty()
+            && !self.is_running(Operation::ImportChannels)
+            && chrono::Utc::now().timestamp() - config.last_opml_sync >= 24 * 60 * 60
+        {
+            self.spawn(Operation::ImportChannels, |mut worker| async move {
+                worker.sync_opml_subscriptions().await;
+            });
+        }
+        if config.digest_daily
+            && !config.smtp_host.is_empty()
+            && !self.is_running(Operation::Digest)
+            && chrono::Utc::now().timestamp() - config.last_digest_sent >= 24 * 60 * 60
+        {
+            self.spawn(Operation::Digest, |mut worker| async move {
+                worker.send_digest().await;
+            });
+        }
+    }
+
+    async fn remove_orphans(&mut self) {
+        match db::remove_orphans().await {
+            Ok(0) => {}
+            Ok(removed) => info!("Removed {} rows of deleted channels.", removed),
+            Err(err) => {
+                self.report_error(
+                    ErrorKind::Database,
+                    "Failed to check database integrity",
+                    err.to_string(),
+                );
+                return;
+            }
+        }
+        CONFIG.lock().last_integrity_check = chrono::Utc::now().timestamp();
+    }
+
+    async fn send_digest(&mut self) {
+        let config = ConfigBuilder::from_current();
+        if config.smtp_host.is_empty() || config.digest_to.is_empty() {
+            self.report(
+                WorkerError::new(
+                    ErrorKind::Digest,
+                    "Failed to send digest",
+                    "SMTP settings are incomplete",
+                )
+                .warning(),
+            );
+            return;
+        }
+
+        let items = match db::get_all_items().await {
+            Ok(items) => items,
+            Err(err) => {
+                self.report_error(
+                    ErrorKind::Database,
+                    "Failed to fetch items from db",
+                    err.to_string(),
+                );
+                return;
+            }
+        };
+        let items: Vec<Item> = items
+            .into_iter()
+            .filter(|item| !item.dismissed && item.published > config.last_digest_sent)
+            .filter(|item| {
+                config.digest_channels.is_empty() || config.digest_channels.contains(&item.channel)
+            })
+            .collect();
+
+        let now = chrono::Utc::now().timestamp();
+
+        if items.is_empty() {
+            info!("No new items for digest.");
+        } else {
+            let subject = format!("Tinyrss digest: {} new items", items.len());
+            if let Err(err) = digest::send_email(&config, &subject, &items).await {
+                self.report(
+                    WorkerError::new(ErrorKind::Digest, "Failed to send digest", err.to_string())
+                        .with_retry(ToWorker::SendDigest),
+                );
+                return;
+            }
+            info!("Sent digest with {} items.", items.len());
+        }
+
+        CONFIG.lock().last_digest_sent = now;
+    }
+
+    fn switch_profile(&mut self, profile: Option<String>) {
+        // Running operations would write their results into the next profile's database.
+        for (operation, handle) in self.tasks.lock().drain(..) {
+            if !handle.is_finished() {
+                info!("Stopping {:?} to switch profiles.", operation);
+                handle.abort();
+            }
+        }
+        self.empty_trash();
+        if let Err(err) = ConfigBuilder::from_current().save() {
+            error!("Failed to save config: {}", err.to_string());
+        };
+        self.save_cookies();
+
+        info!(
+            "Switching to profile: {}",
+            profile.as_deref().unwrap_or("default")
+        );
+
+        utils::set_profile(profile);
+        ConfigBuilder::from_file().apply();
+
+        self.loopback.send(ToWorker::Startup).unwrap();
+    }
+
+    /// Loads a thumbnail in the background, so slow image hosts don't hold up the worker.
+    fn load_thumbnail(&mut self, url: String) {
+        let client = match self.http_client() {
+            Some(client) => client,
+            None => return,
+        };
+        let sender = self.sender.clone();
+        let egui_ctx = self.egui_ctx.clone();
+        tokio::spawn(async move {
+            let image = match thumbnails::load(&client, &url).await {
+                Ok(image) => Some(image),
+                Err(err) => {
+                    info!("Failed to load thumbnail {}: {}", url, err);
+                    None
+                }
             };
+            sender.send(ToApp::Thumbnail { url, image }).unwrap();
+            egui_ctx.request_repaint();
+        });
+    }
+
+    fn download(&mut self, url: &str, title: &str) {
+        let client = match self.http_client() {
+            Some(client) => client,
+            None => return,
         };
+        let started = self.downloads.lock().start(
+            client,
+            url,
+            title,
+            self.sender.clone(),
+            self.egui_ctx.clone(),
+        );
+        if let Err(err) = started {
+            self.report_error(ErrorKind::Filesystem, "Failed to start download", err);
+        }
     }
 
-    fn report_error(&mut self, description: impl Into<String>, message: impl Into<String>) {
+    fn import_cookies(&mut self, path: &std::path::Path, domain: &str) {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) => {
+                self.report_error(
+                    ErrorKind::Filesystem,
+                    "Failed to read cookies file",
+                    err.to_string(),
+                );
+                return;
+            }
+        };
+        let domain = Some(domain.trim()).filter(|domain| !domain.is_empty());
+        match cookies::import_netscape(&self.cookies, &text, domain) {
+            Ok(count) => info!("Imported {} cookies.", count),
+            Err(err) => {
+                self.report_error(
+                    ErrorKind::Parse { channel_id: None },
+                    "Failed to import cookies",
+                    err,
+                );
+                return;
+            }
+        }
+        self.save_cookies();
+        self.update_cookie_list();
+    }
+
+    fn clear_cookies(&mut self, domain: &str) {
+        cookies::clear_domain(&self.cookies, domain);
+        self.save_cookies();
+        self.update_cookie_list();
+    }
+
+    fn save_cookies(&mut self) {
+        if let Err(err) = cookies::save(&self.cookies) {
+            error!("Failed to save cookies: {}", err);
+        }
+    }
+
+    fn update_cookie_list(&mut self) {
         self.sender
-            .send(ToApp::WorkerError {
-                error: WorkerError::new(description, message),
+            .send(ToApp::UpdateCookies {
+                domains: cookies::domains(&self.cookies),
             })
             .unwrap();
     }
+
+    async fn get_item(&mut self, id: &str) -> Option<db::Item> {
+        match db::get_item(id).await {
+            Ok(item) => item,
+            Err(err) => {
+                self.report_error(
+                    ErrorKind::Database,
+                    "Failed to fetch item from db",
+                    err.to_string(),
+                );
+                None
+            }
+        }
+    }
+
+    async fn save_item(&mut self, id: &str) {
+        let Some(item) = self.get_item(id).await else {
+            return;
+        };
+        let name = utils::file_name(item.title.as_deref().unwrap_or("article"));
+        let file_handle = rfd::AsyncFileDialog::new()
+            .add_filter("PDF", &["pdf"])
+            .add_filter("HTML", &["html"])
+            .set_file_name(format!("{}.pdf", name))
+            .save_file()
+            .await;
+        if let Some(file_handle) = file_handle {
+            let path = file_handle.path();
+            let config = ConfigBuilder::from_current();
+            let is_html = path
+                .extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("html"));
+            let data = if is_html {
+                print::render_html(&item, &config, false).into_bytes()
+            } else {
+                print::render_pdf(&item, &config)
+            };
+            if let Err(err) = std::fs::write(path, data) {
+                self.report_error(
+                    ErrorKind::Filesystem,
+                    "Failed to write file",
+                    err.to_string(),
+                );
+            } else {
+                info!("Saved article {}.", item.link);
+            }
+        }
+    }
+
+    /// Shares in the background, a slow chat server would hold up the worker otherwise.
+    async fn share_item(&mut self, id: &str, service: ChatService) {
+        let Some(item) = self.get_item(id).await else {
+            return;
+        };
+        let client = match self.http_client() {
+            Some(client) => client,
+            None => return,
+        };
+        let mut worker = self.clone();
+        tokio::spawn(async move {
+            let config = ConfigBuilder::from_current();
+            match integrations::share(&client, &config, service, &item).await {
+                Ok(()) => info!("Shared {} to {}.", item.link, service.label()),
+                Err(err) => worker.report_error(
+                    ErrorKind::Network {
+                        channel_id: None,
+                        status: None,
+                    },
+                    format!("Failed to share to {}", service.label()),
+                    err,
+                ),
+            }
+        });
+    }
+
+    async fn save_note(&mut self, id: &str) {
+        let Some(item) = self.get_item(id).await else {
+            return;
+        };
+        let note = match markdown::render_note(&item) {
+            Ok(note) => note,
+            Err(err) => {
+                self.report_error(ErrorKind::System, "Failed to convert article", err);
+                return;
+            }
+        };
+        let Some(folder) = CONFIG
+            .lock()
+            .notes_folder
+            .clone()
+            .or_else(dirs::document_dir)
+        else {
+            self.report_error(
+                ErrorKind::Filesystem,
+                "Failed to save note",
+                "No notes folder, choose one in the settings",
+            );
+            return;
+        };
+        let name = utils::file_name(item.title.as_deref().unwrap_or("article"));
+        let mut path = folder.join(format!("{}.md", name));
+        let mut number = 1;
+        while path.exists() {
+            path = folder.join(format!("{} ({}).md", name, number));
+            number += 1;
+        }
+        if let Err(err) = std::fs::create_dir_all(&folder).and_then(|_| std::fs::write(&path, note))
+        {
+            self.report_error(
+                ErrorKind::Filesystem,
+                "Failed to save note",
+                err.to_string(),
+            );
+        } else {
+            info!("Saved note {}.", path.display());
+        }
+    }
+
+    async fn open_item(&mut self, id: &str) {
+        let Some(item) = self.get_item(id).await else {
+            return;
+        };
+        // Feeds choose the link, the system opener would also run local files.
+        let web = reqwest::Url::parse(&item.link)
+            .is_ok_and(|url| matches!(url.scheme(), "http" | "https"));
+        if !web {
+            self.report_error(
+                ErrorKind::System,
+                "Failed to open link",
+                format!("{}: only web links are opened", item.link),
+            );
+            return;
+        }
+        if let Err(err) = open::that(&item.link) {
+            self.report_error(
+                ErrorKind::System,
+                "Failed to open link",
+                format!("{}: {}", item.link, err),
+            );
+            return;
+        }
+        if let Err(err) = db::record_open(id, chrono::Utc::now().timestamp()).await {
+            self.report_error(
+                ErrorKind::Database,
+                "Failed to record opened item",
+                err.to_string(),
+            );
+        }
+        if CONFIG.lock().auto_dismiss_on_open && !item.dismissed {
+            self.set_dismissed(id, true).await;
+        }
+    }
+
+    async fn print_item(&mut self, id: &str) {
+        let Some(item) = self.get_item(id).await else {
+            return;
+        };
+        let html = print::render_html(&item, &ConfigBuilder::from_current(), true);
+        // The file gets a fresh unpredictable name and is kept, since the browser reads it
+        // after this returns.
+        let written = tempfile::Builder::new()
+            .prefix("tinyrss-print-")
+            .suffix(".html")
+            .tempfile()
+            .and_then(|mut file| {
+                std::io::Write::write_all(&mut file, html.as_bytes())?;
+                file.into_temp_path().keep().map_err(|err| err.error)
+            });
+        let path = match written {
+            Ok(path) => path,
+            Err(err) => {
+                self.report_error(
+                    ErrorKind::Filesystem,
+                    "Failed to write file",
+                    err.to_string(),
+                );
+                return;
+            }
+        };
+        if let Err(err) = open::that(&path) {
+            self.report_error(
+                ErrorKind::System,
+                "Failed to open the browser",
+                err.to_string(),
+            );
+        }
+    }
+
+    fn send_to(&mut self, command: &str, url: &str, title: &str) {
+        let args = match utils::expand_command(command, url, title) {
+            Ok(args) => args,
+            Err(err) => {
+                self.report_error(ErrorKind::System, "Failed to parse command", err);
+                return;
+            }
+        };
+
+        info!("Running external command: {}", args[0]);
+
+        match std::process::Command::new(&args[0])
+            .args(&args[1..])
+            .spawn()
+        {
+            Ok(mut child) => {
+                std::thread::spawn(move || child.wait());
+            }
+            Err(err) => self.report_error(
+                ErrorKind::System,
+                format!("Failed to run {}", args[0]),
+                err.to_string(),
+            ),
+        }
+    }
+
+    fn report_error(
+        &mut self,
+        kind: ErrorKind,
+        description: impl Into<String>,
+        message: impl Into<String>,
+    ) {
+        self.report(WorkerError::new(kind, description, message));
+    }
+
+    fn http_client(&mut self) -> Option<Client> {
+        self.http_clients().map(|clients| clients.client)
+    }
+
+    fn http_clients(&mut self) -> Option<utils::HttpClients> {
+        let mut http_clients = self.http_clients.lock();
+        if http_clients
+            .as_ref()
+            .is_none_or(utils::HttpClients::is_stale)
+        {
+            match utils::HttpClients::new(self.cookies.clone()) {
+                Ok(clients) => *http_clients = Some(clients),
+                Err(err) => {
+                    drop(http_clients);
+                    self.report_error(ErrorKind::System, "Invalid network settings", err);
+                    return None;
+                }
+            }
+        }
+        http_clients.clone()
+    }
+
+    fn report(&mut self, error: WorkerError) {
+        self.sender.send(ToApp::WorkerError { error }).unwrap();
+    }
+}
+
+/// Turns the entries of a parsed feed into items of `channel`.
+fn feed_items(channel: &Channel, feed: Feed, first_seen: i64) -> Vec<Item> {
+    let quirks = Quirk::for_channel(channel);
+    let mut items: Vec<Item> = vec![];
+    for entry in feed.entries {
+        let mut item = Item {
+            id: entry.id.clone(),
+            channel_title: channel.title.clone(),
+            channel: channel.id.clone(),
+            dismissed: false,
+            first_seen,
+            ..Default::default()
+        };
+
+        // The article is the alternate link, other links are kept as secondary ones.
+        let main = entry
+            .links
+            .iter()
+            .position(|link| link.rel.as_deref().is_none_or(|rel| rel == "alternate"))
+            .or((!entry.links.is_empty()).then_some(0));
+        item.link = match main {
+            Some(main) => entry.links[main].href.clone(),
+            None => "<no link>".to_string(),
+        };
+        item.links = ItemLinks(
+            entry
+                .links
+                .iter()
+                .enumerate()
+                .filter(|(index, link)| Some(*index) != main && link.href != item.link)
+                .map(|(_, link)| ItemLink {
+                    href: link.href.clone(),
+                    rel: link.rel.clone(),
+                    title: link.title.clone(),
+                })
+                .collect(),
+        );
+
+        item.title = entry.title.as_ref().map(|text| text.content.clone());
+
+        item.summary = entry.summary.as_ref().map(|text| text.content.clone());
+
+        let authors: Vec<&str> = entry
+            .authors
+            .iter()
+            .map(|person| person.name.trim())
+            .filter(|name| !name.is_empty())
+            .collect();
+        item.author = (!authors.is_empty()).then(|| authors.join(", "));
+
+        item.content = entry
+            .content
+            .as_ref()
+            .and_then(|content| content.body.clone());
+
+        if let Some(body) = item.content.as_ref().or(item.summary.as_ref()) {
+            item.reading_time = utils::estimate_reading_time(body);
+        }
+
+        if let Some(published) = entry.published {
+            item.published = published.timestamp()
+        } else if let Some(updated) = entry.updated {
+            item.published = updated.timestamp()
+        } else {
+            item.published = 0;
+        }
+
+        item.updated = entry
+            .updated
+            .map(|updated| updated.timestamp())
+            .unwrap_or(0);
+
+        // Media RSS, used by YouTube and podcast feeds.
+        for media in &entry.media {
+            if item.thumbnail.is_none() {
+                item.thumbnail = media
+                    .thumbnails
+                    .first()
+                    .map(|thumbnail| thumbnail.image.uri.clone());
+            }
+            if item.summary.is_none() {
+                item.summary = media.description.as_ref().map(|text| text.content.clone());
+            }
+            if item.enclosure.is_none() {
+                item.enclosure = media
+                    .content
+                    .iter()
+                    .filter(|content| {
+                        content.content_type.as_ref().is_none_or(|mime| {
+                            mime.type_() != "text"
+                                && mime.essence_str() != "application/x-shockwave-flash"
+                        })
+                    })
+                    .find_map(|content| Some(content.url.as_ref()?.to_string()));
+            }
+            if item.duration == 0 {
+                item.duration = media
+                    .duration
+                    .or_else(|| media.content.iter().find_map(|content| content.duration))
+                    .map(|duration| duration.as_secs() as i64)
+                    .unwrap_or(0);
+            }
+        }
+
+        if item.enclosure.is_none() {
+            item.enclosure = item
+                .links
+                .with_rel("enclosure")
+                .map(|link| link.href.clone());
+        }
+
+        // Aggregators like Lobsters and Hacker News link the discussion from the summary.
+        item.comments = item
+            .links
+            .with_rel("replies")
+            .map(|link| link.href.clone())
+            .or_else(|| utils::link_labelled(item.summary.as_deref()?, "Comments"))
+            .filter(|comments| *comments != item.link);
+
+        Quirk::apply_all(&quirks, &entry, &mut item);
+
+        items.push(item);
+    }
+    items
+}
+
+/// Requests `link` with the channel's custom headers. The client stops at redirects to
+/// another origin, which are followed here without the headers, so credentials in them
+/// don't leak to other sites.
+async fn send_feed_request(
+    client: &Client,
+    link: &str,
+    headers: &[(String, String)],
+) -> reqwest::Result<reqwest::Response> {
+    let mut resp = headers
+        .iter()
+        .fold(client.get(link), |request, (name, value)| {
+            request.header(name, value)
+        })
+        .send()
+        .await?;
+    for _ in 0..utils::MAX_REDIRECTS {
+        let location = resp
+            .status()
+            .is_redirection()
+            .then(|| resp.headers().get(reqwest::header::LOCATION))
+            .flatten()
+            .and_then(|location| location.to_str().ok())
+            .and_then(|location| resp.url().join(location).ok());
+        let Some(location) = location else {
+            break;
+        };
+        resp = client.get(location).send().await?;
+    }
+    Ok(resp)
+}
+
+/// Downloads the feed of a channel. Channels with a trusted certificate are fetched without
+/// certificate validation and the served certificate is compared with the trusted one instead.
+async fn fetch_channel(
+    client: &Client,
+    pinned_client: &Client,
+    channel: &Channel,
+    headers: &[(String, String)],
+    max_size: usize,
+) -> Result<Bytes, WorkerError> {
+    let network_error = |err: reqwest::Error| {
+        WorkerError::new(
+            ErrorKind::Network {
+                channel_id: Some(channel.id.clone()),
+                status: err.status().map(|status| status.as_u16()),
+            },
+            "Web request failed",
+            err.to_string(),
+        )
+    };
+    let certificate_error = |fingerprint: Option<String>, reason: String| match fingerprint {
+        Some(fingerprint) => WorkerError::new(
+            ErrorKind::Certificate {
+                channel_id: channel.id.clone(),
+                fingerprint: fingerprint.clone(),
+            },
+            "Untrusted certificate",
+            format!("{}. SHA-256 fingerprint: {}", reason, fingerprint),
+        )
+        .with_retry(ToWorker::SetTrustedCertificate {
+            id: channel.id.clone(),
+            fingerprint: Some(fingerprint),
+        }),
+        None => WorkerError::new(
+            ErrorKind::Network {
+                channel_id: Some(channel.id.clone()),
+                status: None,
+            },
+            "Untrusted certificate",
+            reason,
+        ),
+    };
+
+    let resp = match &channel.tls_fingerprint {
+        Some(trusted) => {
+            // Custom headers often carry credentials, they only go out once the server has
+            // shown the trusted certificate. The answer is checked again, as the second
+            // request may open a new connection.
+            let probe = if headers.is_empty() {
+                None
+            } else {
+                Some(
+                    send_feed_request(pinned_client, &channel.link, &[])
+                        .await
+                        .map_err(network_error)?,
+                )
+            };
+            let changed = |resp: &reqwest::Response| {
+                let fingerprint = utils::peer_fingerprint(resp);
+                (fingerprint.as_ref() != Some(trusted)).then(|| {
+                    certificate_error(
+                        fingerprint,
+                        "The certificate changed since it was trusted".to_string(),
+                    )
+                })
+            };
+            if let Some(err) = probe.as_ref().and_then(changed) {
+                return Err(err);
+            }
+            let resp = send_feed_request(pinned_client, &channel.link, headers)
+                .await
+                .map_err(network_error)?;
+            if let Some(err) = changed(&resp) {
+                return Err(err);
+            }
+            resp
+        }
+        None => match send_feed_request(client, &channel.link, headers).await {
+            Ok(resp) => resp,
+            Err(err) if err.is_connect() => {
+                // Connect once more without validation. If that works, validation was what
+                // failed, and the answer tells which certificate is served. The custom headers
+                // stay out of it, the server may be anyone.
+                let fingerprint = match send_feed_request(pinned_client, &channel.link, &[]).await {
+                    Ok(resp) => utils::peer_fingerprint(&resp),
+                    Err(_) => None,
+                };
+                if fingerprint.is_none() {
+                    return Err(network_error(err));
+                }
+                let mut reason = err.to_string();
+                let mut source = std::error::Error::source(&err);
+                while let Some(err) = source {
+                    reason = format!("{}: {}", reason, err);
+                    source = err.source();
+                }
+                return Err(certificate_error(fingerprint, reason));
+            }
+            Err(err) => return Err(network_error(err)),
+        },
+    };
+    let resp = resp.error_for_status().map_err(network_error)?;
+    utils::read_body(resp, max_size).await.map_err(|err| {
+        WorkerError::new(
+            ErrorKind::Network {
+                channel_id: Some(channel.id.clone()),
+                status: None,
+            },
+            "Web request failed",
+            err,
+        )
+    })
 }
@@ -0,0 +1,39 @@
+use reqwest::{Client, Url};
+
+/// Asks a Kill the Newsletter server for a new inbox named `title`. Returns the link of the
+/// feed the emails to the inbox show up in.
+pub async fn create_inbox(client: &Client, server: &str, title: &str) -> Result<String, String> {
+    let server = server.trim().trim_end_matches('/');
+    let html = client
+        .post(format!("{}/", server))
+        .form(&[("title", title)])
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|err| err.to_string())?
+        .text()
+        .await
+        .map_err(|err| err.to_string())?;
+    // The answer is a page showing the address and the feed link, the latter ends in the
+    // same reference as the former.
+    let reference = html
+        .split("/feeds/")
+        .skip(1)
+        .find_map(|rest| {
+            let (reference, _) = rest.split_once(".xml")?;
+            reference
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric())
+                .then_some(reference)
+        })
+        .ok_or("The server answered without a feed link")?;
+    Ok(format!("{}/feeds/{}.xml", server, reference))
+}
+
+/// Email address of the inbox behind the feed at `link`, the feed's reference at the
+/// server's domain.
+pub fn newsletter_address(link: &str) -> Option<String> {
+    let url = Url::parse(link).ok()?;
+    let reference = url.path().strip_prefix("/feeds/")?.strip_suffix(".xml")?;
+    Some(format!("{}@{}", reference, url.host_str()?))
+}
@@ -0,0 +1,529 @@
+use super::config::{ConfigBuilder, ReaderFont};
+use super::db::Item;
+use super::digest::{escape, format_date};
+use super::utils;
+use eframe::egui::{FontData, FontDefinitions, FontFamily};
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::collections::BTreeMap;
+use std::io::Write;
+use ttf_parser::{name_id, Face, GlyphId};
+
+/// A4 in PDF points.
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const PAGE_MARGIN: f32 = 64.0;
+
+/// Elements that start a new paragraph when the article is flattened to text.
+const BLOCK_TAGS: &[&str] = &[
+    "p",
+    "br",
+    "div",
+    "li",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "blockquote",
+    "pre",
+    "tr",
+    "section",
+    "figure",
+];
+
+/// Splits an article into paragraphs of plain text.
+pub fn paragraphs(html: &str) -> Vec<String> {
+    let mut marked = String::with_capacity(html.len());
+    let lower = html.to_ascii_lowercase();
+    let mut rest = 0;
+    for (position, _) in lower.match_indices('<') {
+        let name: String = lower[position + 1..]
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect();
+        if BLOCK_TAGS.contains(&name.as_str()) {
+            marked.push_str(&html[rest..position]);
+            marked.push('\n');
+            rest = position;
+        }
+    }
+    marked.push_str(&html[rest..]);
+
+    utils::strip_html(&marked)
+        .split('\n')
+        .map(|paragraph| {
+            utils::decode_entities(&paragraph.split_whitespace().collect::<Vec<_>>().join(" "))
+        })
+        .filter(|paragraph| !paragraph.is_empty())
+        .collect()
+}
+
+/// Renders an item into a page laid out with the reader settings. With `print` set the
+/// browser opens its print dialog as soon as the page loads.
+pub fn render_html(item: &Item, config: &ConfigBuilder, print: bool) -> String {
+    let title = item.title.as_deref().unwrap_or("<no title>");
+    let font = match config.reader_font {
+        ReaderFont::Proportional => "Georgia,serif",
+        ReaderFont::Monospace => "monospace",
+    };
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", escape(title)));
+    html.push_str(&format!(
+        "<style>\
+body{{max-width:{}px;margin:2em auto;padding:0 1em;font-family:{};font-size:{}px;line-height:{};text-align:{};color:{};background:{}}}\
+h1{{text-align:left;line-height:1.2}}\
+.meta{{color:#777;font-size:.85em;text-align:left}}\
+@page{{size:A4;margin:2cm}}\
+@media print{{body{{max-width:none;margin:0;padding:0;color:#000;background:#fff}}a{{color:#000}}}}\
+</style>\n",
+        config.reader_line_width,
+        font,
+        config.reader_font_size,
+        1.5 * config.reader_line_height,
+        if config.reader_justify { "justify" } else { "left" },
+        if config.reader_sepia { "#5b4636" } else { "#222" },
+        if config.reader_sepia { "#f4ecd8" } else { "#fff" },
+    ));
+    html.push_str("</head>\n");
+    html.push_str(if print {
+        "<body onload=\"window.print()\">\n"
+    } else {
+        "<body>\n"
+    });
+    html.push_str(&format!("<h1>{}</h1>\n", escape(title)));
+    html.push_str(&format!(
+        "<div class=\"meta\">{} · {} · <a href=\"{}\">{}</a></div>\n",
+        escape(item.channel_title.as_deref().unwrap_or("Unknown")),
+        format_date(item.published),
+        escape(&item.link),
+        escape(&item.link)
+    ));
+    for paragraph in paragraphs(body(item)) {
+        html.push_str(&format!("<p>{}</p>\n", escape(&paragraph)));
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Renders an item into a paginated A4 PDF using the reader's font, size, line height and
+/// justification. Text is set in the fonts the app itself uses, which are embedded whole, so
+/// a document that needs the CJK fallback font grows by several megabytes.
+pub fn render_pdf(item: &Item, config: &ConfigBuilder) -> Vec<u8> {
+    let definitions = utils::font_definitions(config);
+    let family = match config.reader_font {
+        ReaderFont::Proportional => FontFamily::Proportional,
+        ReaderFont::Monospace => FontFamily::Monospace,
+    };
+    let mut fonts = Fonts::new(&definitions, &family);
+    let size = config.reader_font_size.clamp(6.0, 32.0) * 0.75;
+    let leading = size * 1.25 * config.reader_line_height;
+    let width = PAGE_WIDTH - 2.0 * PAGE_MARGIN;
+
+    let mut pages = Vec::new();
+    let mut page = String::new();
+    let mut y = PAGE_HEIGHT - PAGE_MARGIN;
+    let mut next_line = |page: &mut String, y: &mut f32, height: f32| {
+        if *y - height < PAGE_MARGIN {
+            pages.push(std::mem::take(page));
+            *y = PAGE_HEIGHT - PAGE_MARGIN;
+        }
+        *y -= height;
+    };
+
+    let title_size = size * 1.6;
+    for (line, _) in wrap(
+        &fonts,
+        item.title.as_deref().unwrap_or("<no title>"),
+        title_size,
+        width,
+    ) {
+        next_line(&mut page, &mut y, title_size * 1.25);
+        page.push_str(&fonts.text_command(title_size, PAGE_MARGIN, y, 0.0, true, &line));
+    }
+    let meta = format!(
+        "{} · {}",
+        item.channel_title.as_deref().unwrap_or("Unknown"),
+        format_date(item.published)
+    );
+    for line in [meta, item.link.clone()] {
+        for (line, _) in wrap(&fonts, &line, size * 0.85, width) {
+            next_line(&mut page, &mut y, size * 0.85 * 1.25);
+            page.push_str(&fonts.text_command(size * 0.85, PAGE_MARGIN, y, 0.0, false, &line));
+        }
+    }
+    next_line(&mut page, &mut y, leading);
+
+    for paragraph in paragraphs(body(item)) {
+        let lines = wrap(&fonts, &paragraph, size, width);
+        let last = lines.len().saturating_sub(1);
+        for (index, (line, line_width)) in lines.into_iter().enumerate() {
+            next_line(&mut page, &mut y, leading);
+            let spaces = line.matches(' ').count();
+            let word_spacing = if config.reader_justify && index < last && spaces > 0 {
+                (width - line_width) / spaces as f32
+            } else {
+                0.0
+            };
+            page.push_str(&fonts.text_command(size, PAGE_MARGIN, y, word_spacing, false, &line));
+        }
+        next_line(&mut page, &mut y, leading * 0.5);
+    }
+    pages.push(page);
+
+    let total = pages.len();
+    for (index, page) in pages.iter_mut().enumerate() {
+        let number = format!("{} / {}", index + 1, total);
+        let x = (PAGE_WIDTH - fonts.text_width(&number, size * 0.8)) / 2.0;
+        page.push_str(&fonts.text_command(size * 0.8, x, PAGE_MARGIN / 2.0, 0.0, false, &number));
+    }
+    assemble(&pages, &fonts)
+}
+
+fn body(item: &Item) -> &str {
+    item.content
+        .as_deref()
+        .or(item.summary.as_deref())
+        .unwrap_or_default()
+}
+
+/// A TrueType font embedded into the PDF, with the glyphs used from it.
+struct PdfFont<'a> {
+    data: &'a FontData,
+    face: Face<'a>,
+    name: String,
+    /// Glyph ids with the character each one was used for, for copying text out of the PDF.
+    glyphs: BTreeMap<u16, char>,
+}
+
+impl PdfFont<'_> {
+    /// Advance of a glyph in thousandths of the font size.
+    fn advance(&self, glyph: u16) -> f32 {
+        self.face.glyph_hor_advance(GlyphId(glyph)).unwrap_or(0) as f32 * 1000.0
+            / self.face.units_per_em() as f32
+    }
+}
+
+/// A font family in fallback order. Each character is set in the first font that has it.
+struct Fonts<'a> {
+    fonts: Vec<PdfFont<'a>>,
+}
+
+impl<'a> Fonts<'a> {
+    /// Fonts with CFF outlines are left out, as their glyphs can't be addressed by id.
+    fn new(definitions: &'a FontDefinitions, family: &FontFamily) -> Self {
+        let fonts = definitions
+            .families
+            .get(family)
+            .into_iter()
+            .flatten()
+            .filter_map(|name| definitions.font_data.get(name))
+            .filter_map(|data| {
+                let face = Face::parse(&data.font, data.index).ok()?;
+                face.tables().glyf?;
+                let name = face
+                    .names()
+                    .into_iter()
+                    .find(|name| name.name_id == name_id::POST_SCRIPT_NAME && name.is_unicode())
+                    .and_then(|name| name.to_string())
+                    .unwrap_or_default()
+                    .replace(|c: char| !c.is_ascii_alphanumeric() && c != '-', "");
+                Some(PdfFont {
+                    data,
+                    face,
+                    name: if name.is_empty() { "Font".into() } else { name },
+                    glyphs: BTreeMap::new(),
+                })
+            })
+            .collect();
+        Self { fonts }
+    }
+
+    /// The font and glyph for `c`, or the missing glyph of the first font if none has it.
+    fn glyph(&self, c: char) -> (usize, u16) {
+        self.fonts
+            .iter()
+            .enumerate()
+            .find_map(|(index, font)| Some((index, font.face.glyph_index(c)?.0)))
+            .unwrap_or((0, 0))
+    }
+
+    fn text_width(&self, text: &str, size: f32) -> f32 {
+        let units: f32 = text
+            .chars()
+            .map(|c| {
+                let (font, glyph) = self.glyph(c);
+                self.fonts.get(font).map_or(0.0, |font| font.advance(glyph))
+            })
+            .sum();
+        units * size / 1000.0
+    }
+
+    /// Sets a line of text. Spaces are widened by `word_spacing` by hand, since the `Tw`
+    /// operator only applies to single byte encodings. Bold is faked with a stroked outline.
+    fn text_command(
+        &mut self,
+        size: f32,
+        x: f32,
+        y: f32,
+        word_spacing: f32,
+        bold: bool,
+        text: &str,
+    ) -> String {
+        if self.fonts.is_empty() {
+            return String::new();
+        }
+        let mut command = String::new();
+        if bold {
+            command.push_str(&format!("q {:.2} w 2 Tr ", size * 0.03));
+        }
+        command.push_str(&format!("BT {:.2} {:.2} Td", x, y));
+        let mut current = None;
+        for c in text.chars() {
+            let (font, glyph) = self.glyph(c);
+            self.fonts[font].glyphs.entry(glyph).or_insert(c);
+            if current != Some(font) {
+                if current.is_some() {
+                    command.push_str("] TJ");
+                }
+                command.push_str(&format!(" /F{} {:.2} Tf [", font, size));
+                current = Some(font);
+            }
+            command.push_str(&format!("<{:04x}>", glyph));
+            if c == ' ' && word_spacing > 0.0 {
+                command.push_str(&format!(" {:.2} ", -word_spacing * 1000.0 / size));
+            }
+        }
+        if current.is_some() {
+            command.push_str("] TJ");
+        }
+        command.push_str(" ET");
+        if bold {
+            command.push_str(" Q");
+        }
+        command.push('\n');
+        command
+    }
+}
+
+/// Breaks text into lines no wider than `width`, returning each line with its width.
+fn wrap(fonts: &Fonts, text: &str, size: f32, width: f32) -> Vec<(String, f32)> {
+    let space = fonts.text_width(" ", size);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut line_width = 0.0;
+    for word in text.split_whitespace() {
+        let word_width = fonts.text_width(word, size);
+        if !line.is_empty() && line_width + space + word_width > width {
+            lines.push((std::mem::take(&mut line), line_width));
+            line_width = 0.0;
+        }
+        if !line.is_empty() {
+            line.push(' ');
+            line_width += space;
+        }
+        line.push_str(word);
+        line_width += word_width;
+    }
+    if !line.is_empty() {
+        lines.push((line, line_width));
+    }
+    lines
+}
+
+/// The font at `index` as a standalone font file, copied out of a font collection if needed.
+fn standalone_font(data: &[u8], index: u32) -> Option<Vec<u8>> {
+    if !data.starts_with(b"ttcf") {
+        return Some(data.to_vec());
+    }
+    let read_u32 = |offset: usize| {
+        Some(u32::from_be_bytes(
+            data.get(offset..offset + 4)?.try_into().ok()?,
+        ))
+    };
+    let start = read_u32(12 + 4 * index as usize)? as usize;
+    let count = u16::from_be_bytes(data.get(start + 4..start + 6)?.try_into().ok()?) as usize;
+    let mut font = data.get(start..start + 12 + 16 * count)?.to_vec();
+    for table in 0..count {
+        let record = 12 + 16 * table;
+        let offset = read_u32(start + record + 8)? as usize;
+        let length = read_u32(start + record + 12)? as usize;
+        let moved = font.len() as u32;
+        font[record + 8..record + 12].copy_from_slice(&moved.to_be_bytes());
+        font.extend_from_slice(data.get(offset..offset + length)?);
+        font.resize(font.len().next_multiple_of(4), 0);
+    }
+    Some(font)
+}
+
+fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(data)
+        .and_then(|_| encoder.finish())
+        .unwrap_or_default()
+}
+
+/// The objects of an embedded font, numbered from `id`: the composite font, its descendant,
+/// the descriptor, the font program and the map back to Unicode.
+fn font_objects(font: &PdfFont, id: usize) -> Vec<Vec<u8>> {
+    let face = &font.face;
+    let scale = 1000.0 / face.units_per_em() as f32;
+    let bbox = face.global_bounding_box();
+    let program = standalone_font(&font.data.font, font.data.index).unwrap_or_default();
+    let compressed = deflate(&program);
+
+    let widths: Vec<String> = font
+        .glyphs
+        .keys()
+        .map(|glyph| format!("{} [{:.0}]", glyph, font.advance(*glyph)))
+        .collect();
+    let mut to_unicode = String::from(
+        "/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n\
+/CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+/CMapName /Adobe-Identity-UCS def\n/CMapType 2 def\n\
+1 begincodespacerange\n<0000> <ffff>\nendcodespacerange\n",
+    );
+    let glyphs: Vec<(&u16, &char)> = font.glyphs.iter().collect();
+    for chunk in glyphs.chunks(100) {
+        to_unicode.push_str(&format!("{} beginbfchar\n", chunk.len()));
+        for (glyph, c) in chunk {
+            let utf16: String = c
+                .encode_utf16(&mut [0; 2])
+                .iter()
+                .map(|unit| format!("{:04x}", unit))
+                .collect();
+            to_unicode.push_str(&format!("<{:04x}> <{}>\n", glyph, utf16));
+        }
+        to_unicode.push_str("endbfchar\n");
+    }
+    to_unicode.push_str("endcmap\nCMapName currentdict /CMap defineresource pop\nend\nend\n");
+
+    let mut program_stream = format!(
+        "<< /Length {} /Length1 {} /Filter /FlateDecode >>\nstream\n",
+        compressed.len(),
+        program.len()
+    )
+    .into_bytes();
+    program_stream.extend_from_slice(&compressed);
+    program_stream.extend_from_slice(b"\nendstream");
+
+    vec![
+        format!(
+            "<< /Type /Font /Subtype /Type0 /BaseFont /{} /Encoding /Identity-H /DescendantFonts [{} 0 R] /ToUnicode {} 0 R >>",
+            font.name,
+            id + 1,
+            id + 4
+        )
+        .into_bytes(),
+        format!(
+            "<< /Type /Font /Subtype /CIDFontType2 /BaseFont /{} /CIDSystemInfo << /Registry (Adobe) /Ordering (Identity) /Supplement 0 >> /FontDescriptor {} 0 R /W [{}] /CIDToGIDMap /Identity >>",
+            font.name,
+            id + 2,
+            widths.join(" ")
+        )
+        .into_bytes(),
+        format!(
+            "<< /Type /FontDescriptor /FontName /{} /Flags {} /FontBBox [{:.0} {:.0} {:.0} {:.0}] /ItalicAngle 0 /Ascent {:.0} /Descent {:.0} /CapHeight {:.0} /StemV 80{} >>",
+            font.name,
+            if face.is_monospaced() { 33 } else { 32 },
+            bbox.x_min as f32 * scale,
+            bbox.y_min as f32 * scale,
+            bbox.x_max as f32 * scale,
+            bbox.y_max as f32 * scale,
+            face.ascender() as f32 * scale,
+            face.descender() as f32 * scale,
+            face.capital_height().unwrap_or(face.ascender()) as f32 * scale,
+            if program.is_empty() {
+                String::new()
+            } else {
+                format!(" /FontFile2 {} 0 R", id + 3)
+            }
+        )
+        .into_bytes(),
+        program_stream,
+        format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            to_unicode.len(),
+            to_unicode
+        )
+        .into_bytes(),
+    ]
+}
+
+/// Wraps page content streams into a PDF document with the fonts they use.
+fn assemble(pages: &[String], fonts: &Fonts) -> Vec<u8> {
+    let used: Vec<(usize, &PdfFont)> = fonts
+        .fonts
+        .iter()
+        .enumerate()
+        .filter(|(_, font)| !font.glyphs.is_empty())
+        .collect();
+    // Catalog and page tree come first, then the fonts and then a page and its content per page.
+    let first_page = 3 + 5 * used.len();
+    let page_ids: Vec<usize> = (0..pages.len())
+        .map(|index| first_page + 2 * index)
+        .collect();
+    let mut objects = vec![
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            page_ids
+                .iter()
+                .map(|id| format!("{} 0 R", id))
+                .collect::<Vec<_>>()
+                .join(" "),
+            pages.len()
+        )
+        .into_bytes(),
+    ];
+    let mut resources = String::new();
+    for (index, font) in used {
+        let id = objects.len() + 1;
+        resources.push_str(&format!("/F{} {} 0 R ", index, id));
+        objects.extend(font_objects(font, id));
+    }
+    for (page, id) in pages.iter().zip(&page_ids) {
+        objects.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << {}>> >> /Contents {} 0 R >>",
+                PAGE_WIDTH,
+                PAGE_HEIGHT,
+                resources,
+                id + 1
+            )
+            .into_bytes(),
+        );
+        objects
+            .push(format!("<< /Length {} >>\nstream\n{}endstream", page.len(), page).into_bytes());
+    }
+
+    // The comment after the header marks the file as binary.
+    let mut pdf = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n", index + 1).as_bytes());
+        pdf.extend_from_slice(object);
+        pdf.extend_from_slice(b"\nendobj\n");
+    }
+    let xref = pdf.len();
+    pdf.extend_from_slice(
+        format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes(),
+    );
+    for offset in offsets {
+        pdf.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    pdf.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        )
+        .as_bytes(),
+    );
+    pdf
+}
@@ -0,0 +1,323 @@
+use super::db::{Channel, Item};
+use super::utils;
+use feed_rs::model::Entry;
+
+/// Longest title made from the text of a post, in characters.
+const SOCIAL_TITLE_LENGTH: usize = 80;
+
+/// Fix-up for the feeds of one site, applied to each item after the feed is parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quirk {
+    /// Strips the "submitted by ... [link] [comments]" footer Reddit appends to every post.
+    Reddit,
+    /// Links and thumbnails built from the video id.
+    YouTube,
+    /// Keeps the discussion link from the summary as the comments of the item.
+    HackerNews,
+    /// Titles posts of Mastodon and Bluesky accounts, which have none, with their first words.
+    Social,
+}
+
+impl Quirk {
+    pub const ALL: [Quirk; 4] = [
+        Quirk::Reddit,
+        Quirk::YouTube,
+        Quirk::HackerNews,
+        Quirk::Social,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Quirk::Reddit => "Reddit",
+            Quirk::YouTube => "YouTube",
+            Quirk::HackerNews => "Hacker News",
+            Quirk::Social => "Social",
+        }
+    }
+
+    /// Name the quirk is saved under in [`Channel::quirks`].
+    fn name(self) -> &'static str {
+        match self {
+            Quirk::Reddit => "reddit",
+            Quirk::YouTube => "youtube",
+            Quirk::HackerNews => "hackernews",
+            Quirk::Social => "social",
+        }
+    }
+
+    /// Whether the quirk is turned on for a feed at `link` on `site` when the channel doesn't
+    /// choose its own.
+    fn detects(self, link: &str, site: &str) -> bool {
+        match self {
+            Quirk::Reddit => site == "reddit.com",
+            Quirk::YouTube => site == "youtube.com",
+            Quirk::HackerNews => ["ycombinator.com", "hnrss.org"].contains(&site),
+            // Fediverse servers run under any domain, their profile feeds look alike.
+            Quirk::Social => site == "bsky.app" || (link.contains("/@") && link.ends_with(".rss")),
+        }
+    }
+
+    /// Quirks of the feed at `link`.
+    pub fn detect(link: &str) -> Vec<Quirk> {
+        let Some(site) = utils::site_of(link) else {
+            return vec![];
+        };
+        Self::ALL
+            .into_iter()
+            .filter(|quirk| quirk.detects(link, &site))
+            .collect()
+    }
+
+    /// Quirks applied to the feed of `channel`, detected from its link unless it has its own.
+    pub fn for_channel(channel: &Channel) -> Vec<Quirk> {
+        match &channel.quirks {
+            Some(names) => Self::parse_list(names),
+            None => Self::detect(&channel.link),
+        }
+    }
+
+    /// Reads a list saved by [`Quirk::join`], unknown names are skipped.
+    pub fn parse_list(names: &str) -> Vec<Quirk> {
+        names
+            .split(',')
+            .filter_map(|name| {
+                Self::ALL
+                    .into_iter()
+                    .find(|quirk| quirk.name() == name.trim())
+            })
+            .collect()
+    }
+
+    pub fn join(quirks: &[Quirk]) -> String {
+        quirks
+            .iter()
+            .map(|quirk| quirk.name())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Runs the quirks one after another, in the given order.
+    pub fn apply_all(quirks: &[Quirk], entry: &Entry, item: &mut Item) {
+        for quirk in quirks {
+            quirk.apply(entry, item);
+        }
+    }
+
+    fn apply(self, entry: &Entry, item: &mut Item) {
+        match self {
+            Quirk::Reddit => reddit(item),
+            Quirk::YouTube => youtube(entry, item),
+            Quirk::HackerNews => hacker_news(item),
+            Quirk::Social => social(item),
+        }
+    }
+}
+
+fn reddit(item: &mut Item) {
+    for body in [&mut item.summary, &mut item.content].into_iter().flatten() {
+        let Some(start) = body.rfind("submitted by") else {
+            continue;
+        };
+        if item.comments.is_none() {
+            item.comments = utils::link_labelled(&body[start..], "[comments]");
+        }
+        body.truncate(start);
+        let kept = body.trim_end().trim_end_matches("&#32;").trim_end().len();
+        body.truncate(kept);
+    }
+    if item.comments.as_deref() == Some(item.link.as_str()) {
+        item.comments = None;
+    }
+    for body in [&mut item.summary, &mut item.content] {
+        if body
+            .as_deref()
+            .is_some_and(|body| utils::strip_html(body).trim().is_empty())
+        {
+            *body = None;
+        }
+    }
+}
+
+fn youtube(entry: &Entry, item: &mut Item) {
+    let id = entry
+        .id
+        .strip_prefix("yt:video:")
+        .or_else(|| item.link.split_once("watch?v=").map(|(_, rest)| rest))
+        .or_else(|| item.link.split_once("/shorts/").map(|(_, rest)| rest))
+        .map(|rest| rest.split(['&', '?', '/', '#']).next().unwrap_or_default())
+        .filter(|id| !id.is_empty())
+        .map(str::to_string);
+    let Some(id) = id else {
+        return;
+    };
+    // Shorts get the regular watch page, which external players understand as well.
+    item.link = format!("https://www.youtube.com/watch?v={}", id);
+    // The watch page isn't an enclosure, it can't be downloaded. Players get it as the link.
+    if item.thumbnail.is_none() {
+        item.thumbnail = Some(format!("https://i.ytimg.com/vi/{}/hqdefault.jpg", id));
+    }
+}
+
+fn hacker_news(item: &mut Item) {
+    let Some(summary) = item.summary.as_deref() else {
+        return;
+    };
+    let comments =
+        utils::hrefs(summary).find(|href| href.contains("news.ycombinator.com/item?id="));
+    // The front page feed has nothing but the comments link as its description.
+    if utils::strip_html(summary).trim() == "Comments" {
+        item.summary = None;
+    }
+    if let Some(comments) = comments.filter(|comments| *comments != item.link) {
+        item.comments = Some(comments);
+    }
+}
+
+fn social(item: &mut Item) {
+    if item
+        .title
+        .as_deref()
+        .is_some_and(|title| !title.trim().is_empty())
+    {
+        return;
+    }
+    let Some(body) = item.content.as_deref().or(item.summary.as_deref()) else {
+        return;
+    };
+    let text = utils::decode_entities(&utils::strip_html(body));
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let mut title = String::new();
+    for word in &words {
+        if !title.is_empty() && title.chars().count() + word.chars().count() > SOCIAL_TITLE_LENGTH {
+            title.push('…');
+            break;
+        }
+        if !title.is_empty() {
+            title.push(' ');
+        }
+        title.push_str(word);
+    }
+    if !title.is_empty() {
+        item.title = Some(title);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(link: &str, summary: &str) -> Item {
+        Item {
+            link: link.to_string(),
+            summary: Some(summary.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn detects_sites() {
+        assert_eq!(
+            Quirk::detect("https://old.reddit.com/r/rust/.rss"),
+            [Quirk::Reddit]
+        );
+        assert_eq!(
+            Quirk::detect("https://www.youtube.com/feeds/videos.xml?channel_id=UC1"),
+            [Quirk::YouTube]
+        );
+        assert_eq!(
+            Quirk::detect("https://hnrss.org/frontpage"),
+            [Quirk::HackerNews]
+        );
+        assert_eq!(
+            Quirk::detect("https://news.ycombinator.com/rss"),
+            [Quirk::HackerNews]
+        );
+        assert_eq!(
+            Quirk::detect("https://mastodon.social/@someone.rss"),
+            [Quirk::Social]
+        );
+        assert_eq!(
+            Quirk::detect("https://bsky.app/profile/someone/rss"),
+            [Quirk::Social]
+        );
+        assert!(Quirk::detect("https://blog.example.com/feed").is_empty());
+        assert!(Quirk::detect("not a link").is_empty());
+    }
+
+    #[test]
+    fn parses_saved_lists() {
+        assert_eq!(
+            Quirk::parse_list("reddit, youtube,unknown,hackernews"),
+            [Quirk::Reddit, Quirk::YouTube, Quirk::HackerNews]
+        );
+        assert!(Quirk::parse_list("").is_empty());
+        let all = Quirk::join(&Quirk::ALL);
+        assert_eq!(Quirk::parse_list(&all), Quirk::ALL);
+    }
+
+    #[test]
+    fn strips_reddit_footer() {
+        let mut item = item(
+            "https://example.com/article",
+            r#"<p>Post text</p> &#32; submitted by &#32; <a href="https://www.reddit.com/user/someone">/u/someone</a> <br/> <span><a href="https://example.com/article">[link]</a></span> &#32; <span><a href="https://www.reddit.com/r/rust/comments/abc/post/">[comments]</a></span>"#,
+        );
+        reddit(&mut item);
+        assert_eq!(item.summary.as_deref(), Some("<p>Post text</p>"));
+        assert_eq!(
+            item.comments.as_deref(),
+            Some("https://www.reddit.com/r/rust/comments/abc/post/")
+        );
+    }
+
+    #[test]
+    fn drops_reddit_comments_pointing_at_the_item() {
+        let link = "https://www.reddit.com/r/rust/comments/abc/post/";
+        let mut item = item(
+            link,
+            &format!(r#"submitted by <a href="{}">[comments]</a>"#, link),
+        );
+        reddit(&mut item);
+        assert_eq!(item.summary, None);
+        assert_eq!(item.comments, None);
+    }
+
+    #[test]
+    fn keeps_hacker_news_discussion() {
+        let mut item = item(
+            "https://example.com/article",
+            r#"<a href="https://news.ycombinator.com/item?id=1&amp;p=2">Comments</a>"#,
+        );
+        hacker_news(&mut item);
+        assert_eq!(item.summary, None);
+        assert_eq!(
+            item.comments.as_deref(),
+            Some("https://news.ycombinator.com/item?id=1&p=2")
+        );
+    }
+
+    #[test]
+    fn skips_hacker_news_self_posts() {
+        let link = "https://news.ycombinator.com/item?id=1";
+        let summary = format!(r#"<p>Ask HN</p><a href="{}">Comments</a>"#, link);
+        let mut item = item(link, &summary);
+        hacker_news(&mut item);
+        assert_eq!(item.summary.as_deref(), Some(summary.as_str()));
+        assert_eq!(item.comments, None);
+    }
+
+    #[test]
+    fn builds_youtube_links_without_enclosure() {
+        let entry = Entry {
+            id: "yt:video:abc123".to_string(),
+            ..Default::default()
+        };
+        let mut item = item("https://www.youtube.com/shorts/abc123?feature=share", "");
+        youtube(&entry, &mut item);
+        assert_eq!(item.link, "https://www.youtube.com/watch?v=abc123");
+        assert_eq!(
+            item.thumbnail.as_deref(),
+            Some("https://i.ytimg.com/vi/abc123/hqdefault.jpg")
+        );
+        assert_eq!(item.enclosure, None);
+    }
+}
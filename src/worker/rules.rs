@@ -0,0 +1,70 @@
+use super::db::Item;
+use super::utils;
+use serde::{Deserialize, Serialize};
+
+/// Keywords of the blocklist bundled to be shared, e.g. "hide sponsored content on common
+/// tech feeds". Exchanged as YAML.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RulePack {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rule {
+    pub keyword: String,
+    /// Why the keyword is in the pack, shown before importing it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl Rule {
+    /// What the rule does once imported.
+    pub fn describe(&self) -> String {
+        format!(
+            "Blocks new items whose title or summary contains \"{}\"",
+            self.keyword.trim()
+        )
+    }
+}
+
+impl RulePack {
+    /// Pack of the current blocklist.
+    pub fn from_keywords(name: &str, keywords: &[String]) -> Self {
+        Self {
+            name: name.to_string(),
+            description: None,
+            rules: keywords
+                .iter()
+                .map(|keyword| Rule {
+                    keyword: keyword.clone(),
+                    note: None,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn to_yaml(&self) -> String {
+        serde_yaml::to_string(self).unwrap_or_else(|err| err.to_string())
+    }
+
+    /// Reads a pack pasted by the user, rules without a keyword are skipped.
+    pub fn from_yaml(yaml: &str) -> Result<Self, String> {
+        let mut pack: RulePack = serde_yaml::from_str(yaml).map_err(|err| err.to_string())?;
+        pack.rules.retain(|rule| !rule.keyword.trim().is_empty());
+        if pack.rules.is_empty() {
+            return Err("The pack has no rules".to_string());
+        }
+        Ok(pack)
+    }
+}
+
+/// Whether the blocklist `keywords` keep `item` from being saved.
+pub fn blocks_item(keywords: &[String], item: &Item) -> bool {
+    utils::is_blocked(keywords, item.title.as_deref().unwrap_or_default())
+        || utils::is_blocked(keywords, item.summary.as_deref().unwrap_or_default())
+}
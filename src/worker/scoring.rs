@@ -0,0 +1,38 @@
+use super::db::Item;
+use std::collections::HashMap;
+
+/// Posting frequency is measured over this many days.
+const FREQUENCY_WINDOW_DAYS: i64 = 30;
+/// Slows the decay of the first hours, so an item isn't outranked just for being minutes older.
+const AGE_OFFSET_HOURS: f32 = 2.0;
+/// How fast items sink with age.
+const GRAVITY: f32 = 1.5;
+
+/// Scores items for the "Hot" feed order, higher is hotter.
+///
+/// Scores fall with age like on link aggregators, divided by the square root of how many
+/// items the channel posts per day. A blog posting twice a month thus stays on top for days,
+/// while a news site posting fifty items a day drops below it within hours.
+pub fn score_items(items: &[Item], now: i64) -> HashMap<String, f32> {
+    let window_start = now - FREQUENCY_WINDOW_DAYS * 24 * 60 * 60;
+    let mut posts: HashMap<&str, usize> = HashMap::new();
+    for item in items.iter().filter(|item| item.published > window_start) {
+        *posts.entry(item.channel.as_str()).or_default() += 1;
+    }
+
+    items
+        .iter()
+        .map(|item| {
+            // Channels quiet during the whole window still count as posting once in it.
+            let count = posts
+                .get(item.channel.as_str())
+                .copied()
+                .unwrap_or(0)
+                .max(1);
+            let per_day = count as f32 / FREQUENCY_WINDOW_DAYS as f32;
+            let age_hours = (now - item.published).max(0) as f32 / 3600.0;
+            let recency = (age_hours + AGE_OFFSET_HOURS).powf(-GRAVITY);
+            (item.id.clone(), recency / per_day.sqrt())
+        })
+        .collect()
+}
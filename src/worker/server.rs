@@ -0,0 +1,301 @@
+use super::{db, digest, utils, ToWorker, CONFIG};
+use crossbeam_channel::Sender;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use serde::Deserialize;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use tracing::{error, info};
+
+#[derive(Deserialize)]
+struct SubscribeRequest {
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct DismissRequest {
+    id: String,
+    #[serde(default = "default_true")]
+    dismissed: bool,
+}
+
+#[derive(Deserialize)]
+struct StarRequest {
+    id: String,
+    #[serde(default = "default_true")]
+    starred: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+pub async fn serve(port: u16, sender: Sender<ToWorker>) {
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+
+    let make_service = make_service_fn(move |_| {
+        let sender = sender.clone();
+        async move { Ok::<_, Infallible>(service_fn(move |req| handle(req, sender.clone()))) }
+    });
+
+    let server = match Server::try_bind(&addr) {
+        Ok(builder) => builder.serve(make_service),
+        Err(err) => {
+            error!("Failed to bind local server: {}", err.to_string());
+            return;
+        }
+    };
+
+    info!("Local server listening on {}.", addr);
+
+    if let Err(err) = server.await {
+        error!("Local server stopped: {}", err.to_string());
+    }
+}
+
+async fn handle(
+    req: Request<Body>,
+    sender: Sender<ToWorker>,
+) -> Result<Response<Body>, Infallible> {
+    let origin = extension_origin(&req);
+    let mut response = match (req.method(), req.uri().path()) {
+        (&Method::OPTIONS, _) => respond(StatusCode::NO_CONTENT, ""),
+        (&Method::GET, "/status") => status().await,
+        (&Method::POST, "/subscribe") => {
+            if authorized(&req) {
+                subscribe(req, sender).await
+            } else {
+                error_response(StatusCode::UNAUTHORIZED, "invalid or missing token")
+            }
+        }
+        (&Method::GET, "/feed.xml") => {
+            if authorized(&req) || feed_token_given(&req) {
+                feed().await
+            } else {
+                error_response(StatusCode::UNAUTHORIZED, "invalid or missing token")
+            }
+        }
+        (_, path) if path.starts_with("/api/") => {
+            if authorized(&req) {
+                api(req, sender).await
+            } else {
+                error_response(StatusCode::UNAUTHORIZED, "invalid or missing token")
+            }
+        }
+        _ => not_found(),
+    };
+    // Only browser extensions may read the answers, web pages get no CORS headers.
+    if let Some(origin) = origin {
+        let headers = response.headers_mut();
+        headers.insert("Access-Control-Allow-Origin", origin);
+        headers.insert(
+            "Access-Control-Allow-Methods",
+            hyper::header::HeaderValue::from_static("GET, POST, OPTIONS"),
+        );
+        headers.insert(
+            "Access-Control-Allow-Headers",
+            hyper::header::HeaderValue::from_static("Content-Type, Authorization"),
+        );
+        headers.insert(
+            hyper::header::VARY,
+            hyper::header::HeaderValue::from_static("Origin"),
+        );
+    }
+    Ok(response)
+}
+
+/// Origin of the request if it comes from a browser extension.
+fn extension_origin(req: &Request<Body>) -> Option<hyper::header::HeaderValue> {
+    let origin = req.headers().get(hyper::header::ORIGIN)?;
+    let text = origin.to_str().ok()?;
+    [
+        "chrome-extension://",
+        "moz-extension://",
+        "safari-web-extension://",
+    ]
+    .iter()
+    .any(|scheme| text.starts_with(scheme))
+    .then(|| origin.clone())
+}
+
+/// Whether the request carries the API token in its `Authorization` header.
+fn authorized(req: &Request<Body>) -> bool {
+    let token = CONFIG.lock().local_server_token.clone();
+    let header = req
+        .headers()
+        .get(hyper::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    !token.is_empty()
+        && header.is_some_and(|given| utils::constant_time_eq(given.as_bytes(), token.as_bytes()))
+}
+
+/// Whether the link carries the feed token, which only opens the republished feed.
+fn feed_token_given(req: &Request<Body>) -> bool {
+    let token = CONFIG.lock().local_feed_token.clone();
+    !token.is_empty()
+        && query_param(req, "token")
+            .is_some_and(|given| utils::constant_time_eq(given.as_bytes(), token.as_bytes()))
+}
+
+fn query_param<'a>(req: &'a Request<Body>, name: &str) -> Option<&'a str> {
+    req.uri().query().and_then(|query| {
+        query.split('&').find_map(|pair| {
+            pair.split_once('=')
+                .filter(|(key, _)| *key == name)
+                .map(|(_, value)| value)
+        })
+    })
+}
+
+async fn feed() -> Response<Body> {
+    let selection = CONFIG.lock().republished_items;
+    match db::get_all_items().await {
+        Ok(items) => {
+            let items = digest::republished_items(items, selection);
+            Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/rss+xml; charset=utf-8")
+                .body(Body::from(digest::render_rss("Tinyrss", &items)))
+                .unwrap()
+        }
+        Err(err) => error_response(StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+    }
+}
+
+async fn api(req: Request<Body>, sender: Sender<ToWorker>) -> Response<Body> {
+    match (req.method(), req.uri().path()) {
+        (&Method::GET, "/api/channels") => match db::get_all_channels().await {
+            Ok(channels) => json_response(&channels),
+            Err(err) => error_response(StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+        },
+        (&Method::GET, "/api/items") => {
+            let state = query_param(&req, "state").unwrap_or("new").to_string();
+            match db::get_all_items().await {
+                Ok(items) => {
+                    let items: Vec<db::Item> = items
+                        .into_iter()
+                        .filter(|item| match state.as_str() {
+                            "dismissed" => item.dismissed,
+                            "later" => !item.dismissed && item.later,
+                            "starred" => item.starred,
+                            "all" => true,
+                            _ => !item.dismissed && !item.later,
+                        })
+                        .collect();
+                    json_response(&items)
+                }
+                Err(err) => error_response(StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+            }
+        }
+        (&Method::POST, "/api/refresh") => send(&sender, ToWorker::UpdateFeed),
+        (&Method::POST, "/api/dismiss") => match parse_body::<DismissRequest>(req).await {
+            Ok(request) => send(
+                &sender,
+                ToWorker::SetDismissed {
+                    id: request.id,
+                    dismissed: request.dismissed,
+                },
+            ),
+            Err(response) => response,
+        },
+        (&Method::POST, "/api/star") => match parse_body::<StarRequest>(req).await {
+            Ok(request) => send(
+                &sender,
+                ToWorker::SetStarred {
+                    id: request.id,
+                    starred: request.starred,
+                },
+            ),
+            Err(response) => response,
+        },
+        _ => not_found(),
+    }
+}
+
+async fn parse_body<T: serde::de::DeserializeOwned>(
+    req: Request<Body>,
+) -> Result<T, Response<Body>> {
+    // Plain forms can be posted by any page without a preflight, JSON can't.
+    let json = req
+        .headers()
+        .get(hyper::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|value| value.trim().eq_ignore_ascii_case("application/json"));
+    if !json {
+        return Err(error_response(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "content type must be application/json",
+        ));
+    }
+    let bytes = hyper::body::to_bytes(req.into_body())
+        .await
+        .map_err(|err| error_response(StatusCode::BAD_REQUEST, err.to_string()))?;
+    serde_json::from_slice(&bytes)
+        .map_err(|err| error_response(StatusCode::BAD_REQUEST, err.to_string()))
+}
+
+fn send(sender: &Sender<ToWorker>, message: ToWorker) -> Response<Body> {
+    match sender.send(message) {
+        Ok(()) => respond(StatusCode::ACCEPTED, r#"{"status":"accepted"}"#),
+        Err(err) => error_response(StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+    }
+}
+
+async fn status() -> Response<Body> {
+    let channels = match db::get_all_channels().await {
+        Ok(channels) => channels.len(),
+        Err(err) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+    };
+    let body = serde_json::json!({
+        "name": "tinyrss",
+        "version": env!("CARGO_PKG_VERSION"),
+        "channels": channels,
+    });
+    respond(StatusCode::OK, body.to_string())
+}
+
+async fn subscribe(req: Request<Body>, sender: Sender<ToWorker>) -> Response<Body> {
+    let request: SubscribeRequest = match parse_body(req).await {
+        Ok(request) => request,
+        Err(response) => return response,
+    };
+    if !(request.url.starts_with("http://") || request.url.starts_with("https://")) {
+        return error_response(StatusCode::BAD_REQUEST, "url must be http(s)");
+    }
+
+    info!("Received subscription request: {}", request.url);
+
+    send(
+        &sender,
+        ToWorker::AddChannel {
+            link: request.url,
+            request_id: None,
+        },
+    )
+}
+
+fn not_found() -> Response<Body> {
+    error_response(StatusCode::NOT_FOUND, "not found")
+}
+
+fn json_response<T: serde::Serialize>(value: &T) -> Response<Body> {
+    match serde_json::to_string(value) {
+        Ok(body) => respond(StatusCode::OK, body),
+        Err(err) => error_response(StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+    }
+}
+
+fn error_response(status: StatusCode, message: impl Into<String>) -> Response<Body> {
+    let body = serde_json::json!({ "error": message.into() });
+    respond(status, body.to_string())
+}
+
+fn respond(status: StatusCode, body: impl Into<Body>) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json")
+        .body(body.into())
+        .unwrap()
+}
@@ -0,0 +1,118 @@
+use reqwest::Client;
+use serde::Deserialize;
+
+/// Bluesky's public API, answers without an account.
+const BLUESKY_API: &str = "https://public.api.bsky.app/xrpc";
+
+/// Account on a social network, given instead of a feed link when subscribing.
+#[derive(Debug, PartialEq)]
+pub enum Handle {
+    /// `@user@example.social` on Mastodon or another Fediverse server.
+    Fediverse { user: String, host: String },
+    /// `@alice.bsky.social`, Bluesky handles are domain names.
+    Bluesky(String),
+}
+
+impl Handle {
+    /// Reads a handle, `None` for anything else, e.g. links.
+    pub fn parse(text: &str) -> Option<Handle> {
+        let text = text.trim();
+        if text.contains('/') || text.contains(char::is_whitespace) {
+            return None;
+        }
+        // Without the `@` a plain domain could be meant as a site rather than a Bluesky handle.
+        let (prefixed, text) = match text.strip_prefix('@') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        match text.split_once('@') {
+            Some((user, host)) if !user.is_empty() && host.contains('.') => {
+                Some(Handle::Fediverse {
+                    user: user.to_string(),
+                    host: host.to_lowercase(),
+                })
+            }
+            None if prefixed && text.contains('.') && text.len() < 254 => {
+                Some(Handle::Bluesky(text.to_lowercase()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Link of the feed with the account's posts.
+    pub async fn resolve(&self, client: &Client) -> Result<String, String> {
+        match self {
+            Handle::Fediverse { user, host } => {
+                let profile = webfinger_profile(client, user, host)
+                    .await
+                    .unwrap_or_else(|| format!("https://{}/@{}", host, user));
+                // Mastodon serves the public posts of every profile as RSS.
+                Ok(format!("{}.rss", profile.trim_end_matches('/')))
+            }
+            Handle::Bluesky(handle) => {
+                // The DID stays the same when the account changes its handle.
+                let did = bluesky_did(client, handle).await?;
+                Ok(format!("https://bsky.app/profile/{}/rss", did))
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct WebfingerLink {
+    rel: String,
+    href: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Webfinger {
+    #[serde(default)]
+    links: Vec<WebfingerLink>,
+}
+
+/// Profile page of a Fediverse account as announced by its server, which may use another
+/// domain for profiles than for handles.
+async fn webfinger_profile(client: &Client, user: &str, host: &str) -> Option<String> {
+    let url = format!(
+        "https://{}/.well-known/webfinger?resource=acct:{}@{}",
+        host, user, host
+    );
+    let bytes = client
+        .get(url)
+        .send()
+        .await
+        .ok()?
+        .error_for_status()
+        .ok()?
+        .bytes()
+        .await
+        .ok()?;
+    let webfinger: Webfinger = serde_json::from_slice(&bytes).ok()?;
+    webfinger
+        .links
+        .into_iter()
+        .find(|link| link.rel == "http://webfinger.net/rel/profile-page")?
+        .href
+}
+
+#[derive(Deserialize)]
+struct ResolvedHandle {
+    did: String,
+}
+
+async fn bluesky_did(client: &Client, handle: &str) -> Result<String, String> {
+    let url = format!(
+        "{}/com.atproto.identity.resolveHandle?handle={}",
+        BLUESKY_API, handle
+    );
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|err| format!("Failed to look up @{}: {}", handle, err))?;
+    let bytes = response.bytes().await.map_err(|err| err.to_string())?;
+    serde_json::from_slice::<ResolvedHandle>(&bytes)
+        .map(|resolved| resolved.did)
+        .map_err(|err| format!("Failed to look up @{}: {}", handle, err))
+}
@@ -0,0 +1,125 @@
+use super::{utils, CONFIG};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+/// State change of a single item, as written to the journal.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "field", content = "value", rename_all = "snake_case")]
+pub enum Change {
+    Dismissed(bool),
+    Starred(bool),
+    Later(bool),
+}
+
+impl Change {
+    fn field(&self) -> &'static str {
+        match self {
+            Change::Dismissed(_) => "dismissed",
+            Change::Starred(_) => "starred",
+            Change::Later(_) => "later",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct JournalEntry {
+    time: i64,
+    item: String,
+    change: Change,
+}
+
+/// Id of this machine, generated the first time it is needed.
+pub fn device_id() -> String {
+    let mut config = CONFIG.lock();
+    if config.sync_device_id.is_empty() {
+        config.sync_device_id = utils::generate_token()[..8].to_string();
+    }
+    config.sync_device_id.clone()
+}
+
+/// Sync folder and the id of this machine, when syncing is enabled.
+fn settings() -> Option<(PathBuf, String)> {
+    let folder = CONFIG.lock().sync_folder.clone()?;
+    Some((folder, device_id()))
+}
+
+fn journal_name(device: &str) -> String {
+    format!("tinyrss-{}.jsonl", device)
+}
+
+/// Appends changes made on this machine to its journal in the sync folder.
+pub fn record(changes: &[(String, Change)]) -> std::io::Result<()> {
+    let (folder, device) = match settings() {
+        Some(settings) => settings,
+        None => return Ok(()),
+    };
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let time = chrono::Utc::now().timestamp_millis();
+    let mut lines = String::new();
+    for (item, change) in changes {
+        let entry = JournalEntry {
+            time,
+            item: item.clone(),
+            change: *change,
+        };
+        lines.push_str(&serde_json::to_string(&entry)?);
+        lines.push('\n');
+    }
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(folder.join(journal_name(&device)))?;
+    file.write_all(lines.as_bytes())
+}
+
+/// Reads the journals of all machines and returns the changes where another machine has the
+/// latest word, so they can be applied to the local database.
+pub fn pending_changes() -> std::io::Result<Vec<(String, Change)>> {
+    let (folder, device) = match settings() {
+        Some(settings) => settings,
+        None => return Ok(vec![]),
+    };
+
+    let mut latest: HashMap<(String, &'static str), (i64, String, Change)> = HashMap::new();
+
+    for entry in std::fs::read_dir(&folder)? {
+        let path = entry?.path();
+        let journal_device = match path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix("tinyrss-"))
+            .and_then(|name| name.strip_suffix(".jsonl"))
+        {
+            Some(journal_device) => journal_device.to_string(),
+            None => continue,
+        };
+
+        let reader = std::io::BufReader::new(std::fs::File::open(&path)?);
+        for line in reader.lines() {
+            // Half-synced journals may end in a partial line, which is picked up next time.
+            let entry: JournalEntry = match serde_json::from_str(&line?) {
+                Ok(entry) => entry,
+                Err(_) => continue,
+            };
+            let key = (entry.item, entry.change.field());
+            let newer = latest
+                .get(&key)
+                .is_none_or(|(time, other, _)| (entry.time, &journal_device) > (*time, other));
+            if newer {
+                latest.insert(key, (entry.time, journal_device.clone(), entry.change));
+            }
+        }
+    }
+
+    Ok(latest
+        .into_iter()
+        .filter(|(_, (_, journal_device, _))| *journal_device != device)
+        .map(|((item, _), (_, _, change))| (item, change))
+        .collect())
+}
@@ -0,0 +1,46 @@
+use super::utils;
+use eframe::egui::ColorImage;
+use reqwest::Client;
+use std::path::PathBuf;
+use tracing::error;
+
+/// Bigger images are not downloaded.
+const MAX_THUMBNAIL_SIZE: usize = 2 * 1024 * 1024;
+
+/// Thumbnails are scaled down to fit a square of this many pixels.
+const THUMBNAIL_PIXELS: u32 = 320;
+
+fn get_thumbnail_dir() -> PathBuf {
+    utils::get_app_dir().join("thumbnails")
+}
+
+/// Loads the image at `url` from the cache, downloading it on first use, and decodes it.
+pub async fn load(client: &Client, url: &str) -> Result<ColorImage, String> {
+    let path = get_thumbnail_dir().join(utils::sha256_hex(url));
+    let bytes = match std::fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            let resp = client
+                .get(url)
+                .send()
+                .await
+                .and_then(|resp| resp.error_for_status())
+                .map_err(|err| err.to_string())?;
+            let bytes = utils::read_body(resp, MAX_THUMBNAIL_SIZE).await?.to_vec();
+            if let Err(err) = std::fs::create_dir_all(get_thumbnail_dir())
+                .and_then(|_| std::fs::write(&path, &bytes))
+            {
+                error!("Failed to cache thumbnail: {}", err.to_string());
+            }
+            bytes
+        }
+    };
+    let image = image::load_from_memory(&bytes)
+        .map_err(|err| err.to_string())?
+        .thumbnail(THUMBNAIL_PIXELS, THUMBNAIL_PIXELS)
+        .to_rgba8();
+    Ok(ColorImage::from_rgba_unmultiplied(
+        [image.width() as usize, image.height() as usize],
+        image.as_raw(),
+    ))
+}
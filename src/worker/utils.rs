@@ -1,10 +1,494 @@
+use super::config::ConfigBuilder;
+use super::db::Channel;
+use super::{dns, DnsMode, CONFIG};
+use bytes::{Bytes, BytesMut};
+use eframe::egui::{FontData, FontDefinitions, FontFamily};
+use parking_lot::Mutex;
+use reqwest_cookie_store::CookieStoreMutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::net::TcpStream;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 
-pub fn get_app_dir() -> std::path::PathBuf {
+const USER_AGENT: &str = concat!("tinyrss/", env!("CARGO_PKG_VERSION"));
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Redirects followed for one request, as many as reqwest follows by default.
+pub const MAX_REDIRECTS: usize = 10;
+
+/// System fonts probed in order for glyphs missing from egui's default fonts.
+const CJK_FALLBACK_FONTS: &[&str] = &[
+    "C:\\Windows\\Fonts\\msyh.ttc",
+    "C:\\Windows\\Fonts\\YuGothM.ttc",
+    "C:\\Windows\\Fonts\\malgun.ttf",
+    "/System/Library/Fonts/PingFang.ttc",
+    "/System/Library/Fonts/Hiragino Sans GB.ttc",
+    "/System/Library/Fonts/AppleSDGothicNeo.ttc",
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+];
+
+/// How long the answer of [`detect_metered`] is reused, as asking starts a process.
+const METERED_CACHE: Duration = Duration::from_secs(5 * 60);
+
+/// Last answer of [`detect_metered`] and when it was given.
+static METERED: Mutex<Option<(Instant, Option<bool>)>> = parking_lot::const_mutex(None);
+
+/// Named profile whose database and config are used instead of the default ones.
+static PROFILE: Mutex<Option<String>> = parking_lot::const_mutex(None);
+
+pub fn get_base_dir() -> std::path::PathBuf {
     let config_dir = dirs::config_dir().unwrap();
     config_dir.join("tinyrss")
 }
 
+pub fn get_app_dir() -> std::path::PathBuf {
+    match PROFILE.lock().as_ref() {
+        Some(profile) => get_base_dir().join("profiles").join(profile),
+        None => get_base_dir(),
+    }
+}
+
+/// Logs are shared by all profiles, so they live next to the default profile's files.
+pub fn get_log_dir() -> std::path::PathBuf {
+    get_base_dir().join("logs")
+}
+
+/// Returns the most recently written log file, which is the one currently being appended to.
+pub fn latest_log_file() -> Option<std::path::PathBuf> {
+    std::fs::read_dir(get_log_dir())
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().ends_with(".log"))
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+}
+
+/// Reads the last `max_lines` lines of the current log file, looking at no more than its last 256 KiB.
+pub fn read_log_tail(max_lines: usize) -> std::io::Result<Vec<String>> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    const TAIL_BYTES: u64 = 256 * 1024;
+
+    let path = match latest_log_file() {
+        Some(path) => path,
+        None => return Ok(Vec::new()),
+    };
+    let mut file = std::fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(TAIL_BYTES)))?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    let text = String::from_utf8_lossy(&bytes);
+    let mut lines: Vec<&str> = text.lines().collect();
+    if len > TAIL_BYTES && !lines.is_empty() {
+        // The first line is most likely cut in the middle.
+        lines.remove(0);
+    }
+    let start = lines.len().saturating_sub(max_lines);
+    Ok(lines[start..].iter().map(|line| line.to_string()).collect())
+}
+
+pub fn get_profile() -> Option<String> {
+    PROFILE.lock().clone()
+}
+
+pub fn set_profile(profile: Option<String>) {
+    *PROFILE.lock() = profile;
+}
+
+/// Profile names may only contain letters, digits, `-` and `_`, since they are used as directory names.
+pub fn is_valid_profile_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+pub fn list_profiles() -> Vec<String> {
+    let mut profiles: Vec<String> = std::fs::read_dir(get_base_dir().join("profiles"))
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_dir())
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .filter(|name| is_valid_profile_name(name))
+                .collect()
+        })
+        .unwrap_or_default();
+    profiles.sort();
+    profiles
+}
+
+/// Limits requests both in total and per host, so many feeds on one server
+/// are fetched a few at a time while other hosts keep using the free slots.
+pub struct RequestLimiter {
+    global: Arc<Semaphore>,
+    per_host: usize,
+    hosts: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+/// Held for the duration of a request.
+pub struct RequestPermit {
+    _host: OwnedSemaphorePermit,
+    _global: OwnedSemaphorePermit,
+}
+
+impl RequestLimiter {
+    pub fn new(global: usize, per_host: usize) -> Self {
+        Self {
+            global: Arc::new(Semaphore::new(global.max(1))),
+            per_host: per_host.max(1),
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn acquire(&self, link: &str) -> RequestPermit {
+        let host = reqwest::Url::parse(link)
+            .ok()
+            .and_then(|url| url.host_str().map(|host| host.to_string()))
+            .unwrap_or_default();
+        let host_semaphore = self
+            .hosts
+            .lock()
+            .entry(host)
+            .or_insert_with(|| Arc::new(Semaphore::new(self.per_host)))
+            .clone();
+        // The host permit is taken first so requests waiting on a busy host don't hold global slots.
+        let host = host_semaphore.acquire_owned().await.unwrap();
+        let global = self.global.clone().acquire_owned().await.unwrap();
+        RequestPermit {
+            _host: host,
+            _global: global,
+        }
+    }
+}
+
+/// Clients shared by every request of the worker, so connections and TLS sessions
+/// are reused between refreshes. Proxies set in the environment are picked up by reqwest.
+#[derive(Clone)]
+pub struct HttpClients {
+    /// Resolves hosts as set in the DNS setting and sends the cookies stored in the jar.
+    pub client: reqwest::Client,
+    /// Like `client`, but only follows redirects within one origin, for feed requests.
+    /// Redirects to other origins are followed by the caller, without the channel's headers.
+    pub feeds: reqwest::Client,
+    /// Skips certificate validation and records the served certificate, so it can be
+    /// compared with a fingerprint the user trusted instead. Has no cookie jar, the stored
+    /// cookies are only sent over validated connections. Follows redirects like `feeds`.
+    pub pinned: reqwest::Client,
+    /// Like `client` without the cookie jar, for posting to webhooks and other services
+    /// that have no business with the cookies of feeds.
+    pub plain: reqwest::Client,
+    /// DNS mode, DNS server, proxy and timeout the clients were built with.
+    settings: (DnsMode, String, String, u64),
+}
+
+impl HttpClients {
+    pub fn new(cookies: Arc<CookieStoreMutex>) -> Result<Self, String> {
+        let settings = Self::current_settings();
+        let resolver = dns::CustomResolver::from_config(settings.0, &settings.1)?;
+        let proxy = match settings.2.trim() {
+            "" => None,
+            url => Some(reqwest::Proxy::all(url).map_err(|err| format!("Proxy: {}", err))?),
+        };
+        let builder = || {
+            let mut builder = reqwest::Client::builder()
+                .user_agent(USER_AGENT)
+                .connect_timeout(CONNECT_TIMEOUT);
+            if settings.3 > 0 {
+                builder = builder.timeout(Duration::from_secs(settings.3));
+            }
+            if let Some(proxy) = &proxy {
+                builder = builder.proxy(proxy.clone());
+            }
+            match &resolver {
+                Some(resolver) => builder.dns_resolver(resolver.clone()),
+                None => builder,
+            }
+        };
+        let client = builder()
+            .cookie_provider(cookies.clone())
+            .build()
+            .map_err(|err| err.to_string())?;
+        let feeds = builder()
+            .cookie_provider(cookies)
+            .redirect(same_origin_redirects())
+            .build()
+            .map_err(|err| err.to_string())?;
+        let pinned = builder()
+            .redirect(same_origin_redirects())
+            .danger_accept_invalid_certs(true)
+            .tls_info(true)
+            .build()
+            .map_err(|err| err.to_string())?;
+        let plain = builder().build().map_err(|err| err.to_string())?;
+        Ok(Self {
+            client,
+            feeds,
+            pinned,
+            plain,
+            settings,
+        })
+    }
+
+    fn current_settings() -> (DnsMode, String, String, u64) {
+        let config = CONFIG.lock();
+        (
+            config.dns_mode,
+            config.dns_server.clone(),
+            config.proxy.clone(),
+            config.request_timeout_seconds,
+        )
+    }
+
+    /// Whether the network settings changed since the clients were built.
+    pub fn is_stale(&self) -> bool {
+        self.settings != Self::current_settings()
+    }
+}
+
+/// Follows redirects that stay on the origin of the url redirecting and stops at others,
+/// handing the redirect response to the caller.
+fn same_origin_redirects() -> reqwest::redirect::Policy {
+    reqwest::redirect::Policy::custom(|attempt| {
+        let same_origin = attempt
+            .previous()
+            .last()
+            .is_some_and(|previous| previous.origin() == attempt.url().origin());
+        if !same_origin {
+            attempt.stop()
+        } else if attempt.previous().len() > MAX_REDIRECTS {
+            attempt.error("too many redirects")
+        } else {
+            attempt.follow()
+        }
+    })
+}
+
+/// Feed link with the scheme and host lowercased and without a trailing slash, so the
+/// different spellings of one link compare equal.
+pub fn canonical_link(link: &str) -> String {
+    let link = link.trim().trim_end_matches('/');
+    match link.split_once("://") {
+        Some((scheme, rest)) => {
+            let (host, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+            format!(
+                "{}://{}{}",
+                scheme.to_lowercase(),
+                host.to_lowercase(),
+                path
+            )
+        }
+        None => link.to_string(),
+    }
+}
+
+/// Key under which links that likely serve the same feed compare equal: the canonical link
+/// without its scheme and a leading `www.`, so `http://www.x.org/feed/` matches
+/// `https://x.org/feed`.
+pub fn duplicate_key(link: &str) -> String {
+    let link = canonical_link(link);
+    let rest = link
+        .split_once("://")
+        .map_or(link.as_str(), |(_, rest)| rest);
+    rest.strip_prefix("www.").unwrap_or(rest).to_string()
+}
+
+/// Site a link belongs to: its host without subdomains, e.g. `reddit.com` for
+/// `old.reddit.com`. Hosts under two-part suffixes like `co.uk` keep one more label.
+pub fn site_of(link: &str) -> Option<String> {
+    let host = reqwest::Url::parse(link).ok()?.host_str()?.to_lowercase();
+    if host.parse::<std::net::IpAddr>().is_ok() || host.starts_with('[') {
+        return Some(host);
+    }
+    let labels: Vec<&str> = host.split('.').filter(|label| !label.is_empty()).collect();
+    let keep = match labels[..] {
+        [.., second, top] if top.len() == 2 && second.len() <= 3 && labels.len() > 2 => 3,
+        _ => 2,
+    };
+    Some(labels[labels.len().saturating_sub(keep)..].join("."))
+}
+
+/// Timestamp of the local midnight starting `date`, the first one when a time change repeats it.
+pub fn local_midnight(date: chrono::NaiveDate) -> Option<i64> {
+    date.and_hms_opt(0, 0, 0)?
+        .and_local_timezone(chrono::Local)
+        .earliest()
+        .map(|midnight| midnight.timestamp())
+}
+
+/// A command for a helper program run in the background. On Windows it gets no console
+/// window, which would otherwise flash up.
+fn background_command(program: &str) -> std::process::Command {
+    #[allow(unused_mut)]
+    let mut command = std::process::Command::new(program);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        // CREATE_NO_WINDOW
+        command.creation_flags(0x08000000);
+    }
+    command
+}
+
+/// Whether the operating system reports the network connection as metered, e.g. a phone
+/// hotspot. `None` where it doesn't tell. Blocks while asking, unless the last answer is
+/// younger than [`METERED_CACHE`].
+pub fn detect_metered() -> Option<bool> {
+    let mut cached = METERED.lock();
+    if let Some((at, metered)) = *cached {
+        if at.elapsed() < METERED_CACHE {
+            return metered;
+        }
+    }
+    let metered = ask_metered();
+    *cached = Some((Instant::now(), metered));
+    metered
+}
+
+fn ask_metered() -> Option<bool> {
+    if cfg!(target_os = "windows") {
+        let script = "[Windows.Networking.Connectivity.NetworkInformation, Windows.Networking.Connectivity, ContentType = WindowsRuntime] | Out-Null; [Windows.Networking.Connectivity.NetworkInformation]::GetInternetConnectionProfile().GetConnectionCost().NetworkCostType";
+        let output = background_command("powershell")
+            .args(["-NoProfile", "-Command", script])
+            .output()
+            .ok()?;
+        match String::from_utf8_lossy(&output.stdout).trim() {
+            "Unrestricted" => Some(false),
+            "Fixed" | "Variable" => Some(true),
+            _ => None,
+        }
+    } else if cfg!(target_os = "linux") {
+        // NetworkManager's NMMetered: 1 yes, 2 no, 3 guessed yes, 4 guessed no.
+        let output = background_command("busctl")
+            .args([
+                "get-property",
+                "org.freedesktop.NetworkManager",
+                "/org/freedesktop/NetworkManager",
+                "org.freedesktop.NetworkManager",
+                "Metered",
+            ])
+            .output()
+            .ok()?;
+        match String::from_utf8_lossy(&output.stdout).trim() {
+            "u 1" | "u 3" => Some(true),
+            "u 2" | "u 4" => Some(false),
+            _ => None,
+        }
+    } else {
+        None
+    }
+}
+
+/// Plays the system's notification sound with the platform's own player, as the app has
+/// no audio output of its own. Failures are only logged.
+pub fn play_alert_sound() {
+    let (program, args): (&str, &[&str]) = if cfg!(target_os = "windows") {
+        (
+            "powershell",
+            &[
+                "-NoProfile",
+                "-Command",
+                "[System.Media.SystemSounds]::Exclamation.Play()",
+            ],
+        )
+    } else if cfg!(target_os = "macos") {
+        ("afplay", &["/System/Library/Sounds/Glass.aiff"])
+    } else {
+        ("canberra-gtk-play", &["--id", "message-new-instant"])
+    };
+    match background_command(program).args(args).spawn() {
+        Ok(mut child) => {
+            std::thread::spawn(move || child.wait());
+        }
+        Err(err) => tracing::warn!("Failed to play alert sound: {}", err),
+    }
+}
+
+/// Whether two feed links point to the same place.
+pub fn same_link(a: &str, b: &str) -> bool {
+    canonical_link(a) == canonical_link(b)
+}
+
+/// Lowercase hex SHA-256 of `text`.
+pub fn sha256_hex(text: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    Sha256::digest(text.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Id of the channel subscribed to `link`: the SHA-256 of its canonical form as hex.
+/// Feeds' own ids aren't used, as feeds change them and many share generic ones.
+pub fn channel_id(link: &str) -> String {
+    sha256_hex(&canonical_link(link))
+}
+
+/// OPML document listing `channels`, as exported to a file or uploaded for syncing.
+pub fn render_opml(channels: &[Channel]) -> Result<String, opml::Error> {
+    let xml = r#"<opml version="2.0"><head/><body><outline text="Outline"/></body></opml>"#;
+    let mut opml = opml::OPML::from_str(xml)?;
+
+    let mut group = opml::Outline::default();
+    for channel in channels {
+        group.add_feed(channel.title.as_deref().unwrap_or("Unknown"), &channel.link);
+    }
+    opml.body.outlines.push(group);
+
+    opml.to_string()
+}
+
+/// Size limit for downloaded feeds, in bytes. A limit of 0 MB lets feeds of any size through.
+pub fn max_feed_size() -> usize {
+    match CONFIG.lock().max_feed_size_mb {
+        0 => usize::MAX,
+        mb => mb as usize * 1024 * 1024,
+    }
+}
+
+/// Reads a response body chunk by chunk, giving up as soon as it grows past `limit` bytes
+/// instead of buffering whatever the server keeps sending.
+pub async fn read_body(mut resp: reqwest::Response, limit: usize) -> Result<Bytes, String> {
+    let too_large = || format!("Response is larger than {} MB", limit / 1024 / 1024);
+    if resp
+        .content_length()
+        .is_some_and(|length| length > limit as u64)
+    {
+        return Err(too_large());
+    }
+    let mut body = BytesMut::new();
+    while let Some(chunk) = resp.chunk().await.map_err(|err| err.to_string())? {
+        if body.len() + chunk.len() > limit {
+            return Err(too_large());
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body.freeze())
+}
+
+/// SHA-256 fingerprint of the certificate a response was served with, as colon separated hex.
+pub fn peer_fingerprint(resp: &reqwest::Response) -> Option<String> {
+    use sha2::{Digest, Sha256};
+
+    let der = resp
+        .extensions()
+        .get::<reqwest::tls::TlsInfo>()?
+        .peer_certificate()?;
+    let hex: Vec<String> = Sha256::digest(der)
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect();
+    Some(hex.join(":"))
+}
+
 pub async fn is_online() -> bool {
     const ADDRS: [&str; 2] = ["clients3.google.com:80", "detectportal.firefox.com:80"];
     for addr in ADDRS {
@@ -14,3 +498,209 @@ pub async fn is_online() -> bool {
     }
     false
 }
+
+/// 128 random bits from the operating system, hex encoded.
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).expect("operating system has no random source");
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Compares secrets in time independent of where they differ.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Estimates reading time in minutes for an HTML or plain text body at 200 words per minute.
+pub fn estimate_reading_time(body: &str) -> i64 {
+    let words = strip_html(body).split_whitespace().count() as i64;
+    if words == 0 {
+        0
+    } else {
+        (words + 199) / 200
+    }
+}
+
+pub fn strip_html(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => {
+                in_tag = true;
+                text.push(' ');
+            }
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text
+}
+
+/// `name` without characters that aren't allowed in file names on any system.
+pub fn file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            _ => c,
+        })
+        .take(80)
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// Replaces the character references that commonly survive in feed text.
+pub fn decode_entities(text: &str) -> String {
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Percent-encodes everything but unreserved characters, for use in a query value.
+pub fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Targets of the links in `html`, with entities decoded.
+pub fn hrefs(html: &str) -> impl Iterator<Item = String> + '_ {
+    html.split("href=\"")
+        .skip(1)
+        .filter_map(|rest| rest.split_once('"'))
+        .map(|(href, _)| decode_entities(href))
+}
+
+/// Target of the first link in `html` whose text is `label`.
+pub fn link_labelled(html: &str, label: &str) -> Option<String> {
+    let end = html.find(&format!(">{}</a>", label))?;
+    hrefs(&html[..end]).last()
+}
+
+/// Elements dropped from downloaded pages, as they hold no article text.
+pub const NON_CONTENT_TAGS: &[&str] = &[
+    "script", "style", "noscript", "nav", "header", "footer", "aside", "form", "iframe",
+];
+
+/// Cuts the article out of a web page: the inner HTML of its `<article>`, `<main>` or
+/// `<body>` element, whichever comes first, without scripts, navigation and similar parts.
+/// Only allowlisted tags and attributes are kept, so no event handlers or inline styles.
+/// `None` if no text is left.
+pub fn readable_html(html: &str) -> Option<String> {
+    // ASCII lowercasing keeps byte offsets, so positions found in `lower` apply to `html`.
+    let lower = html.to_ascii_lowercase();
+    let (start, end) = ["article", "main", "body"].iter().find_map(|tag| {
+        let open = find_tag(&lower, tag)?;
+        let start = open + lower[open..].find('>')? + 1;
+        let end = lower.rfind(&format!("</{}>", tag))?;
+        (end > start).then_some((start, end))
+    })?;
+
+    let content = ammonia::Builder::default()
+        .rm_tags(NON_CONTENT_TAGS)
+        .clean_content_tags(NON_CONTENT_TAGS.iter().copied().collect())
+        .clean(&html[start..end])
+        .to_string();
+
+    (!strip_html(&content).trim().is_empty()).then_some(content)
+}
+
+/// Position of the first `<tag` opening an element named exactly `tag`.
+fn find_tag(lower: &str, tag: &str) -> Option<usize> {
+    let pattern = format!("<{}", tag);
+    let mut position = 0;
+    while let Some(offset) = lower[position..].find(&pattern) {
+        let open = position + offset;
+        let next = lower[open + pattern.len()..].chars().next();
+        if next.is_none_or(|c| c == '>' || c == '/' || c.is_ascii_whitespace()) {
+            return Some(open);
+        }
+        position = open + pattern.len();
+    }
+    None
+}
+
+/// Splits a command template into arguments and fills in the item placeholders.
+/// Placeholders are substituted per argument, so values are never re-parsed by a shell.
+pub fn expand_command(template: &str, url: &str, title: &str) -> Result<Vec<String>, String> {
+    let args = shell_words::split(template).map_err(|err| err.to_string())?;
+    if args.is_empty() {
+        return Err("command is empty".to_string());
+    }
+    Ok(args
+        .into_iter()
+        .map(|arg| arg.replace("{url}", url).replace("{title}", title))
+        .collect())
+}
+
+/// Whether `text` contains any of the blocklisted keywords, ignoring case.
+pub fn is_blocked(keywords: &[String], text: &str) -> bool {
+    let text = text.to_lowercase();
+    keywords
+        .iter()
+        .filter(|keyword| !keyword.trim().is_empty())
+        .any(|keyword| text.contains(&keyword.trim().to_lowercase()))
+}
+
+/// The fonts used by the UI and for PDFs: egui's default fonts, the custom fonts from the
+/// settings in front of them and the first CJK fallback font found as a last resort.
+pub fn font_definitions(config: &ConfigBuilder) -> FontDefinitions {
+    let mut fonts = FontDefinitions::default();
+
+    let mut loaded = 0;
+    for path in &config.custom_fonts {
+        match std::fs::read(path) {
+            Ok(bytes) => {
+                let name = format!("custom_{}", loaded);
+                fonts
+                    .font_data
+                    .insert(name.clone(), FontData::from_owned(bytes));
+                fonts
+                    .families
+                    .entry(FontFamily::Proportional)
+                    .or_default()
+                    .insert(loaded, name.clone());
+                fonts
+                    .families
+                    .entry(FontFamily::Monospace)
+                    .or_default()
+                    .push(name);
+                loaded += 1;
+            }
+            Err(err) => {
+                tracing::error!("Failed to load font {}: {}", path.display(), err);
+            }
+        }
+    }
+
+    if config.cjk_fallback_font {
+        if let Some(bytes) = CJK_FALLBACK_FONTS
+            .iter()
+            .find_map(|path| std::fs::read(path).ok())
+        {
+            fonts
+                .font_data
+                .insert("cjk_fallback".into(), FontData::from_owned(bytes));
+            for family in [FontFamily::Proportional, FontFamily::Monospace] {
+                fonts
+                    .families
+                    .entry(family)
+                    .or_default()
+                    .push("cjk_fallback".into());
+            }
+        }
+    }
+
+    fonts
+}
@@ -0,0 +1,88 @@
+use super::ConfigBuilder;
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+const OPML_FILE: &str = "tinyrss.opml";
+const STATE_FILE: &str = "tinyrss-state.json";
+
+/// Item state of one machine at the time it last synced.
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    pub device: String,
+    pub time: i64,
+    pub items: Vec<ItemState>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ItemState {
+    pub id: String,
+    pub dismissed: bool,
+    pub starred: bool,
+    pub later: bool,
+}
+
+fn file_url(config: &ConfigBuilder, name: &str) -> String {
+    format!("{}/{}", config.webdav_url.trim_end_matches('/'), name)
+}
+
+async fn get(
+    client: &Client,
+    config: &ConfigBuilder,
+    name: &str,
+) -> Result<Option<String>, Box<dyn Error>> {
+    let response = client
+        .get(file_url(config, name))
+        .basic_auth(
+            &config.webdav_username,
+            Some(config.webdav_password.as_str()),
+        )
+        .send()
+        .await?;
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    Ok(Some(response.error_for_status()?.text().await?))
+}
+
+async fn put(
+    client: &Client,
+    config: &ConfigBuilder,
+    name: &str,
+    body: String,
+) -> Result<(), Box<dyn Error>> {
+    client
+        .put(file_url(config, name))
+        .basic_auth(
+            &config.webdav_username,
+            Some(config.webdav_password.as_str()),
+        )
+        .body(body)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+/// Fetches the subscriptions and state snapshot last uploaded by any machine.
+pub async fn download(
+    client: &Client,
+    config: &ConfigBuilder,
+) -> Result<(Option<String>, Option<Snapshot>), Box<dyn Error>> {
+    let opml = get(client, config, OPML_FILE).await?;
+    let snapshot = match get(client, config, STATE_FILE).await? {
+        Some(json) => Some(serde_json::from_str(&json)?),
+        None => None,
+    };
+    Ok((opml, snapshot))
+}
+
+pub async fn upload(
+    client: &Client,
+    config: &ConfigBuilder,
+    opml: String,
+    snapshot: &Snapshot,
+) -> Result<(), Box<dyn Error>> {
+    put(client, config, OPML_FILE, opml).await?;
+    put(client, config, STATE_FILE, serde_json::to_string(snapshot)?).await
+}